@@ -0,0 +1,105 @@
+use serde::Serialize;
+
+/// Сколько последних выборок образуют скользящее окно
+pub const ANOMALY_WINDOW: i64 = 20;
+/// Меньше выборок — медиана ненадёжна, проверку пропускаем
+const ANOMALY_MIN_HISTORY: usize = 5;
+
+/// Допустимые отклонения от скользящей медианы
+#[derive(Debug, Clone, Copy)]
+pub struct AnomalyThresholds {
+    pub velocity_kmh: f64,
+    pub altitude_km: f64,
+}
+
+/// Отклонение одного поля выборки
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Anomaly {
+    pub field: &'static str,
+    pub value: f64,
+    pub median: f64,
+    pub reason: String,
+}
+
+pub fn median(values: &[f64]) -> Option<f64> {
+    let mut v: Vec<f64> = values.iter().copied().filter(|x| x.is_finite()).collect();
+    if v.is_empty() {
+        return None;
+    }
+    v.sort_by(f64::total_cmp);
+    let mid = v.len() / 2;
+    Some(if v.len().is_multiple_of(2) { (v[mid - 1] + v[mid]) / 2.0 } else { v[mid] })
+}
+
+/// Сравнивает скорость и высоту новой выборки с медианой предыдущих
+pub fn detect(
+    velocity_kmh: Option<f64>,
+    altitude_km: Option<f64>,
+    velocity_history: &[f64],
+    altitude_history: &[f64],
+    thresholds: AnomalyThresholds,
+) -> Vec<Anomaly> {
+    [
+        ("velocity_kmh", velocity_kmh, velocity_history, thresholds.velocity_kmh),
+        ("altitude_km", altitude_km, altitude_history, thresholds.altitude_km),
+    ]
+    .into_iter()
+    .filter_map(|(field, value, history, limit)| {
+        let value = value?;
+        if history.len() < ANOMALY_MIN_HISTORY {
+            return None;
+        }
+        let median = median(history)?;
+        let deviation = (value - median).abs();
+        (deviation > limit).then(|| Anomaly {
+            field,
+            value,
+            median,
+            reason: format!(
+                "{} {:.1} deviates {:.1} from rolling median {:.1} (limit {})",
+                field, value, deviation, median, limit
+            ),
+        })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: AnomalyThresholds = AnomalyThresholds {
+        velocity_kmh: 1000.0,
+        altitude_km: 25.0,
+    };
+
+    #[test]
+    fn median_of_odd_even_and_empty() {
+        assert_eq!(median(&[3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(median(&[4.0, 1.0, 3.0, 2.0]), Some(2.5));
+        assert_eq!(median(&[]), None);
+    }
+
+    #[test]
+    fn flags_outliers_only() {
+        let vel = [27_600.0, 27_590.0, 27_610.0, 27_605.0, 27_595.0, 90_000.0];
+        let alt = [420.0, 421.0, 419.5, 420.5, 420.2, 420.1];
+
+        assert!(detect(Some(27_620.0), Some(421.0), &vel, &alt, LIMITS).is_empty());
+
+        let found = detect(Some(0.0), Some(480.0), &vel, &alt, LIMITS);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].field, "velocity_kmh");
+        assert_eq!(found[0].median, 27_602.5);
+        assert_eq!(found[1].field, "altitude_km");
+        assert!(found[1].reason.contains("rolling median"));
+    }
+
+    #[test]
+    fn needs_history_and_values() {
+        let short = [27_600.0, 27_600.0];
+        assert!(detect(Some(0.0), None, &short, &[], LIMITS).is_empty());
+        let vel = [27_600.0; 10];
+        assert!(detect(None, None, &vel, &[420.0; 10], LIMITS).is_empty());
+    }
+}
@@ -0,0 +1,209 @@
+use chrono::{Days, NaiveDate};
+use serde_json::Value;
+
+use crate::models::ApodEntryRow;
+
+/// Первый выпуск Astronomy Picture of the Day
+pub const FIRST_APOD_DATE: NaiveDate = match NaiveDate::from_ymd_opt(1995, 6, 16) {
+    Some(date) => date,
+    None => panic!("invalid first APOD date"),
+};
+
+/// Сколько дней APOD отдаёт нам за один запрос start_date/end_date
+pub const APOD_CHUNK_DAYS: u64 = 30;
+
+/// Один день APOD для apod_entries
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApodEntry {
+    pub date: NaiveDate,
+    pub title: Option<String>,
+    pub explanation: Option<String>,
+    pub media_type: Option<String>,
+    pub url: Option<String>,
+    pub hdurl: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub copyright: Option<String>,
+    pub raw: Value,
+}
+
+impl ApodEntry {
+    fn from_value(item: &Value) -> Result<Self, String> {
+        let text = |key: &str| item.get(key).and_then(Value::as_str).map(|s| s.trim().to_string());
+        let date = text("date").ok_or("missing date")?;
+        Ok(Self {
+            date: NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|_| format!("bad date {:?}", date))?,
+            title: text("title"),
+            explanation: text("explanation"),
+            media_type: text("media_type"),
+            url: text("url"),
+            hdurl: text("hdurl"),
+            thumbnail_url: text("thumbnail_url"),
+            copyright: text("copyright"),
+            raw: item.clone(),
+        })
+    }
+}
+
+/// Дни из ответа APOD: массив при start_date/end_date, объект при date
+pub fn entries(payload: &Value) -> Result<Vec<ApodEntry>, String> {
+    match payload {
+        Value::Array(items) => items.iter().map(ApodEntry::from_value).collect(),
+        Value::Object(_) => ApodEntry::from_value(payload).map(|entry| vec![entry]),
+        _ => Err("expected an APOD object or array".into()),
+    }
+}
+
+/// Дата в формате YYYY-MM-DD не раньше первого выпуска и не позже `today`
+pub fn parse_date(raw: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let date = NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d")
+        .map_err(|_| format!("{:?} is not a YYYY-MM-DD date", raw))?;
+    if date < FIRST_APOD_DATE {
+        return Err(format!("APOD starts on {}", FIRST_APOD_DATE));
+    }
+    if date > today {
+        return Err(format!("{} is in the future", date));
+    }
+    Ok(date)
+}
+
+/// Отрезки [start, end] не длиннее `days` дней, покрывающие весь диапазон
+pub fn chunks(start: NaiveDate, end: NaiveDate, days: u64) -> Vec<(NaiveDate, NaiveDate)> {
+    let mut out = Vec::new();
+    let mut from = start;
+    while from <= end {
+        let to = (from + Days::new(days.max(1) - 1)).min(end);
+        out.push((from, to));
+        from = to + Days::new(1);
+    }
+    out
+}
+
+/// Шаблон ILIKE для поиска подстроки: %, _ и \ в запросе ищутся буквально
+pub fn like_pattern(query: &str) -> String {
+    let mut pattern = String::from("%");
+    for c in query.trim().chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+/// Размер картинки дня для /space/apod/image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaSize {
+    /// url: обычное разрешение или превью видео
+    Thumb,
+    /// hdurl, если есть
+    Full,
+}
+
+impl MediaSize {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "" | "thumb" => Ok(Self::Thumb),
+            "full" => Ok(Self::Full),
+            _ => Err("size must be thumb or full".into()),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Thumb => "thumb",
+            Self::Full => "full",
+        }
+    }
+}
+
+/// Откуда скачивать картинку дня. У видео есть только превью thumbnail_url,
+/// и без него отдать нечего
+pub fn media_url(entry: &ApodEntryRow, size: MediaSize) -> Result<&str, String> {
+    if entry.media_type.as_deref() != Some("image") {
+        return entry.thumbnail_url.as_deref().ok_or_else(|| {
+            format!(
+                "APOD {} is a {} without a thumbnail",
+                entry.date,
+                entry.media_type.as_deref().unwrap_or("entry")
+            )
+        });
+    }
+    let url = match size {
+        MediaSize::Thumb => entry.url.as_deref(),
+        MediaSize::Full => entry.hdurl.as_deref().or(entry.url.as_deref()),
+    };
+    url.ok_or_else(|| format!("APOD {} has no image url", entry.date))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn validates_dates_and_splits_ranges() {
+        let today = day("2026-10-16");
+        assert_eq!(parse_date("2026-10-16", today), Ok(today));
+        assert!(parse_date("1995-06-15", today).unwrap_err().contains("1995-06-16"));
+        assert!(parse_date("2026-10-17", today).unwrap_err().contains("future"));
+        assert!(parse_date("16.10.2026", today).is_err());
+
+        let parts = chunks(day("2026-01-01"), day("2026-03-01"), 30);
+        assert_eq!(
+            parts,
+            vec![
+                (day("2026-01-01"), day("2026-01-30")),
+                (day("2026-01-31"), day("2026-03-01")),
+            ]
+        );
+        assert_eq!(chunks(today, today, 30), vec![(today, today)]);
+    }
+
+    #[test]
+    fn picks_media_url_by_size() {
+        let mut entry = ApodEntryRow {
+            date: day("2026-10-15"),
+            title: None,
+            explanation: None,
+            media_type: Some("image".into()),
+            url: Some("https://apod/image.jpg".into()),
+            hdurl: Some("https://apod/image_hd.jpg".into()),
+            thumbnail_url: None,
+            copyright: None,
+            fetched_at: chrono::Utc::now(),
+        };
+        assert_eq!(media_url(&entry, MediaSize::Thumb), Ok("https://apod/image.jpg"));
+        assert_eq!(media_url(&entry, MediaSize::Full), Ok("https://apod/image_hd.jpg"));
+
+        entry.media_type = Some("video".into());
+        assert!(media_url(&entry, MediaSize::Full).unwrap_err().contains("video without a thumbnail"));
+        entry.thumbnail_url = Some("https://img.youtube/x.jpg".into());
+        assert_eq!(media_url(&entry, MediaSize::Full), Ok("https://img.youtube/x.jpg"));
+        assert!(MediaSize::parse("huge").is_err());
+    }
+
+    #[test]
+    fn escapes_like_wildcards() {
+        assert_eq!(like_pattern(" nebula "), "%nebula%");
+        assert_eq!(like_pattern("100%_sure\\"), "%100\\%\\_sure\\\\%");
+    }
+
+    #[test]
+    fn parses_single_day_and_ranges() {
+        let one = json!({"date": "2026-10-15", "title": " Aurora ", "media_type": "video",
+                         "url": "https://youtube/embed/x", "thumbnail_url": "https://img/x.jpg"});
+        let parsed = entries(&one).unwrap();
+        assert_eq!(parsed[0].date, day("2026-10-15"));
+        assert_eq!(parsed[0].title.as_deref(), Some("Aurora"));
+        assert_eq!(parsed[0].hdurl, None);
+
+        let range = json!([{"date": "2026-10-14"}, {"date": "2026-10-15"}]);
+        assert_eq!(entries(&range).unwrap().len(), 2);
+        assert!(entries(&json!([{"title": "no date"}])).is_err());
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Название корабля МКС в ответе open-notify
+const ISS_CRAFT: &str = "ISS";
+
+/// Сводка по людям на орбите из ответа open-notify astros.json
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AstrosSummary {
+    pub total: usize,
+    pub by_craft: BTreeMap<String, usize>,
+    pub iss_crew: Vec<String>,
+}
+
+impl AstrosSummary {
+    pub fn from_payload(payload: &Value) -> Result<Self, String> {
+        if let Some(message) = payload.get("message").and_then(Value::as_str) {
+            if message != "success" {
+                return Err(format!("upstream reported {:?}", message));
+            }
+        }
+        let people = payload
+            .get("people")
+            .and_then(Value::as_array)
+            .ok_or("missing people array")?;
+
+        let mut by_craft: BTreeMap<String, usize> = BTreeMap::new();
+        let mut iss_crew = Vec::new();
+        for person in people {
+            let craft = person.get("craft").and_then(Value::as_str).unwrap_or("unknown").trim();
+            *by_craft.entry(craft.to_string()).or_default() += 1;
+            if craft.eq_ignore_ascii_case(ISS_CRAFT) {
+                if let Some(name) = person.get("name").and_then(Value::as_str) {
+                    iss_crew.push(name.trim().to_string());
+                }
+            }
+        }
+
+        Ok(Self {
+            total: people.len(),
+            by_craft,
+            iss_crew,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn groups_by_craft() {
+        let summary = AstrosSummary::from_payload(&json!({
+            "message": "success",
+            "number": 4,
+            "people": [
+                {"name": "Oleg Kononenko", "craft": "ISS"},
+                {"name": "Jiang Xinlin", "craft": "Tiangong"},
+                {"name": "Tracy Dyson", "craft": "ISS"},
+                {"name": "Ye Guangfu", "craft": "Tiangong"}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(summary.total, 4);
+        assert_eq!(summary.by_craft["ISS"], 2);
+        assert_eq!(summary.by_craft["Tiangong"], 2);
+        assert_eq!(summary.iss_crew, vec!["Oleg Kononenko", "Tracy Dyson"]);
+    }
+
+    #[test]
+    fn rejects_failures() {
+        assert!(AstrosSummary::from_payload(&json!({"message": "failure"})).is_err());
+        assert!(AstrosSummary::from_payload(&json!({"message": "success"})).is_err());
+        assert!(AstrosSummary::from_payload(&json!("<html>")).is_err());
+    }
+}
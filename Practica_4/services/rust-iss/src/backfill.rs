@@ -0,0 +1,75 @@
+use serde::Serialize;
+
+/// Сколько моментов времени wheretheiss.at принимает в одном запросе /positions
+pub const POSITIONS_BATCH: usize = 10;
+
+/// Итог дозагрузки; при таймауте содержит то, что успели сделать
+#[derive(Debug, Default, Serialize)]
+pub struct BackfillProgress {
+    pub inserted: u64,
+    /// Моменты, для которых выборка уже есть в журнале
+    pub skipped: u64,
+    pub failed: u64,
+    pub errors: Vec<String>,
+}
+
+/// Эпохи (секунды) от `from` до `to` включительно с шагом `step`
+pub fn epochs(from: i64, to: i64, step: i64) -> Vec<i64> {
+    if step <= 0 || from > to {
+        return Vec::new();
+    }
+    (0..=(to - from) / step).map(|i| from + i * step).collect()
+}
+
+/// Эпохи, рядом с которыми (ближе `tolerance` секунд) в журнале ещё нет
+/// выборки; `existing` должен быть отсортирован
+pub fn missing_epochs(epochs: &[i64], existing: &[i64], tolerance: i64) -> Vec<i64> {
+    epochs
+        .iter()
+        .copied()
+        .filter(|&t| {
+            let i = existing.partition_point(|&e| e < t - tolerance);
+            existing.get(i).is_none_or(|&e| e > t + tolerance)
+        })
+        .collect()
+}
+
+/// URL bulk-запроса положений: `<satellite_url>/positions?timestamps=a,b,c`
+pub fn positions_url(satellite_url: &str, epochs: &[i64]) -> String {
+    let list: Vec<String> = epochs.iter().map(i64::to_string).collect();
+    format!(
+        "{}/positions?timestamps={}",
+        satellite_url.trim_end_matches('/'),
+        list.join(",")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epoch_grid() {
+        assert_eq!(epochs(100, 130, 10), vec![100, 110, 120, 130]);
+        assert_eq!(epochs(100, 135, 10), vec![100, 110, 120, 130]);
+        assert_eq!(epochs(100, 100, 10), vec![100]);
+        assert!(epochs(130, 100, 10).is_empty());
+        assert!(epochs(100, 130, 0).is_empty());
+    }
+
+    #[test]
+    fn skips_epochs_close_to_existing() {
+        let grid = epochs(0, 100, 20);
+        assert_eq!(missing_epochs(&grid, &[], 5), grid);
+        assert_eq!(missing_epochs(&grid, &[-5, 23, 61, 200], 5), vec![40, 80, 100]);
+        assert_eq!(missing_epochs(&grid, &[35, 45], 5), vec![0, 20, 60, 80, 100]);
+    }
+
+    #[test]
+    fn bulk_url() {
+        assert_eq!(
+            positions_url("https://api.wheretheiss.at/v1/satellites/25544/", &[1, 2, 3]),
+            "https://api.wheretheiss.at/v1/satellites/25544/positions?timestamps=1,2,3"
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// JSON с ключами объектов в лексикографическом порядке на любой глубине;
+/// порядок элементов массивов сохраняется
+pub fn canonical_json(v: &Value) -> String {
+    let mut out = String::new();
+    write_canonical(v, &mut out);
+    out
+}
+
+fn write_canonical(v: &Value, out: &mut String) {
+    match v {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, k) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(k.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[k], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+/// SHA-256 канонического JSON в hex — не зависит от порядка ключей
+pub fn content_hash(v: &Value) -> String {
+    Sha256::digest(canonical_json(v).as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn hash_ignores_key_order() {
+        let a: Value = serde_json::from_str(r#"{"id":"OSD-1","meta":{"b":2,"a":[1,{"y":1,"x":0}]}}"#).unwrap();
+        let b: Value = serde_json::from_str(r#"{"meta":{"a":[1,{"x":0,"y":1}],"b":2},"id":"OSD-1"}"#).unwrap();
+        assert_eq!(canonical_json(&a), r#"{"id":"OSD-1","meta":{"a":[1,{"x":0,"y":1}],"b":2}}"#);
+        assert_eq!(content_hash(&a), content_hash(&b));
+        assert_eq!(content_hash(&a).len(), 64);
+    }
+
+    #[test]
+    fn hash_detects_changes() {
+        let base = json!({"id": "OSD-1", "tags": ["a", "b"], "status": "public"});
+        assert_ne!(content_hash(&base), content_hash(&json!({"id": "OSD-1", "tags": ["b", "a"], "status": "public"})));
+        assert_ne!(content_hash(&base), content_hash(&json!({"id": "OSD-1", "tags": ["a", "b"], "status": "draft"})));
+        assert_ne!(content_hash(&json!({"v": 1})), content_hash(&json!({"v": "1"})));
+    }
+}
@@ -0,0 +1,175 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::flr::parse_time;
+use crate::geo::extract_number;
+
+/// Событие DONKI /CME; поля, которые нам не нужны, не описываются
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawCme {
+    #[serde(rename = "activityID")]
+    activity_id: String,
+    start_time: Option<String>,
+    source_location: Option<String>,
+    link: Option<String>,
+    note: Option<String>,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    cme_analyses: Vec<RawAnalysis>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawAnalysis {
+    #[serde(default, deserialize_with = "null_as_false")]
+    is_most_accurate: bool,
+    #[serde(default, deserialize_with = "lenient_number")]
+    speed: Option<f64>,
+    #[serde(default, deserialize_with = "lenient_number")]
+    half_angle: Option<f64>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    enlil_list: Vec<RawEnlil>,
+}
+
+/// Прогон модели WSA-ENLIL. estimatedShockArrivalTime — прибытие ударной
+/// волны к Земле; удары по другим объектам перечислены в impactList
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawEnlil {
+    estimated_shock_arrival_time: Option<String>,
+    #[serde(rename = "isEarthGB", default, deserialize_with = "null_as_false")]
+    is_earth_gb: bool,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    impact_list: Vec<RawImpact>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawImpact {
+    location: Option<String>,
+}
+
+impl RawEnlil {
+    fn hits_earth(&self) -> bool {
+        self.estimated_shock_arrival_time.is_some()
+            || self.is_earth_gb
+            || self.impact_list.iter().any(|i| i.location.as_deref() == Some("Earth"))
+    }
+}
+
+fn null_as_empty<'de, D: Deserializer<'de>, T: Deserialize<'de>>(d: D) -> Result<Vec<T>, D::Error> {
+    Ok(Option::<Vec<T>>::deserialize(d)?.unwrap_or_default())
+}
+
+fn null_as_false<'de, D: Deserializer<'de>>(d: D) -> Result<bool, D::Error> {
+    Ok(Option::<bool>::deserialize(d)?.unwrap_or_default())
+}
+
+/// Число или строка с числом; всё остальное — None, а не ошибка события
+fn lenient_number<'de, D: Deserializer<'de>>(d: D) -> Result<Option<f64>, D::Error> {
+    Ok(extract_number(&Value::deserialize(d)?))
+}
+
+/// CME для /space/cme/summary
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CmeEvent {
+    pub activity_id: String,
+    pub start_time: String,
+    pub source_location: Option<String>,
+    /// Скорость выбранного анализа, км/с
+    pub speed: Option<f64>,
+    pub half_angle: Option<f64>,
+    pub analysis_type: Option<String>,
+    /// Хоть один прогон ENLIL любого анализа предсказывает удар по Земле
+    pub earth_directed: bool,
+    /// Самое раннее предсказанное прибытие ударной волны к Земле
+    pub estimated_shock_arrival_time: Option<String>,
+    pub note: Option<String>,
+    pub link: Option<String>,
+}
+
+impl CmeEvent {
+    fn from_raw(raw: RawCme) -> Option<Self> {
+        let start_time = raw.start_time?;
+        // isMostAccurate, а если такого анализа нет — самый быстрый
+        let chosen = raw.cme_analyses.iter().find(|a| a.is_most_accurate).or_else(|| {
+            raw.cme_analyses
+                .iter()
+                .filter(|a| a.speed.is_some())
+                .max_by(|a, b| a.speed.unwrap_or_default().total_cmp(&b.speed.unwrap_or_default()))
+        });
+        let runs = || raw.cme_analyses.iter().flat_map(|a| &a.enlil_list);
+        Some(Self {
+            activity_id: raw.activity_id,
+            start_time,
+            source_location: raw.source_location.filter(|s| !s.is_empty()),
+            speed: chosen.and_then(|a| a.speed),
+            half_angle: chosen.and_then(|a| a.half_angle),
+            analysis_type: chosen.and_then(|a| a.kind.clone()),
+            earth_directed: runs().any(RawEnlil::hits_earth),
+            estimated_shock_arrival_time: runs()
+                .filter_map(|r| r.estimated_shock_arrival_time.as_deref())
+                .min_by_key(|t| parse_time(t))
+                .map(str::to_string),
+            note: raw.note,
+            link: raw.link,
+        })
+    }
+}
+
+/// События из ответа DONKI с началом не раньше `since`, по времени начала.
+/// Событие, которое не удалось разобрать, пропускается, а не валит весь ответ
+pub fn events(payload: &Value, since: DateTime<Utc>) -> Result<Vec<CmeEvent>, String> {
+    let items = payload.as_array().ok_or("expected an array of CMEs")?;
+    let mut out: Vec<CmeEvent> = items
+        .iter()
+        .filter_map(|item| RawCme::deserialize(item).ok())
+        .filter_map(CmeEvent::from_raw)
+        .filter(|e| parse_time(&e.start_time).is_some_and(|t| t >= since))
+        .collect();
+    out.sort_by_key(|e| parse_time(&e.start_time));
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_recorded_cmes() {
+        let payload: Value = serde_json::from_str(include_str!("../tests/fixtures/donki_cme.json")).unwrap();
+        let since = parse_time("2026-10-11T00:00Z").unwrap();
+        let events = events(&payload, since).unwrap();
+        let ids: Vec<&str> = events.iter().map(|e| e.activity_id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "2026-10-12T04:36:00-CME-001",
+                "2026-10-14T19:12:00-CME-001",
+                "2026-10-15T06:00:00-CME-001",
+                "2026-10-15T22:24:00-CME-001"
+            ]
+        );
+
+        // isMostAccurate важнее скорости
+        assert_eq!((events[0].speed, events[0].half_angle), (Some(480.0), Some(18.0)));
+        assert!(!events[0].earth_directed);
+
+        // Скорость строкой; прибытие к Земле из второго прогона ENLIL
+        assert_eq!(events[1].speed, Some(1350.0));
+        assert!(events[1].earth_directed);
+        assert_eq!(events[1].estimated_shock_arrival_time.as_deref(), Some("2026-10-16T21:00Z"));
+
+        // Без isMostAccurate — самый быстрый анализ; isEarthGB тоже удар по Земле
+        assert_eq!(events[2].speed, Some(640.0));
+        assert!(events[2].earth_directed);
+        assert_eq!(events[2].source_location, None);
+
+        assert_eq!(events[3].speed, None);
+        assert!(!events[3].earth_directed);
+
+        assert!(super::events(&serde_json::json!({}), since).is_err());
+    }
+}
@@ -0,0 +1,106 @@
+use serde_json::{json, Value};
+
+use crate::errors::ApiError;
+
+/// Уровень zstd: снимки пишутся редко, читаются чаще, а выше 3 выигрыш
+/// на JSON уже невелик
+const ZSTD_LEVEL: i32 = 3;
+
+/// Как хранить крупные снимки space_cache
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CacheCompression {
+    None,
+    Zstd,
+}
+
+impl CacheCompression {
+    /// Значение CACHE_COMPRESSION; пустое — без сжатия
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "" | "none" => Ok(Self::None),
+            "zstd" => Ok(Self::Zstd),
+            other => Err(format!("CACHE_COMPRESSION: expected zstd or none, got {:?}", other)),
+        }
+    }
+}
+
+/// Снимок в том виде, в каком он ложится в колонки payload и payload_compressed
+pub struct PackedPayload {
+    pub payload: Value,
+    pub compressed: Option<Vec<u8>>,
+}
+
+/// Сжимает снимок, если включено сжатие и JSON не короче `min_bytes`;
+/// в payload тогда остаётся заглушка с исходным размером
+pub fn pack(payload: Value, mode: CacheCompression, min_bytes: u64) -> PackedPayload {
+    let plain = PackedPayload { payload, compressed: None };
+    if mode == CacheCompression::None {
+        return plain;
+    }
+    let Ok(bytes) = serde_json::to_vec(&plain.payload) else {
+        return plain;
+    };
+    if (bytes.len() as u64) < min_bytes {
+        return plain;
+    }
+    match zstd::encode_all(bytes.as_slice(), ZSTD_LEVEL) {
+        Ok(compressed) => PackedPayload {
+            payload: json!({ "compressed": "zstd", "bytes": bytes.len() }),
+            compressed: Some(compressed),
+        },
+        Err(_) => plain,
+    }
+}
+
+/// Исходный снимок: распакованный payload_compressed или payload как есть
+pub fn unpack(payload: Value, compressed: Option<Vec<u8>>) -> Result<Value, String> {
+    let Some(compressed) = compressed else {
+        return Ok(payload);
+    };
+    let bytes = zstd::decode_all(compressed.as_slice()).map_err(|e| format!("zstd: {}", e))?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("compressed payload: {}", e))
+}
+
+/// Строка space_cache, снимок которой может храниться сжатым
+pub trait StoredPayload {
+    fn payload_slots(&mut self) -> (&mut Value, &mut Option<Vec<u8>>);
+}
+
+/// Строка с распакованным снимком в payload; все читатели space_cache
+/// пропускают строки через неё
+pub fn read_payload<R: StoredPayload>(mut row: R) -> Result<R, ApiError> {
+    let (payload, compressed) = row.payload_slots();
+    if compressed.is_some() {
+        *payload = unpack(std::mem::take(payload), compressed.take()).map_err(ApiError::internal)?;
+    }
+    Ok(row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packs_only_large_payloads() {
+        let small = json!({"title": "M31"});
+        let packed = pack(small.clone(), CacheCompression::Zstd, 1024);
+        assert_eq!(packed.payload, small);
+        assert!(packed.compressed.is_none());
+
+        let large = json!({"items": vec!["near earth object"; 500]});
+        let packed = pack(large.clone(), CacheCompression::Zstd, 1024);
+        let compressed = packed.compressed.clone().unwrap();
+        assert!(compressed.len() < serde_json::to_vec(&large).unwrap().len());
+        assert_eq!(packed.payload["compressed"], "zstd");
+        assert_eq!(unpack(packed.payload, Some(compressed)).unwrap(), large);
+
+        assert!(pack(large, CacheCompression::None, 0).compressed.is_none());
+    }
+
+    #[test]
+    fn parses_mode() {
+        assert_eq!(CacheCompression::parse(""), Ok(CacheCompression::None));
+        assert_eq!(CacheCompression::parse(" ZSTD "), Ok(CacheCompression::Zstd));
+        assert!(CacheCompression::parse("gzip").is_err());
+    }
+}
@@ -1,4 +1,13 @@
+use std::collections::HashMap;
 use std::env;
+use std::time::Duration;
+
+use crate::compression::CacheCompression;
+use crate::launches;
+use crate::mars;
+
+/// NORAD-номер МКС — спутник по умолчанию во всех ISS-эндпоинтах
+pub const ISS_NORAD_ID: i64 = 25544;
 
 /// Конфигурация приложения
 #[derive(Clone, Debug)]
@@ -6,40 +15,511 @@ pub struct Config {
     pub database_url: String,
     pub nasa_api_url: String,
     pub nasa_api_key: String,
-    pub where_iss_url: String,
+    /// WHERE_ISS_URL: основной адрес положений и запасные зеркала по порядку
+    pub where_iss_urls: Vec<String>,
+    /// NORAD-номера опрашиваемых спутников
+    pub satellites: Vec<i64>,
     pub fetch_every_seconds: u64,
     pub iss_every_seconds: u64,
     pub apod_every_seconds: u64,
     pub neo_every_seconds: u64,
     pub donki_every_seconds: u64,
+    /// Кэшируемые типы событий DONKI в верхнем регистре; известны ли они,
+    /// проверяет `feeds::donki_types`
+    pub donki_types: Vec<String>,
+    /// Окна запроса из DONKI_<TYPE>_DAYS по типу
+    pub donki_window_days: HashMap<String, u64>,
     pub spacex_every_seconds: u64,
+    /// Как часто дозагружать прошедшие запуски SpaceX в spacex_launches
+    pub spacex_past_every_seconds: u64,
+    pub astros_every_seconds: u64,
+    pub tle_every_seconds: u64,
+    pub epic_every_seconds: u64,
+    pub mars_photos_every_seconds: u64,
+    pub eonet_every_seconds: u64,
+    pub kp_every_seconds: u64,
+    pub solar_wind_every_seconds: u64,
+    /// Не меньше launches::LL2_MIN_INTERVAL_SECS
+    pub ll2_every_seconds: u64,
+    /// Ключ Launch Library 2; без него действует анонимный лимит
+    pub ll2_api_key: String,
+    /// Сколько запросов /neo/rest/v1/neo/{id} к NASA допускается в час
+    /// для неизвестных локально объектов
+    pub neo_lookups_per_hour: u64,
+    /// Обходить ли весь каталог NEO через /neo/rest/v1/neo/browse
+    pub neo_browse_enabled: bool,
+    pub neo_browse_every_seconds: u64,
+    /// Страниц каталога за один прогон
+    pub neo_browse_pages_per_run: u64,
+    /// Пауза между страницами каталога, чтобы не исчерпать лимит ключа
+    pub neo_browse_delay_ms: u64,
+    /// Марсоход, последние снимки которого кэшируются
+    pub mars_rover: &'static str,
+    /// Во сколько интервалов обновления источник считается устаревшим
+    pub stale_factor: f64,
+    /// После скольких ошибок фоновой задачи подряд /health сообщает degraded
+    pub task_failure_threshold: u32,
+    pub closest_max_window_hours: u64,
+    /// Размер страницы /osdr/list, если limit не передан
+    pub osdr_list_limit: u64,
+    /// Сколько страниц OSDR API проходить за одну синхронизацию
+    pub osdr_max_pages: u64,
+    /// Где искать поля датасета в ответе OSDR
+    pub osdr_keys: OsdrKeys,
+    /// Повторы запросов к upstream по умолчанию
+    pub http_retry: RetryPolicy,
+    /// Политики повторов отдельных источников по имени, см. `retry_policy`
+    pub http_retry_overrides: HashMap<String, RetryPolicy>,
+    /// Сайт или почта для связи в User-Agent исходящих запросов
+    pub service_contact: Option<String>,
+    /// Прокси для всех исходящих запросов; важнее HTTPS_PROXY / HTTP_PROXY
+    pub outbound_proxy_url: Option<String>,
+    /// Сколько /health отдаёт прошлый итог пробного запроса через прокси
+    pub proxy_check_every_seconds: u64,
+    /// Таймаут запросов к upstream по умолчанию
+    pub http_timeout: Duration,
+    /// Таймауты отдельных источников по имени, см. `http_timeout`
+    pub http_timeout_overrides: HashMap<String, Duration>,
+    /// После скольких неудачных запросов подряд к хосту запросы к нему
+    /// перестают отправляться
+    pub circuit_failure_threshold: u32,
+    /// Сколько секунд хост не опрашивается после размыкания
+    pub circuit_cooldown_seconds: u64,
+    /// Бюджет запросов к api.nasa.gov: размер ведра и пополнение в час
+    pub nasa_rate_capacity: u64,
+    pub nasa_rate_refill_per_hour: u64,
+    /// Сколько ждать жетона, прежде чем пропустить запрос
+    pub nasa_rate_max_wait_ms: u64,
+    /// Допустимое отклонение скорости от скользящей медианы, км/ч
+    pub anomaly_velocity_kmh: u64,
+    /// Допустимое отклонение высоты от скользящей медианы, км
+    pub anomaly_altitude_km: u64,
+    /// Наибольший разрыв между выборками, через который ещё интерполируем
+    pub interpolate_max_gap_secs: u64,
+    /// Пауза между запросами дозагрузки истории, чтобы не упереться в rate limit
+    pub backfill_delay_ms: u64,
+    /// Сколько дней хранить iss_fetch_log; 0 — хранить всегда
+    pub iss_retention_days: u64,
+    /// Сколько дней хранить space_cache; 0 — хранить всегда
+    pub space_cache_retention_days: u64,
+    /// Сжимать ли крупные снимки space_cache
+    pub cache_compression: CacheCompression,
+    /// Снимки не короче этого размера JSON, байт, хранятся сжатыми
+    pub cache_compress_min_bytes: u64,
+    /// Наибольший размер картинки APOD, которую качаем для /space/apod/image, байт
+    pub apod_media_max_bytes: u64,
+    pub cleanup_every_seconds: u64,
+    /// Определять страну или океан под МКС по встроенным контурам
+    pub geo_lookup_enabled: bool,
+}
+
+/// Кандидаты для каждого поля OSDR по порядку приоритета; допускаются
+/// пути через точку, например `metadata.study.title`
+#[derive(Clone, Debug)]
+pub struct OsdrKeys {
+    pub id: Vec<String>,
+    pub title: Vec<String>,
+    pub status: Vec<String>,
+    pub updated: Vec<String>,
+    pub organism: Vec<String>,
+    pub assay_type: Vec<String>,
+    pub mission: Vec<String>,
+}
+
+impl OsdrKeys {
+    /// OSDR_*_KEYS через запятую, иначе пути, встречающиеся в API поиска
+    /// и в API датасетов OSDR
+    pub fn from_env() -> Self {
+        Self {
+            id: parse_env_list(
+                "OSDR_ID_KEYS",
+                &["dataset_id", "id", "uuid", "studyId", "accession", "osdr_id"],
+            ),
+            title: parse_env_list("OSDR_TITLE_KEYS", &["title", "name", "label"]),
+            status: parse_env_list("OSDR_STATUS_KEYS", &["status", "state", "lifecycle"]),
+            updated: parse_env_list(
+                "OSDR_UPDATED_KEYS",
+                &["updated", "updated_at", "modified", "lastUpdated", "timestamp"],
+            ),
+            organism: parse_env_list(
+                "OSDR_ORGANISM_KEYS",
+                &["organism", "Organism", "metadata.organism", "characteristics.organism"],
+            ),
+            assay_type: parse_env_list(
+                "OSDR_ASSAY_TYPE_KEYS",
+                &[
+                    "assay_type",
+                    "Study Assay Technology Type",
+                    "metadata.study assay technology type",
+                    "assays.technology_type",
+                ],
+            ),
+            mission: parse_env_list(
+                "OSDR_MISSION_KEYS",
+                &[
+                    "mission.name",
+                    "mission",
+                    "Mission",
+                    "metadata.mission.name",
+                    "project_identifier",
+                    "Project Identifier",
+                    "metadata.project identifier",
+                ],
+            ),
+        }
+    }
+}
+
+/// DONKI_TYPES через запятую без учёта регистра, без повторов; пустое
+/// значение — FLR,CME
+pub fn parse_donki_types(raw: &str) -> Vec<String> {
+    let raw = if raw.trim().is_empty() { "FLR,CME" } else { raw };
+    let mut types: Vec<String> = Vec::new();
+    for part in raw.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let kind = part.to_ascii_uppercase();
+        if !types.contains(&kind) {
+            types.push(kind);
+        }
+    }
+    types
+}
+
+/// Окна типов из DONKI_<TYPE>_DAYS, например DONKI_SEP_DAYS=30; не меньше дня
+fn donki_window_days(types: &[String]) -> HashMap<String, u64> {
+    types
+        .iter()
+        .filter_map(|kind| {
+            let days = env::var(format!("DONKI_{}_DAYS", kind)).ok()?.trim().parse::<u64>().ok()?;
+            Some((kind.clone(), days.max(1)))
+        })
+        .collect()
+}
+
+/// Сколько раз пытаться и с какой паузы начинать экспоненциальный откат
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Всего попыток, включая первую; не меньше 1
+    pub attempts: u32,
+    pub base_delay_ms: u64,
 }
 
 impl Config {
     pub fn from_env() -> Result<Self, String> {
+        let database_url = env::var("DATABASE_URL").map_err(|_| "DATABASE_URL is required".to_string())?;
+        Self::with_database_url(database_url)
+    }
+
+    /// Всё, кроме адреса БД, из окружения
+    pub fn with_database_url(database_url: String) -> Result<Self, String> {
+        let http_retry = RetryPolicy {
+            attempts: parse_env_u64("HTTP_RETRY_MAX", 3).clamp(1, 10) as u32,
+            base_delay_ms: parse_env_u64("HTTP_RETRY_BASE_MS", 500),
+        };
+        let nasa_api_key = env::var("NASA_API_KEY").unwrap_or_default();
+        // Лимиты из документации api.nasa.gov: 1000 запросов в час на ключ,
+        // у DEMO_KEY — 30 в час
+        let nasa_hourly_limit = if nasa_api_key.is_empty() || nasa_api_key == "DEMO_KEY" { 30 } else { 1000 };
+        let seeded_retry = HashMap::from([
+            // Прежние настройки синхронизации OSDR продолжают действовать
+            (
+                "osdr".to_string(),
+                RetryPolicy {
+                    attempts: parse_env_u64("OSDR_RETRY_ATTEMPTS", 4).clamp(1, 10) as u32,
+                    base_delay_ms: parse_env_u64("OSDR_RETRY_BASE_DELAY_MS", 2000),
+                },
+            ),
+            // Каждая попытка расходует анонимный лимит LL2 в 15 запросов в час
+            ("launches_ll2".to_string(), RetryPolicy { attempts: 1, ..http_retry.clone() }),
+        ]);
+        let donki_types = parse_donki_types(&env::var("DONKI_TYPES").unwrap_or_default());
         Ok(Self {
-            database_url: env::var("DATABASE_URL")
-                .map_err(|_| "DATABASE_URL is required".to_string())?,
+            database_url,
             
             nasa_api_url: env::var("NASA_API_URL").unwrap_or_else(|_| {
                 "https://visualization.osdr.nasa.gov/biodata/api/v2/datasets/?format=json"
                     .to_string()
             }),
             
-            nasa_api_key: env::var("NASA_API_KEY").unwrap_or_default(),
+            nasa_api_key,
             
-            where_iss_url: env::var("WHERE_ISS_URL").unwrap_or_else(|_| {
-                "https://api.wheretheiss.at/v1/satellites/25544".to_string()
-            }),
+            where_iss_urls: parse_iss_urls(&env::var("WHERE_ISS_URL").unwrap_or_default())?,
             
+            satellites: parse_satellites(&env::var("SATELLITES").unwrap_or_default())?,
+
             fetch_every_seconds: parse_env_u64("FETCH_EVERY_SECONDS", 600),
             iss_every_seconds: parse_env_u64("ISS_EVERY_SECONDS", 120),
             apod_every_seconds: parse_env_u64("APOD_EVERY_SECONDS", 43200),
             neo_every_seconds: parse_env_u64("NEO_EVERY_SECONDS", 7200),
             donki_every_seconds: parse_env_u64("DONKI_EVERY_SECONDS", 3600),
+            donki_window_days: donki_window_days(&donki_types),
+            donki_types,
             spacex_every_seconds: parse_env_u64("SPACEX_EVERY_SECONDS", 3600),
+            spacex_past_every_seconds: parse_env_u64("SPACEX_PAST_EVERY_SECONDS", 86400),
+            astros_every_seconds: parse_env_u64("ASTROS_EVERY_SECONDS", 86400),
+            tle_every_seconds: parse_env_u64("TLE_EVERY_SECONDS", 21600),
+            epic_every_seconds: parse_env_u64("EPIC_EVERY_SECONDS", 21600),
+            mars_photos_every_seconds: parse_env_u64("MARS_PHOTOS_EVERY_SECONDS", 21600),
+            eonet_every_seconds: parse_env_u64("EONET_EVERY_SECONDS", 3600),
+            kp_every_seconds: parse_env_u64("KP_EVERY_SECONDS", 600),
+            solar_wind_every_seconds: parse_env_u64("SOLAR_WIND_EVERY_SECONDS", 600),
+            ll2_every_seconds: parse_env_u64("LL2_EVERY_SECONDS", 3600).max(launches::LL2_MIN_INTERVAL_SECS),
+            ll2_api_key: env::var("LL2_API_KEY").unwrap_or_default(),
+            neo_lookups_per_hour: parse_env_u64("NEO_LOOKUPS_PER_HOUR", 30),
+            neo_browse_enabled: parse_env_bool("NEO_BROWSE_ENABLED", false),
+            neo_browse_every_seconds: parse_env_u64("NEO_BROWSE_EVERY_SECONDS", 3600),
+            neo_browse_pages_per_run: parse_env_u64("NEO_BROWSE_PAGES_PER_RUN", 5).max(1),
+            neo_browse_delay_ms: parse_env_u64("NEO_BROWSE_DELAY_MS", 2000),
+            mars_rover: mars::parse_rover(&env::var("MARS_ROVER").unwrap_or_else(|_| "curiosity".into()))
+                .map_err(|e| format!("MARS_ROVER: {}", e))?,
+            stale_factor: parse_env_f64("STALE_FACTOR", 2.0).max(1.0),
+            task_failure_threshold: parse_env_u64("TASK_FAILURE_THRESHOLD", 3).min(u32::MAX as u64) as u32,
+            closest_max_window_hours: parse_env_u64("ISS_CLOSEST_MAX_WINDOW_HOURS", 24 * 31),
+            osdr_list_limit: parse_env_u64("OSDR_LIST_LIMIT", 20),
+            osdr_max_pages: parse_env_u64("OSDR_MAX_PAGES", 50),
+            osdr_keys: OsdrKeys::from_env(),
+            http_retry: http_retry.clone(),
+            http_retry_overrides: retry_overrides(env::vars(), &http_retry, seeded_retry),
+            service_contact: env::var("SERVICE_CONTACT").ok().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()),
+            outbound_proxy_url: parse_proxy_url(env::var("OUTBOUND_PROXY_URL").ok())?,
+            proxy_check_every_seconds: parse_env_u64("PROXY_CHECK_EVERY_SECONDS", 60),
+            http_timeout: parse_timeout("HTTP_TIMEOUT_SECONDS", env::var("HTTP_TIMEOUT_SECONDS").ok(), 30)?,
+            // Переменные <SOURCE>_TIMEOUT_SECONDS разбираются, когда известен
+            // реестр источников, см. `load_timeout_overrides`
+            http_timeout_overrides: default_timeouts(),
+            circuit_failure_threshold: parse_env_u64("CIRCUIT_FAILURE_THRESHOLD", 5).clamp(1, u32::MAX as u64) as u32,
+            circuit_cooldown_seconds: parse_env_u64("CIRCUIT_COOLDOWN_SECONDS", 60),
+            nasa_rate_capacity: parse_env_u64("NASA_RATE_CAPACITY", nasa_hourly_limit),
+            nasa_rate_refill_per_hour: parse_env_u64("NASA_RATE_REFILL_PER_HOUR", nasa_hourly_limit),
+            nasa_rate_max_wait_ms: parse_env_u64("NASA_RATE_MAX_WAIT_MS", 5000),
+            anomaly_velocity_kmh: parse_env_u64("ISS_ANOMALY_VELOCITY_KMH", 1000),
+            anomaly_altitude_km: parse_env_u64("ISS_ANOMALY_ALTITUDE_KM", 25),
+            interpolate_max_gap_secs: parse_env_u64("ISS_INTERPOLATE_MAX_GAP_SECS", 600),
+            backfill_delay_ms: parse_env_u64("ISS_BACKFILL_DELAY_MS", 1500),
+            iss_retention_days: parse_env_u64("ISS_RETENTION_DAYS", 0),
+            space_cache_retention_days: parse_env_u64("SPACE_CACHE_RETENTION_DAYS", 0),
+            cache_compression: CacheCompression::parse(&env::var("CACHE_COMPRESSION").unwrap_or_default())?,
+            cache_compress_min_bytes: parse_env_u64("CACHE_COMPRESS_MIN_BYTES", 64 * 1024),
+            apod_media_max_bytes: parse_env_u64("APOD_MEDIA_MAX_BYTES", 15 * 1024 * 1024),
+            cleanup_every_seconds: parse_env_u64("CLEANUP_EVERY_SECONDS", 3600),
+            geo_lookup_enabled: parse_env_bool("GEO_LOOKUP_ENABLED", false),
         })
     }
+
+    /// Политика повторов запросов источника: HTTP_RETRY_MAX_<SOURCE>
+    /// и HTTP_RETRY_BASE_MS_<SOURCE>, иначе общая
+    pub fn retry_policy(&self, source: &str) -> RetryPolicy {
+        self.http_retry_overrides.get(source).unwrap_or(&self.http_retry).clone()
+    }
+
+    /// Таймаут запроса источника: <SOURCE>_TIMEOUT_SECONDS, иначе общий
+    pub fn http_timeout(&self, source: &str) -> Duration {
+        self.http_timeout_overrides.get(source).copied().unwrap_or(self.http_timeout)
+    }
+
+    /// Таймауты из <SOURCE>_TIMEOUT_SECONDS для источников реестра `registered`
+    /// и запросов из REQUEST_SOURCES
+    pub fn load_timeout_overrides(&mut self, registered: &[&str]) -> Result<(), String> {
+        self.http_timeout_overrides = timeout_overrides(env::vars(), registered)?;
+        Ok(())
+    }
+
+    /// Устарел ли источник с интервалом обновления `interval`, данные которого
+    /// проверялись `age_sec` секунд назад; без данных источник устарел
+    pub fn is_stale(&self, interval: Duration, age_sec: Option<f64>) -> bool {
+        is_stale_age(age_sec, interval.as_secs(), self.stale_factor)
+    }
+
+    /// Cache-Control max-age ответов с данными источника: десятая часть
+    /// интервала обновления, от 1 секунды до часа
+    pub fn cache_max_age_secs(&self, interval: Duration) -> u64 {
+        (interval.as_secs() / 10).clamp(1, 3600)
+    }
+
+    /// Интервал опроса МКС
+    pub fn iss_interval(&self) -> Duration {
+        Duration::from_secs(self.iss_every_seconds)
+    }
+
+    /// URL wheretheiss.at для спутника по первому адресу WHERE_ISS_URL с
+    /// NORAD-номером: номер в конце заменяется на нужный, иначе дописывается.
+    /// None — все адреса ведут на фиксированные файлы вроде iss-now.json
+    pub fn satellite_url(&self, norad_id: i64) -> Option<String> {
+        let url = self.where_iss_urls.iter().find(|url| !is_fixed_file(url))?;
+        Some(satellite_url(url, norad_id))
+    }
+
+    /// Все зеркала, которые знают спутник, в порядке из WHERE_ISS_URL
+    pub fn satellite_mirror_urls(&self, norad_id: i64) -> Vec<String> {
+        self.where_iss_urls.iter().filter_map(|url| mirror_url(url, norad_id)).collect()
+    }
+}
+
+/// Адрес, путь которого кончается файлом (open-notify iss-now.json), отдаёт
+/// только МКС; остальные адресуют спутник NORAD-номером, как wheretheiss.at
+fn mirror_url(url: &str, norad_id: i64) -> Option<String> {
+    match is_fixed_file(url) {
+        true => (norad_id == ISS_NORAD_ID).then(|| url.to_string()),
+        false => Some(satellite_url(url, norad_id)),
+    }
+}
+
+fn is_fixed_file(url: &str) -> bool {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.path_segments()?.next_back().map(|last| last.contains('.')))
+        .unwrap_or(false)
+}
+
+/// Адреса через запятую; пустое значение — только wheretheiss.at
+fn parse_iss_urls(raw: &str) -> Result<Vec<String>, String> {
+    let mut urls = Vec::new();
+    for part in raw.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        match reqwest::Url::parse(part) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") && url.has_host() => urls.push(part.to_string()),
+            _ => return Err(format!("WHERE_ISS_URL: invalid URL {:?}", part)),
+        }
+    }
+    if urls.is_empty() {
+        urls.push("https://api.wheretheiss.at/v1/satellites/25544".to_string());
+    }
+    Ok(urls)
+}
+
+fn satellite_url(where_iss_url: &str, norad_id: i64) -> String {
+    let base = where_iss_url.trim_end_matches('/');
+    let base = match base.rsplit_once('/') {
+        Some((prefix, last)) if last.parse::<i64>().is_ok() => prefix,
+        _ => base,
+    };
+    format!("{}/{}", base, norad_id)
+}
+
+fn is_stale_age(age_sec: Option<f64>, interval_secs: u64, factor: f64) -> bool {
+    match age_sec {
+        Some(age) => age > interval_secs as f64 * factor,
+        None => true,
+    }
+}
+
+/// Список NORAD-номеров через запятую; пустое значение — только МКС
+fn parse_satellites(raw: &str) -> Result<Vec<i64>, String> {
+    let mut ids = Vec::new();
+    for part in raw.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        match part.parse::<i64>() {
+            Ok(id) if id > 0 => {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+            _ => return Err(format!("SATELLITES: invalid NORAD id {:?}", part)),
+        }
+    }
+    if ids.is_empty() {
+        ids.push(ISS_NORAD_ID);
+    }
+    Ok(ids)
+}
+
+/// Политики источников из переменных HTTP_RETRY_MAX_<SOURCE>
+/// и HTTP_RETRY_BASE_MS_<SOURCE> поверх `seeded`; имя источника приводится
+/// к нижнему регистру, нечисловые значения пропускаются
+fn retry_overrides(
+    vars: impl IntoIterator<Item = (String, String)>,
+    default: &RetryPolicy,
+    mut seeded: HashMap<String, RetryPolicy>,
+) -> HashMap<String, RetryPolicy> {
+    for (key, value) in vars {
+        let Ok(n) = value.trim().parse::<u64>() else {
+            continue;
+        };
+        if let Some(source) = key.strip_prefix("HTTP_RETRY_MAX_") {
+            seeded.entry(source.to_lowercase()).or_insert_with(|| default.clone()).attempts =
+                n.clamp(1, 10) as u32;
+        } else if let Some(source) = key.strip_prefix("HTTP_RETRY_BASE_MS_") {
+            seeded.entry(source.to_lowercase()).or_insert_with(|| default.clone()).base_delay_ms = n;
+        }
+    }
+    seeded
+}
+
+/// OUTBOUND_PROXY_URL: http(s)-адрес с хостом; пустое значение — прокси нет
+fn parse_proxy_url(raw: Option<String>) -> Result<Option<String>, String> {
+    let Some(raw) = raw.map(|s| s.trim().to_string()).filter(|s| !s.is_empty()) else {
+        return Ok(None);
+    };
+    match reqwest::Url::parse(&raw) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") && url.has_host() => Ok(Some(raw)),
+        _ => Err(format!(
+            "OUTBOUND_PROXY_URL must be an http:// or https:// URL, got {:?}",
+            crate::http::redact_proxy_url(&raw)
+        )),
+    }
+}
+
+/// Больше десяти минут запрос к upstream не ждём
+const MAX_TIMEOUT_SECONDS: u64 = 600;
+
+/// Таймаут из переменной `key`; ноль, больше MAX_TIMEOUT_SECONDS или не
+/// число — ошибка конфигурации, а не молчаливое значение по умолчанию
+fn parse_timeout(key: &str, raw: Option<String>, default: u64) -> Result<Duration, String> {
+    let Some(raw) = raw else {
+        return Ok(Duration::from_secs(default));
+    };
+    match raw.trim().parse::<u64>() {
+        Ok(secs) if (1..=MAX_TIMEOUT_SECONDS).contains(&secs) => Ok(Duration::from_secs(secs)),
+        _ => Err(format!(
+            "{} must be a whole number of seconds between 1 and {}, got {:?}",
+            key, MAX_TIMEOUT_SECONDS, raw
+        )),
+    }
+}
+
+/// Запросы к upstream вне реестра space_cache со своим таймаутом
+pub const REQUEST_SOURCES: &[&str] = &[
+    "iss",
+    "iss_backfill",
+    "apod_media",
+    "apod_archive",
+    "spacex_past",
+    "neo_browse",
+    "neo_lookup",
+    "osdr",
+];
+
+/// Встроенные таймауты: МКС опрашивается часто и ждёт меньше, картинки APOD
+/// бывают большими
+fn default_timeouts() -> HashMap<String, Duration> {
+    HashMap::from([
+        ("iss".to_string(), Duration::from_secs(20)),
+        ("iss_backfill".to_string(), Duration::from_secs(20)),
+        ("apod_media".to_string(), Duration::from_secs(60)),
+    ])
+}
+
+/// Таймауты источников из <SOURCE>_TIMEOUT_SECONDS поверх встроенных. Чужие
+/// переменные с тем же суффиксом пропускаются с предупреждением и запуск не
+/// ломают; плохое значение у известного источника — ошибка
+fn timeout_overrides(
+    vars: impl IntoIterator<Item = (String, String)>,
+    registered: &[&str],
+) -> Result<HashMap<String, Duration>, String> {
+    let mut overrides = default_timeouts();
+    for (key, value) in vars {
+        let Some(source) = key.strip_suffix("_TIMEOUT_SECONDS") else {
+            continue;
+        };
+        if source.is_empty() || source == "HTTP" {
+            continue;
+        }
+        let source = source.to_lowercase();
+        if !registered.contains(&source.as_str()) && !REQUEST_SOURCES.contains(&source.as_str()) {
+            tracing::warn!("{} ignored: {:?} is not a known source", key, source);
+            continue;
+        }
+        let timeout = parse_timeout(&key, Some(value), 0)?;
+        overrides.insert(source, timeout);
+    }
+    Ok(overrides)
 }
 
 fn parse_env_u64(key: &str, default: u64) -> u64 {
@@ -49,3 +529,152 @@ fn parse_env_u64(key: &str, default: u64) -> u64 {
         .unwrap_or(default)
 }
 
+fn parse_env_f64(key: &str, default: f64) -> f64 {
+    env::var(key)
+        .ok()
+        .and_then(|s| s.trim().parse::<f64>().ok())
+        .filter(|v| v.is_finite())
+        .unwrap_or(default)
+}
+
+/// Список через запятую; пустое или отсутствующее значение — default
+fn parse_env_list(key: &str, default: &[&str]) -> Vec<String> {
+    let raw = env::var(key).unwrap_or_default();
+    let items: Vec<String> = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect();
+    if items.is_empty() {
+        default.iter().map(|s| s.to_string()).collect()
+    } else {
+        items
+    }
+}
+
+fn parse_env_bool(key: &str, default: bool) -> bool {
+    match env::var(key).map(|s| s.trim().to_ascii_lowercase()) {
+        Ok(s) if matches!(s.as_str(), "1" | "true" | "yes" | "on") => true,
+        Ok(s) if matches!(s.as_str(), "0" | "false" | "no" | "off") => false,
+        _ => default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn satellites_list() {
+        assert_eq!(parse_satellites("").unwrap(), vec![ISS_NORAD_ID]);
+        assert_eq!(parse_satellites(" 25544, 48274 ,25544,").unwrap(), vec![25544, 48274]);
+        assert!(parse_satellites("25544,abc").is_err());
+        assert!(parse_satellites("-1").is_err());
+    }
+
+    #[test]
+    fn satellite_url_replaces_trailing_id() {
+        let iss = "https://api.wheretheiss.at/v1/satellites/25544";
+        assert_eq!(satellite_url(iss, 48274), "https://api.wheretheiss.at/v1/satellites/48274");
+        assert_eq!(
+            satellite_url("https://api.wheretheiss.at/v1/satellites/", 25544),
+            iss
+        );
+    }
+
+    #[test]
+    fn iss_mirrors_in_order() {
+        let urls = parse_iss_urls(
+            " https://api.wheretheiss.at/v1/satellites/25544 , http://api.open-notify.org/iss-now.json,",
+        )
+        .unwrap();
+        assert_eq!(urls.len(), 2);
+        assert_eq!(mirror_url(&urls[1], ISS_NORAD_ID).as_deref(), Some("http://api.open-notify.org/iss-now.json"));
+        assert_eq!(mirror_url(&urls[1], 48274), None);
+        assert_eq!(
+            mirror_url(&urls[0], 48274).as_deref(),
+            Some("https://api.wheretheiss.at/v1/satellites/48274")
+        );
+        assert_eq!(parse_iss_urls("").unwrap().len(), 1);
+        assert!(parse_iss_urls("https://ok.example/1,not a url").is_err());
+
+        // Фиксированный файл первым не мешает адресовать спутник по номеру
+        let mut config = Config::with_database_url(String::new()).unwrap();
+        config.where_iss_urls = urls.into_iter().rev().collect();
+        assert_eq!(
+            config.satellite_url(48274).as_deref(),
+            Some("https://api.wheretheiss.at/v1/satellites/48274")
+        );
+        config.where_iss_urls.truncate(1);
+        assert_eq!(config.satellite_url(ISS_NORAD_ID), None);
+    }
+
+    #[test]
+    fn timeouts_by_source_reject_zero_and_absurd() {
+        let vars = |pairs: &[(&str, &str)]| {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<Vec<_>>()
+        };
+        let registered = ["neo", "tle"];
+        let overrides = timeout_overrides(
+            vars(&[
+                ("ISS_TIMEOUT_SECONDS", "5"),
+                ("NEO_TIMEOUT_SECONDS", "60"),
+                ("OSDR_TIMEOUT_SECONDS", "90"),
+                ("HTTP_TIMEOUT_SECONDS", "10"),
+            ]),
+            &registered,
+        )
+        .unwrap();
+        assert_eq!(overrides["iss"], Duration::from_secs(5));
+        assert_eq!(overrides["neo"], Duration::from_secs(60));
+        assert_eq!(overrides["osdr"], Duration::from_secs(90));
+        assert_eq!(overrides["apod_media"], Duration::from_secs(60));
+        assert!(!overrides.contains_key("http"));
+
+        let err = timeout_overrides(vars(&[("NEO_TIMEOUT_SECONDS", "0")]), &registered).unwrap_err();
+        assert!(err.starts_with("NEO_TIMEOUT_SECONDS must be"), "{}", err);
+        assert!(timeout_overrides(vars(&[("TLE_TIMEOUT_SECONDS", "601")]), &registered).is_err());
+        // Переменные других программ не мешают запуску
+        let overrides =
+            timeout_overrides(vars(&[("PGCONNECT_TIMEOUT_SECONDS", "soon"), ("TLE_TIMEOUT_SECONDS", "15")]), &registered)
+                .unwrap();
+        assert!(!overrides.contains_key("pgconnect"));
+        assert_eq!(overrides["tle"], Duration::from_secs(15));
+        assert!(parse_timeout("HTTP_TIMEOUT_SECONDS", Some("soon".into()), 30).is_err());
+        assert_eq!(parse_timeout("HTTP_TIMEOUT_SECONDS", None, 30), Ok(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn retry_overrides_by_source() {
+        let default = RetryPolicy { attempts: 3, base_delay_ms: 500 };
+        let seeded = HashMap::from([("osdr".to_string(), RetryPolicy { attempts: 4, base_delay_ms: 2000 })]);
+        let vars = [
+            ("HTTP_RETRY_MAX_APOD", "5"),
+            ("HTTP_RETRY_BASE_MS_OSDR", "100"),
+            ("HTTP_RETRY_MAX_TLE", "99"),
+            ("HTTP_RETRY_MAX_EPIC", "many"),
+            ("HTTP_RETRY_MAX", "7"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        let overrides = retry_overrides(vars, &default, seeded);
+        assert_eq!((overrides["apod"].attempts, overrides["apod"].base_delay_ms), (5, 500));
+        assert_eq!((overrides["osdr"].attempts, overrides["osdr"].base_delay_ms), (4, 100));
+        assert_eq!(overrides["tle"].attempts, 10);
+        assert_eq!(overrides.len(), 3);
+    }
+
+    #[test]
+    fn donki_types_list() {
+        assert_eq!(parse_donki_types(""), vec!["FLR", "CME"]);
+        assert_eq!(parse_donki_types(" sep, FLR,Sep "), vec!["SEP", "FLR"]);
+    }
+
+    #[test]
+    fn staleness_uses_interval_and_factor() {
+        assert!(!is_stale_age(Some(100.0), 60, 2.0));
+        assert!(!is_stale_age(Some(120.0), 60, 2.0));
+        assert!(is_stale_age(Some(121.0), 60, 2.0));
+        assert!(is_stale_age(None, 60, 2.0));
+    }
+}
@@ -0,0 +1,106 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// Открытое природное событие EONET с точкой последнего наблюдения
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EonetEvent {
+    pub id: String,
+    pub title: String,
+    pub categories: Vec<String>,
+    /// Дата последней геометрии события
+    pub date: Option<String>,
+    pub lat: f64,
+    pub lon: f64,
+    /// Точка взята из первой вершины полигона, а не из Point
+    pub approximate: bool,
+    pub link: Option<String>,
+}
+
+/// События из ответа /api/v3/events; закрытые и события без координат пропускаются
+pub fn events(payload: &Value) -> Result<Vec<EonetEvent>, String> {
+    let items = payload
+        .get("events")
+        .and_then(Value::as_array)
+        .ok_or("missing events array")?;
+    Ok(items.iter().filter_map(event).collect())
+}
+
+fn event(item: &Value) -> Option<EonetEvent> {
+    if item.get("closed").is_some_and(|c| !c.is_null()) {
+        return None;
+    }
+    let text = |key: &str| item.get(key).and_then(Value::as_str).map(str::to_string);
+    // Геометрии приходят по возрастанию даты, но порядок не гарантирован
+    let geometry = item
+        .get("geometry")?
+        .as_array()?
+        .iter()
+        .max_by_key(|g| g.get("date").and_then(Value::as_str))?;
+    let (lon, lat, approximate) = match geometry.get("type")?.as_str()? {
+        "Point" => {
+            let (lon, lat) = coordinate(geometry.get("coordinates")?)?;
+            (lon, lat, false)
+        }
+        "Polygon" => {
+            let (lon, lat) = coordinate(geometry.pointer("/coordinates/0/0")?)?;
+            (lon, lat, true)
+        }
+        _ => return None,
+    };
+    let categories = item
+        .get("categories")
+        .and_then(Value::as_array)
+        .map(|cs| {
+            cs.iter()
+                .filter_map(|c| c.get("title").and_then(Value::as_str).map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(EonetEvent {
+        id: text("id")?,
+        title: text("title").unwrap_or_default(),
+        categories,
+        date: geometry.get("date").and_then(Value::as_str).map(str::to_string),
+        lat,
+        lon,
+        approximate,
+        link: text("link"),
+    })
+}
+
+/// GeoJSON-пара [lon, lat]
+fn coordinate(value: &Value) -> Option<(f64, f64)> {
+    let pair = value.as_array()?;
+    Some((pair.first()?.as_f64()?, pair.get(1)?.as_f64()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn takes_latest_point_and_flags_polygons() {
+        let payload = json!({"events": [
+            {"id": "EONET_1", "title": "Storm", "closed": null,
+             "categories": [{"id": "severeStorms", "title": "Severe Storms"}],
+             "geometry": [
+                {"date": "2026-10-14T00:00:00Z", "type": "Point", "coordinates": [-60.0, 15.0]},
+                {"date": "2026-10-15T00:00:00Z", "type": "Point", "coordinates": [-62.5, 16.5]}
+             ]},
+            {"id": "EONET_2", "title": "Fire",
+             "geometry": [{"date": "2026-10-13T00:00:00Z", "type": "Polygon",
+                           "coordinates": [[[30.0, 50.0], [31.0, 50.0], [31.0, 51.0]]]}]},
+            {"id": "EONET_3", "title": "Old", "closed": "2026-10-01T00:00:00Z",
+             "geometry": [{"date": "2026-09-30T00:00:00Z", "type": "Point", "coordinates": [0.0, 0.0]}]},
+            {"id": "EONET_4", "title": "No geometry", "geometry": []}
+        ]});
+        let events = events(&payload).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!((events[0].lat, events[0].lon), (16.5, -62.5));
+        assert_eq!(events[0].categories, vec!["Severe Storms".to_string()]);
+        assert!(!events[0].approximate);
+        assert_eq!((events[1].lat, events[1].lon), (50.0, 30.0));
+        assert!(events[1].approximate);
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Архив снимков EPIC; файлы доступны без ключа API
+const EPIC_ARCHIVE_URL: &str = "https://epic.gsfc.nasa.gov/archive/natural";
+
+/// Кадр из ответа EPIC /api/natural/images
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EpicImage {
+    pub identifier: String,
+    pub image: String,
+    pub caption: Option<String>,
+    /// Время съёмки, UTC
+    pub date: NaiveDateTime,
+}
+
+impl EpicImage {
+    /// Самый поздний кадр снимка; кадры без image или с неразборчивой датой пропускаются
+    pub fn latest(payload: &Value) -> Result<Self, String> {
+        let frames = payload.as_array().ok_or("expected an array of images")?;
+        frames
+            .iter()
+            .filter_map(Self::from_frame)
+            .max_by_key(|f| f.date)
+            .ok_or_else(|| "no images in payload".to_string())
+    }
+
+    fn from_frame(frame: &Value) -> Option<Self> {
+        let text = |key: &str| frame.get(key).and_then(Value::as_str).map(str::to_string);
+        let date = NaiveDateTime::parse_from_str(&text("date")?, "%Y-%m-%d %H:%M:%S").ok()?;
+        Some(Self {
+            identifier: text("identifier").unwrap_or_default(),
+            image: text("image")?,
+            caption: text("caption"),
+            date,
+        })
+    }
+
+    /// URL файла в архиве: `png`, `jpg` или `thumbs` (jpg-превью)
+    pub fn archive_url(&self, kind: &str) -> String {
+        let ext = if kind == "png" { "png" } else { "jpg" };
+        format!(
+            "{}/{}/{}/{}.{}",
+            EPIC_ARCHIVE_URL,
+            self.date.format("%Y/%m/%d"),
+            kind,
+            self.image,
+            ext
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn builds_archive_url_for_latest_frame() {
+        let payload = json!([
+            {"identifier": "20261015003633", "image": "epic_1b_20261015003633", "date": "2026-10-15 00:31:45"},
+            {"identifier": "20261015221122", "image": "epic_1b_20261015221122", "date": "2026-10-15 22:06:30",
+             "caption": "This image was taken by NASA's EPIC camera"},
+            {"identifier": "broken", "image": "x", "date": "yesterday"}
+        ]);
+        let latest = EpicImage::latest(&payload).unwrap();
+        assert_eq!(latest.identifier, "20261015221122");
+        assert_eq!(
+            latest.archive_url("png"),
+            "https://epic.gsfc.nasa.gov/archive/natural/2026/10/15/png/epic_1b_20261015221122.png"
+        );
+        assert_eq!(
+            latest.archive_url("thumbs"),
+            "https://epic.gsfc.nasa.gov/archive/natural/2026/10/15/thumbs/epic_1b_20261015221122.jpg"
+        );
+
+        assert!(EpicImage::latest(&json!([])).is_err());
+        assert!(EpicImage::latest(&json!({"error": "rate limited"})).is_err());
+    }
+}
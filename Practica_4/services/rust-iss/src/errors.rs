@@ -1,5 +1,5 @@
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -8,13 +8,13 @@ use std::fmt;
 
 /// Единый формат ошибок для всего приложения
 /// Всегда возвращает HTTP 200 с ok: false для предсказуемости
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ApiError {
     pub ok: bool,
     pub error: ErrorDetails,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ErrorDetails {
     pub code: String,
     pub message: String,
@@ -41,10 +41,30 @@ impl ApiError {
         Self::new(format!("UPSTREAM_{}", status), message)
     }
 
+    /// Upstream ответил успешно, но тело не удалось разобрать
+    pub fn invalid_payload(message: impl Into<String>) -> Self {
+        Self::new("UPSTREAM_INVALID_PAYLOAD", message)
+    }
+
     pub fn not_found(message: impl Into<String>) -> Self {
         Self::new("NOT_FOUND", message)
     }
 
+    /// Операция уже выполняется
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new("CONFLICT", message)
+    }
+
+    /// Исчерпан лимит запросов
+    pub fn rate_limited(message: impl Into<String>) -> Self {
+        Self::new("RATE_LIMITED", message)
+    }
+
+    /// HTTP-статус upstream, если ошибка создана через `upstream`
+    pub fn upstream_status(&self) -> Option<u16> {
+        self.error.code.strip_prefix("UPSTREAM_")?.parse().ok()
+    }
+
     pub fn internal(message: impl Into<String>) -> Self {
         Self::new("INTERNAL_ERROR", message)
     }
@@ -81,6 +101,44 @@ impl From<sqlx::Error> for ApiError {
     }
 }
 
+/// Сетевая причина ошибки reqwest: DNS, TLS, таймаут, обрыв соединения.
+/// hyper и TLS-бэкенд не отдают типов наружу, поэтому DNS и TLS узнаются
+/// по тексту цепочки source, а обрыв — по io::ErrorKind. Текст самой ошибки
+/// reqwest не смотрится: в нём URL запроса вместе с api_key и прочим query
+fn network_error_code(err: &reqwest::Error) -> Option<&'static str> {
+    use std::io::ErrorKind;
+
+    let mut texts = Vec::new();
+    let mut io_kind = None;
+    let mut source = std::error::Error::source(err);
+    while let Some(e) = source {
+        if let Some(io) = e.downcast_ref::<std::io::Error>() {
+            io_kind = io_kind.or(Some(io.kind()));
+        }
+        texts.push(e.to_string().to_lowercase());
+        source = e.source();
+    }
+    let mentions = |words: &[&str]| texts.iter().any(|t| words.iter().any(|w| t.contains(w)));
+
+    if err.is_timeout() || io_kind == Some(ErrorKind::TimedOut) {
+        Some(if err.is_connect() { "UPSTREAM_CONNECT_TIMEOUT" } else { "UPSTREAM_READ_TIMEOUT" })
+    } else if mentions(&["dns error", "failed to lookup address"]) {
+        Some("UPSTREAM_DNS")
+    } else if mentions(&["ssl", "tls", "certificate"]) {
+        Some("UPSTREAM_TLS")
+    } else if matches!(
+        io_kind,
+        Some(ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted | ErrorKind::BrokenPipe | ErrorKind::UnexpectedEof)
+    ) || mentions(&["connection closed before message completed"])
+    {
+        Some("UPSTREAM_CONNECTION_RESET")
+    } else if err.is_connect() {
+        Some("UPSTREAM_CONNECT_FAILED")
+    } else {
+        None
+    }
+}
+
 /// Конвертация reqwest ошибок
 impl From<reqwest::Error> for ApiError {
     fn from(err: reqwest::Error) -> Self {
@@ -88,6 +146,8 @@ impl From<reqwest::Error> for ApiError {
         
         if let Some(status) = err.status() {
             ApiError::upstream(status.as_u16(), err.to_string())
+        } else if let Some(code) = network_error_code(&err) {
+            ApiError::new(code, err.to_string())
         } else {
             ApiError::internal(format!("HTTP client error: {}", err))
         }
@@ -124,3 +184,118 @@ pub fn ok<T: Serialize>(data: T) -> ApiResult<T> {
     Ok(Json(ApiSuccess::new(data)))
 }
 
+/// Успешный ответ с ETag и Cache-Control; если клиент прислал совпадающий
+/// If-None-Match — 304 без тела
+#[derive(Debug)]
+pub struct Cached<T: Serialize> {
+    etag: String,
+    max_age_secs: u64,
+    not_modified: bool,
+    body: ApiSuccess<T>,
+}
+
+impl<T: Serialize> IntoResponse for Cached<T> {
+    fn into_response(self) -> Response {
+        let mut response = if self.not_modified {
+            StatusCode::NOT_MODIFIED.into_response()
+        } else {
+            Json(self.body).into_response()
+        };
+        let headers = response.headers_mut();
+        if let Ok(etag) = HeaderValue::from_str(&self.etag) {
+            headers.insert(header::ETAG, etag);
+        }
+        if let Ok(cache) = HeaderValue::from_str(&format!("max-age={}", self.max_age_secs)) {
+            headers.insert(header::CACHE_CONTROL, cache);
+        }
+        response
+    }
+}
+
+pub type CachedResult<T> = Result<Cached<T>, ApiError>;
+
+/// Успешный ответ с ETag; `tag` — версия данных без кавычек
+pub fn ok_with_etag<T: Serialize>(
+    data: T,
+    tag: &str,
+    max_age_secs: u64,
+    request: &HeaderMap,
+) -> CachedResult<T> {
+    let etag = format!("\"{}\"", tag);
+    let not_modified = request
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| etag_matches(v, &etag));
+    Ok(Cached { etag, max_age_secs, not_modified, body: ApiSuccess::new(data) })
+}
+
+/// Сравнение If-None-Match: список через запятую, `*`, слабые W/-теги
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_if_none_match_lists() {
+        assert!(etag_matches("\"a\"", "\"a\""));
+        assert!(etag_matches("\"x\", W/\"a\"", "\"a\""));
+        assert!(etag_matches("*", "\"a\""));
+        assert!(!etag_matches("\"ab\"", "\"a\""));
+    }
+
+    #[test]
+    fn not_modified_has_empty_body() {
+        let mut request = HeaderMap::new();
+        request.insert(header::IF_NONE_MATCH, HeaderValue::from_static("\"v1\""));
+        let data = serde_json::json!({ "n": 1 });
+        let response = ok_with_etag(data.clone(), "v1", 30, &request).unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], "\"v1\"");
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "max-age=30");
+
+        let fresh = ok_with_etag(data, "v2", 30, &request).unwrap().into_response();
+        assert_eq!(fresh.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn classifies_network_failures() {
+        use std::time::Duration;
+        use tokio::io::AsyncWriteExt;
+
+        // Отвечает через 300 мс и обрывает тело на середине
+        let slow = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = slow.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = slow.accept().await {
+                tokio::spawn(async move {
+                    tokio::time::sleep(Duration::from_millis(300)).await;
+                    let _ = socket.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 10\r\n\r\nabc").await;
+                });
+            }
+        });
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let client = reqwest::Client::new();
+        let code = |err: reqwest::Error| ApiError::from(err).error.code;
+
+        let cases = [
+            (client.get("http://nonexistent.invalid/"), "UPSTREAM_DNS"),
+            (client.get(format!("https://{}/", addr)), "UPSTREAM_TLS"),
+            (client.get(format!("http://{}/", addr)).timeout(Duration::from_millis(50)), "UPSTREAM_READ_TIMEOUT"),
+            (client.get(format!("http://{}/", closed)), "UPSTREAM_CONNECT_FAILED"),
+            // Слова из URL на классификацию не влияют
+            (client.get(format!("http://{}/tls?api_key=ssl", closed)), "UPSTREAM_CONNECT_FAILED"),
+        ];
+        for (request, expected) in cases {
+            assert_eq!(code(request.send().await.unwrap_err()), expected);
+        }
+        let truncated = client.get(format!("http://{}/", addr)).send().await.unwrap().text().await.unwrap_err();
+        assert_eq!(code(truncated), "UPSTREAM_CONNECTION_RESET");
+    }
+}
+
@@ -0,0 +1,626 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+use crate::astros::AstrosSummary;
+use crate::eonet;
+use crate::epic::EpicImage;
+use crate::gst;
+use crate::kp;
+use crate::launches::{self, LL2_MIN_INTERVAL_SECS, LL2_REQUESTS_PER_HOUR};
+use crate::mars;
+use crate::neo;
+use crate::ratelimit::RateLimiter;
+use crate::solarwind;
+use crate::config::Config;
+use crate::errors::ApiError;
+use crate::sources::SpaceSource;
+use crate::spacex;
+use crate::{fetch_json, fetch_json_with_headers, fetch_tle, record_fetch_error, store_neo_objects, AppState, Fetched};
+
+/// Встроенные источники space_cache в порядке вывода; типы DONKI — из DONKI_TYPES
+pub fn builtin(donki_types: &[DonkiType]) -> Vec<Arc<dyn SpaceSource>> {
+    let mut sources: Vec<Arc<dyn SpaceSource>> = vec![
+        Arc::new(JsonFeed {
+            name: "apod",
+            url: "https://api.nasa.gov/planetary/apod",
+            interval: |cfg| cfg.apod_every_seconds,
+            query: |st| nasa_query(st, vec![("thumbs", "true".to_string())]),
+            identity_key: None,
+            headline: |p| pick(p, &["title", "url", "media_type", "date"]),
+        }),
+        Arc::new(NeoFeed),
+    ];
+    sources.extend(
+        donki_types
+            .iter()
+            .map(|t| Arc::new(DonkiFeed { kind: t.kind, window_days: t.window_days }) as Arc<dyn SpaceSource>),
+    );
+    let rest: Vec<Arc<dyn SpaceSource>> = vec![
+        Arc::new(JsonFeed {
+            name: "spacex",
+            url: "https://api.spacexdata.com/v4/launches/next",
+            interval: |cfg| cfg.spacex_every_seconds,
+            query: |_| Vec::new(),
+            identity_key: None,
+            headline: |p| pick(p, &["name", "date_utc", "flight_number"]),
+        }),
+        Arc::new(JsonFeed {
+            name: "spacex_upcoming",
+            url: "https://api.spacexdata.com/v4/launches/upcoming",
+            interval: |cfg| cfg.spacex_every_seconds,
+            query: |_| Vec::new(),
+            identity_key: Some("id"),
+            headline: spacex_upcoming_headline,
+        }),
+        Arc::new(AstrosFeed),
+        Arc::new(TleFeed),
+        Arc::new(JsonFeed {
+            name: "epic",
+            url: "https://api.nasa.gov/EPIC/api/natural/images",
+            interval: |cfg| cfg.epic_every_seconds,
+            query: |st| nasa_query(st, Vec::new()),
+            identity_key: Some("identifier"),
+            headline: epic_headline,
+        }),
+        Arc::new(MarsPhotosFeed),
+        Arc::new(JsonFeed {
+            name: "eonet",
+            url: "https://eonet.gsfc.nasa.gov/api/v3/events",
+            interval: |cfg| cfg.eonet_every_seconds,
+            query: |_| vec![("status", "open".to_string())],
+            identity_key: Some("id"),
+            headline: eonet_headline,
+        }),
+        Arc::new(JsonFeed {
+            name: "donki_notifications",
+            url: "https://api.nasa.gov/DONKI/notifications",
+            interval: |cfg| cfg.donki_every_seconds,
+            query: |st| nasa_query(st, vec![("type", "all".to_string())]),
+            identity_key: Some("messageID"),
+            headline: |p| events_headline(p, "messageIssueTime", &["messageType"]),
+        }),
+        Arc::new(JsonFeed {
+            name: "kp",
+            url: "https://services.swpc.noaa.gov/json/planetary_k_index_1m.json",
+            interval: |cfg| cfg.kp_every_seconds,
+            query: |_| Vec::new(),
+            identity_key: Some("time_tag"),
+            headline: kp_headline,
+        }),
+        Arc::new(JsonFeed {
+            name: "solar_wind",
+            url: "https://services.swpc.noaa.gov/products/solar-wind/plasma-7-day.json",
+            interval: |cfg| cfg.solar_wind_every_seconds,
+            query: |_| Vec::new(),
+            identity_key: None,
+            headline: solar_wind_headline,
+        }),
+        Arc::new(Ll2Feed::new()),
+    ];
+    sources.extend(rest);
+    sources
+}
+
+/// JSON-лента с постоянным URL; параметры запроса строятся при каждом вызове
+struct JsonFeed {
+    name: &'static str,
+    url: &'static str,
+    interval: fn(&Config) -> u64,
+    query: fn(&AppState) -> Vec<(&'static str, String)>,
+    identity_key: Option<&'static str>,
+    headline: fn(&Value) -> Value,
+}
+
+impl SpaceSource for JsonFeed {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn interval(&self, cfg: &Config) -> Duration {
+        Duration::from_secs((self.interval)(cfg))
+    }
+
+    fn fetch<'a>(&'a self, st: &'a AppState) -> BoxFuture<'a, Result<Option<Fetched>, ApiError>> {
+        Box::pin(async move {
+            let query = (self.query)(st);
+            fetch_json(st, self.name, self.url, &query).await
+        })
+    }
+
+    fn identity_key(&self) -> Option<&'static str> {
+        self.identity_key
+    }
+
+    fn headline(&self, payload: &Value) -> Value {
+        (self.headline)(payload)
+    }
+}
+
+/// Перечисленные поля объекта; отсутствующие пропускаются
+fn pick(payload: &Value, keys: &[&str]) -> Value {
+    let fields = keys
+        .iter()
+        .filter_map(|k| payload.get(*k).map(|v| (k.to_string(), v.clone())))
+        .collect();
+    Value::Object(fields)
+}
+
+/// Число предстоящих запусков и ближайший из них
+fn spacex_upcoming_headline(payload: &Value) -> Value {
+    match spacex::upcoming(payload, Utc::now()) {
+        Ok(launches) => json!({
+            "count": launches.len(),
+            "next": launches.first().map(|l| json!({ "name": l.name, "date_utc": l.date_utc }))
+        }),
+        Err(_) => json!({}),
+    }
+}
+
+/// Число сближений и ближайшее по расстоянию за период
+fn neo_headline(payload: &Value) -> Value {
+    let closest = payload
+        .get("near_earth_objects")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flat_map(|days| days.values())
+        .filter_map(Value::as_array)
+        .flatten()
+        .filter_map(|neo| {
+            let km = neo
+                .pointer("/close_approach_data/0/miss_distance/kilometers")
+                .and_then(|v| v.as_str().and_then(|s| s.parse::<f64>().ok()).or_else(|| v.as_f64()))?;
+            Some((km, neo.get("name")?.clone()))
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0));
+    json!({
+        "element_count": payload.get("element_count"),
+        "closest": closest.map(|(km, name)| json!({ "name": name, "miss_distance_km": km }))
+    })
+}
+
+/// Число событий DONKI и самое позднее из них по `time_key` с полями `extra`
+fn events_headline(payload: &Value, time_key: &str, extra: &[&str]) -> Value {
+    let events = payload.as_array().map(Vec::as_slice).unwrap_or_default();
+    let latest = events
+        .iter()
+        .filter(|e| e.get(time_key).and_then(Value::as_str).is_some())
+        .max_by_key(|e| e.get(time_key).and_then(Value::as_str));
+    let mut keys = vec![time_key];
+    keys.extend_from_slice(extra);
+    json!({
+        "count": events.len(),
+        "latest": latest.map(|e| pick(e, &keys))
+    })
+}
+
+/// Число кадров EPIC и самый поздний из них
+fn epic_headline(payload: &Value) -> Value {
+    let count = payload.as_array().map_or(0, Vec::len);
+    match EpicImage::latest(payload) {
+        Ok(latest) => json!({ "count": count, "latest_date": latest.date, "latest_image": latest.image }),
+        Err(_) => json!({ "count": count }),
+    }
+}
+
+/// Число бурь за период и наибольший Kp последней из них
+fn gst_headline(payload: &Value) -> Value {
+    let count = payload.as_array().map_or(0, Vec::len);
+    match gst::latest_storm(payload) {
+        Ok(Some(storm)) => json!({ "count": count, "latest_start": storm.start_time, "max_kp": storm.max_kp }),
+        _ => json!({ "count": count }),
+    }
+}
+
+/// Последний Kp и уровень активности
+fn kp_headline(payload: &Value) -> Value {
+    match kp::samples(payload).ok().and_then(|s| s.last().copied()) {
+        Some(latest) => json!({ "time": latest.time, "kp": latest.kp, "level": kp::level(latest.kp).level }),
+        None => json!({}),
+    }
+}
+
+/// Последнее полное измерение солнечного ветра
+fn solar_wind_headline(payload: &Value) -> Value {
+    match solarwind::samples(payload).ok().and_then(|s| solarwind::latest(&s)) {
+        Some(latest) => json!({ "time": latest.time, "speed": latest.speed, "density": latest.density }),
+        None => json!({}),
+    }
+}
+
+/// Число открытых событий EONET по категориям
+fn eonet_headline(payload: &Value) -> Value {
+    let Ok(events) = eonet::events(payload) else {
+        return json!({});
+    };
+    let mut by_category = serde_json::Map::new();
+    for category in events.iter().flat_map(|e| &e.categories) {
+        let n = by_category.get(category).and_then(Value::as_u64).unwrap_or(0);
+        by_category.insert(category.clone(), (n + 1).into());
+    }
+    json!({ "count": events.len(), "by_category": by_category })
+}
+
+/// Хост NASA API; запросы к нему расходуют общий бюджет ключа
+pub const NASA_API_HOST: &str = "api.nasa.gov";
+
+/// Параметры запроса к api.nasa.gov, с ключом, если он задан
+pub fn nasa_query(st: &AppState, mut query: Vec<(&'static str, String)>) -> Vec<(&'static str, String)> {
+    if !st.config.nasa_api_key.is_empty() {
+        query.push(("api_key", st.config.nasa_api_key.clone()));
+    }
+    query
+}
+
+/// Тип событий DONKI: сегмент URL, имя источника и ключ идентичности события
+#[derive(Debug)]
+pub struct DonkiKind {
+    pub kind: &'static str,
+    pub source: &'static str,
+    id_key: &'static str,
+    /// Окно запроса, если DONKI_<TYPE>_DAYS не задан
+    pub default_days: u64,
+    headline: fn(&Value) -> Value,
+}
+
+/// Поддерживаемые типы DONKI; SEP редки, поэтому окно у них шире
+pub const DONKI_KINDS: &[DonkiKind] = &[
+    DonkiKind {
+        kind: "FLR",
+        source: "flr",
+        id_key: "flrID",
+        default_days: 5,
+        headline: |p| events_headline(p, "peakTime", &["classType"]),
+    },
+    DonkiKind {
+        kind: "CME",
+        source: "cme",
+        id_key: "activityID",
+        default_days: 5,
+        headline: |p| events_headline(p, "startTime", &["note"]),
+    },
+    DonkiKind { kind: "GST", source: "gst", id_key: "gstID", default_days: 5, headline: gst_headline },
+    DonkiKind {
+        kind: "SEP",
+        source: "sep",
+        id_key: "sepID",
+        default_days: 30,
+        headline: |p| events_headline(p, "eventTime", &[]),
+    },
+    DonkiKind {
+        kind: "IPS",
+        source: "ips",
+        id_key: "activityID",
+        default_days: 5,
+        headline: |p| events_headline(p, "eventTime", &["location"]),
+    },
+    DonkiKind {
+        kind: "HSS",
+        source: "hss",
+        id_key: "hssID",
+        default_days: 5,
+        headline: |p| events_headline(p, "eventTime", &[]),
+    },
+    DonkiKind {
+        kind: "RBE",
+        source: "rbe",
+        id_key: "rbeID",
+        default_days: 5,
+        headline: |p| events_headline(p, "eventTime", &[]),
+    },
+    DonkiKind {
+        kind: "MPC",
+        source: "mpc",
+        id_key: "mpcID",
+        default_days: 5,
+        headline: |p| events_headline(p, "eventTime", &[]),
+    },
+];
+
+/// Тип событий DONKI и глубина его запроса в днях
+#[derive(Clone, Debug)]
+pub struct DonkiType {
+    pub kind: &'static DonkiKind,
+    pub window_days: u64,
+}
+
+/// Типы из DONKI_TYPES с окнами из DONKI_<TYPE>_DAYS; без окна берётся
+/// встроенное. Неизвестный тип — ошибка конфигурации
+pub fn donki_types(names: &[String], window_days: &HashMap<String, u64>) -> Result<Vec<DonkiType>, String> {
+    names
+        .iter()
+        .map(|name| {
+            let kind = DONKI_KINDS.iter().find(|k| k.kind.eq_ignore_ascii_case(name)).ok_or_else(|| {
+                let known: Vec<&str> = DONKI_KINDS.iter().map(|k| k.kind).collect();
+                format!("DONKI_TYPES: unknown type {:?}, expected any of {}", name, known.join(","))
+            })?;
+            let window_days = window_days.get(kind.kind).copied().unwrap_or(kind.default_days);
+            Ok(DonkiType { kind, window_days })
+        })
+        .collect()
+}
+
+/// События DONKI одного типа за последние `window_days` дней
+struct DonkiFeed {
+    kind: &'static DonkiKind,
+    window_days: u64,
+}
+
+impl SpaceSource for DonkiFeed {
+    fn name(&self) -> &'static str {
+        self.kind.source
+    }
+
+    fn interval(&self, cfg: &Config) -> Duration {
+        Duration::from_secs(cfg.donki_every_seconds)
+    }
+
+    fn fetch<'a>(&'a self, st: &'a AppState) -> BoxFuture<'a, Result<Option<Fetched>, ApiError>> {
+        Box::pin(async move {
+            let url = format!("https://api.nasa.gov/DONKI/{}", self.kind.kind);
+            let to = Utc::now().date_naive();
+            let from = to - chrono::Days::new(self.window_days);
+            let query = nasa_query(st, vec![("startDate", from.to_string()), ("endDate", to.to_string())]);
+            fetch_json(st, self.name(), &url, &query).await
+        })
+    }
+
+    fn identity_key(&self) -> Option<&'static str> {
+        Some(self.kind.id_key)
+    }
+
+    fn headline(&self, payload: &Value) -> Value {
+        (self.kind.headline)(payload)
+    }
+}
+
+/// open-notify доступен только по HTTP
+const ASTROS_URL: &str = "http://api.open-notify.org/astros.json";
+
+/// Экипажи на орбите; ответ без списка людей не сохраняется
+struct AstrosFeed;
+
+impl SpaceSource for AstrosFeed {
+    fn name(&self) -> &'static str {
+        "astros"
+    }
+
+    fn interval(&self, cfg: &Config) -> Duration {
+        Duration::from_secs(cfg.astros_every_seconds)
+    }
+
+    fn fetch<'a>(&'a self, st: &'a AppState) -> BoxFuture<'a, Result<Option<Fetched>, ApiError>> {
+        Box::pin(async move {
+            let Some(fetched) = fetch_json(st, self.name(), ASTROS_URL, &[]).await? else {
+                return Ok(None);
+            };
+            if let Err(reason) = AstrosSummary::from_payload(&fetched.json) {
+                let status = Some(fetched.meta.http_status as u16);
+                let meta = fetched.meta;
+                let err = ApiError::invalid_payload(reason);
+                let (latency, attempts) = (meta.latency_ms, meta.attempts as u32);
+                record_fetch_error(&st.pool, self.name(), status, &err, latency, attempts, &meta.request_id).await;
+                return Err(err);
+            }
+            Ok(Some(fetched))
+        })
+    }
+
+    fn headline(&self, payload: &Value) -> Value {
+        match AstrosSummary::from_payload(payload) {
+            Ok(summary) => json!({ "total": summary.total, "iss_crew": summary.iss_crew.len() }),
+            Err(_) => json!({}),
+        }
+    }
+}
+
+/// TLE МКС с Celestrak
+struct TleFeed;
+
+impl SpaceSource for TleFeed {
+    fn name(&self) -> &'static str {
+        "tle"
+    }
+
+    fn interval(&self, cfg: &Config) -> Duration {
+        Duration::from_secs(cfg.tle_every_seconds)
+    }
+
+    fn fetch<'a>(&'a self, st: &'a AppState) -> BoxFuture<'a, Result<Option<Fetched>, ApiError>> {
+        Box::pin(async move { fetch_tle(st).await.map(|(_, fetched)| Some(fetched)) })
+    }
+
+    fn headline(&self, payload: &Value) -> Value {
+        pick(payload, &["name", "norad_id", "epoch"])
+    }
+}
+
+/// Лента NeoWs вокруг текущей даты; каждое сближение дополнительно
+/// раскладывается в neo_objects
+struct NeoFeed;
+
+impl SpaceSource for NeoFeed {
+    fn name(&self) -> &'static str {
+        "neo"
+    }
+
+    fn interval(&self, cfg: &Config) -> Duration {
+        Duration::from_secs(cfg.neo_every_seconds)
+    }
+
+    fn fetch<'a>(&'a self, st: &'a AppState) -> BoxFuture<'a, Result<Option<Fetched>, ApiError>> {
+        Box::pin(async move {
+            let today = Utc::now().date_naive();
+            let start = today - chrono::Days::new(neo::FEED_DAYS_BEFORE);
+            let end = today + chrono::Days::new(neo::FEED_DAYS_AHEAD);
+            let query = nasa_query(st, vec![
+                ("start_date", start.to_string()),
+                ("end_date", end.to_string()),
+            ]);
+            let fetched = fetch_json(st, self.name(), "https://api.nasa.gov/neo/rest/v1/feed", &query).await?;
+            // Снимок в space_cache важнее таблицы: её ошибка загрузку не срывает
+            if let Some(fetched) = &fetched {
+                match store_neo_objects(&st.pool, &fetched.json).await {
+                    Ok(n) => info!("neo_objects: {} approaches upserted", n),
+                    Err(e) => warn!("neo_objects not updated: {}", e.error.message),
+                }
+            }
+            Ok(fetched)
+        })
+    }
+
+    fn headline(&self, payload: &Value) -> Value {
+        neo_headline(payload)
+    }
+}
+
+/// Последние снимки марсохода MARS_ROVER
+struct MarsPhotosFeed;
+
+impl SpaceSource for MarsPhotosFeed {
+    fn name(&self) -> &'static str {
+        "mars_photos"
+    }
+
+    fn interval(&self, cfg: &Config) -> Duration {
+        Duration::from_secs(cfg.mars_photos_every_seconds)
+    }
+
+    fn fetch<'a>(&'a self, st: &'a AppState) -> BoxFuture<'a, Result<Option<Fetched>, ApiError>> {
+        Box::pin(async move {
+            let url = format!(
+                "https://api.nasa.gov/mars-photos/api/v1/rovers/{}/latest_photos",
+                st.config.mars_rover
+            );
+            fetch_json(st, self.name(), &url, &nasa_query(st, Vec::new())).await
+        })
+    }
+
+    fn headline(&self, payload: &Value) -> Value {
+        match mars::photos(payload, None) {
+            Ok(photos) => json!({
+                "count": photos.len(),
+                "sol": photos.first().and_then(|p| p.sol),
+                "earth_date": photos.first().and_then(|p| p.earth_date.clone())
+            }),
+            Err(_) => json!({}),
+        }
+    }
+}
+
+/// Предстоящие запуски всех провайдеров из Launch Library 2. Лимит
+/// upstream строгий, поэтому запросы считаются и здесь: /space/refresh
+/// не может обойти его, как бы часто его ни вызывали
+struct Ll2Feed {
+    requests: Mutex<RateLimiter>,
+}
+
+impl Ll2Feed {
+    fn new() -> Self {
+        Self { requests: Mutex::new(RateLimiter::new(LL2_REQUESTS_PER_HOUR, Duration::from_secs(3600))) }
+    }
+}
+
+impl SpaceSource for Ll2Feed {
+    fn name(&self) -> &'static str {
+        "launches_ll2"
+    }
+
+    fn interval(&self, cfg: &Config) -> Duration {
+        Duration::from_secs(cfg.ll2_every_seconds.max(LL2_MIN_INTERVAL_SECS))
+    }
+
+    fn fetch<'a>(&'a self, st: &'a AppState) -> BoxFuture<'a, Result<Option<Fetched>, ApiError>> {
+        Box::pin(async move {
+            let acquired = self
+                .requests
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .try_acquire(Instant::now());
+            if let Err(wait) = acquired {
+                return Err(ApiError::rate_limited(format!(
+                    "Launch Library 2 allows {} requests per hour, retry in {} s",
+                    LL2_REQUESTS_PER_HOUR,
+                    wait.as_secs().max(1)
+                )));
+            }
+            let mut headers = Vec::new();
+            if !st.config.ll2_api_key.is_empty() {
+                headers.push(("Authorization", format!("Token {}", st.config.ll2_api_key)));
+            }
+            let query = [("limit", "100".to_string())];
+            fetch_json_with_headers(st, self.name(), "https://ll.thespacedevs.com/2.2.0/launch/upcoming/", &query, &headers)
+                .await
+        })
+    }
+
+    fn identity_key(&self) -> Option<&'static str> {
+        Some("id")
+    }
+
+    fn headline(&self, payload: &Value) -> Value {
+        match launches::ll2_launches(payload) {
+            Ok(items) => json!({
+                "count": items.len(),
+                "next": items.iter().min_by_key(|l| l.net).map(|l| json!({ "name": l.name, "net": l.net }))
+            }),
+            Err(_) => json!({}),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(name: &str) -> Arc<dyn SpaceSource> {
+        let donki = donki_types(&["FLR".to_string(), "CME".to_string()], &HashMap::new()).unwrap();
+        builtin(&donki).into_iter().find(|s| s.name() == name).unwrap()
+    }
+
+    #[test]
+    fn resolves_donki_types_and_windows() {
+        let names = crate::config::parse_donki_types("sep,flr");
+        let days = HashMap::from([("FLR".to_string(), 2)]);
+        let types = donki_types(&names, &days).unwrap();
+        let resolved: Vec<_> = types.iter().map(|t| (t.kind.source, t.window_days)).collect();
+        assert_eq!(resolved, vec![("sep", 30), ("flr", 2)]);
+
+        let err = donki_types(&["XYZ".to_string()], &HashMap::new()).unwrap_err();
+        assert!(err.contains("FLR,CME,GST,SEP"), "{}", err);
+    }
+
+    #[test]
+    fn headlines_pick_key_fields() {
+        let apod = json!({"title": "M31", "url": "https://x/m31.jpg", "explanation": "long text"});
+        assert_eq!(feed("apod").headline(&apod), json!({"title": "M31", "url": "https://x/m31.jpg"}));
+
+        let flr = json!([
+            {"flrID": "F1", "peakTime": "2026-10-10T01:00Z", "classType": "M1.0"},
+            {"flrID": "F2", "peakTime": "2026-10-12T03:00Z", "classType": "X2.1"},
+            {"flrID": "F3", "peakTime": null}
+        ]);
+        assert_eq!(
+            feed("flr").headline(&flr),
+            json!({"count": 3, "latest": {"peakTime": "2026-10-12T03:00Z", "classType": "X2.1"}})
+        );
+        assert_eq!(feed("cme").headline(&json!({})), json!({"count": 0, "latest": null}));
+
+        let neo = json!({
+            "element_count": 2,
+            "near_earth_objects": {"2026-10-16": [
+                {"name": "far", "close_approach_data": [{"miss_distance": {"kilometers": "9000000.5"}}]},
+                {"name": "near", "close_approach_data": [{"miss_distance": {"kilometers": "380000.1"}}]}
+            ]}
+        });
+        assert_eq!(
+            feed("neo").headline(&neo),
+            json!({"element_count": 2, "closest": {"name": "near", "miss_distance_km": 380000.1}})
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Классы рентгеновских вспышек GOES по возрастанию потока
+const CLASS_LETTERS: [char; 5] = ['A', 'B', 'C', 'M', 'X'];
+
+/// Класс вспышки вида "M2.3": буква и множитель
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlareClass {
+    pub letter: char,
+    pub magnitude: f64,
+}
+
+impl FlareClass {
+    /// None для всего, что не похоже на букву A/B/C/M/X с числом
+    pub fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        let letter = raw.chars().next()?.to_ascii_uppercase();
+        if !CLASS_LETTERS.contains(&letter) {
+            return None;
+        }
+        let magnitude: f64 = raw[1..].trim().parse().ok()?;
+        (magnitude.is_finite() && magnitude > 0.0).then_some(Self { letter, magnitude })
+    }
+
+    /// Пиковый поток, Вт/м²: A1 = 1e-8, каждая следующая буква — ×10
+    pub fn flux(&self) -> f64 {
+        let power = CLASS_LETTERS.iter().position(|&c| c == self.letter).unwrap_or(0) as i32;
+        self.magnitude * 10f64.powi(power - 8)
+    }
+}
+
+/// Вспышка из ответа DONKI /FLR
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Flare {
+    pub flr_id: String,
+    pub begin_time: Option<String>,
+    pub peak_time: Option<String>,
+    /// None — вспышка ещё идёт
+    pub end_time: Option<String>,
+    pub class_type: Option<String>,
+    pub source_location: Option<String>,
+    pub active_region_num: Option<i64>,
+    pub link: Option<String>,
+}
+
+impl Flare {
+    fn from_value(item: &Value) -> Option<Self> {
+        let text = |key: &str| item.get(key).and_then(Value::as_str).map(str::to_string);
+        Some(Self {
+            flr_id: text("flrID")?,
+            begin_time: text("beginTime"),
+            peak_time: text("peakTime"),
+            end_time: text("endTime"),
+            class_type: text("classType"),
+            source_location: text("sourceLocation"),
+            active_region_num: item.get("activeRegionNum").and_then(Value::as_i64),
+            link: text("link"),
+        })
+    }
+
+    /// Момент, по которому вспышка попадает в окно: пик, а без него начало
+    fn time(&self) -> Option<DateTime<Utc>> {
+        self.peak_time.as_deref().or(self.begin_time.as_deref()).and_then(parse_time)
+    }
+}
+
+/// Время DONKI вида "2026-10-12T03:00Z"
+pub fn parse_time(raw: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(raw)
+        .map(|t| t.with_timezone(&Utc))
+        .or_else(|_| NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%MZ").map(|t| t.and_utc()))
+        .ok()
+}
+
+/// Вспышки из нескольких снимков, от нового к старому: одна и та же вспышка
+/// берётся из самого нового снимка, где она есть
+pub fn flares<'a>(payloads: impl IntoIterator<Item = &'a Value>) -> Vec<Flare> {
+    let mut seen = HashSet::new();
+    payloads
+        .into_iter()
+        .filter_map(Value::as_array)
+        .flatten()
+        .filter_map(Flare::from_value)
+        .filter(|f| seen.insert(f.flr_id.clone()))
+        .collect()
+}
+
+/// Число вспышек и их flrID
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ClassBucket {
+    pub count: usize,
+    pub ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FlareSummary {
+    pub total: usize,
+    /// По букве класса A/B/C/M/X; нулевые классы тоже выводятся
+    pub by_class: BTreeMap<char, ClassBucket>,
+    /// classType отсутствует или не разобран
+    pub unclassified: ClassBucket,
+    pub largest: Option<Flare>,
+    pub ongoing: Vec<Flare>,
+}
+
+/// Сводка по вспышкам с пиком (или началом) не раньше `since`
+pub fn summary(flares: &[Flare], since: DateTime<Utc>) -> FlareSummary {
+    let mut by_class: BTreeMap<char, ClassBucket> =
+        CLASS_LETTERS.iter().map(|&c| (c, ClassBucket::default())).collect();
+    let mut unclassified = ClassBucket::default();
+    let mut largest: Option<(f64, &Flare)> = None;
+    let mut in_window: Vec<&Flare> = flares.iter().filter(|f| f.time().is_some_and(|t| t >= since)).collect();
+    in_window.sort_by_key(|f| f.time());
+    for flare in &in_window {
+        let class = flare.class_type.as_deref().and_then(FlareClass::parse);
+        let bucket = match class {
+            Some(class) => by_class.entry(class.letter).or_default(),
+            None => &mut unclassified,
+        };
+        bucket.count += 1;
+        bucket.ids.push(flare.flr_id.clone());
+        if let Some(class) = class {
+            if largest.is_none_or(|(flux, _)| class.flux() > flux) {
+                largest = Some((class.flux(), flare));
+            }
+        }
+    }
+    FlareSummary {
+        total: in_window.len(),
+        by_class,
+        unclassified,
+        largest: largest.map(|(_, f)| f.clone()),
+        ongoing: in_window.iter().filter(|f| f.end_time.is_none()).map(|f| (*f).clone()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn counts_classes_and_finds_largest() {
+        assert_eq!(FlareClass::parse("X1.5"), Some(FlareClass { letter: 'X', magnitude: 1.5 }));
+        assert!(FlareClass::parse("M9.9").unwrap().flux() < FlareClass::parse("X1.0").unwrap().flux());
+        for bad in ["", "Q2.0", "M", "Mx", "Ж1.0", "M-1"] {
+            assert_eq!(FlareClass::parse(bad), None, "{:?}", bad);
+        }
+
+        let newer = json!([
+            {"flrID": "F3", "beginTime": "2026-10-15T10:00Z", "peakTime": "2026-10-15T10:20Z",
+             "endTime": null, "classType": "M2.1", "sourceLocation": "S10W20", "activeRegionNum": 14242},
+            {"flrID": "F2", "peakTime": "2026-10-14T08:00Z", "endTime": "2026-10-14T08:30Z", "classType": "X1.1"}
+        ]);
+        let older = json!([
+            {"flrID": "F2", "peakTime": "2026-10-14T08:00Z", "endTime": null, "classType": "X1.0"},
+            {"flrID": "F1", "peakTime": "2026-10-13T01:00Z", "endTime": "2026-10-13T01:40Z", "classType": "C9.?"},
+            {"flrID": "F0", "peakTime": "2026-10-01T01:00Z", "classType": "X9.0"}
+        ]);
+        let all = flares([&newer, &older]);
+        assert_eq!(all.len(), 4);
+
+        let since = parse_time("2026-10-11T00:00Z").unwrap();
+        let s = summary(&all, since);
+        assert_eq!(s.total, 3);
+        assert_eq!(s.by_class[&'X'], ClassBucket { count: 1, ids: vec!["F2".into()] });
+        assert_eq!(s.by_class[&'M'].count, 1);
+        assert_eq!(s.by_class[&'A'].count, 0);
+        assert_eq!(s.unclassified.ids, vec!["F1"]);
+        let largest = s.largest.unwrap();
+        assert_eq!((largest.flr_id.as_str(), largest.class_type.as_deref()), ("F2", Some("X1.1")));
+        assert_eq!(s.ongoing.iter().map(|f| f.flr_id.as_str()).collect::<Vec<_>>(), vec!["F3"]);
+    }
+}
@@ -0,0 +1,699 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+/// Средний радиус Земли, км
+pub const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// Экваториальный радиус и сжатие эллипсоида WGS84
+const WGS84_A_KM: f64 = 6378.137;
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+
+/// Число из JSON: поддерживает как числа, так и строки вида "51.6"
+pub fn extract_number(v: &Value) -> Option<f64> {
+    v.as_f64()
+        .or_else(|| v.as_str().and_then(|s| s.trim().parse::<f64>().ok()))
+        .filter(|n| n.is_finite())
+}
+
+/// Разница долгот `to - from`, приведённая к диапазону [-180, 180)
+/// (переход через антимеридиан считается коротким путём)
+pub fn normalize_lon_delta(from_lon: f64, to_lon: f64) -> f64 {
+    normalize_lon(to_lon - from_lon)
+}
+
+/// Долгота, приведённая к диапазону [-180, 180)
+pub fn normalize_lon(lon: f64) -> f64 {
+    let wrapped = (lon + 180.0).rem_euclid(360.0) - 180.0;
+    // rem_euclid от крошечного отрицательного числа округляется до 360.0
+    if wrapped >= 180.0 {
+        wrapped - 360.0
+    } else {
+        wrapped
+    }
+}
+
+pub fn haversine_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let rlat1 = lat1.to_radians();
+    let rlat2 = lat2.to_radians();
+    let dlat = (lat2 - lat1).to_radians();
+    let dlon = normalize_lon_delta(lon1, lon2).to_radians();
+    let a = (dlat / 2.0).sin().powi(2)
+        + rlat1.cos() * rlat2.cos() * (dlon / 2.0).sin().powi(2);
+    // Погрешность округления у полюсов может вывести `a` за пределы [0, 1]
+    let a = a.clamp(0.0, 1.0);
+    let c = 2.0 * a.sqrt().atan2((1.0 - a).sqrt());
+    EARTH_RADIUS_KM * c
+}
+
+/// Середина дуги большого круга между двумя точками.
+/// Для антиподальных точек середина не определена — возвращается `None`.
+pub fn great_circle_midpoint(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> Option<(f64, f64)> {
+    let rlat1 = lat1.to_radians();
+    let rlat2 = lat2.to_radians();
+    let dlon = normalize_lon_delta(lon1, lon2).to_radians();
+
+    let bx = rlat2.cos() * dlon.cos();
+    let by = rlat2.cos() * dlon.sin();
+    let denom = ((rlat1.cos() + bx).powi(2) + by.powi(2)).sqrt();
+    if denom < 1e-12 {
+        return None;
+    }
+
+    let lat = (rlat1.sin() + rlat2.sin()).atan2(denom);
+    let lon = lon1.to_radians() + by.atan2(rlat1.cos() + bx);
+    Some((lat.to_degrees(), normalize_lon(lon.to_degrees())))
+}
+
+/// Точка на доле `f` дуги большого круга между двумя точками (сферическая
+/// интерполяция); дуга всегда короткая, в том числе через антимеридиан.
+/// None для диаметрально противоположных точек
+pub fn great_circle_interpolate(
+    lat1: f64,
+    lon1: f64,
+    lat2: f64,
+    lon2: f64,
+    f: f64,
+) -> Option<(f64, f64)> {
+    let to_vec = |lat: f64, lon: f64| {
+        let (lat, lon) = (lat.to_radians(), lon.to_radians());
+        [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+    };
+    let a = to_vec(lat1, lon1);
+    let b = to_vec(lat2, lon2);
+    let dot = (a[0] * b[0] + a[1] * b[1] + a[2] * b[2]).clamp(-1.0, 1.0);
+    let omega = dot.acos();
+    if omega < 1e-12 {
+        return Some((lat1, normalize_lon(lon1)));
+    }
+    if (std::f64::consts::PI - omega).abs() < 1e-9 {
+        return None;
+    }
+
+    let (ka, kb) = (((1.0 - f) * omega).sin() / omega.sin(), (f * omega).sin() / omega.sin());
+    let p = [0, 1, 2].map(|i| ka * a[i] + kb * b[i]);
+    let lat = p[2].atan2((p[0] * p[0] + p[1] * p[1]).sqrt());
+    let lon = p[1].atan2(p[0]);
+    Some((lat.to_degrees(), normalize_lon(lon.to_degrees())))
+}
+
+/// Разбивает трек (lat, lon) на участки так, чтобы ни один отрезок не пересекал
+/// антимеридиан: в месте пересечения добавляются точки на ±180° с интерполированной широтой
+pub fn split_at_antimeridian(points: &[(f64, f64)]) -> Vec<Vec<(f64, f64)>> {
+    let mut segments: Vec<Vec<(f64, f64)>> = Vec::new();
+    let mut current: Vec<(f64, f64)> = Vec::new();
+
+    for &(lat, lon) in points {
+        if let Some(&(prev_lat, prev_lon)) = current.last() {
+            if (lon - prev_lon).abs() > 180.0 {
+                let dlon = normalize_lon_delta(prev_lon, lon);
+                let edge = if dlon > 0.0 { 180.0 } else { -180.0 };
+                let frac = if dlon == 0.0 { 0.0 } else { (edge - prev_lon) / dlon };
+                let cross_lat = prev_lat + (lat - prev_lat) * frac;
+                current.push((cross_lat, edge));
+                segments.push(std::mem::take(&mut current));
+                current.push((cross_lat, -edge));
+            }
+        }
+        current.push((lat, lon));
+    }
+
+    if !current.is_empty() {
+        segments.push(current);
+    }
+    segments
+}
+
+/// Начальный азимут движения из точки 1 в точку 2, градусы [0, 360)
+pub fn initial_bearing_deg(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let rlat1 = lat1.to_radians();
+    let rlat2 = lat2.to_radians();
+    let dlon = normalize_lon_delta(lon1, lon2).to_radians();
+    let y = dlon.sin() * rlat2.cos();
+    let x = rlat1.cos() * rlat2.sin() - rlat1.sin() * rlat2.cos() * dlon.cos();
+    let deg = y.atan2(x).to_degrees();
+    (deg + 360.0) % 360.0
+}
+
+/// Геодезические координаты (WGS84) в ECEF, км
+pub fn geodetic_to_ecef(lat: f64, lon: f64, alt_km: f64) -> [f64; 3] {
+    let e2 = WGS84_F * (2.0 - WGS84_F);
+    let (slat, clat) = lat.to_radians().sin_cos();
+    let (slon, clon) = lon.to_radians().sin_cos();
+    let n = WGS84_A_KM / (1.0 - e2 * slat * slat).sqrt();
+    [
+        (n + alt_km) * clat * clon,
+        (n + alt_km) * clat * slon,
+        (n * (1.0 - e2) + alt_km) * slat,
+    ]
+}
+
+/// Положение цели относительно наблюдателя
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LookAngles {
+    pub range_km: f64,
+    pub azimuth_deg: f64,
+    pub elevation_deg: f64,
+}
+
+/// Наклонная дальность, азимут и угол места цели (ECEF, км) для наблюдателя
+pub fn look_angles(obs_lat: f64, obs_lon: f64, obs_alt_km: f64, target: [f64; 3]) -> LookAngles {
+    let obs = geodetic_to_ecef(obs_lat, obs_lon, obs_alt_km);
+    let d = [target[0] - obs[0], target[1] - obs[1], target[2] - obs[2]];
+
+    let (slat, clat) = obs_lat.to_radians().sin_cos();
+    let (slon, clon) = obs_lon.to_radians().sin_cos();
+    // Топоцентрическая система ENU
+    let east = -slon * d[0] + clon * d[1];
+    let north = -slat * clon * d[0] - slat * slon * d[1] + clat * d[2];
+    let up = clat * clon * d[0] + clat * slon * d[1] + slat * d[2];
+
+    let range_km = (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt();
+    let elevation_deg = if range_km > 0.0 {
+        (up / range_km).clamp(-1.0, 1.0).asin().to_degrees()
+    } else {
+        90.0
+    };
+    LookAngles {
+        range_km,
+        azimuth_deg: east.atan2(north).to_degrees().rem_euclid(360.0),
+        elevation_deg,
+    }
+}
+
+const CARDINALS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE",
+    "S", "SSW", "SW", "WSW", "W", "WNW", "NW", "NNW",
+];
+
+/// 16-румбовое обозначение направления по азимуту
+pub fn cardinal_direction(bearing_deg: f64) -> &'static str {
+    let idx = ((bearing_deg.rem_euclid(360.0) / 22.5).round() as usize) % CARDINALS.len();
+    CARDINALS[idx]
+}
+
+/// Положение Солнца: склонение, уравнение времени и подсолнечная точка
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolarPosition {
+    pub declination_deg: f64,
+    pub equation_of_time_min: f64,
+    pub subsolar_lat: f64,
+    pub subsolar_lon: f64,
+}
+
+/// Упрощённый алгоритм Astronomical Almanac, точность порядка 0.01°
+pub fn solar_position(t: DateTime<Utc>) -> SolarPosition {
+    let unix = t.timestamp() as f64 + f64::from(t.timestamp_subsec_millis()) / 1000.0;
+    let n = unix / 86_400.0 + 2_440_587.5 - 2_451_545.0;
+
+    let mean_lon = (280.460 + 0.985_647_4 * n).rem_euclid(360.0);
+    let g = (357.528 + 0.985_600_3 * n).rem_euclid(360.0).to_radians();
+    let ecl_lon = (mean_lon + 1.915 * g.sin() + 0.020 * (2.0 * g).sin()).to_radians();
+    let obliquity = (23.439 - 0.000_000_4 * n).to_radians();
+
+    let right_ascension = (obliquity.cos() * ecl_lon.sin())
+        .atan2(ecl_lon.cos())
+        .to_degrees();
+    let declination_deg = (obliquity.sin() * ecl_lon.sin()).asin().to_degrees();
+    // Разница средней и истинной долготы, приведённая к (-180, 180]
+    let eot_deg = normalize_lon(mean_lon - right_ascension);
+
+    let utc_hours = unix.rem_euclid(86_400.0) / 3600.0;
+    SolarPosition {
+        declination_deg,
+        equation_of_time_min: eot_deg * 4.0,
+        subsolar_lat: declination_deg,
+        subsolar_lon: normalize_lon(15.0 * (12.0 - utc_hours) - eot_deg),
+    }
+}
+
+/// Высота Солнца над горизонтом в точке, без учёта рефракции
+pub fn solar_elevation_deg(lat: f64, lon: f64, t: DateTime<Utc>) -> f64 {
+    let sun = solar_position(t);
+    let (slat, clat) = lat.to_radians().sin_cos();
+    let (sdec, cdec) = sun.declination_deg.to_radians().sin_cos();
+    let hour_angle = (lon - sun.subsolar_lon).to_radians();
+    (slat * sdec + clat * cdec * hour_angle.cos())
+        .clamp(-1.0, 1.0)
+        .asin()
+        .to_degrees()
+}
+
+/// Освещён ли объект над точкой (lat, lon) на высоте alt_km.
+/// Цилиндрическая модель тени Земли: полутень не учитывается
+pub fn is_sunlit(lat: f64, lon: f64, alt_km: f64, t: DateTime<Utc>) -> bool {
+    let sun = solar_position(t);
+    let (sdec, cdec) = sun.declination_deg.to_radians().sin_cos();
+    let (slon, clon) = sun.subsolar_lon.to_radians().sin_cos();
+    let sun_dir = [cdec * clon, cdec * slon, sdec];
+
+    let r = geodetic_to_ecef(lat, lon, alt_km);
+    let along = r[0] * sun_dir[0] + r[1] * sun_dir[1] + r[2] * sun_dir[2];
+    if along >= 0.0 {
+        return true;
+    }
+    let r2 = r[0] * r[0] + r[1] * r[1] + r[2] * r[2];
+    (r2 - along * along).sqrt() > EARTH_RADIUS_KM
+}
+
+/// Угловой радиус зоны видимости спутника на высоте alt_km
+/// (круг, из которого он над горизонтом), радианы
+pub fn footprint_angle_rad(alt_km: f64) -> f64 {
+    (EARTH_RADIUS_KM / (EARTH_RADIUS_KM + alt_km.max(0.0))).acos()
+}
+
+/// Точка в `angle_rad` по дуге большого круга от (lat, lon) по азимуту bearing_deg
+pub fn destination_point(lat: f64, lon: f64, bearing_deg: f64, angle_rad: f64) -> (f64, f64) {
+    let (slat, clat) = lat.to_radians().sin_cos();
+    let (sd, cd) = angle_rad.sin_cos();
+    let bearing = bearing_deg.to_radians();
+    let lat2 = (slat * cd + clat * sd * bearing.cos()).clamp(-1.0, 1.0).asin();
+    let lon2 = lon.to_radians() + (bearing.sin() * sd * clat).atan2(cd - slat * lat2.sin());
+    (lat2.to_degrees(), normalize_lon(lon2.to_degrees()))
+}
+
+/// Контур зоны видимости из `segments` точек — замкнутые кольца (lat, lon)
+/// против часовой стрелки, как требует RFC 7946. Круг через антимеридиан
+/// режется на два кольца; если внутри полюс, кольцо идёт по краю карты
+/// и замыкается через полюс
+pub fn footprint_rings(lat: f64, lon: f64, alt_km: f64, segments: usize) -> Vec<Vec<(f64, f64)>> {
+    let angle = footprint_angle_rad(alt_km);
+    // Азимут убывает, чтобы обход на карте шёл против часовой стрелки
+    let circle: Vec<(f64, f64)> = (0..segments)
+        .map(|i| {
+            let bearing = 360.0 * (segments - i) as f64 / segments as f64;
+            destination_point(lat, lon, bearing, angle)
+        })
+        .collect();
+
+    if angle >= (90.0 - lat.abs()).to_radians() {
+        return vec![polar_cap_ring(circle, if lat >= 0.0 { 90.0 } else { -90.0 })];
+    }
+
+    // Долготы разворачиваем вокруг центра, чтобы кольцо было непрерывным
+    let unwrapped: Vec<(f64, f64)> = circle
+        .iter()
+        .map(|&(plat, plon)| (plat, lon + normalize_lon_delta(lon, plon)))
+        .collect();
+    if unwrapped.iter().all(|p| p.1.abs() <= 180.0) {
+        return vec![close_ring(unwrapped)];
+    }
+
+    let edge = if lon >= 0.0 { 180.0 } else { -180.0 };
+    let near = clip_at_meridian(&unwrapped, edge, edge > 0.0);
+    let far = clip_at_meridian(&unwrapped, edge, edge < 0.0)
+        .into_iter()
+        .map(|(plat, plon)| (plat, plon - 2.0 * edge))
+        .collect();
+    vec![close_ring(near), close_ring(far)]
+}
+
+/// Часть кольца по одну сторону меридиана `edge` (Сазерленд — Ходжман)
+fn clip_at_meridian(ring: &[(f64, f64)], edge: f64, keep_west: bool) -> Vec<(f64, f64)> {
+    let inside = |p: (f64, f64)| if keep_west { p.1 <= edge } else { p.1 >= edge };
+    let mut out = Vec::new();
+    for (i, &cur) in ring.iter().enumerate() {
+        let prev = ring[(i + ring.len() - 1) % ring.len()];
+        if inside(cur) != inside(prev) {
+            let f = (edge - prev.1) / (cur.1 - prev.1);
+            out.push((prev.0 + (cur.0 - prev.0) * f, edge));
+        }
+        if inside(cur) {
+            out.push(cur);
+        }
+    }
+    out
+}
+
+/// Круг вокруг полюса: точки по возрастанию долготы от −180 до 180,
+/// затем по краю карты до полюса и обратно
+fn polar_cap_ring(mut circle: Vec<(f64, f64)>, pole_lat: f64) -> Vec<(f64, f64)> {
+    circle.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let (first, last) = match (circle.first(), circle.last()) {
+        (Some(&f), Some(&l)) => (f, l),
+        _ => return Vec::new(),
+    };
+    // Широта, на которой круг пересекает антимеридиан
+    let span = first.1 + 360.0 - last.1;
+    let seam_lat = if span > 0.0 {
+        last.0 + (first.0 - last.0) * (180.0 - last.1) / span
+    } else {
+        last.0
+    };
+
+    let mut ring = Vec::with_capacity(circle.len() + 4);
+    ring.push((seam_lat, -180.0));
+    ring.extend(circle);
+    ring.extend([(seam_lat, 180.0), (pole_lat, 180.0), (pole_lat, -180.0)]);
+    if pole_lat < 0.0 {
+        // Вокруг южного полюса тот же обход шёл бы по часовой стрелке
+        ring.reverse();
+    }
+    close_ring(ring)
+}
+
+fn close_ring(mut ring: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+    if let Some(&first) = ring.first() {
+        ring.push(first);
+    }
+    ring
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn assert_close(actual: f64, expected: f64, tol: f64) {
+        assert!(
+            (actual - expected).abs() <= tol,
+            "expected {expected} ± {tol}, got {actual}"
+        );
+    }
+
+    /// Сетка координат, включая точки у полюсов и у антимеридиана
+    fn grid() -> Vec<(f64, f64)> {
+        let lats = [-89.999, -89.0, -60.0, -0.5, 0.0, 33.3, 75.0, 89.9, 90.0];
+        let lons = [-180.0, -179.95, -90.0, 0.0, 45.5, 179.95, 179.999];
+        lats.iter()
+            .flat_map(|&lat| lons.iter().map(move |&lon| (lat, lon)))
+            .collect()
+    }
+
+    #[test]
+    fn extract_number_fallbacks() {
+        assert_eq!(extract_number(&json!(51.6)), Some(51.6));
+        assert_eq!(extract_number(&json!(-12)), Some(-12.0));
+        assert_eq!(extract_number(&json!("27600.5")), Some(27600.5));
+        assert_eq!(extract_number(&json!(" 1.5 ")), Some(1.5));
+        assert_eq!(extract_number(&json!("north")), None);
+        assert_eq!(extract_number(&json!("NaN")), None);
+        assert_eq!(extract_number(&json!(null)), None);
+        assert_eq!(extract_number(&json!({"latitude": 1.0})), None);
+        assert_eq!(extract_number(&json!({})["latitude"]), None);
+    }
+
+    #[test]
+    fn lon_delta_takes_short_way() {
+        assert_close(normalize_lon_delta(179.9, -179.8), 0.3, 1e-9);
+        assert_close(normalize_lon_delta(-179.8, 179.9), -0.3, 1e-9);
+        assert_close(normalize_lon_delta(10.0, 20.0), 10.0, 1e-9);
+        assert_close(normalize_lon_delta(0.0, 180.0), -180.0, 1e-9);
+
+        for (_, a) in grid() {
+            for (_, b) in grid() {
+                let d = normalize_lon_delta(a, b);
+                assert!((-180.0..180.0).contains(&d), "delta {d} out of range");
+                assert_close(normalize_lon(a + d), normalize_lon(b), 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn great_circle_interpolation() {
+        for &(lat1, lon1) in &grid() {
+            for &(lat2, lon2) in &grid() {
+                let Some((lat, lon)) = great_circle_interpolate(lat1, lon1, lat2, lon2, 0.5) else {
+                    continue;
+                };
+                // Середина дуги равноудалена от концов и лежит на кратчайшем пути
+                let (d1, d2) = (haversine_km(lat1, lon1, lat, lon), haversine_km(lat, lon, lat2, lon2));
+                assert_close(d1, d2, 1e-3);
+                assert_close(d1 + d2, haversine_km(lat1, lon1, lat2, lon2), 1e-3);
+            }
+        }
+        // Через антимеридиан — коротким путём
+        let (lat, lon) = great_circle_interpolate(0.0, 179.0, 0.0, -179.0, 0.25).unwrap();
+        assert_close(lat, 0.0, 1e-9);
+        assert_close(lon, 179.5, 1e-9);
+        let (lat, lon) = great_circle_interpolate(10.0, 20.0, 30.0, 40.0, 0.0).unwrap();
+        assert_close(lat, 10.0, 1e-9);
+        assert_close(lon, 20.0, 1e-9);
+        assert!(great_circle_interpolate(0.0, 0.0, 0.0, 180.0, 0.5).is_none());
+    }
+
+    #[test]
+    fn haversine_properties() {
+        for &(lat1, lon1) in &grid() {
+            assert_close(haversine_km(lat1, lon1, lat1, lon1), 0.0, 1e-6);
+            for &(lat2, lon2) in &grid() {
+                let d = haversine_km(lat1, lon1, lat2, lon2);
+                assert!(d.is_finite());
+                assert!((0.0..=std::f64::consts::PI * EARTH_RADIUS_KM + 1e-6).contains(&d));
+                assert_close(d, haversine_km(lat2, lon2, lat1, lon1), 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn haversine_across_antimeridian_is_short() {
+        let d = haversine_km(0.0, 179.9, 0.0, -179.9);
+        assert_close(d, 22.24, 0.05);
+    }
+
+    #[test]
+    fn haversine_near_pole() {
+        // Все меридианы сходятся в полюсе: расстояние не зависит от долготы
+        let a = haversine_km(89.999, 0.0, 90.0, 0.0);
+        let b = haversine_km(89.999, 120.0, 90.0, -75.0);
+        assert_close(a, b, 1e-6);
+        assert_close(haversine_km(89.9, 0.0, 89.9, 180.0), 22.24, 0.05);
+    }
+
+    #[test]
+    fn midpoint_is_equidistant() {
+        for &(lat1, lon1) in &grid() {
+            for &(lat2, lon2) in &grid() {
+                let Some((mlat, mlon)) = great_circle_midpoint(lat1, lon1, lat2, lon2) else {
+                    continue;
+                };
+                let total = haversine_km(lat1, lon1, lat2, lon2);
+                let d1 = haversine_km(lat1, lon1, mlat, mlon);
+                let d2 = haversine_km(mlat, mlon, lat2, lon2);
+                assert_close(d1, total / 2.0, 1e-3);
+                assert_close(d2, total / 2.0, 1e-3);
+                assert!((-180.0..180.0).contains(&mlon));
+            }
+        }
+    }
+
+    #[test]
+    fn midpoint_across_antimeridian() {
+        let (lat, lon) = great_circle_midpoint(0.0, 179.0, 0.0, -179.0).unwrap();
+        assert_close(lat, 0.0, 1e-9);
+        assert_close(lon.abs(), 180.0, 1e-9);
+    }
+
+    #[test]
+    fn midpoint_identical_and_antipodal() {
+        let (lat, lon) = great_circle_midpoint(51.6, -47.8, 51.6, -47.8).unwrap();
+        assert_close(lat, 51.6, 1e-9);
+        assert_close(lon, -47.8, 1e-9);
+        assert!(great_circle_midpoint(0.0, 0.0, 0.0, 180.0).is_none());
+    }
+
+    #[test]
+    fn split_track_at_antimeridian() {
+        let track = [(0.0, 178.0), (1.0, 179.5), (2.0, -179.5), (3.0, -178.0)];
+        let segments = split_at_antimeridian(&track);
+        assert_eq!(segments.len(), 2);
+
+        let (end_lat, end_lon) = *segments[0].last().unwrap();
+        let (start_lat, start_lon) = segments[1][0];
+        assert_close(end_lon, 180.0, 1e-9);
+        assert_close(start_lon, -180.0, 1e-9);
+        assert_close(end_lat, 1.5, 1e-9);
+        assert_close(start_lat, 1.5, 1e-9);
+        assert_eq!(segments[0].len() + segments[1].len(), track.len() + 2);
+    }
+
+    #[test]
+    fn split_track_without_crossing() {
+        let track = [(0.0, -10.0), (1.0, 0.0), (2.0, 10.0)];
+        assert_eq!(split_at_antimeridian(&track), vec![track.to_vec()]);
+        assert!(split_at_antimeridian(&[]).is_empty());
+    }
+
+    #[test]
+    fn target_directly_overhead() {
+        let iss = geodetic_to_ecef(51.5, -0.1, 420.0);
+        let angles = look_angles(51.5, -0.1, 0.0, iss);
+        assert_close(angles.range_km, 420.0, 1e-6);
+        assert_close(angles.elevation_deg, 90.0, 1e-6);
+    }
+
+    #[test]
+    fn ecef_reference_points() {
+        let [x, y, z] = geodetic_to_ecef(0.0, 0.0, 0.0);
+        assert_close(x, 6378.137, 1e-9);
+        assert_close(y, 0.0, 1e-9);
+        assert_close(z, 0.0, 1e-9);
+
+        let [x, y, z] = geodetic_to_ecef(90.0, 0.0, 0.0);
+        assert_close(x, 0.0, 1e-9);
+        assert_close(y, 0.0, 1e-9);
+        assert_close(z, 6356.752, 1e-3);
+    }
+
+    #[test]
+    fn look_angles_cardinal_directions() {
+        // Цель на той же высоте 400 км, смещённая на 5° к северу и к востоку
+        let north = look_angles(0.0, 0.0, 0.0, geodetic_to_ecef(5.0, 0.0, 400.0));
+        assert_close(north.azimuth_deg, 0.0, 1e-6);
+        assert!(north.elevation_deg > 0.0 && north.elevation_deg < 90.0);
+
+        let east = look_angles(0.0, 0.0, 0.0, geodetic_to_ecef(0.0, 5.0, 400.0));
+        assert_close(east.azimuth_deg, 90.0, 1e-6);
+        assert_close(east.elevation_deg, north.elevation_deg, 0.5);
+    }
+
+    #[test]
+    fn target_below_horizon() {
+        let far = look_angles(0.0, 0.0, 0.0, geodetic_to_ecef(0.0, 60.0, 420.0));
+        assert!(far.elevation_deg < 0.0);
+        assert!(far.range_km > 6000.0);
+    }
+
+    #[test]
+    fn bearing_along_axes() {
+        assert_close(initial_bearing_deg(0.0, 0.0, 10.0, 0.0), 0.0, 1e-9);
+        assert_close(initial_bearing_deg(0.0, 0.0, 0.0, 10.0), 90.0, 1e-9);
+        assert_close(initial_bearing_deg(0.0, 0.0, -10.0, 0.0), 180.0, 1e-9);
+        assert_close(initial_bearing_deg(0.0, 0.0, 0.0, -10.0), 270.0, 1e-9);
+    }
+
+    #[test]
+    fn bearing_known_city_pair() {
+        // Москва -> Санкт-Петербург, около 320°
+        let b = initial_bearing_deg(55.7558, 37.6173, 59.9343, 30.3351);
+        assert_close(b, 320.2, 0.5);
+        assert_eq!(cardinal_direction(b), "NW");
+    }
+
+    #[test]
+    fn bearing_across_antimeridian_points_east() {
+        let b = initial_bearing_deg(10.0, 179.9, 10.1, -179.8);
+        assert!(b > 45.0 && b < 90.0, "bearing {b} is not roughly east");
+        assert_eq!(cardinal_direction(b), "ENE");
+
+        let back = initial_bearing_deg(10.0, -179.8, 10.0, 179.9);
+        assert!(back > 260.0 && back < 280.0, "bearing {back} is not roughly west");
+    }
+
+    #[test]
+    fn cardinal_boundaries() {
+        assert_eq!(cardinal_direction(0.0), "N");
+        assert_eq!(cardinal_direction(359.0), "N");
+        assert_eq!(cardinal_direction(45.0), "NE");
+        assert_eq!(cardinal_direction(202.5), "SSW");
+        assert_eq!(cardinal_direction(-90.0), "W");
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    /// На восходе и закате центр диска находится на −0.833° (рефракция + радиус)
+    const HORIZON_DEG: f64 = -0.833;
+
+    #[test]
+    fn declination_at_equinox_and_solstice() {
+        assert_close(solar_position(utc("2024-03-20T03:06:00Z")).declination_deg, 0.0, 0.05);
+        assert_close(solar_position(utc("2024-06-20T20:51:00Z")).declination_deg, 23.44, 0.05);
+        assert_close(solar_position(utc("2024-12-21T09:20:00Z")).declination_deg, -23.44, 0.05);
+    }
+
+    #[test]
+    fn equation_of_time_extremes() {
+        // Начало ноября: солнечные часы спешат примерно на 16.4 мин, середина февраля — отстают на 14.2
+        assert_close(solar_position(utc("2024-11-03T12:00:00Z")).equation_of_time_min, 16.4, 0.3);
+        assert_close(solar_position(utc("2024-02-11T12:00:00Z")).equation_of_time_min, -14.2, 0.3);
+    }
+
+    #[test]
+    fn known_sunrise_and_sunset() {
+        // Гринвич, летнее солнцестояние: восход 03:43 UTC, закат 20:21 UTC
+        assert_close(solar_elevation_deg(51.4769, 0.0, utc("2024-06-21T03:43:00Z")), HORIZON_DEG, 0.3);
+        assert_close(solar_elevation_deg(51.4769, 0.0, utc("2024-06-21T20:21:00Z")), HORIZON_DEG, 0.3);
+        // Нью-Йорк, зимнее солнцестояние: восход 12:16 UTC, закат 21:32 UTC
+        assert_close(solar_elevation_deg(40.7128, -74.006, utc("2024-12-21T12:16:00Z")), HORIZON_DEG, 0.3);
+        assert_close(solar_elevation_deg(40.7128, -74.006, utc("2024-12-21T21:32:00Z")), HORIZON_DEG, 0.3);
+    }
+
+    #[test]
+    fn sun_overhead_at_subsolar_point() {
+        let t = utc("2024-06-20T20:51:00Z");
+        let sun = solar_position(t);
+        assert_close(solar_elevation_deg(sun.subsolar_lat, sun.subsolar_lon, t), 90.0, 1e-6);
+    }
+
+    #[test]
+    fn iss_illumination() {
+        let t = utc("2024-03-20T12:00:00Z");
+        let sun = solar_position(t);
+        let anti_lon = normalize_lon(sun.subsolar_lon + 180.0);
+        assert!(is_sunlit(sun.subsolar_lat, sun.subsolar_lon, 420.0, t));
+        assert!(!is_sunlit(-sun.subsolar_lat, anti_lon, 420.0, t));
+        // Чуть за терминатором МКС ещё освещена, а поверхность под ней уже нет
+        let beyond = normalize_lon(sun.subsolar_lon + 100.0);
+        assert!(solar_elevation_deg(0.0, beyond, t) < 0.0);
+        assert!(is_sunlit(0.0, beyond, 420.0, t));
+        assert!(!is_sunlit(0.0, beyond, 0.0, t));
+    }
+
+    /// Удвоенная ориентированная площадь кольца на плоскости (lon, lat)
+    fn signed_area(ring: &[(f64, f64)]) -> f64 {
+        ring.windows(2)
+            .map(|w| w[0].1 * w[1].0 - w[1].1 * w[0].0)
+            .sum()
+    }
+
+    #[test]
+    fn footprint_radius_of_iss() {
+        // acos(6371 / 6791) ≈ 20.26°, около 2252 км по поверхности
+        assert_close(footprint_angle_rad(420.0).to_degrees(), 20.256, 0.001);
+        assert_close(EARTH_RADIUS_KM * footprint_angle_rad(420.0), 2252.4, 0.1);
+        assert_eq!(footprint_angle_rad(0.0), 0.0);
+    }
+
+    #[test]
+    fn footprint_simple_circle() {
+        let rings = footprint_rings(51.0, 10.0, 420.0, 64);
+        assert_eq!(rings.len(), 1);
+        let ring = &rings[0];
+        assert_eq!(ring.len(), 65);
+        assert_eq!(ring.first(), ring.last());
+        assert!(signed_area(ring) > 0.0);
+        let radius = EARTH_RADIUS_KM * footprint_angle_rad(420.0);
+        for &(lat, lon) in ring {
+            assert_close(haversine_km(51.0, 10.0, lat, lon), radius, 1e-6);
+        }
+    }
+
+    #[test]
+    fn footprint_split_at_antimeridian() {
+        let rings = footprint_rings(0.0, 175.0, 420.0, 64);
+        assert_eq!(rings.len(), 2);
+        for ring in &rings {
+            assert_eq!(ring.first(), ring.last());
+            assert!(signed_area(ring) > 0.0);
+            assert!(ring.iter().all(|p| p.1.abs() <= 180.0));
+        }
+        assert!(rings[0].iter().any(|p| p.1 == 180.0));
+        assert!(rings[1].iter().any(|p| p.1 == -180.0));
+        assert!(rings[1].iter().all(|p| p.1 < 0.0));
+    }
+
+    #[test]
+    fn footprint_around_pole() {
+        for lat in [80.0, -80.0] {
+            let rings = footprint_rings(lat, 30.0, 420.0, 64);
+            assert_eq!(rings.len(), 1);
+            let ring = &rings[0];
+            assert_eq!(ring.first(), ring.last());
+            assert!(signed_area(ring) > 0.0, "lat {lat}");
+            assert!(ring.contains(&(lat.signum() * 90.0, 180.0)));
+            assert!(ring.contains(&(lat.signum() * 90.0, -180.0)));
+            assert!(ring.iter().all(|p| p.1.abs() <= 180.0));
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// Геомагнитная буря из ответа DONKI /GST
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GeomagneticStorm {
+    pub gst_id: String,
+    pub start_time: String,
+    /// Наибольший Kp из allKpIndex; None, если измерений нет
+    pub max_kp: Option<f64>,
+    /// Когда наблюдался наибольший Kp
+    pub max_kp_observed_at: Option<String>,
+    pub link: Option<String>,
+}
+
+/// Самая поздняя по startTime буря; Ok(None) — за период бурь не было
+pub fn latest_storm(payload: &Value) -> Result<Option<GeomagneticStorm>, String> {
+    let items = payload.as_array().ok_or("expected an array of storms")?;
+    Ok(items
+        .iter()
+        .filter_map(storm)
+        .max_by(|a, b| a.start_time.cmp(&b.start_time)))
+}
+
+fn storm(item: &Value) -> Option<GeomagneticStorm> {
+    let text = |v: &Value, key: &str| v.get(key).and_then(Value::as_str).map(str::to_string);
+    let peak = item
+        .get("allKpIndex")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|kp| Some((kp.get("kpIndex")?.as_f64()?, text(kp, "observedTime"))))
+        .max_by(|a, b| a.0.total_cmp(&b.0));
+    Some(GeomagneticStorm {
+        gst_id: text(item, "gstID")?,
+        start_time: text(item, "startTime")?,
+        max_kp: peak.as_ref().map(|p| p.0),
+        max_kp_observed_at: peak.and_then(|p| p.1),
+        link: text(item, "link"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn picks_latest_storm_and_peak_kp() {
+        let payload = json!([
+            {"gstID": "2026-10-12T03:00:00-GST-001", "startTime": "2026-10-12T03:00Z",
+             "allKpIndex": [{"observedTime": "2026-10-12T06:00Z", "kpIndex": 6.0}]},
+            {"gstID": "2026-10-14T21:00:00-GST-001", "startTime": "2026-10-14T21:00Z",
+             "allKpIndex": [
+                {"observedTime": "2026-10-15T00:00Z", "kpIndex": 5.33},
+                {"observedTime": "2026-10-15T03:00Z", "kpIndex": 7.67},
+                {"observedTime": "2026-10-15T06:00Z", "kpIndex": 6.0}
+             ]}
+        ]);
+        let storm = latest_storm(&payload).unwrap().unwrap();
+        assert_eq!(storm.gst_id, "2026-10-14T21:00:00-GST-001");
+        assert_eq!(storm.max_kp, Some(7.67));
+        assert_eq!(storm.max_kp_observed_at.as_deref(), Some("2026-10-15T03:00Z"));
+
+        assert_eq!(latest_storm(&json!([])), Ok(None));
+        assert!(latest_storm(&json!({})).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+
+use crate::spacex::UpcomingLaunch;
+
+/// Предел длины строки iCalendar в октетах без CRLF (RFC 5545, 3.1)
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Календарь VCALENDAR с событием на каждый запуск. Запуски без id
+/// пропускаются: UID обязателен, а другого стабильного ключа нет.
+/// Запуски с точностью до месяца и грубее становятся событиями на весь день
+/// первого числа месяца
+pub fn launches_calendar(launches: &[UpcomingLaunch], now: DateTime<Utc>) -> String {
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//rust_iss//SpaceX launches//EN");
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, "X-WR-CALNAME:SpaceX launches");
+    let stamp = format_utc(now);
+    for launch in launches {
+        let Some(id) = launch.id.as_deref() else {
+            continue;
+        };
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}", escape_text(id)));
+        push_line(&mut out, &format!("DTSTAMP:{}", stamp));
+        let mut description = Vec::new();
+        if launch.approximate {
+            let day = NaiveDate::from_ymd_opt(launch.date_utc.year(), launch.date_utc.month(), 1)
+                .unwrap_or_else(|| launch.date_utc.date_naive());
+            push_line(&mut out, &format!("DTSTART;VALUE=DATE:{}", day.format("%Y%m%d")));
+            push_line(
+                &mut out,
+                &format!("DTEND;VALUE=DATE:{}", (day + Days::new(1)).format("%Y%m%d")),
+            );
+            description.push(format!(
+                "Date known only to the {}; the launch is shown on the first of the month.",
+                launch.date_precision.as_deref().unwrap_or("month")
+            ));
+        } else {
+            push_line(&mut out, &format!("DTSTART:{}", format_utc(launch.date_utc)));
+        }
+        push_line(&mut out, &format!("SUMMARY:{}", escape_text(&launch.name)));
+        if let Some(details) = &launch.details {
+            description.push(details.clone());
+        }
+        if let Some(webcast) = &launch.webcast {
+            description.push(format!("Webcast: {}", webcast));
+            push_line(&mut out, &format!("URL:{}", webcast));
+        }
+        if !description.is_empty() {
+            push_line(&mut out, &format!("DESCRIPTION:{}", escape_text(&description.join("\n\n"))));
+        }
+        push_line(&mut out, "END:VEVENT");
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+fn format_utc(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Экранирование значения TEXT: \, ;, , и переводы строк
+pub fn escape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Дописывает строку с переносом по 75 октетов: продолжение начинается
+/// с пробела, многобайтовые символы UTF-8 не разрываются
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        let len = c.len_utf8();
+        if width + len > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            // пробел продолжения тоже входит в длину строки
+            width = 1;
+        }
+        out.push(c);
+        width += len;
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Свойство разобранного календаря: имя, параметры и значение
+    #[derive(Debug)]
+    struct Property {
+        name: String,
+        params: Vec<String>,
+        value: String,
+    }
+
+    /// Небольшой разборщик iCalendar: проверяет CRLF, длину строк
+    /// и вложенность BEGIN/END, склеивает перенесённые строки
+    /// и снимает экранирование; возвращает события
+    fn parse(ics: &str) -> Vec<Vec<Property>> {
+        assert!(ics.ends_with("\r\n"));
+        let mut lines: Vec<String> = Vec::new();
+        for raw in ics.strip_suffix("\r\n").unwrap().split("\r\n") {
+            assert!(!raw.contains('\n'), "bare LF in {:?}", raw);
+            assert!(raw.len() <= MAX_LINE_OCTETS, "line too long: {:?}", raw);
+            match raw.strip_prefix(' ') {
+                Some(rest) => lines.last_mut().expect("continuation first").push_str(rest),
+                None => lines.push(raw.to_string()),
+            }
+        }
+        let mut stack = Vec::new();
+        let mut events = Vec::new();
+        let mut current = Vec::new();
+        for line in lines {
+            let (head, value) = line.split_once(':').expect("property without value");
+            let mut parts = head.split(';');
+            let name = parts.next().unwrap().to_string();
+            let params = parts.map(str::to_string).collect();
+            match name.as_str() {
+                "BEGIN" => stack.push(value.to_string()),
+                "END" => {
+                    assert_eq!(stack.pop().as_deref(), Some(value));
+                    if value == "VEVENT" {
+                        events.push(std::mem::take(&mut current));
+                    }
+                }
+                _ => current.push(Property { name, params, value: unescape(value) }),
+            }
+        }
+        assert!(stack.is_empty());
+        events
+    }
+
+    fn unescape(value: &str) -> String {
+        let mut out = String::new();
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('n' | 'N') => out.push('\n'),
+                    Some(c @ ('\\' | ';' | ',')) => out.push(c),
+                    other => panic!("bad escape {:?}", other),
+                },
+                ';' | ',' => panic!("unescaped {:?} in {:?}", c, value),
+                c => out.push(c),
+            }
+        }
+        out
+    }
+
+    fn prop<'a>(event: &'a [Property], name: &str) -> Option<&'a Property> {
+        event.iter().find(|p| p.name == name)
+    }
+
+    fn launch(id: Option<&str>, name: &str, date: &str, precision: &str) -> UpcomingLaunch {
+        UpcomingLaunch {
+            id: id.map(str::to_string),
+            name: name.to_string(),
+            date_utc: DateTime::parse_from_rfc3339(date).unwrap().with_timezone(&Utc),
+            approximate: !matches!(precision, "day" | "hour"),
+            date_precision: Some(precision.to_string()),
+            rocket: None,
+            launchpad: None,
+            webcast: None,
+            details: None,
+        }
+    }
+
+    #[test]
+    fn renders_parseable_calendar() {
+        let now = DateTime::parse_from_rfc3339("2026-10-16T12:00:00Z").unwrap().with_timezone(&Utc);
+        let mut starlink = launch(Some("62dd70d5202306255024d139"), "Starlink 12-9", "2026-10-20T03:05:00Z", "hour");
+        starlink.details = Some(format!("Falcon 9; booster B1080, {}", "очень длинное описание ".repeat(5)));
+        starlink.webcast = Some("https://youtu.be/x".into());
+        let crew = launch(Some("crew13"), "Crew-13", "2026-11-15T00:00:00Z", "month");
+        let ics = launches_calendar(&[starlink, crew, launch(None, "No id", "2026-12-01T00:00:00Z", "day")], now);
+
+        let events = parse(&ics);
+        assert_eq!(events.len(), 2);
+
+        let first = &events[0];
+        assert_eq!(prop(first, "UID").unwrap().value, "62dd70d5202306255024d139");
+        assert_eq!(prop(first, "DTSTAMP").unwrap().value, "20261016T120000Z");
+        assert_eq!(prop(first, "DTSTART").unwrap().value, "20261020T030500Z");
+        assert_eq!(prop(first, "SUMMARY").unwrap().value, "Starlink 12-9");
+        let description = &prop(first, "DESCRIPTION").unwrap().value;
+        assert!(description.starts_with("Falcon 9; booster B1080, очень"));
+        assert!(description.ends_with("\n\nWebcast: https://youtu.be/x"));
+
+        let second = &events[1];
+        let start = prop(second, "DTSTART").unwrap();
+        assert_eq!(start.params, vec!["VALUE=DATE"]);
+        assert_eq!(start.value, "20261101");
+        assert_eq!(prop(second, "DTEND").unwrap().value, "20261102");
+        assert!(prop(second, "DESCRIPTION").unwrap().value.contains("first of the month"));
+    }
+
+    #[test]
+    fn folds_without_splitting_characters() {
+        let mut out = String::new();
+        push_line(&mut out, &format!("SUMMARY:{}", "ж".repeat(80)));
+        for line in out.trim_end_matches("\r\n").split("\r\n") {
+            assert!(line.len() <= MAX_LINE_OCTETS);
+        }
+        assert_eq!(escape_text("a,b;c\\d\r\ne"), "a\\,b\\;c\\\\d\\ne");
+    }
+}
@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use sqlx::{postgres::PgRow, Row};
+
+use crate::geo::{extract_number, great_circle_interpolate};
+
+/// Положение МКС, разобранное из ответа upstream
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IssPosition {
+    #[serde(deserialize_with = "lenient_f64")]
+    pub latitude: f64,
+    #[serde(deserialize_with = "lenient_f64")]
+    pub longitude: f64,
+    #[serde(default, deserialize_with = "lenient_opt_f64")]
+    pub altitude: Option<f64>,
+    #[serde(default, deserialize_with = "lenient_opt_f64")]
+    pub velocity: Option<f64>,
+    #[serde(default, deserialize_with = "lenient_opt_i64")]
+    pub timestamp: Option<i64>,
+    #[serde(default)]
+    pub visibility: Option<String>,
+}
+
+impl IssPosition {
+    /// Разбор и проверка диапазонов; ошибка содержит причину отказа.
+    /// Понимает и плоский ответ wheretheiss.at, и open-notify iss-now.json,
+    /// где координаты вложены в iss_position, а timestamp лежит уровнем выше
+    pub fn from_payload(payload: &Value) -> Result<Self, String> {
+        let flat = match payload.get("iss_position") {
+            Some(Value::Object(inner)) => {
+                let mut flat = inner.clone();
+                if let Some(ts) = payload.get("timestamp") {
+                    flat.entry("timestamp").or_insert_with(|| ts.clone());
+                }
+                Value::Object(flat)
+            }
+            _ => payload.clone(),
+        };
+        let pos: IssPosition =
+            serde_json::from_value(flat).map_err(|e| format!("malformed payload: {}", e))?;
+        pos.validate()?;
+        Ok(pos)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !(-90.0..=90.0).contains(&self.latitude) {
+            return Err(format!("latitude {} out of range [-90, 90]", self.latitude));
+        }
+        if !(-180.0..=180.0).contains(&self.longitude) {
+            return Err(format!("longitude {} out of range [-180, 180]", self.longitude));
+        }
+        if let Some(alt) = self.altitude {
+            if alt < 0.0 {
+                return Err(format!("altitude {} is negative", alt));
+            }
+        }
+        if let Some(v) = self.velocity {
+            if v < 0.0 {
+                return Err(format!("velocity {} is negative", v));
+            }
+        }
+        Ok(())
+    }
+
+    /// Момент измерения по данным upstream
+    pub fn sample_time(&self) -> Option<DateTime<Utc>> {
+        self.timestamp.and_then(|t| Utc.timestamp_opt(t, 0).single())
+    }
+}
+
+/// Положение из строки iss_fetch_log: типизированные колонки, а для старых
+/// строк без них — разбор сырого payload
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredPosition {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude_km: Option<f64>,
+    pub velocity_kmh: Option<f64>,
+    pub sample_time: Option<DateTime<Utc>>,
+    pub visibility: Option<String>,
+}
+
+impl StoredPosition {
+    /// Ожидает колонки latitude, longitude, altitude_km, velocity_kmh,
+    /// sample_time, visibility и payload
+    pub fn from_row(row: &PgRow) -> Option<Self> {
+        let typed = (
+            row.try_get::<Option<f64>, _>("latitude").ok().flatten(),
+            row.try_get::<Option<f64>, _>("longitude").ok().flatten(),
+        );
+        if let (Some(latitude), Some(longitude)) = typed {
+            return Some(Self {
+                latitude,
+                longitude,
+                altitude_km: row.try_get("altitude_km").ok().flatten(),
+                velocity_kmh: row.try_get("velocity_kmh").ok().flatten(),
+                sample_time: row.try_get("sample_time").ok().flatten(),
+                visibility: row.try_get("visibility").ok().flatten(),
+            });
+        }
+
+        let payload: Value = row.try_get("payload").ok()?;
+        IssPosition::from_payload(&payload).ok().map(Self::from)
+    }
+}
+
+impl StoredPosition {
+    /// Оценка положения на доле `f` между двумя выборками: широта и долгота —
+    /// по дуге большого круга, высота и скорость — линейно. Поля, которых нет
+    /// хотя бы у одной из выборок, остаются пустыми
+    pub fn interpolate(&self, next: &Self, f: f64, at: DateTime<Utc>) -> Option<Self> {
+        let (latitude, longitude) =
+            great_circle_interpolate(self.latitude, self.longitude, next.latitude, next.longitude, f)?;
+        let lerp = |a: Option<f64>, b: Option<f64>| Some(a? + (b? - a?) * f);
+        Some(Self {
+            latitude,
+            longitude,
+            altitude_km: lerp(self.altitude_km, next.altitude_km),
+            velocity_kmh: lerp(self.velocity_kmh, next.velocity_kmh),
+            sample_time: Some(at),
+            visibility: None,
+        })
+    }
+}
+
+impl From<IssPosition> for StoredPosition {
+    fn from(p: IssPosition) -> Self {
+        Self {
+            sample_time: p.sample_time(),
+            latitude: p.latitude,
+            longitude: p.longitude,
+            altitude_km: p.altitude,
+            velocity_kmh: p.velocity,
+            visibility: p.visibility,
+        }
+    }
+}
+
+/// Неудач подряд, после которых зеркало уходит в конец очереди
+const MIRROR_FAILURE_THRESHOLD: u32 = 3;
+/// Сколько зеркало остаётся в конце очереди
+const MIRROR_DEMOTION: chrono::Duration = chrono::Duration::minutes(10);
+
+#[derive(Debug, Clone, Default)]
+struct MirrorState {
+    consecutive_failures: u32,
+    demoted_until: Option<DateTime<Utc>>,
+}
+
+/// Здоровье зеркал источника положений. Зеркало, отказавшее
+/// MIRROR_FAILURE_THRESHOLD раз подряд, на MIRROR_DEMOTION опрашивается
+/// последним, чтобы не ждать его таймаут в каждом цикле
+#[derive(Debug, Default)]
+pub struct Mirrors {
+    states: RwLock<HashMap<String, MirrorState>>,
+}
+
+impl Mirrors {
+    /// Порядок перебора: сначала незаниженные в исходном порядке, затем остальные
+    pub fn order(&self, urls: Vec<String>, now: DateTime<Utc>) -> Vec<String> {
+        let states = self.states.read().unwrap_or_else(|e| e.into_inner());
+        let demoted = |url: &String| states.get(url).and_then(|s| s.demoted_until).is_some_and(|until| until > now);
+        let (healthy, demoted): (Vec<_>, Vec<_>) = urls.into_iter().partition(|u| !demoted(u));
+        healthy.into_iter().chain(demoted).collect()
+    }
+
+    pub fn succeeded(&self, url: &str) {
+        self.states.write().unwrap_or_else(|e| e.into_inner()).remove(url);
+    }
+
+    pub fn failed(&self, url: &str, now: DateTime<Utc>) {
+        let mut states = self.states.write().unwrap_or_else(|e| e.into_inner());
+        let state = states.entry(url.to_string()).or_default();
+        state.consecutive_failures += 1;
+        if state.consecutive_failures >= MIRROR_FAILURE_THRESHOLD {
+            state.demoted_until = Some(now + MIRROR_DEMOTION);
+        }
+    }
+}
+
+fn lenient_f64<'de, D: Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+    let v = Value::deserialize(d)?;
+    extract_number(&v).ok_or_else(|| serde::de::Error::custom(format!("expected number, got {}", v)))
+}
+
+fn lenient_opt_f64<'de, D: Deserializer<'de>>(d: D) -> Result<Option<f64>, D::Error> {
+    let v = Value::deserialize(d)?;
+    Ok(extract_number(&v))
+}
+
+fn lenient_opt_i64<'de, D: Deserializer<'de>>(d: D) -> Result<Option<i64>, D::Error> {
+    let v = Value::deserialize(d)?;
+    Ok(extract_number(&v).map(|n| n as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_wheretheiss_payload() {
+        let pos = IssPosition::from_payload(&json!({
+            "name": "iss", "id": 25544,
+            "latitude": 51.2, "longitude": -47.8,
+            "altitude": 421.3, "velocity": 27580.1,
+            "visibility": "daylight", "timestamp": 1700000000
+        }))
+        .unwrap();
+        assert_eq!(pos.latitude, 51.2);
+        assert_eq!(pos.visibility.as_deref(), Some("daylight"));
+        assert_eq!(pos.sample_time().unwrap().timestamp(), 1700000000);
+    }
+
+    #[test]
+    fn normalizes_open_notify_payload() {
+        let pos = IssPosition::from_payload(&json!({
+            "message": "success", "timestamp": 1700000000,
+            "iss_position": {"latitude": "51.2", "longitude": "-47.8"}
+        }))
+        .unwrap();
+        assert_eq!((pos.latitude, pos.longitude), (51.2, -47.8));
+        assert_eq!((pos.altitude, pos.velocity), (None, None));
+        assert_eq!(pos.sample_time().unwrap().timestamp(), 1700000000);
+    }
+
+    #[test]
+    fn failing_mirror_moves_to_the_back() {
+        let now = Utc::now();
+        let urls = || vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let mirrors = Mirrors::default();
+        for _ in 0..MIRROR_FAILURE_THRESHOLD - 1 {
+            mirrors.failed("a", now);
+        }
+        assert_eq!(mirrors.order(urls(), now), urls());
+
+        mirrors.failed("a", now);
+        assert_eq!(mirrors.order(urls(), now), vec!["b", "c", "a"]);
+        assert_eq!(mirrors.order(urls(), now + MIRROR_DEMOTION), urls());
+
+        mirrors.failed("a", now);
+        mirrors.succeeded("a");
+        assert_eq!(mirrors.order(urls(), now), urls());
+    }
+
+    #[test]
+    fn accepts_string_numbers_and_missing_optionals() {
+        let pos = IssPosition::from_payload(&json!({"latitude": "-12.5", "longitude": "170.25"}))
+            .unwrap();
+        assert_eq!((pos.latitude, pos.longitude), (-12.5, 170.25));
+        assert_eq!(pos.altitude, None);
+        assert_eq!(pos.sample_time(), None);
+    }
+
+    fn stored(lat: f64, lon: f64, alt: f64, vel: f64) -> StoredPosition {
+        StoredPosition {
+            latitude: lat,
+            longitude: lon,
+            altitude_km: Some(alt),
+            velocity_kmh: Some(vel),
+            sample_time: None,
+            visibility: Some("daylight".into()),
+        }
+    }
+
+    #[test]
+    fn interpolates_midway_between_samples() {
+        let at = Utc.timestamp_opt(1_700_000_060, 0).unwrap();
+        let a = stored(0.0, 10.0, 420.0, 27_500.0);
+        let b = stored(0.0, 14.0, 424.0, 27_700.0);
+        let mid = a.interpolate(&b, 0.5, at).unwrap();
+        assert!(mid.latitude.abs() < 1e-9);
+        assert!((mid.longitude - 12.0).abs() < 1e-9);
+        assert_eq!(mid.altitude_km, Some(422.0));
+        assert_eq!(mid.velocity_kmh, Some(27_600.0));
+        assert_eq!(mid.sample_time, Some(at));
+        assert_eq!(mid.visibility, None);
+    }
+
+    #[test]
+    fn interpolates_across_antimeridian() {
+        let at = Utc.timestamp_opt(1_700_000_060, 0).unwrap();
+        let a = stored(-1.0, 178.0, 420.0, 27_600.0);
+        let mut b = stored(1.0, -178.0, 420.0, 27_600.0);
+        b.altitude_km = None;
+        let mid = a.interpolate(&b, 0.5, at).unwrap();
+        assert!(mid.latitude.abs() < 1e-6);
+        assert!((mid.longitude.abs() - 180.0).abs() < 1e-6, "{}", mid.longitude);
+        assert_eq!(mid.altitude_km, None);
+    }
+
+    #[test]
+    fn rejects_bad_payloads() {
+        assert!(IssPosition::from_payload(&json!({})).is_err());
+        assert!(IssPosition::from_payload(&json!({"latitude": 95.0, "longitude": 0.0})).is_err());
+        assert!(IssPosition::from_payload(&json!({"latitude": 0.0, "longitude": -181.0})).is_err());
+        assert!(IssPosition::from_payload(&json!({"latitude": "n/a", "longitude": 0.0})).is_err());
+    }
+}
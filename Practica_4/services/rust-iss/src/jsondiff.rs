@@ -0,0 +1,165 @@
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// Одно отличие; path — JSON Pointer, элементы массивов с ключом
+/// идентичности адресуются как `[key=value]`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    pub path: String,
+    pub kind: ChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<Value>,
+}
+
+/// Структурный diff двух документов. Массивы, все элементы которых — объекты
+/// со скалярным `id_key`, сравниваются по нему, а не по позиции, поэтому
+/// перестановка событий изменением не считается
+pub fn diff(old: &Value, new: &Value, id_key: Option<&str>) -> Vec<Change> {
+    let mut changes = Vec::new();
+    walk(old, new, "", id_key, &mut changes);
+    changes
+}
+
+fn walk(old: &Value, new: &Value, path: &str, id_key: Option<&str>, out: &mut Vec<Change>) {
+    match (old, new) {
+        (Value::Object(a), Value::Object(b)) => {
+            for (key, av) in a {
+                let child = format!("{}/{}", path, escape(key));
+                match b.get(key) {
+                    Some(bv) => walk(av, bv, &child, id_key, out),
+                    None => out.push(removed(child, av)),
+                }
+            }
+            for (key, bv) in b {
+                if !a.contains_key(key) {
+                    out.push(added(format!("{}/{}", path, escape(key)), bv));
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => match id_key.filter(|k| keyed(a, k) && keyed(b, k)) {
+            Some(key) => walk_keyed(a, b, path, key, out),
+            None => {
+                for (i, av) in a.iter().enumerate() {
+                    let child = format!("{}/{}", path, i);
+                    match b.get(i) {
+                        Some(bv) => walk(av, bv, &child, id_key, out),
+                        None => out.push(removed(child, av)),
+                    }
+                }
+                for (i, bv) in b.iter().enumerate().skip(a.len()) {
+                    out.push(added(format!("{}/{}", path, i), bv));
+                }
+            }
+        },
+        _ if old != new => out.push(Change {
+            path: path.to_string(),
+            kind: ChangeKind::Changed,
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+        }),
+        _ => {}
+    }
+}
+
+fn walk_keyed(a: &[Value], b: &[Value], path: &str, key: &str, out: &mut Vec<Change>) {
+    let id_of = |v: &Value| identity(v, key).unwrap_or_default();
+    for av in a {
+        let id = id_of(av);
+        let child = format!("{}/[{}={}]", path, key, escape(&id));
+        match b.iter().find(|bv| id_of(bv) == id) {
+            Some(bv) => walk(av, bv, &child, Some(key), out),
+            None => out.push(removed(child, av)),
+        }
+    }
+    for bv in b {
+        let id = id_of(bv);
+        if !a.iter().any(|av| id_of(av) == id) {
+            out.push(added(format!("{}/[{}={}]", path, key, escape(&id)), bv));
+        }
+    }
+}
+
+/// Значение ключа идентичности элемента: строка или число
+fn identity(item: &Value, key: &str) -> Option<String> {
+    match item.get(key)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn keyed(items: &[Value], key: &str) -> bool {
+    items.iter().all(|v| identity(v, key).is_some())
+}
+
+/// Экранирование сегмента по RFC 6901
+fn escape(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+fn added(path: String, value: &Value) -> Change {
+    Change { path, kind: ChangeKind::Added, old: None, new: Some(value.clone()) }
+}
+
+fn removed(path: String, value: &Value) -> Change {
+    Change { path, kind: ChangeKind::Removed, old: Some(value.clone()), new: None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reports_object_and_index_changes() {
+        let old = json!({"title": "M87", "tags": ["a", "b"], "gone": 1, "a/b": 1});
+        let new = json!({"title": "M31", "tags": ["a"], "fresh": true, "a/b": 2});
+        let paths: Vec<(String, ChangeKind)> = diff(&old, &new, None)
+            .into_iter()
+            .map(|c| (c.path, c.kind))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("/a~1b".to_string(), ChangeKind::Changed),
+                ("/gone".to_string(), ChangeKind::Removed),
+                ("/tags/1".to_string(), ChangeKind::Removed),
+                ("/title".to_string(), ChangeKind::Changed),
+                ("/fresh".to_string(), ChangeKind::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn keyed_arrays_ignore_reordering() {
+        let old = json!([
+            {"flrID": "F1", "classType": "M1.0"},
+            {"flrID": "F2", "classType": "C3.2"}
+        ]);
+        let new = json!([
+            {"flrID": "F3", "classType": "X1.1"},
+            {"flrID": "F2", "classType": "C3.2"},
+            {"flrID": "F1", "classType": "M1.2"}
+        ]);
+        let changes = diff(&old, &new, Some("flrID"));
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].path, "/[flrID=F1]/classType");
+        assert_eq!(changes[0].new, Some(json!("M1.2")));
+        assert_eq!(changes[1].path, "/[flrID=F3]");
+        assert_eq!(changes[1].kind, ChangeKind::Added);
+
+        // Без ключа у элемента — сравнение по позиции
+        assert_eq!(diff(&old, &json!([{"x": 1}]), Some("flrID")).len(), 4);
+        assert!(diff(&old, &old, Some("flrID")).is_empty());
+    }
+}
@@ -0,0 +1,151 @@
+use std::fmt::Write as _;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+/// Точка трека для KML: долгота, широта и высота в метрах
+pub struct KmlPoint {
+    pub when: DateTime<Utc>,
+    pub lat: f64,
+    pub lon: f64,
+    pub alt_m: f64,
+}
+
+/// Небольшой построитель KML: следит за вложенностью тегов
+/// и экранирует весь текст, так что разметка не собирается вручную
+pub struct KmlWriter {
+    out: String,
+    open: Vec<&'static str>,
+}
+
+impl KmlWriter {
+    pub fn new() -> Self {
+        let mut w = Self {
+            out: String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"),
+            open: Vec::new(),
+        };
+        w.out.push_str(
+            "<kml xmlns=\"http://www.opengis.net/kml/2.2\" \
+             xmlns:gx=\"http://www.google.com/kml/ext/2.2\">\n",
+        );
+        w.open.push("kml");
+        w
+    }
+
+    pub fn open(&mut self, tag: &'static str) -> &mut Self {
+        self.indent();
+        let _ = writeln!(self.out, "<{}>", tag);
+        self.open.push(tag);
+        self
+    }
+
+    pub fn open_with_id(&mut self, tag: &'static str, id: &str) -> &mut Self {
+        self.indent();
+        let _ = writeln!(self.out, "<{} id=\"{}\">", tag, escape_xml(id));
+        self.open.push(tag);
+        self
+    }
+
+    pub fn close(&mut self) -> &mut Self {
+        if let Some(tag) = self.open.pop() {
+            self.indent();
+            let _ = writeln!(self.out, "</{}>", tag);
+        }
+        self
+    }
+
+    /// Элемент с текстовым содержимым
+    pub fn text(&mut self, tag: &'static str, value: &str) -> &mut Self {
+        self.indent();
+        let _ = writeln!(self.out, "<{tag}>{}</{tag}>", escape_xml(value));
+        self
+    }
+
+    /// Закрывает все открытые теги
+    pub fn finish(mut self) -> String {
+        while !self.open.is_empty() {
+            self.close();
+        }
+        self.out
+    }
+
+    fn indent(&mut self) {
+        for _ in 0..self.open.len() {
+            self.out.push_str("  ");
+        }
+    }
+}
+
+pub fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Документ с одним gx:Track; высота абсолютная, чтобы трек был виден в 3D
+pub fn track_document(name: &str, description: &str, points: &[KmlPoint]) -> String {
+    let mut w = KmlWriter::new();
+    w.open("Document").text("name", name);
+
+    w.open_with_id("Style", "track")
+        .open("LineStyle")
+        .text("color", "ff00a5ff")
+        .text("width", "3")
+        .close()
+        .close();
+
+    w.open("Placemark")
+        .text("name", name)
+        .text("description", description)
+        .text("styleUrl", "#track")
+        .open("gx:Track")
+        .text("altitudeMode", "absolute");
+    for p in points {
+        w.text("when", &p.when.to_rfc3339_opts(SecondsFormat::Secs, true));
+    }
+    for p in points {
+        w.text("gx:coord", &format!("{} {} {:.0}", p.lon, p.lat, p.alt_m));
+    }
+    w.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn escapes_text() {
+        assert_eq!(escape_xml("a<b & \"c\"'d'>"), "a&lt;b &amp; &quot;c&quot;&apos;d&apos;&gt;");
+        assert_eq!(escape_xml("МКС"), "МКС");
+    }
+
+    #[test]
+    fn renders_track_with_altitude() {
+        let t0 = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let points = [
+            KmlPoint { when: t0, lat: 51.5, lon: -0.1, alt_m: 420_000.0 },
+            KmlPoint { when: t0 + chrono::Duration::minutes(2), lat: 52.0, lon: 7.5, alt_m: 420_500.0 },
+        ];
+        let kml = track_document("ISS <25544>", "from & to", &points);
+
+        assert!(kml.starts_with("<?xml"));
+        assert!(kml.contains("<name>ISS &lt;25544&gt;</name>"));
+        assert!(kml.contains("<description>from &amp; to</description>"));
+        assert!(kml.contains("<altitudeMode>absolute</altitudeMode>"));
+        assert!(kml.contains("<when>2024-03-01T12:02:00Z</when>"));
+        assert!(kml.contains("<gx:coord>-0.1 51.5 420000</gx:coord>"));
+        assert!(kml.trim_end().ends_with("</kml>"));
+        // Теги сбалансированы
+        assert_eq!(kml.matches("<gx:Track>").count(), kml.matches("</gx:Track>").count());
+        assert_eq!(kml.matches("<Placemark>").count(), 1);
+    }
+}
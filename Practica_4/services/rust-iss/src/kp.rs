@@ -0,0 +1,123 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::geo::extract_number;
+
+/// Одно измерение планетарного индекса Kp
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct KpSample {
+    pub time: DateTime<Utc>,
+    pub kp: f64,
+}
+
+/// Уровень геомагнитной активности; g_scale — шкала бурь NOAA G1–G5
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct KpLevel {
+    /// quiet, unsettled или storm
+    pub level: &'static str,
+    pub g_scale: Option<&'static str>,
+}
+
+/// Измерения из ответа SWPC по времени. Строки — объекты с time_tag
+/// и estimated_kp/kp_index или массивы под строкой-заголовком, как в других
+/// продуктах SWPC; числа бывают строками, строки без времени или Kp пропускаются
+pub fn samples(payload: &Value) -> Result<Vec<KpSample>, String> {
+    let rows = payload.as_array().ok_or("expected an array of Kp rows")?;
+    let header: Option<Vec<&str>> = rows
+        .first()
+        .and_then(Value::as_array)
+        .filter(|cells| cells.iter().all(Value::is_string))
+        .map(|cells| cells.iter().filter_map(Value::as_str).collect());
+    let field = |row: &'_ Value, key: &str| -> Option<Value> {
+        match row {
+            Value::Object(_) => row.get(key).cloned(),
+            Value::Array(cells) => {
+                let i = header.as_ref()?.iter().position(|h| h.eq_ignore_ascii_case(key))?;
+                cells.get(i).cloned()
+            }
+            _ => None,
+        }
+    };
+    let mut out: Vec<KpSample> = rows
+        .iter()
+        .skip(usize::from(header.is_some()))
+        .filter_map(|row| {
+            let time = parse_time(field(row, "time_tag")?.as_str()?)?;
+            let kp = ["estimated_kp", "kp_index", "kp"]
+                .iter()
+                .find_map(|key| field(row, key).as_ref().and_then(extract_number))?;
+            Some(KpSample { time, kp })
+        })
+        .collect();
+    out.sort_by_key(|s| s.time);
+    Ok(out)
+}
+
+/// SWPC пишет время в UTC без зоны: "2026-10-16T11:59:00" или "2026-10-16 11:59:00.000"
+pub fn parse_time(raw: &str) -> Option<DateTime<Utc>> {
+    let raw = raw.trim();
+    if let Ok(t) = DateTime::parse_from_rfc3339(raw) {
+        return Some(t.with_timezone(&Utc));
+    }
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(raw, f).ok())
+        .map(|t| t.and_utc())
+}
+
+/// Уровень по Kp, округлённому до целого, как в шкале NOAA: 5- (4.67) уже G1
+pub fn level(kp: f64) -> KpLevel {
+    let (level, g_scale) = match kp.round() as i64 {
+        i64::MIN..=2 => ("quiet", None),
+        3 | 4 => ("unsettled", None),
+        5 => ("storm", Some("G1")),
+        6 => ("storm", Some("G2")),
+        7 => ("storm", Some("G3")),
+        8 => ("storm", Some("G4")),
+        _ => ("storm", Some("G5")),
+    };
+    KpLevel { level, g_scale }
+}
+
+/// Наибольшее измерение не раньше `since`
+pub fn max_since(samples: &[KpSample], since: DateTime<Utc>) -> Option<KpSample> {
+    samples
+        .iter()
+        .filter(|s| s.time >= since)
+        .max_by(|a, b| a.kp.total_cmp(&b.kp))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_rows_and_maps_levels() {
+        let payload = json!([
+            {"time_tag": "2026-10-16T11:58:00", "kp_index": 4, "estimated_kp": "4.67", "kp": "5M"},
+            null,
+            {"time_tag": "2026-10-16T11:57:00", "kp_index": 3, "estimated_kp": null},
+            {"time_tag": "2026-10-15T08:00:00", "estimated_kp": 7.33},
+            {"time_tag": null, "estimated_kp": 9}
+        ]);
+        let parsed = samples(&payload).unwrap();
+        let kps: Vec<f64> = parsed.iter().map(|s| s.kp).collect();
+        assert_eq!(kps, vec![7.33, 3.0, 4.67]);
+        assert_eq!(parsed[2].time.to_rfc3339(), "2026-10-16T11:58:00+00:00");
+
+        let since = parsed[2].time - chrono::Duration::hours(24);
+        assert_eq!(max_since(&parsed, since).map(|s| s.kp), Some(4.67));
+
+        let table = json!([["time_tag", "Kp"], ["2026-10-16 09:00:00.000", "2.33"], ["bad", "1"]]);
+        assert_eq!(samples(&table).unwrap().iter().map(|s| s.kp).collect::<Vec<_>>(), vec![2.33]);
+        assert!(samples(&json!({})).is_err());
+
+        assert_eq!(level(2.33), KpLevel { level: "quiet", g_scale: None });
+        assert_eq!(level(4.33), KpLevel { level: "unsettled", g_scale: None });
+        assert_eq!(level(4.67), KpLevel { level: "storm", g_scale: Some("G1") });
+        assert_eq!(level(9.0).g_scale, Some("G5"));
+    }
+}
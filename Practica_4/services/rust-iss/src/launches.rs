@@ -0,0 +1,178 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::spacex::UpcomingLaunch;
+
+/// Анонимный лимит Launch Library 2: 15 запросов в час
+pub const LL2_REQUESTS_PER_HOUR: usize = 15;
+
+/// Чаще этого LL2 не опрашивается, что бы ни было в конфиге
+pub const LL2_MIN_INTERVAL_SECS: u64 = 3600 / LL2_REQUESTS_PER_HOUR as u64;
+
+/// Насколько могут расходиться даты одного запуска в LL2 и SpaceX API
+const SAME_LAUNCH_WINDOW_HOURS: i64 = 48;
+
+/// Предстоящий запуск любого провайдера для /launches/upcoming
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Launch {
+    pub name: String,
+    /// No Earlier Than — самое раннее возможное время запуска
+    pub net: DateTime<Utc>,
+    pub provider: Option<String>,
+    pub pad: Option<String>,
+    pub mission: Option<String>,
+    pub status: Option<String>,
+    pub webcast: Option<String>,
+    /// ll2, spacex или оба, если запуск нашёлся в обоих источниках
+    pub sources: Vec<&'static str>,
+}
+
+/// Запуски из ответа LL2 /launch/upcoming/; записи без имени или net пропускаются
+pub fn ll2_launches(payload: &Value) -> Result<Vec<Launch>, String> {
+    let results = payload
+        .get("results")
+        .and_then(Value::as_array)
+        .ok_or("missing results array")?;
+    let text = |item: &Value, pointer: &str| item.pointer(pointer).and_then(Value::as_str).map(str::to_string);
+    Ok(results
+        .iter()
+        .filter_map(|item| {
+            let net = DateTime::parse_from_rfc3339(item.get("net")?.as_str()?).ok()?;
+            Some(Launch {
+                name: text(item, "/name")?,
+                net: net.with_timezone(&Utc),
+                provider: text(item, "/launch_service_provider/name"),
+                pad: text(item, "/pad/name"),
+                mission: text(item, "/mission/description"),
+                status: text(item, "/status/name"),
+                webcast: item
+                    .get("vidURLs")
+                    .and_then(Value::as_array)
+                    .and_then(|urls| urls.first())
+                    .and_then(|v| v.get("url").or(Some(v)))
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                sources: vec!["ll2"],
+            })
+        })
+        .collect())
+}
+
+impl From<&UpcomingLaunch> for Launch {
+    fn from(l: &UpcomingLaunch) -> Self {
+        Self {
+            name: l.name.clone(),
+            net: l.date_utc,
+            provider: Some("SpaceX".into()),
+            pad: l.launchpad.clone(),
+            mission: l.details.clone(),
+            status: None,
+            webcast: l.webcast.clone(),
+            sources: vec!["spacex"],
+        }
+    }
+}
+
+/// Слова имени в нижнем регистре. В LL2 имя вида "Falcon 9 Block 5 |
+/// Starlink Group 12-9", сравнивается только часть после "|"
+fn name_words(name: &str) -> Vec<String> {
+    let mission = name.rsplit('|').next().unwrap_or(name);
+    mission
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Один и тот же запуск: даты близки, и все слова одного имени есть в другом
+fn same_launch(a: &Launch, b: &Launch) -> bool {
+    if (a.net - b.net).abs() > Duration::hours(SAME_LAUNCH_WINDOW_HOURS) {
+        return false;
+    }
+    let (a, b) = (name_words(&a.name), name_words(&b.name));
+    let (short, long) = if a.len() <= b.len() { (&a, &b) } else { (&b, &a) };
+    !short.is_empty() && short.iter().all(|w| long.contains(w))
+}
+
+/// Запуски LL2, дополненные запусками SpaceX, которых в LL2 нет. У совпавших
+/// остаётся запись LL2, а пустые поля заполняются из SpaceX. Результат
+/// отсортирован по net
+pub fn merge(ll2: Vec<Launch>, spacex: &[UpcomingLaunch]) -> Vec<Launch> {
+    let mut merged = ll2;
+    for launch in spacex.iter().map(Launch::from) {
+        match merged.iter_mut().find(|m| m.sources == ["ll2"] && same_launch(m, &launch)) {
+            Some(m) => {
+                m.mission = m.mission.take().or(launch.mission);
+                m.webcast = m.webcast.take().or(launch.webcast);
+                m.sources.push("spacex");
+            }
+            None => merged.push(launch),
+        }
+    }
+    merged.sort_by_key(|l| l.net);
+    merged
+}
+
+/// Фильтр ?provider=: подстрока имени провайдера без учёта регистра
+pub fn matches_provider(launch: &Launch, provider: &str) -> bool {
+    launch
+        .provider
+        .as_deref()
+        .is_some_and(|p| p.to_lowercase().contains(&provider.trim().to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn merges_spacex_into_ll2_by_name_and_date() {
+        let ll2 = json!({"count": 3, "results": [
+            {"name": "Falcon 9 Block 5 | Starlink Group 12-9", "net": "2026-10-20T03:00:00Z",
+             "status": {"name": "Go for Launch"}, "launch_service_provider": {"name": "SpaceX"},
+             "pad": {"name": "Space Launch Complex 40"}, "mission": {"description": null}},
+            {"name": "Electron | Kinéis 6", "net": "2026-10-18T10:00:00Z",
+             "launch_service_provider": {"name": "Rocket Lab"}, "vidURLs": [{"url": "https://rl/live"}]},
+            {"name": "No net"}
+        ]});
+        let ll2 = ll2_launches(&ll2).unwrap();
+        assert_eq!(ll2.len(), 2);
+
+        let date = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let spacex_launch = |name: &str, at: &str| UpcomingLaunch {
+            id: None,
+            name: name.into(),
+            date_utc: date(at),
+            date_precision: Some("hour".into()),
+            approximate: false,
+            rocket: None,
+            launchpad: None,
+            webcast: Some("https://youtu.be/x".into()),
+            details: Some("Starlink mission".into()),
+        };
+        let spacex = [
+            spacex_launch("Starlink 12-9", "2026-10-20T05:00:00Z"),
+            spacex_launch("Starlink 12-9", "2026-11-20T05:00:00Z"),
+            spacex_launch("Crew-13", "2026-10-25T00:00:00Z"),
+        ];
+        let merged = merge(ll2, &spacex);
+        let names: Vec<(&str, &[&str])> = merged.iter().map(|l| (l.name.as_str(), l.sources.as_slice())).collect();
+        assert_eq!(
+            names,
+            vec![
+                ("Electron | Kinéis 6", &["ll2"][..]),
+                ("Falcon 9 Block 5 | Starlink Group 12-9", &["ll2", "spacex"][..]),
+                ("Crew-13", &["spacex"][..]),
+                ("Starlink 12-9", &["spacex"][..]),
+            ]
+        );
+        assert_eq!(merged[0].webcast.as_deref(), Some("https://rl/live"));
+        assert_eq!(merged[1].mission.as_deref(), Some("Starlink mission"));
+        assert_eq!(merged[1].status.as_deref(), Some("Go for Launch"));
+        assert!(matches_provider(&merged[0], " rocket "));
+        assert!(!matches_provider(&merged[0], "spacex"));
+        assert!(ll2_launches(&json!([])).is_err());
+    }
+}
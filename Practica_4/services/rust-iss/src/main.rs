@@ -1,33 +1,226 @@
+mod anomaly;
+mod apod;
+mod astros;
+mod backfill;
+mod canonical;
+mod cme;
+mod compression;
 mod errors;
 mod config;
+mod eonet;
+mod epic;
+mod feeds;
+mod flr;
+mod geo;
+mod gst;
+mod http;
+mod ics;
+mod iss;
+mod jsondiff;
+mod kml;
+mod kp;
+mod launches;
+mod mars;
+mod models;
+mod neo;
+mod notifications;
+mod orbit;
+mod osdr;
+mod osdr_sort;
+mod pick;
+mod ratelimit;
+mod regions;
+mod singleflight;
+mod solarwind;
+mod sources;
+mod spacex;
+mod tasks;
+mod tle;
+mod trend;
+mod weather;
+mod webhooks;
 
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::convert::Infallible;
+use std::future::Future;
 use std::time::Duration;
 
 use axum::{
-    extract::{Path, Query, State},
-    routing::get,
+    body::Body,
+    extract::{
+        rejection::JsonRejection,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::{header, HeaderMap},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{delete, get, post},
     Json, Router,
 };
-use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
-use serde::Serialize;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use futures::{Stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::{postgres::PgPoolOptions, PgPool, Row};
-use tracing::{error, info};
+use tokio::sync::{broadcast, Mutex};
+use tower_http::compression::CompressionLayer;
+use tracing::{error, info, warn};
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
-use errors::{ok, ApiError, ApiResult};
-use config::Config;
+use errors::{ok, ok_with_etag, ApiError, ApiResult, CachedResult};
+use anomaly::{AnomalyThresholds, ANOMALY_WINDOW};
+use apod::{ApodEntry, MediaSize, APOD_CHUNK_DAYS};
+use astros::AstrosSummary;
+use backfill::{BackfillProgress, POSITIONS_BATCH};
+use canonical::content_hash;
+use compression::{pack, read_payload, unpack, CacheCompression};
+use config::{Config, ISS_NORAD_ID};
+use epic::EpicImage;
+use iss::{IssPosition, Mirrors, StoredPosition};
+use singleflight::SingleFlight;
+use jsondiff::{diff as json_diff, ChangeKind};
+use kml::{track_document, KmlPoint};
+use models::{
+    ApodEntryRow, ApodGalleryRow, ApodMediaRow, FetchMetricsRow, IssLogRow, NeoObjectRow, OsdrChangeRow, OsdrItemRow, OsdrSyncLogRow, SpaceCacheDump, SpaceCacheEntry,
+    SpaceCacheRow, SpaceSourceRow, SpacexLaunchRow, SpacexYearStatsRow, WebhookDeliveryRow, WebhookRow,
+};
+use neo::{BrowseProgress, NeoApproach};
+use orbit::{estimate_orbit, OrbitSample};
+use osdr::{OsdrMeta, PreparedItem, StoredItem, SyncStats, TrackedFields};
+use osdr_sort::{ListCursor, OsdrSort};
+use pick::{s_pick, t_pick};
+use ratelimit::RateLimiter;
+use regions::Regions;
+use sources::{SourceRegistry, SpaceSource};
+use tasks::TaskStatusMap;
+use tle::{predict_passes, Observer, Propagator, Tle};
+use trend::{compute_trend, seconds_between, Trend, TrendSample};
+use webhooks::{ItemEvent, EVENT_OSDR_NEW, EVENT_OSDR_UPDATED, WEBHOOK_EVENTS};
+use geo::{
+    extract_number, footprint_angle_rad, footprint_rings, geodetic_to_ecef, haversine_km,
+    is_sunlit, look_angles, solar_elevation_deg, solar_position, split_at_antimeridian,
+    EARTH_RADIUS_KM,
+};
 
 #[derive(Serialize)]
 struct Health {
     status: &'static str,
     now: DateTime<Utc>,
+    /// Какая-то фоновая задача падает больше TASK_FAILURE_THRESHOLD раз подряд
+    degraded: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    failing_tasks: Vec<String>,
+    /// Проверка прокси; только если он используется
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proxy: Option<ProxyCheck>,
+}
+
+/// Пробный HEAD через прокси к одному из upstream
+#[derive(Clone, Serialize)]
+struct ProxyCheck {
+    #[serde(flatten)]
+    proxy: http::ProxyInfo,
+    target: &'static str,
+    checked_at: DateTime<Utc>,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    http_status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
 }
 
 #[derive(Clone)]
 struct AppState {
     pool: PgPool,
     config: Config,
+    /// Сколько повторяющихся ответов ISS не было записано
+    iss_duplicates_skipped: Arc<AtomicU64>,
+    /// Идёт пакетная съёмка /fetch/burst
+    iss_burst_running: Arc<AtomicBool>,
+    /// Новые позиции МКС в формате /last для живых подписчиков
+    iss_events: broadcast::Sender<Value>,
+    /// Итог последней очистки
+    last_cleanup: Arc<Mutex<Option<CleanupReport>>>,
+    /// Не даёт запускать очистки параллельно; итог прошлой читается, пока идёт новая
+    cleanup_running: Arc<Mutex<()>>,
+    /// Справочник регионов для обратного геокодирования; None, если выключено
+    regions: Option<Arc<Regions>>,
+    /// Момент старта идущей синхронизации OSDR; None, если она не идёт
+    osdr_sync_started: Arc<std::sync::Mutex<Option<DateTime<Utc>>>>,
+    /// Источники space_cache; не больше одной загрузки каждого одновременно
+    sources: SourceRegistry,
+    /// Успехи и ошибки фоновых задач для /tasks/status и /health
+    tasks: Arc<std::sync::RwLock<TaskStatusMap>>,
+    /// Лимит запросов к NASA из /neo/object для неизвестных объектов
+    neo_lookups: Arc<std::sync::Mutex<RateLimiter>>,
+    /// Размыкатели и бюджеты запросов по хостам upstream
+    upstream: Arc<http::Upstream>,
+    /// Прокси исходящих запросов, если он есть
+    proxy: Option<http::ProxyInfo>,
+    /// Последняя проверка прокси для /health
+    proxy_check: Arc<Mutex<Option<ProxyCheck>>>,
+    /// Источники, ответившие 429 с Retry-After: не запрашивать до этого момента
+    not_before: Arc<std::sync::RwLock<HashMap<String, DateTime<Utc>>>>,
+    /// Какие зеркала WHERE_ISS_URL сейчас отказывают
+    iss_mirrors: Arc<Mirrors>,
+    /// Идущие по запросу клиентов загрузки источников space_cache
+    space_flights: Arc<SingleFlight<Result<FetchRun, ApiError>>>,
+    /// Идущие по запросу клиентов загрузки положений спутников
+    iss_flights: Arc<SingleFlight<Result<IssStoreOutcome, ApiError>>>,
+}
+
+impl AppState {
+    /// Состояние без справочника регионов и сведений о прокси; их main
+    /// заполняет сам
+    fn new(pool: PgPool, config: Config, sources: SourceRegistry) -> reqwest::Result<Self> {
+        Ok(Self {
+            pool,
+            config: config.clone(),
+            iss_duplicates_skipped: Arc::new(AtomicU64::new(0)),
+            iss_burst_running: Arc::new(AtomicBool::new(false)),
+            iss_events: broadcast::channel(ISS_EVENTS_CAPACITY).0,
+            last_cleanup: Arc::new(Mutex::new(None)),
+            cleanup_running: Arc::new(Mutex::new(())),
+            regions: None,
+            osdr_sync_started: Arc::new(std::sync::Mutex::new(None)),
+            sources,
+            tasks: Arc::new(std::sync::RwLock::new(TaskStatusMap::default())),
+            neo_lookups: Arc::new(std::sync::Mutex::new(RateLimiter::new(
+                config.neo_lookups_per_hour as usize,
+                Duration::from_secs(3600),
+            ))),
+            upstream: Arc::new(
+                http::Upstream::new(
+                    http::build_client(
+                        config.outbound_proxy_url.as_deref(),
+                        &http::user_agent(config.service_contact.as_deref()),
+                    )?,
+                    http::CircuitBreakers::new(
+                        config.circuit_failure_threshold,
+                        Duration::from_secs(config.circuit_cooldown_seconds),
+                    ),
+                )
+                .with_budget(
+                    feeds::NASA_API_HOST,
+                    http::Budget::new(
+                        config.nasa_rate_capacity,
+                        config.nasa_rate_refill_per_hour,
+                        Duration::from_millis(config.nasa_rate_max_wait_ms),
+                    ),
+                ),
+            ),
+            proxy: None,
+            proxy_check: Arc::new(Mutex::new(None)),
+            not_before: Arc::new(std::sync::RwLock::new(HashMap::new())),
+            iss_mirrors: Arc::new(Mirrors::default()),
+            space_flights: Arc::new(SingleFlight::default()),
+            iss_flights: Arc::new(SingleFlight::default()),
+        })
+    }
 }
 
 #[tokio::main]
@@ -41,7 +234,11 @@ async fn main() -> anyhow::Result<()> {
     dotenvy::dotenv().ok();
 
     // Загрузка конфигурации
-    let config = Config::from_env().map_err(|e| anyhow::anyhow!("{}", e))?;
+    let mut config = Config::from_env().map_err(|e| anyhow::anyhow!("{}", e))?;
+    let donki_types =
+        feeds::donki_types(&config.donki_types, &config.donki_window_days).map_err(|e| anyhow::anyhow!("{}", e))?;
+    let sources = SourceRegistry::new(feeds::builtin(&donki_types));
+    config.load_timeout_overrides(&sources.names()).map_err(|e| anyhow::anyhow!("{}", e))?;
 
     // Подключение к БД с обработкой ошибок
     let pool = PgPoolOptions::new()
@@ -51,9 +248,28 @@ async fn main() -> anyhow::Result<()> {
 
     init_db(&pool).await?;
 
+    let regions = if config.geo_lookup_enabled {
+        let regions = Regions::load().map_err(|e| anyhow::anyhow!("{}", e))?;
+        Some(Arc::new(regions))
+    } else {
+        None
+    };
+
+    let proxy = http::proxy_in_use(config.outbound_proxy_url.as_deref(), |var| std::env::var(var).ok());
+    match &proxy {
+        Some(p) => info!(
+            "outbound proxy {} (from {}), NO_PROXY={:?}",
+            p.url,
+            p.source,
+            std::env::var("NO_PROXY").or_else(|_| std::env::var("no_proxy")).unwrap_or_default()
+        ),
+        None => info!("no outbound proxy, connecting to upstreams directly"),
+    }
+
     let state = AppState {
-        pool: pool.clone(),
-        config: config.clone(),
+        regions,
+        proxy: proxy.clone(),
+        ..AppState::new(pool.clone(), config.clone(), sources)?
     };
 
     // Запуск фоновых задач
@@ -62,14 +278,81 @@ async fn main() -> anyhow::Result<()> {
     // Настройка роутов
     let app = Router::new()
         .route("/health", get(health_handler))
+        .route("/tasks/status", get(tasks_status))
+        .route("/quota", get(quota))
         .route("/last", get(last_iss))
         .route("/fetch", get(trigger_iss))
+        .route("/fetch/burst", post(trigger_iss_burst))
         .route("/iss/trend", get(iss_trend))
-        .route("/osdr/sync", get(osdr_sync))
+        .route("/iss/history", get(iss_history))
+        .route("/iss/track.geojson", get(iss_track_geojson))
+        .route("/iss/track.kml", get(iss_track_kml))
+        .route("/iss/footprint.geojson", get(iss_footprint_geojson))
+        .route("/iss/export.csv", get(iss_export_csv))
+        .route("/iss/stream", get(iss_stream))
+        .route("/iss/ws", get(iss_ws))
+        .route("/iss/passes", get(iss_passes))
+        .route("/iss/tle", get(iss_tle))
+        .route("/iss/closest", get(iss_closest))
+        .route("/iss/stats", get(iss_stats))
+        .route("/iss/gaps", get(iss_gaps))
+        .route("/iss/anomalies", get(iss_anomalies))
+        .route("/iss/orbit", get(iss_orbit))
+        .route("/iss/at", get(iss_at))
+        .route("/iss/interpolate", get(iss_interpolate))
+        .route("/osdr/sync", post(osdr_sync).get(osdr_sync_deprecated))
+        .route("/osdr/sync/history", get(osdr_sync_history))
+        .route("/osdr/stats", get(osdr_stats))
         .route("/osdr/list", get(osdr_list))
+        .route("/osdr/export.ndjson", get(osdr_export_ndjson))
+        .route("/osdr/item/:dataset_id", get(osdr_item))
+        .route("/osdr/item/id/:id", get(osdr_item_by_id))
+        .route("/osdr/item/:dataset_id/changes", get(osdr_item_changes))
+        .route("/osdr/changes", get(osdr_changes))
+        .route("/neo/list", get(neo_list))
+        .route("/neo/hazardous", get(neo_hazardous))
+        .route("/neo/closest", get(neo_closest))
+        .route("/neo/object/:neo_reference_id", get(neo_object))
+        .route("/webhooks", post(create_webhook).get(list_webhooks))
+        .route("/webhooks/:id", delete(delete_webhook))
+        .route("/webhooks/:id/deliveries", get(webhook_deliveries))
+        .route("/space/sources", get(space_sources))
+        .route(
+            "/space/export.ndjson",
+            get(space_export_ndjson).layer(CompressionLayer::new()),
+        )
+        .route("/space/import.ndjson", post(space_import_ndjson))
         .route("/space/:src/latest", get(space_latest))
-        .route("/space/refresh", get(space_refresh))
+        .route("/space/:src/history", get(space_history))
+        .route("/space/:src/diff", get(space_diff))
+        .route("/space/:src/at", get(space_at))
+        .route("/space/:src/fetch-metrics", get(space_fetch_metrics))
+        .route("/space/refresh", post(space_refresh).get(space_refresh_deprecated))
         .route("/space/summary", get(space_summary))
+        .route("/space/astros/summary", get(astros_summary))
+        .route("/spacex/upcoming", get(spacex_upcoming))
+        .route("/spacex/countdown", get(spacex_countdown))
+        .route("/spacex/launches", get(spacex_launches))
+        .route("/spacex/launches.ics", get(spacex_launches_ics))
+        .route("/launches/upcoming", get(launches_upcoming))
+        .route("/spacex/stats", get(spacex_stats))
+        .route("/space/apod", get(apod_gallery))
+        .route("/space/apod/backfill", post(apod_backfill))
+        .route("/space/apod/image", get(apod_image))
+        .route("/space/apod/day/:date", get(apod_day))
+        .route("/space/epic/latest-image", get(epic_latest_image))
+        .route("/space/mars/photos", get(mars_photos))
+        .route("/space/eonet/near", get(eonet_near))
+        .route("/space/gst/current", get(gst_current))
+        .route("/space/flr/summary", get(flr_summary))
+        .route("/space/cme/summary", get(cme_summary))
+        .route("/space/weather/score", get(space_weather_score))
+        .route("/space/kp/current", get(kp_current))
+        .route("/space/solar-wind/latest", get(solar_wind_latest))
+        .route("/space/notifications", get(space_notifications))
+        .route("/admin/cleanup", post(admin_cleanup))
+        .route("/admin/cleanup/status", get(admin_cleanup_status))
+        .route("/admin/iss/backfill", post(admin_iss_backfill))
         .with_state(state);
 
     let listener = tokio::net::TcpListener::bind(("0.0.0.0", 3000))
@@ -83,13 +366,104 @@ async fn main() -> anyhow::Result<()> {
 }
 
 /* ---------- Health Check ---------- */
-async fn health_handler() -> ApiResult<Health> {
+async fn health_handler(State(st): State<AppState>) -> ApiResult<Health> {
+    let failing_tasks = read_tasks(&st).failing(st.config.task_failure_threshold);
+    let proxy = match &st.proxy {
+        Some(info) => Some(cached_proxy_check(&st, info).await),
+        None => None,
+    };
     ok(Health {
         status: "ok",
         now: Utc::now(),
+        degraded: !failing_tasks.is_empty() || proxy.as_ref().is_some_and(|p| !p.ok),
+        failing_tasks,
+        proxy,
     })
 }
 
+/// Куда /health отправляет пробный запрос через прокси
+const PROXY_PROBE_URL: &str = "https://api.nasa.gov/";
+const PROXY_PROBE_TIMEOUT_SECS: u64 = 5;
+
+/// Итог проверки прокси не старше PROXY_CHECK_EVERY_SECONDS: частые /health
+/// не гоняют пробный запрос каждый раз. Пока идёт проверка, остальные её ждут
+async fn cached_proxy_check(st: &AppState, info: &http::ProxyInfo) -> ProxyCheck {
+    let mut cached = st.proxy_check.lock().await;
+    let max_age = chrono::Duration::seconds(st.config.proxy_check_every_seconds as i64);
+    if let Some(check) = cached.as_ref().filter(|c| Utc::now() - c.checked_at < max_age) {
+        return check.clone();
+    }
+    let check = check_proxy(st, info).await;
+    *cached = Some(check.clone());
+    check
+}
+
+/// Прокси работает, если upstream ответил хоть как-то: 407 и 502–504 —
+/// ответы самого прокси, а не upstream
+async fn check_proxy(st: &AppState, info: &http::ProxyInfo) -> ProxyCheck {
+    let result = st
+        .upstream
+        .client
+        .head(PROXY_PROBE_URL)
+        .timeout(Duration::from_secs(PROXY_PROBE_TIMEOUT_SECS))
+        .send()
+        .await;
+    let (http_status, error) = match result {
+        Ok(resp) => (Some(resp.status().as_u16()), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+    ProxyCheck {
+        proxy: info.clone(),
+        target: PROXY_PROBE_URL,
+        checked_at: Utc::now(),
+        ok: http_status.is_some_and(|s| !matches!(s, 407 | 502..=504)),
+        http_status,
+        error,
+    }
+}
+
+/// Последний запуск, успех и ошибка каждой фоновой задачи
+async fn tasks_status(State(st): State<AppState>) -> ApiResult<Value> {
+    let tasks = read_tasks(&st).clone();
+    ok(serde_json::json!({
+        "failure_threshold": st.config.task_failure_threshold,
+        "tasks": tasks,
+        "circuits": st.upstream.breakers.snapshot()
+    }))
+}
+
+/// Бюджеты запросов к upstream: сколько жетонов осталось у нас и сколько
+/// запросов, по X-RateLimit-Remaining, осталось у ключа
+async fn quota(State(st): State<AppState>) -> ApiResult<Value> {
+    ok(serde_json::json!({ "hosts": st.upstream.budgets() }))
+}
+
+fn read_tasks(st: &AppState) -> std::sync::RwLockReadGuard<'_, TaskStatusMap> {
+    st.tasks.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn write_tasks(st: &AppState) -> std::sync::RwLockWriteGuard<'_, TaskStatusMap> {
+    st.tasks.write().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Прогон фоновой задачи с отметкой старта и итога в st.tasks
+async fn track_task<T>(
+    st: &AppState,
+    task: &str,
+    run: impl Future<Output = Result<T, ApiError>>,
+) -> Result<T, ApiError> {
+    write_tasks(st).started(task, Utc::now());
+    let result = run.await;
+    match &result {
+        Ok(_) => write_tasks(st).succeeded(task, Utc::now()),
+        Err(e) => {
+            let message = format!("{}: {}", e.error.code, e.error.message);
+            write_tasks(st).failed(task, Utc::now(), &e.error.code, message)
+        }
+    }
+    result
+}
+
 /* ---------- DB boot ---------- */
 async fn init_db(pool: &PgPool) -> Result<(), ApiError> {
     // ISS
@@ -104,6 +478,94 @@ async fn init_db(pool: &PgPool) -> Result<(), ApiError> {
     .execute(pool)
     .await?;
 
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_iss_fetch_log_fetched_at
+         ON iss_fetch_log(fetched_at)"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "ALTER TABLE iss_fetch_log
+            ADD COLUMN IF NOT EXISTS latitude DOUBLE PRECISION,
+            ADD COLUMN IF NOT EXISTS longitude DOUBLE PRECISION,
+            ADD COLUMN IF NOT EXISTS altitude_km DOUBLE PRECISION,
+            ADD COLUMN IF NOT EXISTS velocity_kmh DOUBLE PRECISION,
+            ADD COLUMN IF NOT EXISTS sample_time TIMESTAMPTZ,
+            ADD COLUMN IF NOT EXISTS visibility TEXT"
+    )
+    .execute(pool)
+    .await?;
+
+    // Несколько спутников: старые строки относятся к МКС
+    sqlx::query(&format!(
+        "ALTER TABLE iss_fetch_log
+            ADD COLUMN IF NOT EXISTS norad_id BIGINT NOT NULL DEFAULT {}",
+        ISS_NORAD_ID
+    ))
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_iss_fetch_log_sample_time
+         ON iss_fetch_log(sample_time)"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_iss_fetch_log_norad_id
+         ON iss_fetch_log(norad_id, id DESC)"
+    )
+    .execute(pool)
+    .await?;
+
+    // Дозагруженная история пишется позже свежих точек, поэтому «последние»
+    // выборки ищутся по fetched_at, а не по id
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_iss_fetch_log_norad_fetched_at
+         ON iss_fetch_log(norad_id, fetched_at DESC)"
+    )
+    .execute(pool)
+    .await?;
+
+    // Выборки с подозрительной скоростью или высотой; сама строка лога остаётся
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS iss_anomalies(
+            id BIGSERIAL PRIMARY KEY,
+            log_id BIGINT NOT NULL REFERENCES iss_fetch_log(id) ON DELETE CASCADE,
+            norad_id BIGINT NOT NULL,
+            detected_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+            field TEXT NOT NULL,
+            value DOUBLE PRECISION NOT NULL,
+            median DOUBLE PRECISION NOT NULL,
+            reason TEXT NOT NULL
+        )"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_iss_anomalies_log_id
+         ON iss_anomalies(log_id)"
+    )
+    .execute(pool)
+    .await?;
+
+    // Ответы upstream, не прошедшие разбор
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS iss_fetch_rejects(
+            id BIGSERIAL PRIMARY KEY,
+            fetched_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+            source_url TEXT NOT NULL,
+            http_status INTEGER NOT NULL,
+            reason TEXT NOT NULL,
+            body TEXT NOT NULL
+        )"
+    )
+    .execute(pool)
+    .await?;
+
     // OSDR
     sqlx::query(
         "CREATE TABLE IF NOT EXISTS osdr_items(
@@ -126,645 +588,6891 @@ async fn init_db(pool: &PgPool) -> Result<(), ApiError> {
     .execute(pool)
     .await?;
 
-    // space_cache
+    // Поле за полем: как менялись title, status и updated_at датасетов
     sqlx::query(
-        "CREATE TABLE IF NOT EXISTS space_cache(
+        "CREATE TABLE IF NOT EXISTS osdr_changes(
             id BIGSERIAL PRIMARY KEY,
-            source TEXT NOT NULL,
-            fetched_at TIMESTAMPTZ NOT NULL DEFAULT now(),
-            payload JSONB NOT NULL
+            item_id BIGINT NOT NULL REFERENCES osdr_items(id) ON DELETE CASCADE,
+            dataset_id TEXT NOT NULL,
+            field TEXT NOT NULL,
+            old_value TEXT,
+            new_value TEXT,
+            changed_at TIMESTAMPTZ NOT NULL DEFAULT now()
         )"
     )
     .execute(pool)
     .await?;
 
     sqlx::query(
-        "CREATE INDEX IF NOT EXISTS ix_space_cache_source 
-         ON space_cache(source, fetched_at DESC)"
+        "CREATE INDEX IF NOT EXISTS ix_osdr_changes_dataset_id
+         ON osdr_changes(dataset_id, changed_at DESC)"
     )
     .execute(pool)
     .await?;
 
-    Ok(())
-}
-
-/* ---------- Background Tasks ---------- */
-fn spawn_background_tasks(state: AppState) {
-    // OSDR фоновая задача
-    {
-        let st = state.clone();
-        tokio::spawn(async move {
-            loop {
-                if let Err(e) = fetch_and_store_osdr(&st).await {
-                    error!("osdr background task error: {:?}", e);
-                }
-                tokio::time::sleep(Duration::from_secs(st.config.fetch_every_seconds)).await;
-            }
-        });
-    }
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_osdr_changes_changed_at
+         ON osdr_changes(changed_at)"
+    )
+    .execute(pool)
+    .await?;
 
-    // ISS фоновая задача
-    {
-        let st = state.clone();
-        tokio::spawn(async move {
-            loop {
-                if let Err(e) = fetch_and_store_iss(&st.pool, &st.config.where_iss_url).await {
-                    error!("iss background task error: {:?}", e);
-                }
-                tokio::time::sleep(Duration::from_secs(st.config.iss_every_seconds)).await;
-            }
-        });
-    }
+    // Журнал запусков синхронизации OSDR, включая неудачные
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS osdr_sync_log(
+            id BIGSERIAL PRIMARY KEY,
+            started_at TIMESTAMPTZ NOT NULL,
+            finished_at TIMESTAMPTZ NOT NULL,
+            pages INT NOT NULL,
+            inserted INT NOT NULL,
+            updated INT NOT NULL,
+            unchanged INT NOT NULL,
+            error TEXT
+        )"
+    )
+    .execute(pool)
+    .await?;
 
-    // APOD фоновая задача
-    {
-        let st = state.clone();
-        tokio::spawn(async move {
-            loop {
-                if let Err(e) = fetch_apod(&st).await {
-                    error!("apod background task error: {:?}", e);
-                }
-                tokio::time::sleep(Duration::from_secs(st.config.apod_every_seconds)).await;
-            }
-        });
-    }
+    // Число HTTP-попыток с повторами и итог запуска; старым строкам итог
+    // восстанавливается по error
+    sqlx::query(
+        "ALTER TABLE osdr_sync_log
+            ADD COLUMN IF NOT EXISTS attempts INT NOT NULL DEFAULT 0,
+            ADD COLUMN IF NOT EXISTS outcome TEXT NOT NULL DEFAULT 'success'"
+    )
+    .execute(pool)
+    .await?;
 
-    // NeoWs фоновая задача
-    {
-        let st = state.clone();
-        tokio::spawn(async move {
-            loop {
-                if let Err(e) = fetch_neo_feed(&st).await {
-                    error!("neo background task error: {:?}", e);
-                }
-                tokio::time::sleep(Duration::from_secs(st.config.neo_every_seconds)).await;
-            }
-        });
-    }
+    sqlx::query(
+        "UPDATE osdr_sync_log SET outcome = 'failed'
+         WHERE error IS NOT NULL AND outcome = 'success'"
+    )
+    .execute(pool)
+    .await?;
 
-    // DONKI фоновая задача
-    {
-        let st = state.clone();
-        tokio::spawn(async move {
-            loop {
-                if let Err(e) = fetch_donki(&st).await {
-                    error!("donki background task error: {:?}", e);
-                }
-                tokio::time::sleep(Duration::from_secs(st.config.donki_every_seconds)).await;
-            }
-        });
-    }
+    // Хэш канонического raw для инкрементальной синхронизации и отметка
+    // о пропаже датасета из upstream
+    sqlx::query(
+        "ALTER TABLE osdr_items
+            ADD COLUMN IF NOT EXISTS raw_hash TEXT,
+            ADD COLUMN IF NOT EXISTS last_synced_at TIMESTAMPTZ,
+            ADD COLUMN IF NOT EXISTS missing_since TIMESTAMPTZ"
+    )
+    .execute(pool)
+    .await?;
 
-    // SpaceX фоновая задача
-    {
-        let st = state.clone();
-        tokio::spawn(async move {
-            loop {
-                if let Err(e) = fetch_spacex_next(&st).await {
-                    error!("spacex background task error: {:?}", e);
-                }
-                tokio::time::sleep(Duration::from_secs(st.config.spacex_every_seconds)).await;
-            }
-        });
-    }
-}
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_osdr_items_status_updated_at
+         ON osdr_items(status, updated_at)"
+    )
+    .execute(pool)
+    .await?;
 
-/* ---------- ISS Handlers ---------- */
-#[derive(Serialize)]
-struct IssLastResponse {
-    id: i64,
-    fetched_at: DateTime<Utc>,
-    source_url: String,
-    payload: Value,
-}
+    // Организм, тип анализа и миссия из raw; фильтры по ним без учёта регистра
+    sqlx::query(
+        "ALTER TABLE osdr_items
+            ADD COLUMN IF NOT EXISTS organism TEXT,
+            ADD COLUMN IF NOT EXISTS assay_type TEXT,
+            ADD COLUMN IF NOT EXISTS mission TEXT"
+    )
+    .execute(pool)
+    .await?;
 
-async fn last_iss(State(st): State<AppState>) -> ApiResult<Value> {
-    let row_opt = sqlx::query(
-        "SELECT id, fetched_at, source_url, payload
-         FROM iss_fetch_log
-         ORDER BY id DESC LIMIT 1"
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_osdr_items_organism
+         ON osdr_items(lower(organism))"
     )
-    .fetch_optional(&st.pool)
+    .execute(pool)
     .await?;
 
-    if let Some(row) = row_opt {
-        let id: i64 = row.try_get("id").map_err(|e| ApiError::database(e.to_string()))?;
-        let fetched_at: DateTime<Utc> = row.try_get("fetched_at")
-            .map_err(|e| ApiError::database(e.to_string()))?;
-        let source_url: String = row.try_get("source_url")
-            .map_err(|e| ApiError::database(e.to_string()))?;
-        let payload: Value = row.try_get("payload")
-            .unwrap_or_else(|_| serde_json::json!({}));
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_osdr_items_mission
+         ON osdr_items(lower(mission))"
+    )
+    .execute(pool)
+    .await?;
 
-        return ok(serde_json::json!({
-            "id": id,
-            "fetched_at": fetched_at,
-            "source_url": source_url,
-            "payload": payload
-        }));
-    }
+    merge_osdr_dataset_id_duplicates(pool).await?;
 
-    ok(serde_json::json!({"message": "no data"}))
-}
+    // Подписки на события OSDR и журнал их доставки
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS webhooks(
+            id BIGSERIAL PRIMARY KEY,
+            url TEXT NOT NULL,
+            event TEXT NOT NULL,
+            title_filter TEXT,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )"
+    )
+    .execute(pool)
+    .await?;
 
-async fn trigger_iss(State(st): State<AppState>) -> ApiResult<Value> {
-    fetch_and_store_iss(&st.pool, &st.config.where_iss_url).await?;
-    last_iss(State(st)).await
-}
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS webhook_deliveries(
+            id BIGSERIAL PRIMARY KEY,
+            webhook_id BIGINT NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+            event TEXT NOT NULL,
+            item_count INT NOT NULL,
+            attempts INT NOT NULL,
+            http_status INT,
+            success BOOLEAN NOT NULL,
+            error TEXT,
+            delivered_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )"
+    )
+    .execute(pool)
+    .await?;
 
-#[derive(Serialize)]
-struct Trend {
-    movement: bool,
-    delta_km: f64,
-    dt_sec: f64,
-    velocity_kmh: Option<f64>,
-    from_time: Option<DateTime<Utc>>,
-    to_time: Option<DateTime<Utc>>,
-    from_lat: Option<f64>,
-    from_lon: Option<f64>,
-    to_lat: Option<f64>,
-    to_lon: Option<f64>,
-}
-
-async fn iss_trend(State(st): State<AppState>) -> ApiResult<Trend> {
-    let rows = sqlx::query(
-        "SELECT fetched_at, payload FROM iss_fetch_log 
-         ORDER BY id DESC LIMIT 2"
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_webhook_deliveries_webhook_id
+         ON webhook_deliveries(webhook_id, delivered_at DESC)"
     )
-    .fetch_all(&st.pool)
+    .execute(pool)
     .await?;
 
-    if rows.len() < 2 {
-        return ok(Trend {
-            movement: false,
-            delta_km: 0.0,
-            dt_sec: 0.0,
-            velocity_kmh: None,
-            from_time: None,
-            to_time: None,
-            from_lat: None,
-            from_lon: None,
-            to_lat: None,
-            to_lon: None,
-        });
-    }
+    // space_cache
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS space_cache(
+            id BIGSERIAL PRIMARY KEY,
+            source TEXT NOT NULL,
+            fetched_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+            payload JSONB NOT NULL
+        )"
+    )
+    .execute(pool)
+    .await?;
 
-    let t2: DateTime<Utc> = rows[0].try_get("fetched_at")
-        .map_err(|e| ApiError::database(e.to_string()))?;
-    let t1: DateTime<Utc> = rows[1].try_get("fetched_at")
-        .map_err(|e| ApiError::database(e.to_string()))?;
-    let p2: Value = rows[0].try_get("payload")
-        .unwrap_or_else(|_| serde_json::json!({}));
-    let p1: Value = rows[1].try_get("payload")
-        .unwrap_or_else(|_| serde_json::json!({}));
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_space_cache_source 
+         ON space_cache(source, fetched_at DESC)"
+    )
+    .execute(pool)
+    .await?;
 
-    let lat1 = extract_number(&p1["latitude"]);
-    let lon1 = extract_number(&p1["longitude"]);
-    let lat2 = extract_number(&p2["latitude"]);
-    let lon2 = extract_number(&p2["longitude"]);
-    let v2 = extract_number(&p2["velocity"]);
+    // Хэш канонического payload, чтобы не писать одинаковые снимки, и время
+    // последней проверки источника
+    sqlx::query(
+        "ALTER TABLE space_cache
+            ADD COLUMN IF NOT EXISTS payload_hash TEXT,
+            ADD COLUMN IF NOT EXISTS last_checked_at TIMESTAMPTZ"
+    )
+    .execute(pool)
+    .await?;
 
-    let mut delta_km = 0.0;
-    let mut movement = false;
-    
-    if let (Some(a1), Some(o1), Some(a2), Some(o2)) = (lat1, lon1, lat2, lon2) {
-        delta_km = haversine_km(a1, o1, a2, o2);
-        movement = delta_km > 0.1;
-    }
-    
-    let dt_sec = (t2 - t1).num_milliseconds() as f64 / 1000.0;
-
-    ok(Trend {
-        movement,
-        delta_km,
-        dt_sec,
-        velocity_kmh: v2,
-        from_time: Some(t1),
-        to_time: Some(t2),
-        from_lat: lat1,
-        from_lon: lon1,
-        to_lat: lat2,
-        to_lon: lon2,
-    })
-}
+    // Крупные снимки при CACHE_COMPRESSION=zstd: в payload остаётся заглушка
+    sqlx::query("ALTER TABLE space_cache ADD COLUMN IF NOT EXISTS payload_compressed BYTEA")
+        .execute(pool)
+        .await?;
 
-fn extract_number(v: &Value) -> Option<f64> {
-    v.as_f64()
-        .or_else(|| v.as_str().and_then(|s| s.parse::<f64>().ok()))
-}
+    // Постраничная история источника идёт по id
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_space_cache_source_id
+         ON space_cache(source, id DESC)"
+    )
+    .execute(pool)
+    .await?;
 
-fn haversine_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
-    let rlat1 = lat1.to_radians();
-    let rlat2 = lat2.to_radians();
-    let dlat = (lat2 - lat1).to_radians();
-    let dlon = (lon2 - lon1).to_radians();
-    let a = (dlat / 2.0).sin().powi(2) 
-        + rlat1.cos() * rlat2.cos() * (dlon / 2.0).sin().powi(2);
-    let c = 2.0 * a.sqrt().atan2((1.0 - a).sqrt());
-    6371.0 * c
-}
+    // Статус, задержка и размер ответа upstream для /space/:src/fetch-metrics
+    for table in ["space_cache", "iss_fetch_log"] {
+        sqlx::query(&format!(
+            "ALTER TABLE {}
+                ADD COLUMN IF NOT EXISTS http_status SMALLINT,
+                ADD COLUMN IF NOT EXISTS latency_ms INTEGER,
+                ADD COLUMN IF NOT EXISTS body_bytes INTEGER",
+            table
+        ))
+        .execute(pool)
+        .await?;
+    }
 
-/* ---------- OSDR Handlers ---------- */
-async fn osdr_sync(State(st): State<AppState>) -> ApiResult<Value> {
-    let written = fetch_and_store_osdr(&st).await?;
-    ok(serde_json::json!({ "written": written }))
-}
+    // Неудачные запросы к источникам
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS fetch_errors(
+            id BIGSERIAL PRIMARY KEY,
+            source TEXT NOT NULL,
+            http_status SMALLINT,
+            latency_ms INTEGER NOT NULL,
+            error TEXT NOT NULL,
+            occurred_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_fetch_errors_source
+         ON fetch_errors(source, occurred_at DESC)"
+    )
+    .execute(pool)
+    .await?;
+
+    // Число HTTP-попыток вместе с повторами
+    for table in ["space_cache", "iss_fetch_log", "fetch_errors"] {
+        sqlx::query(&format!("ALTER TABLE {} ADD COLUMN IF NOT EXISTS attempts INTEGER", table))
+            .execute(pool)
+            .await?;
+    }
+
+    // Код ApiError: по нему отличаются сбой DNS, таймаут, обрыв и т.п.
+    sqlx::query("ALTER TABLE fetch_errors ADD COLUMN IF NOT EXISTS code TEXT")
+        .execute(pool)
+        .await?;
+
+    // X-Request-Id загрузки: по нему неудача находится в логах и у upstream
+    sqlx::query("ALTER TABLE fetch_errors ADD COLUMN IF NOT EXISTS request_id TEXT")
+        .execute(pool)
+        .await?;
+
+    // Каждый запрос к источнику, удачный или нет; по нему считается
+    // /space/:src/fetch-metrics. body_bytes пуст у 304 и ошибок
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS fetch_attempts(
+            id BIGSERIAL PRIMARY KEY,
+            source TEXT NOT NULL,
+            ok BOOLEAN NOT NULL,
+            http_status SMALLINT,
+            latency_ms INTEGER NOT NULL,
+            body_bytes INTEGER,
+            attempts INTEGER NOT NULL,
+            code TEXT,
+            occurred_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_fetch_attempts_source
+         ON fetch_attempts(source, occurred_at DESC)"
+    )
+    .execute(pool)
+    .await?;
+
+    // ETag / Last-Modified последнего ответа источника для условных запросов
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS source_state(
+            source TEXT PRIMARY KEY,
+            etag TEXT,
+            last_modified TEXT,
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )"
+    )
+    .execute(pool)
+    .await?;
+
+    // Архив APOD по дням; наполняется /space/apod/backfill и /space/apod/day/:date
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS apod_entries(
+            date DATE PRIMARY KEY,
+            title TEXT,
+            explanation TEXT,
+            media_type TEXT,
+            url TEXT,
+            hdurl TEXT,
+            thumbnail_url TEXT,
+            copyright TEXT,
+            raw JSONB NOT NULL,
+            fetched_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )"
+    )
+    .execute(pool)
+    .await?;
+
+    // Прошедшие запуски SpaceX; дозагружаются начиная с последней даты
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS spacex_launches(
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            date_utc TIMESTAMPTZ NOT NULL,
+            success BOOLEAN,
+            rocket TEXT,
+            launchpad TEXT,
+            details TEXT,
+            raw JSONB NOT NULL,
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS ix_spacex_launches_date ON spacex_launches(date_utc)")
+        .execute(pool)
+        .await?;
+
+    // Скачанные картинки APOD для /space/apod/image
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS apod_media(
+            date DATE NOT NULL,
+            size TEXT NOT NULL,
+            source_url TEXT NOT NULL,
+            content_type TEXT NOT NULL,
+            byte_size INTEGER NOT NULL,
+            bytes BYTEA NOT NULL,
+            fetched_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+            PRIMARY KEY (date, size)
+        )"
+    )
+    .execute(pool)
+    .await?;
+
+    // APOD_MEDIA_MAX_BYTES может быть больше 2 ГиБ
+    sqlx::query("ALTER TABLE apod_media ALTER COLUMN byte_size TYPE BIGINT")
+        .execute(pool)
+        .await?;
+
+    // Состояние долгих обходов, например каталога NEO; у источников space_cache пусто
+    sqlx::query("ALTER TABLE source_state ADD COLUMN IF NOT EXISTS progress JSONB")
+        .execute(pool)
+        .await?;
+
+    // Сближения из ленты NeoWs по одному на строку; raw — объект целиком
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS neo_objects(
+            id BIGSERIAL PRIMARY KEY,
+            neo_reference_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            est_diameter_min_m DOUBLE PRECISION,
+            est_diameter_max_m DOUBLE PRECISION,
+            is_potentially_hazardous BOOLEAN NOT NULL DEFAULT false,
+            close_approach_time TIMESTAMPTZ NOT NULL,
+            miss_distance_km DOUBLE PRECISION,
+            relative_velocity_kms DOUBLE PRECISION,
+            raw JSONB NOT NULL,
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+            UNIQUE (neo_reference_id, close_approach_time)
+        )"
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS ix_neo_objects_approach
+         ON neo_objects(close_approach_time)"
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Сводит dataset_id к виду normalize_dataset_id (trim + верхний регистр).
+/// Из строк, совпавших после нормализации, остаётся одна — с самым поздним
+/// updated_at; журнал изменений остальных переносится на неё. Когда дублей
+/// нет, запросы ничего не меняют
+async fn merge_osdr_dataset_id_duplicates(pool: &PgPool) -> Result<(), ApiError> {
+    const NORMALIZED: &str = "NULLIF(upper(btrim(dataset_id, E' \\t\\r\\n')), '')";
+    let ranked = format!(
+        "WITH ranked AS (
+            SELECT id, first_value(id) OVER (
+                PARTITION BY {NORMALIZED}
+                ORDER BY updated_at DESC NULLS LAST, id DESC
+            ) AS keep_id
+            FROM osdr_items WHERE {NORMALIZED} IS NOT NULL
+        )"
+    );
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(&format!(
+        "{ranked}
+         UPDATE osdr_changes c SET item_id = r.keep_id
+         FROM ranked r WHERE c.item_id = r.id AND r.id <> r.keep_id"
+    ))
+    .execute(&mut *tx)
+    .await?;
+
+    let merged = sqlx::query(&format!(
+        "{ranked}
+         DELETE FROM osdr_items o USING ranked r
+         WHERE o.id = r.id AND r.id <> r.keep_id"
+    ))
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    sqlx::query(&format!(
+        "UPDATE osdr_items SET dataset_id = {NORMALIZED}
+         WHERE dataset_id IS DISTINCT FROM {NORMALIZED}"
+    ))
+    .execute(&mut *tx)
+    .await?;
+
+    // В журнале dataset_id обязателен, пустых там не бывает
+    sqlx::query(&format!(
+        "UPDATE osdr_changes SET dataset_id = {NORMALIZED}
+         WHERE dataset_id IS DISTINCT FROM {NORMALIZED} AND {NORMALIZED} IS NOT NULL"
+    ))
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    if merged > 0 {
+        info!("merged {} OSDR rows with duplicate dataset_id", merged);
+    }
+    Ok(())
+}
+
+/* ---------- Background Tasks ---------- */
+fn spawn_background_tasks(state: AppState) {
+    // OSDR фоновая задача
+    {
+        let st = state.clone();
+        tokio::spawn(async move {
+            loop {
+                match try_start_osdr_sync(&st) {
+                    Ok(_guard) => {
+                        if let Err(e) = track_task(&st, "osdr", fetch_and_store_osdr(&st)).await {
+                            error!("osdr background task error: {:?}", e);
+                        }
+                    }
+                    Err(started_at) => {
+                        info!("OSDR sync started at {} is still running, skipping", started_at)
+                    }
+                }
+                tokio::time::sleep(Duration::from_secs(st.config.fetch_every_seconds)).await;
+            }
+        });
+    }
+
+    // Разовое заполнение типизированных колонок у старых строк
+    {
+        let st = state.clone();
+        tokio::spawn(async move {
+            match backfill_iss_columns(&st.pool).await {
+                Ok((0, _)) => {}
+                Ok((updated, unparsed)) => info!(
+                    "iss_fetch_log backfill: {} rows updated, {} unparsable left as is",
+                    updated, unparsed
+                ),
+                Err(e) => error!("iss_fetch_log backfill error: {:?}", e),
+            }
+        });
+    }
+
+    // Разовое заполнение payload_hash у старых снимков space_cache
+    {
+        let st = state.clone();
+        tokio::spawn(async move {
+            match backfill_space_cache_hashes(&st.pool).await {
+                Ok(0) => {}
+                Ok(updated) => info!("space_cache backfill: {} payload hashes computed", updated),
+                Err(e) => error!("space_cache backfill error: {:?}", e),
+            }
+        });
+    }
+
+    // Разовое сжатие крупных снимков, записанных до включения CACHE_COMPRESSION
+    if state.config.cache_compression != CacheCompression::None {
+        let st = state.clone();
+        tokio::spawn(async move {
+            match compress_space_cache(&st.pool, &st.config).await {
+                Ok(0) => {}
+                Ok(compressed) => info!("space_cache compression: {} snapshots compressed", compressed),
+                Err(e) => error!("space_cache compression error: {:?}", e),
+            }
+        });
+    }
+
+    // Разовое заполнение organism/assay_type/mission у строк OSDR
+    {
+        let st = state.clone();
+        tokio::spawn(async move {
+            match backfill_osdr_meta(&st.pool, &st.config.osdr_keys).await {
+                Ok(0) => {}
+                Ok(updated) => info!("osdr_items backfill: {} rows got organism/assay/mission", updated),
+                Err(e) => error!("osdr_items backfill error: {:?}", e),
+            }
+        });
+    }
+
+    // ISS фоновая задача
+    {
+        let st = state.clone();
+        tokio::spawn(async move {
+            loop {
+                // Ошибка любого спутника — ошибка прогона
+                let run = async {
+                    let mut result = Ok(());
+                    for &norad_id in &st.config.satellites {
+                        if let Err(e) = fetch_and_store_iss(&st, norad_id, false).await {
+                            error!("iss background task error (sat {}): {:?}", norad_id, e);
+                            result = Err(e);
+                        }
+                    }
+                    result
+                };
+                let _ = track_task(&st, "iss", run).await;
+                tokio::time::sleep(Duration::from_secs(st.config.iss_every_seconds)).await;
+            }
+        });
+    }
+
+    // Источники space_cache, каждый со своим интервалом
+    for src in state.sources.iter() {
+        let st = state.clone();
+        let src = src.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = track_task(&st, src.name(), fetch_source(&st, &src)).await {
+                    error!("{} background task error: {:?}", src.name(), e);
+                }
+                // После 429 ждём не меньше, чем просил upstream
+                let held_off = held_off_until(&st, src.name())
+                    .and_then(|until| (until - Utc::now()).to_std().ok())
+                    .unwrap_or_default();
+                tokio::time::sleep(src.interval(&st.config).max(held_off)).await;
+            }
+        });
+    }
+
+    // История запусков SpaceX
+    {
+        let st = state.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = track_task(&st, "spacex_past", sync_spacex_past(&st)).await {
+                    error!("spacex_past background task error: {:?}", e);
+                }
+                tokio::time::sleep(Duration::from_secs(st.config.spacex_past_every_seconds)).await;
+            }
+        });
+    }
+
+    // Обход каталога NEO по нескольку страниц за прогон
+    if state.config.neo_browse_enabled {
+        let st = state.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = track_task(&st, "neo_browse", browse_neo_catalog(&st)).await {
+                    error!("neo_browse background task error: {:?}", e);
+                }
+                tokio::time::sleep(Duration::from_secs(st.config.neo_browse_every_seconds)).await;
+            }
+        });
+    }
+
+    // Очистка старых записей
+    {
+        let st = state.clone();
+        tokio::spawn(async move {
+            loop {
+                let report = run_cleanup(&st, "schedule").await;
+                if let Some(e) = &report.error {
+                    error!("cleanup background task error: {}", e);
+                }
+                tokio::time::sleep(Duration::from_secs(st.config.cleanup_every_seconds)).await;
+            }
+        });
+    }
+}
+
+/* ---------- ISS Handlers ---------- */
+/// Высота МКС по умолчанию, если в записи её нет
+const ISS_FALLBACK_ALTITUDE_KM: f64 = 420.0;
+
+async fn last_iss(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+    headers: HeaderMap,
+) -> CachedResult<Value> {
+    let observer = parse_observer(&q)?;
+    let sat = parse_sat_param(&q)?;
+    let mut last = load_last_iss(&st, sat).await?;
+
+    if let Some(obs) = observer {
+        let position = &last["position"];
+        if let (Some(lat), Some(lon)) = (
+            extract_number(&position["latitude"]),
+            extract_number(&position["longitude"]),
+        ) {
+            let altitude = extract_number(&position["altitude_km"]);
+            let iss = geodetic_to_ecef(lat, lon, altitude.unwrap_or(ISS_FALLBACK_ALTITUDE_KM));
+            let angles = look_angles(obs.lat, obs.lon, obs.alt_km, iss);
+            // Момент выборки: sample_time из position либо время загрузки
+            let t = position["sample_time"]
+                .as_str()
+                .or(last["fetched_at"].as_str())
+                .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                .map(|t| t.with_timezone(&Utc))
+                .unwrap_or_else(Utc::now);
+            last["relative"] = serde_json::json!({
+                "range_km": angles.range_km,
+                "azimuth_deg": angles.azimuth_deg,
+                "elevation_deg": angles.elevation_deg,
+                "above_horizon": angles.elevation_deg > 0.0,
+                "altitude_assumed": altitude.is_none(),
+                "observer_sun_elevation_deg": solar_elevation_deg(obs.lat, obs.lon, t)
+            });
+        }
+    }
+
+    // Ответ зависит от sat и наблюдателя, поэтому ETag — хэш самого ответа
+    let etag = content_hash(&last);
+    ok_with_etag(last, &etag, st.config.cache_max_age_secs(st.config.iss_interval()), &headers)
+}
+
+/// Необязательный наблюдатель из `obs_lat`/`obs_lon`/`obs_alt_m`
+fn parse_observer(q: &HashMap<String, String>) -> Result<Option<Observer>, ApiError> {
+    let (lat, lon) = match (parse_f64_param(q, "obs_lat")?, parse_f64_param(q, "obs_lon")?) {
+        (Some(lat), Some(lon)) => (lat, lon),
+        (None, None) => return Ok(None),
+        _ => {
+            return Err(ApiError::validation(
+                "obs_lat and obs_lon must be provided together",
+            ))
+        }
+    };
+    if !(-90.0..=90.0).contains(&lat) {
+        return Err(ApiError::validation("obs_lat must be between -90 and 90"));
+    }
+    if !(-180.0..=180.0).contains(&lon) {
+        return Err(ApiError::validation("obs_lon must be between -180 and 180"));
+    }
+    let alt_m = parse_f64_param(q, "obs_alt_m")?.unwrap_or(0.0);
+    if !(-500.0..=9000.0).contains(&alt_m) {
+        return Err(ApiError::validation("obs_alt_m must be between -500 and 9000"));
+    }
+    Ok(Some(Observer {
+        lat,
+        lon,
+        alt_km: alt_m / 1000.0,
+    }))
+}
+
+/// Последняя запись журнала для спутника
+async fn latest_iss_row(pool: &PgPool, norad_id: i64) -> Result<Option<IssLogRow>, ApiError> {
+    let row = sqlx::query_as::<_, IssLogRow>(
+        "SELECT id, fetched_at, source_url, payload, norad_id,
+                latitude, longitude, altitude_km, velocity_kmh, sample_time, visibility
+         FROM iss_fetch_log
+         WHERE norad_id = $1
+         ORDER BY fetched_at DESC, id DESC LIMIT 1"
+    )
+    .bind(norad_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
+async fn load_last_iss(st: &AppState, norad_id: i64) -> Result<Value, ApiError> {
+    if let Some(row) = latest_iss_row(&st.pool, norad_id).await? {
+        let position = row.position();
+        let fetched_at = row.fetched_at;
+
+        let mut last = serde_json::json!(row);
+        last["norad_id"] = row.norad_id.into();
+        last["position"] = serde_json::json!(position);
+        if let (Some(pos), Some(obj)) = (&position, last.as_object_mut()) {
+            let t = pos.sample_time.unwrap_or(fetched_at);
+            let sunlit = is_sunlit(
+                pos.latitude,
+                pos.longitude,
+                pos.altitude_km.unwrap_or(ISS_FALLBACK_ALTITUDE_KM),
+                t,
+            );
+            let sun = solar_position(t);
+            obj.insert("sunlit".into(), sunlit.into());
+            obj.insert(
+                "subsolar_distance_km".into(),
+                haversine_km(pos.latitude, pos.longitude, sun.subsolar_lat, sun.subsolar_lon).into(),
+            );
+            // Расхождение с visibility от upstream, если его удаётся сопоставить
+            let upstream = pos.visibility.as_deref().and_then(visibility_sunlit);
+            obj.insert(
+                "visibility_disagrees".into(),
+                upstream.map(|u| u != sunlit).into(),
+            );
+            if let Some(regions) = &st.regions {
+                let location = regions.locate(pos.latitude, pos.longitude);
+                obj.insert("location".into(), serde_json::json!(location));
+            }
+        }
+        return Ok(last);
+    }
+
+    Ok(serde_json::json!({"message": "no data", "norad_id": norad_id}))
+}
+
+/// `sat` — NORAD-номер спутника, по умолчанию МКС
+fn parse_sat_param(q: &HashMap<String, String>) -> Result<i64, ApiError> {
+    let sat = parse_i64_param(q, "sat")?.unwrap_or(ISS_NORAD_ID);
+    if sat <= 0 {
+        return Err(ApiError::validation("sat must be a positive NORAD id"));
+    }
+    Ok(sat)
+}
+
+/// `sat` для запросов к upstream: только спутники из SATELLITES
+fn parse_tracked_sat_param(st: &AppState, q: &HashMap<String, String>) -> Result<i64, ApiError> {
+    let sat = parse_sat_param(q)?;
+    if !st.config.satellites.contains(&sat) {
+        return Err(ApiError::validation(format!(
+            "sat {} is not tracked, expected one of {}",
+            sat,
+            st.config.satellites.iter().map(i64::to_string).collect::<Vec<_>>().join(", ")
+        )));
+    }
+    Ok(sat)
+}
+
+/// Значение visibility из wheretheiss.at: "daylight"/"visible" — освещена, "eclipsed" — в тени
+fn visibility_sunlit(visibility: &str) -> Option<bool> {
+    match visibility.trim().to_ascii_lowercase().as_str() {
+        "daylight" | "visible" => Some(true),
+        "eclipsed" => Some(false),
+        _ => None,
+    }
+}
+
+#[derive(Serialize)]
+struct IssHistory {
+    items: Vec<IssLogRow>,
+    count: usize,
+    next_after_id: Option<i64>,
+}
+
+const ISS_HISTORY_DEFAULT_LIMIT: i64 = 100;
+const ISS_HISTORY_MAX_LIMIT: i64 = 1000;
+
+async fn iss_history(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<IssHistory> {
+    let from = parse_time_param(&q, "from")?;
+    let to = parse_time_param(&q, "to")?;
+    let after_id = parse_i64_param(&q, "after_id")?;
+    let sat = parse_sat_param(&q)?;
+    let limit = parse_i64_param(&q, "limit")?.unwrap_or(ISS_HISTORY_DEFAULT_LIMIT);
+
+    if !(1..=ISS_HISTORY_MAX_LIMIT).contains(&limit) {
+        return Err(ApiError::validation(format!(
+            "limit must be between 1 and {}",
+            ISS_HISTORY_MAX_LIMIT
+        )));
+    }
+    if let (Some(f), Some(t)) = (from, to) {
+        if f > t {
+            return Err(ApiError::validation("from must not be later than to"));
+        }
+    }
+
+    // Страницы идут по (fetched_at, id): дозагруженные точки получают id
+    // позже свежих, и порядок по id перемешал бы время
+    let after: Option<DateTime<Utc>> = match after_id {
+        Some(id) => Some(
+            sqlx::query_scalar("SELECT fetched_at FROM iss_fetch_log WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&st.pool)
+                .await?
+                .ok_or_else(|| ApiError::validation(format!("after_id {} does not exist", id)))?,
+        ),
+        None => None,
+    };
+
+    let items = sqlx::query_as::<_, IssLogRow>(
+        "SELECT id, fetched_at, source_url, payload, norad_id,
+                latitude, longitude, altitude_km, velocity_kmh, sample_time, visibility
+         FROM iss_fetch_log
+         WHERE ($1::timestamptz IS NULL OR fetched_at >= $1)
+           AND ($2::timestamptz IS NULL OR fetched_at <= $2)
+           AND ($3::timestamptz IS NULL OR (fetched_at, id) < ($3, $4))
+           AND norad_id = $6
+         ORDER BY fetched_at DESC, id DESC
+         LIMIT $5"
+    )
+    .bind(from)
+    .bind(to)
+    .bind(after)
+    .bind(after_id)
+    .bind(limit)
+    .bind(sat)
+    .fetch_all(&st.pool)
+    .await?;
+
+    let next_after_id = if items.len() as i64 == limit {
+        items.last().map(|i| i.id)
+    } else {
+        None
+    };
+
+    ok(IssHistory {
+        count: items.len(),
+        next_after_id,
+        items,
+    })
+}
+
+/// Точка наземного трека МКС
+struct TrackPoint {
+    fetched_at: DateTime<Utc>,
+    lat: f64,
+    lon: f64,
+    alt_km: Option<f64>,
+}
+
+const TRACK_DEFAULT_LIMIT: i64 = 1000;
+const TRACK_MAX_LIMIT: i64 = 10000;
+
+/// Последние `limit` точек трека в окне [from, to] в хронологическом порядке;
+/// без ?limit= берётся `default_limit`
+async fn load_track(
+    pool: &PgPool,
+    q: &HashMap<String, String>,
+    default_limit: i64,
+) -> Result<Vec<TrackPoint>, ApiError> {
+    let from = parse_time_param(q, "from")?;
+    let to = parse_time_param(q, "to")?;
+    let sat = parse_sat_param(q)?;
+    let limit = parse_i64_param(q, "limit")?.unwrap_or(default_limit);
+    if !(1..=TRACK_MAX_LIMIT).contains(&limit) {
+        return Err(ApiError::validation(format!(
+            "limit must be between 1 and {}",
+            TRACK_MAX_LIMIT
+        )));
+    }
+
+    let rows = sqlx::query(
+        "SELECT fetched_at, payload,
+                latitude, longitude, altitude_km, velocity_kmh, sample_time, visibility
+         FROM iss_fetch_log
+         WHERE ($1::timestamptz IS NULL OR fetched_at >= $1)
+           AND ($2::timestamptz IS NULL OR fetched_at <= $2)
+           AND norad_id = $4
+         ORDER BY fetched_at DESC, id DESC
+         LIMIT $3"
+    )
+    .bind(from)
+    .bind(to)
+    .bind(limit)
+    .bind(sat)
+    .fetch_all(pool)
+    .await?;
+
+    let mut points = Vec::with_capacity(rows.len());
+    for row in rows.iter().rev() {
+        if let Some(p) = StoredPosition::from_row(row) {
+            points.push(TrackPoint {
+                fetched_at: row.try_get("fetched_at")?,
+                lat: p.latitude,
+                lon: p.longitude,
+                alt_km: p.altitude_km,
+            });
+        }
+    }
+    Ok(points)
+}
+
+async fn iss_track_geojson(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> Result<Response, ApiError> {
+    let points = load_track(&st.pool, &q, TRACK_DEFAULT_LIMIT).await?;
+
+    let coords: Vec<(f64, f64)> = points.iter().map(|p| (p.lat, p.lon)).collect();
+    let mut features: Vec<Value> = split_at_antimeridian(&coords)
+        .into_iter()
+        .filter(|segment| segment.len() >= 2)
+        .enumerate()
+        .map(|(i, segment)| {
+            let line: Vec<[f64; 2]> = segment.iter().map(|(lat, lon)| [*lon, *lat]).collect();
+            serde_json::json!({
+                "type": "Feature",
+                "geometry": { "type": "LineString", "coordinates": line },
+                "properties": { "kind": "track", "segment": i }
+            })
+        })
+        .collect();
+
+    for (kind, point) in [("first", points.first()), ("last", points.last())] {
+        if let Some(p) = point {
+            features.push(serde_json::json!({
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [p.lon, p.lat] },
+                "properties": { "kind": kind, "fetched_at": p.fetched_at }
+            }));
+        }
+    }
+
+    let collection = serde_json::json!({
+        "type": "FeatureCollection",
+        "features": features
+    });
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/geo+json")],
+        Json(collection),
+    )
+        .into_response())
+}
+
+const FOOTPRINT_SEGMENTS: usize = 64;
+
+/// Зона видимости по последнему положению: область, где спутник сейчас над горизонтом
+async fn iss_footprint_geojson(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> Result<Response, ApiError> {
+    let sat = parse_sat_param(&q)?;
+    let (row, pos) = latest_iss_row(&st.pool, sat)
+        .await?
+        .and_then(|row| row.position().map(|pos| (row, pos)))
+        .ok_or_else(|| ApiError::not_found(format!("no positions for NORAD {}", sat)))?;
+
+    let altitude_km = pos.altitude_km.unwrap_or(ISS_FALLBACK_ALTITUDE_KM);
+    let rings: Vec<Vec<[f64; 2]>> =
+        footprint_rings(pos.latitude, pos.longitude, altitude_km, FOOTPRINT_SEGMENTS)
+            .into_iter()
+            .map(|ring| ring.into_iter().map(|(lat, lon)| [lon, lat]).collect())
+            .collect();
+    // Через антимеридиан круг распадается на две части (RFC 7946, п. 3.1.9)
+    let geometry = match rings.as_slice() {
+        [ring] => serde_json::json!({ "type": "Polygon", "coordinates": [ring] }),
+        _ => serde_json::json!({
+            "type": "MultiPolygon",
+            "coordinates": rings.iter().map(|ring| vec![ring]).collect::<Vec<_>>()
+        }),
+    };
+
+    let feature = serde_json::json!({
+        "type": "Feature",
+        "geometry": geometry,
+        "properties": {
+            "norad_id": sat,
+            "center_lat": pos.latitude,
+            "center_lon": pos.longitude,
+            "altitude_km": altitude_km,
+            "radius_km": EARTH_RADIUS_KM * footprint_angle_rad(altitude_km),
+            "sample_time": pos.sample_time,
+            "fetched_at": row.fetched_at
+        }
+    });
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/geo+json")],
+        Json(feature),
+    )
+        .into_response())
+}
+
+const KML_DEFAULT_MAX_POINTS: i64 = 2000;
+
+/// Тот же трек, что и в GeoJSON, в виде KML с высотой для Google Earth
+async fn iss_track_kml(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> Result<Response, ApiError> {
+    let max_points = parse_i64_param(&q, "max_points")?.unwrap_or(KML_DEFAULT_MAX_POINTS);
+    if !(2..=TRACK_MAX_LIMIT).contains(&max_points) {
+        return Err(ApiError::validation(format!(
+            "max_points must be between 2 and {}",
+            TRACK_MAX_LIMIT
+        )));
+    }
+    let sat = parse_sat_param(&q)?;
+    // Трек за всё окно, а не только последние TRACK_DEFAULT_LIMIT точек:
+    // прореживание само ограничит размер документа
+    let points = load_track(&st.pool, &q, TRACK_MAX_LIMIT).await?;
+    let points = downsample(&points, max_points as usize);
+
+    let kml_points: Vec<KmlPoint> = points
+        .iter()
+        .map(|p| KmlPoint {
+            when: p.fetched_at,
+            lat: p.lat,
+            lon: p.lon,
+            alt_m: p.alt_km.unwrap_or(ISS_FALLBACK_ALTITUDE_KM) * 1000.0,
+        })
+        .collect();
+    let description = match (points.first(), points.last()) {
+        (Some(first), Some(last)) => format!(
+            "{} points, {} — {}",
+            points.len(),
+            first.fetched_at.to_rfc3339(),
+            last.fetched_at.to_rfc3339()
+        ),
+        _ => "no data".to_string(),
+    };
+    let kml = track_document(&format!("NORAD {} track", sat), &description, &kml_points);
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/vnd.google-earth.kml+xml")],
+        kml,
+    )
+        .into_response())
+}
+
+/// Не больше `max` элементов, равномерно по индексу; первый и последний сохраняются
+fn downsample<T>(items: &[T], max: usize) -> Vec<&T> {
+    if items.len() <= max || max < 2 {
+        return items.iter().collect();
+    }
+    let step = (items.len() - 1) as f64 / (max - 1) as f64;
+    (0..max).map(|i| &items[(i as f64 * step).round() as usize]).collect()
+}
+
+const ISS_CSV_HEADER: &str = "id,fetched_at,latitude,longitude,altitude,velocity,source_url\n";
+
+/// Потоковая выгрузка iss_fetch_log в CSV: строки читаются курсором и
+/// отдаются клиенту по мере чтения, без буферизации всей таблицы
+async fn iss_export_csv(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> Result<Response, ApiError> {
+    let from = parse_time_param(&q, "from")?;
+    let to = parse_time_param(&q, "to")?;
+    let sat = parse_sat_param(&q)?;
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<String, ApiError>>(64);
+    let pool = st.pool.clone();
+
+    tokio::spawn(async move {
+        if tx.send(Ok(ISS_CSV_HEADER.to_string())).await.is_err() {
+            return;
+        }
+
+        let mut rows = sqlx::query(
+            "SELECT id, fetched_at, source_url, payload,
+                    latitude, longitude, altitude_km, velocity_kmh
+             FROM iss_fetch_log
+             WHERE ($1::timestamptz IS NULL OR fetched_at >= $1)
+               AND ($2::timestamptz IS NULL OR fetched_at <= $2)
+               AND norad_id = $3
+             ORDER BY fetched_at, id"
+        )
+        .bind(from)
+        .bind(to)
+        .bind(sat)
+        .fetch(&pool);
+
+        loop {
+            let line = match rows.try_next().await {
+                Ok(Some(row)) => iss_csv_line(&row),
+                Ok(None) => break,
+                Err(e) => Err(e.into()),
+            };
+            let failed = line.is_err();
+            // Клиент отключился — прекращаем чтение
+            if tx.send(line).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    let stream = futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|line| (line, rx))
+    });
+
+    let disposition = format!(
+        "attachment; filename=\"iss_fetch_log_{}.csv\"",
+        Utc::now().format("%Y%m%d")
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response())
+}
+
+fn iss_csv_line(row: &sqlx::postgres::PgRow) -> Result<String, ApiError> {
+    let id: i64 = row.try_get("id")?;
+    let fetched_at: DateTime<Utc> = row.try_get("fetched_at")?;
+    let source_url: String = row.try_get("source_url")?;
+    let position = StoredPosition::from_row(row);
+    Ok(format_iss_csv_line(id, fetched_at, position.as_ref(), &source_url))
+}
+
+/// Координаты берутся из нормализованной позиции, поэтому формат строки
+/// не зависит от того, какое зеркало ответило
+fn format_iss_csv_line(
+    id: i64,
+    fetched_at: DateTime<Utc>,
+    position: Option<&StoredPosition>,
+    source_url: &str,
+) -> String {
+    let num = |n: Option<f64>| n.map(|n| n.to_string()).unwrap_or_default();
+    format!(
+        "{},{},{},{},{},{},{}\n",
+        id,
+        fetched_at.to_rfc3339(),
+        num(position.map(|p| p.latitude)),
+        num(position.map(|p| p.longitude)),
+        num(position.and_then(|p| p.altitude_km)),
+        num(position.and_then(|p| p.velocity_kmh)),
+        csv_field(source_url)
+    )
+}
+
+/// Экранирование поля CSV по RFC 4180
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+const ISS_EVENTS_CAPACITY: usize = 64;
+const SSE_KEEP_ALIVE_SECS: u64 = 15;
+
+/// SSE-поток новых позиций: первым событием отдаётся последняя известная позиция
+async fn iss_stream(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let sat = parse_sat_param(&q)?;
+    // Подписываемся до чтения последней позиции, чтобы не потерять вставку между ними
+    let rx = st.iss_events.subscribe();
+    let latest = load_last_iss(&st, sat).await?;
+
+    let initial = futures::stream::once(async move { latest });
+    let live = futures::stream::unfold(rx, move |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(v) if is_event_for(&v, sat) => return Some((v, rx)),
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    warn!("iss stream subscriber lagged, {} events dropped", n);
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    let events = initial
+        .chain(live)
+        .map(|v| Ok(Event::default().event("position").data(v.to_string())));
+
+    Ok(Sse::new(events).keep_alive(
+        KeepAlive::new().interval(Duration::from_secs(SSE_KEEP_ALIVE_SECS)),
+    ))
+}
+
+/// Событие трансляции относится к спутнику `sat`
+fn is_event_for(event: &Value, sat: i64) -> bool {
+    event["norad_id"].as_i64() == Some(sat)
+}
+
+const WS_MAX_THROTTLE_SECS: i64 = 3600;
+
+/// WebSocket-трансляция новых позиций; `throttle_secs` прореживает поток
+/// для медленных клиентов
+async fn iss_ws(
+    ws: WebSocketUpgrade,
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> Result<Response, ApiError> {
+    let throttle_secs = parse_i64_param(&q, "throttle_secs")?.unwrap_or(0);
+    if !(0..=WS_MAX_THROTTLE_SECS).contains(&throttle_secs) {
+        return Err(ApiError::validation(format!(
+            "throttle_secs must be between 0 and {}",
+            WS_MAX_THROTTLE_SECS
+        )));
+    }
+
+    let sat = parse_sat_param(&q)?;
+
+    let rx = st.iss_events.subscribe();
+    let throttle = Duration::from_secs(throttle_secs as u64);
+    Ok(ws.on_upgrade(move |socket| iss_ws_session(socket, rx, sat, throttle)))
+}
+
+async fn iss_ws_session(
+    mut socket: WebSocket,
+    mut rx: broadcast::Receiver<Value>,
+    sat: i64,
+    throttle: Duration,
+) {
+    let mut last_sent: Option<tokio::time::Instant> = None;
+
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => match incoming {
+                // На ping-фреймы axum отвечает pong автоматически; текстовый
+                // "ping" поддерживаем для браузеров, которые не шлют фреймы
+                Some(Ok(Message::Text(t))) if t.trim().eq_ignore_ascii_case("ping") => {
+                    if socket.send(Message::Text("pong".into())).await.is_err() {
+                        return;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+            event = rx.recv() => match event {
+                Ok(v) if is_event_for(&v, sat) => {
+                    let now = tokio::time::Instant::now();
+                    if last_sent.is_some_and(|t| now.duration_since(t) < throttle) {
+                        continue;
+                    }
+                    if socket.send(Message::Text(v.to_string())).await.is_err() {
+                        return;
+                    }
+                    last_sent = Some(now);
+                }
+                Ok(_) => {}
+                // broadcast вытесняет самые старые сообщения — просто продолжаем
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    warn!("iss ws subscriber lagged, {} events dropped", n);
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    let _ = socket.send(Message::Close(None)).await;
+                    return;
+                }
+            },
+        }
+    }
+}
+
+const PASSES_DEFAULT_HOURS: i64 = 24;
+const PASSES_MAX_HOURS: i64 = 72;
+const PASSES_DEFAULT_MIN_ELEVATION: f64 = 10.0;
+/// TLE старше этого возраста перезапрашивается перед расчётом
+const TLE_MAX_AGE_HOURS: f64 = 7.0 * 24.0;
+
+async fn iss_passes(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let lat = parse_f64_param(&q, "lat")?
+        .ok_or_else(|| ApiError::validation("lat is required"))?;
+    let lon = parse_f64_param(&q, "lon")?
+        .ok_or_else(|| ApiError::validation("lon is required"))?;
+    let alt_m = parse_f64_param(&q, "alt_m")?.unwrap_or(0.0);
+    let hours = parse_i64_param(&q, "hours")?.unwrap_or(PASSES_DEFAULT_HOURS);
+    let min_elevation =
+        parse_f64_param(&q, "min_elevation")?.unwrap_or(PASSES_DEFAULT_MIN_ELEVATION);
+
+    if !(-90.0..=90.0).contains(&lat) {
+        return Err(ApiError::validation("lat must be between -90 and 90"));
+    }
+    if !(-180.0..=180.0).contains(&lon) {
+        return Err(ApiError::validation("lon must be between -180 and 180"));
+    }
+    if !(-500.0..=9000.0).contains(&alt_m) {
+        return Err(ApiError::validation("alt_m must be between -500 and 9000"));
+    }
+    if !(1..=PASSES_MAX_HOURS).contains(&hours) {
+        return Err(ApiError::validation(format!(
+            "hours must be between 1 and {}",
+            PASSES_MAX_HOURS
+        )));
+    }
+    if !(0.0..=90.0).contains(&min_elevation) {
+        return Err(ApiError::validation("min_elevation must be between 0 and 90"));
+    }
+
+    let tle = current_tle(&st).await?;
+    let observer = Observer {
+        lat,
+        lon,
+        alt_km: alt_m / 1000.0,
+    };
+    let from = Utc::now();
+    let to = from + chrono::Duration::hours(hours);
+    let prop = Propagator::new(&tle).map_err(ApiError::invalid_payload)?;
+    let passes = predict_passes(&prop, &observer, from, to, min_elevation);
+
+    let mut body = serde_json::json!({
+        "observer": { "lat": lat, "lon": lon, "alt_m": alt_m },
+        "from": from,
+        "to": to,
+        "min_elevation_deg": min_elevation,
+        "tle_epoch": tle.epoch,
+        "count": passes.len(),
+        "passes": passes
+    });
+    if passes.is_empty() {
+        body["message"] = "no passes in window".into();
+    }
+    ok(body)
+}
+
+async fn iss_tle(State(st): State<AppState>) -> ApiResult<Value> {
+    let tle = current_tle(&st).await?;
+    let age_hours = tle.age_hours(Utc::now());
+    let mut body = serde_json::to_value(&tle).map_err(|e| ApiError::internal(e.to_string()))?;
+    body["epoch_age_hours"] = age_hours.into();
+    ok(body)
+}
+
+/// Последний сохранённый TLE; устаревший или отсутствующий запрашивается заново
+async fn current_tle(st: &AppState) -> Result<Tle, ApiError> {
+    let cached = sqlx::query(
+        "SELECT payload, payload_compressed FROM space_cache
+         WHERE source = 'tle' ORDER BY id DESC LIMIT 1"
+    )
+    .fetch_optional(&st.pool)
+    .await?
+    .and_then(|r| unpack(r.try_get("payload").ok()?, r.try_get("payload_compressed").ok()?).ok())
+    .and_then(|p| {
+        let text = format!(
+            "{}\n{}",
+            p["line1"].as_str().unwrap_or_default(),
+            p["line2"].as_str().unwrap_or_default()
+        );
+        Tle::parse(&text).ok()
+    });
+
+    match cached {
+        Some(tle) if tle.age_hours(Utc::now()) <= TLE_MAX_AGE_HOURS => Ok(tle),
+        _ => {
+            let _guard = st.sources.lock("tle").await;
+            let (tle, fetched) = fetch_tle(st).await?;
+            store_fetched(st, "tle", fetched).await?;
+            Ok(tle)
+        }
+    }
+}
+
+/// Момент наибольшего сближения МКС с точкой за окно [from, to].
+/// Строки читаются курсором, чтобы большие окна не загружались в память целиком
+async fn iss_closest(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let lat = parse_f64_param(&q, "lat")?
+        .ok_or_else(|| ApiError::validation("lat is required"))?;
+    let lon = parse_f64_param(&q, "lon")?
+        .ok_or_else(|| ApiError::validation("lon is required"))?;
+    if !(-90.0..=90.0).contains(&lat) {
+        return Err(ApiError::validation("lat must be between -90 and 90"));
+    }
+    if !(-180.0..=180.0).contains(&lon) {
+        return Err(ApiError::validation("lon must be between -180 and 180"));
+    }
+
+    let to = parse_time_param(&q, "to")?.unwrap_or_else(Utc::now);
+    let from = parse_time_param(&q, "from")?.unwrap_or(to - chrono::Duration::hours(24));
+    if from > to {
+        return Err(ApiError::validation("from must not be later than to"));
+    }
+    let sat = parse_sat_param(&q)?;
+    let max_window = chrono::Duration::hours(st.config.closest_max_window_hours as i64);
+    if to - from > max_window {
+        return Err(ApiError::validation(format!(
+            "window must not exceed {} hours",
+            st.config.closest_max_window_hours
+        )));
+    }
+
+    let mut rows = sqlx::query(
+        "SELECT id, fetched_at, payload,
+                latitude, longitude, altitude_km, velocity_kmh, sample_time, visibility
+         FROM iss_fetch_log
+         WHERE fetched_at >= $1 AND fetched_at <= $2 AND norad_id = $3
+         ORDER BY fetched_at"
+    )
+    .bind(from)
+    .bind(to)
+    .bind(sat)
+    .fetch(&st.pool);
+
+    let mut scanned = 0u64;
+    let mut skipped = 0u64;
+    let mut best: Option<(f64, i64, DateTime<Utc>, StoredPosition)> = None;
+
+    while let Some(row) = rows.try_next().await? {
+        scanned += 1;
+        let Some(pos) = StoredPosition::from_row(&row) else {
+            skipped += 1;
+            continue;
+        };
+        let distance = haversine_km(lat, lon, pos.latitude, pos.longitude);
+        if best.as_ref().is_none_or(|b| distance < b.0) {
+            best = Some((distance, row.try_get("id")?, row.try_get("fetched_at")?, pos));
+        }
+    }
+
+    let closest = best.map(|(distance_km, id, fetched_at, position)| {
+        serde_json::json!({
+            "distance_km": distance_km,
+            "id": id,
+            "fetched_at": fetched_at,
+            "position": position
+        })
+    });
+
+    ok(serde_json::json!({
+        "point": { "lat": lat, "lon": lon },
+        "from": from,
+        "to": to,
+        "scanned": scanned,
+        "skipped_missing_coordinates": skipped,
+        "closest": closest
+    }))
+}
+
+#[derive(Serialize)]
+struct IssDayStats {
+    day: chrono::NaiveDate,
+    samples: i64,
+    avg_velocity_kmh: Option<f64>,
+    min_velocity_kmh: Option<f64>,
+    max_velocity_kmh: Option<f64>,
+    avg_altitude_km: Option<f64>,
+    max_gap_sec: Option<f64>,
+}
+
+const STATS_DEFAULT_DAYS: i64 = 7;
+const STATS_MAX_DAYS: i64 = 366;
+
+/// Посуточные агрегаты iss_fetch_log; дни без выборок тоже попадают в ответ
+/// (samples = 0), чтобы простои были видны на графиках
+async fn iss_stats(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let days = parse_i64_param(&q, "days")?.unwrap_or(STATS_DEFAULT_DAYS);
+    if !(1..=STATS_MAX_DAYS).contains(&days) {
+        return Err(ApiError::validation(format!(
+            "days must be between 1 and {}",
+            STATS_MAX_DAYS
+        )));
+    }
+    let sat = parse_sat_param(&q)?;
+    let exclude_anomalies = parse_bool_param(&q, "exclude_anomalies")?.unwrap_or(false);
+    let tz = q.get("tz").map(|s| s.trim()).filter(|s| !s.is_empty()).unwrap_or("UTC");
+    let tz_known: bool =
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM pg_timezone_names WHERE name = $1)")
+            .bind(tz)
+            .fetch_one(&st.pool)
+            .await?;
+    if !tz_known {
+        return Err(ApiError::validation(format!("unknown time zone: {}", tz)));
+    }
+
+    let rows = sqlx::query(
+        "WITH bounds AS (
+            SELECT date_trunc('day', now() AT TIME ZONE $2) - make_interval(days => $1 - 1) AS first_day,
+                   date_trunc('day', now() AT TIME ZONE $2) AS last_day
+         ),
+         days AS (
+            SELECT generate_series(first_day, last_day, interval '1 day') AS day FROM bounds
+         ),
+         samples AS (
+            SELECT date_trunc('day', fetched_at AT TIME ZONE $2) AS day,
+                   velocity_kmh AS velocity,
+                   altitude_km AS altitude,
+                   EXTRACT(EPOCH FROM fetched_at - lag(fetched_at) OVER (ORDER BY fetched_at))::float8
+                       AS gap_sec
+            FROM iss_fetch_log
+            WHERE fetched_at >= ((SELECT first_day FROM bounds) AT TIME ZONE $2) - interval '1 day'
+              AND norad_id = $3
+              AND NOT ($4 AND EXISTS (
+                  SELECT 1 FROM iss_anomalies a WHERE a.log_id = iss_fetch_log.id))
+         )
+         SELECT d.day::date AS day,
+                count(s.day) AS samples,
+                avg(s.velocity) AS avg_velocity,
+                min(s.velocity) AS min_velocity,
+                max(s.velocity) AS max_velocity,
+                avg(s.altitude) AS avg_altitude,
+                max(s.gap_sec) AS max_gap
+         FROM days d
+         LEFT JOIN samples s ON s.day = d.day
+         GROUP BY d.day
+         ORDER BY d.day"
+    )
+    .bind(days as i32)
+    .bind(tz)
+    .bind(sat)
+    .bind(exclude_anomalies)
+    .fetch_all(&st.pool)
+    .await?;
+
+    let mut stats = Vec::with_capacity(rows.len());
+    for row in rows {
+        stats.push(IssDayStats {
+            day: row.try_get("day")?,
+            samples: row.try_get("samples")?,
+            avg_velocity_kmh: row.try_get("avg_velocity")?,
+            min_velocity_kmh: row.try_get("min_velocity")?,
+            max_velocity_kmh: row.try_get("max_velocity")?,
+            avg_altitude_km: row.try_get("avg_altitude")?,
+            max_gap_sec: row.try_get("max_gap")?,
+        });
+    }
+
+    ok(serde_json::json!({
+        "tz": tz,
+        "days": stats
+    }))
+}
+
+#[derive(Serialize)]
+struct IssGap {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    duration_sec: f64,
+}
+
+const GAPS_DEFAULT_THRESHOLD_SECS: i64 = 600;
+
+/// Интервалы между соседними записями iss_fetch_log длиннее порога.
+/// Границы окна участвуют как виртуальные точки, поэтому простой в начале
+/// окна и «тихо умерший» цикл загрузки в конце тоже попадают в отчёт
+async fn iss_gaps(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let threshold = parse_i64_param(&q, "threshold_secs")?.unwrap_or(GAPS_DEFAULT_THRESHOLD_SECS);
+    if threshold <= 0 {
+        return Err(ApiError::validation("threshold_secs must be positive"));
+    }
+    let to = parse_time_param(&q, "to")?.unwrap_or_else(Utc::now);
+    let from = parse_time_param(&q, "from")?.unwrap_or(to - chrono::Duration::hours(24));
+    if from >= to {
+        return Err(ApiError::validation("from must be earlier than to"));
+    }
+    let sat = parse_sat_param(&q)?;
+
+    let rows = sqlx::query(
+        "WITH points AS (
+            SELECT $1::timestamptz AS t
+            UNION ALL
+            SELECT fetched_at FROM iss_fetch_log
+            WHERE fetched_at > $1 AND fetched_at < $2 AND norad_id = $4
+            UNION ALL
+            SELECT $2::timestamptz
+         ),
+         intervals AS (
+            SELECT lag(t) OVER (ORDER BY t) AS gap_start, t AS gap_end FROM points
+         )
+         SELECT gap_start, gap_end,
+                EXTRACT(EPOCH FROM gap_end - gap_start)::float8 AS duration_sec
+         FROM intervals
+         WHERE gap_start IS NOT NULL
+           AND gap_end - gap_start > make_interval(secs => $3)
+         ORDER BY gap_start"
+    )
+    .bind(from)
+    .bind(to)
+    .bind(threshold as f64)
+    .bind(sat)
+    .fetch_all(&st.pool)
+    .await?;
+
+    let mut gaps = Vec::with_capacity(rows.len());
+    for row in rows {
+        gaps.push(IssGap {
+            start: row.try_get("gap_start")?,
+            end: row.try_get("gap_end")?,
+            duration_sec: row.try_get("duration_sec")?,
+        });
+    }
+
+    let window_sec = seconds_between(from, to);
+    let total_downtime_sec: f64 = gaps.iter().map(|g| g.duration_sec).sum();
+    let longest = gaps
+        .iter()
+        .max_by(|a, b| a.duration_sec.total_cmp(&b.duration_sec))
+        .map(|g| serde_json::json!({ "start": g.start, "end": g.end, "duration_sec": g.duration_sec }));
+    let coverage_pct = (100.0 * (1.0 - total_downtime_sec / window_sec)).clamp(0.0, 100.0);
+
+    ok(serde_json::json!({
+        "from": from,
+        "to": to,
+        "threshold_secs": threshold,
+        "gaps": gaps,
+        "summary": {
+            "gap_count": gaps.len(),
+            "window_sec": window_sec,
+            "total_downtime_sec": total_downtime_sec,
+            "longest_gap": longest,
+            "coverage_pct": coverage_pct
+        }
+    }))
+}
+
+#[derive(Serialize)]
+struct IssAnomalyRow {
+    id: i64,
+    log_id: i64,
+    norad_id: i64,
+    fetched_at: DateTime<Utc>,
+    detected_at: DateTime<Utc>,
+    field: String,
+    value: f64,
+    median: f64,
+    reason: String,
+}
+
+const ANOMALIES_DEFAULT_LIMIT: i64 = 100;
+const ANOMALIES_MAX_LIMIT: i64 = 1000;
+
+/// Выборки, отмеченные как аномальные, от новых к старым
+async fn iss_anomalies(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let from = parse_time_param(&q, "from")?;
+    let to = parse_time_param(&q, "to")?;
+    if let (Some(f), Some(t)) = (from, to) {
+        if f > t {
+            return Err(ApiError::validation("from must not be later than to"));
+        }
+    }
+    let sat = parse_sat_param(&q)?;
+    let limit = parse_i64_param(&q, "limit")?.unwrap_or(ANOMALIES_DEFAULT_LIMIT);
+    if !(1..=ANOMALIES_MAX_LIMIT).contains(&limit) {
+        return Err(ApiError::validation(format!(
+            "limit must be between 1 and {}",
+            ANOMALIES_MAX_LIMIT
+        )));
+    }
+
+    let rows = sqlx::query(
+        "SELECT a.id, a.log_id, a.norad_id, l.fetched_at, a.detected_at,
+                a.field, a.value, a.median, a.reason
+         FROM iss_anomalies a
+         JOIN iss_fetch_log l ON l.id = a.log_id
+         WHERE a.norad_id = $3
+           AND ($1::timestamptz IS NULL OR l.fetched_at >= $1)
+           AND ($2::timestamptz IS NULL OR l.fetched_at <= $2)
+         ORDER BY a.id DESC
+         LIMIT $4"
+    )
+    .bind(from)
+    .bind(to)
+    .bind(sat)
+    .bind(limit)
+    .fetch_all(&st.pool)
+    .await?;
+
+    let mut items = Vec::with_capacity(rows.len());
+    for row in rows {
+        items.push(IssAnomalyRow {
+            id: row.try_get("id")?,
+            log_id: row.try_get("log_id")?,
+            norad_id: row.try_get("norad_id")?,
+            fetched_at: row.try_get("fetched_at")?,
+            detected_at: row.try_get("detected_at")?,
+            field: row.try_get("field")?,
+            value: row.try_get("value")?,
+            median: row.try_get("median")?,
+            reason: row.try_get("reason")?,
+        });
+    }
+
+    ok(serde_json::json!({
+        "from": from,
+        "to": to,
+        "norad_id": sat,
+        "thresholds": {
+            "velocity_kmh": st.config.anomaly_velocity_kmh,
+            "altitude_km": st.config.anomaly_altitude_km,
+            "window": ANOMALY_WINDOW
+        },
+        "count": items.len(),
+        "items": items
+    }))
+}
+
+/// Окно анализа орбиты по умолчанию и предел, часы
+const ORBIT_DEFAULT_HOURS: i64 = 6;
+const ORBIT_MAX_HOURS: i64 = 72;
+/// Максимальный разрыв между соседними точками у пересечения экватора, минуты
+const ORBIT_DEFAULT_MAX_GAP_MIN: i64 = 10;
+
+async fn iss_orbit(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let hours = parse_i64_param(&q, "hours")?.unwrap_or(ORBIT_DEFAULT_HOURS);
+    if !(1..=ORBIT_MAX_HOURS).contains(&hours) {
+        return Err(ApiError::validation(format!(
+            "hours must be between 1 and {}",
+            ORBIT_MAX_HOURS
+        )));
+    }
+    let max_gap_min = parse_i64_param(&q, "max_gap_min")?.unwrap_or(ORBIT_DEFAULT_MAX_GAP_MIN);
+    if !(1..=60).contains(&max_gap_min) {
+        return Err(ApiError::validation("max_gap_min must be between 1 and 60"));
+    }
+    let sat = parse_sat_param(&q)?;
+    let to = Utc::now();
+    let from = to - chrono::Duration::hours(hours);
+
+    let rows = sqlx::query(
+        "SELECT fetched_at, payload,
+                latitude, longitude, altitude_km, velocity_kmh, sample_time, visibility
+         FROM iss_fetch_log
+         WHERE fetched_at >= $1 AND norad_id = $2
+         ORDER BY fetched_at"
+    )
+    .bind(from)
+    .bind(sat)
+    .fetch_all(&st.pool)
+    .await?;
+
+    let mut samples = Vec::with_capacity(rows.len());
+    for row in &rows {
+        let fetched_at: DateTime<Utc> = row.try_get("fetched_at")?;
+        if let Some(p) = StoredPosition::from_row(row) {
+            samples.push(OrbitSample {
+                t: p.sample_time.unwrap_or(fetched_at),
+                latitude: p.latitude,
+                altitude_km: p.altitude_km,
+                velocity_kmh: p.velocity_kmh,
+            });
+        }
+    }
+    samples.sort_by_key(|s| s.t);
+
+    let window = serde_json::json!({
+        "from": from,
+        "to": to,
+        "hours": hours,
+        "max_gap_min": max_gap_min,
+        "norad_id": sat,
+        "samples": samples.len()
+    });
+    match estimate_orbit(&samples, chrono::Duration::minutes(max_gap_min)) {
+        Some(estimate) => ok(serde_json::json!({
+            "status": "ok",
+            "window": window,
+            "orbit": estimate
+        })),
+        None => ok(serde_json::json!({
+            "status": "insufficient_data",
+            "window": window,
+            "message": "fewer than two ascending-node crossings with dense enough samples"
+        })),
+    }
+}
+
+/// Запись журнала рядом с запрошенным моментом
+#[derive(Serialize)]
+struct IssSample {
+    id: i64,
+    fetched_at: DateTime<Utc>,
+    sample_time: Option<DateTime<Utc>>,
+    /// Смещение от запрошенного момента, секунды (отрицательное — раньше)
+    offset_sec: f64,
+    position: Option<StoredPosition>,
+}
+
+/// По какой колонке искать ближайшую запись
+#[derive(Clone, Copy)]
+enum SampleClock {
+    FetchedAt,
+    SampleTime,
+}
+
+impl SampleClock {
+    fn from_query(q: &HashMap<String, String>) -> Result<Self, ApiError> {
+        match q.get("by").map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            None | Some("fetched_at") => Ok(Self::FetchedAt),
+            Some("sample_time") => Ok(Self::SampleTime),
+            Some(_) => Err(ApiError::validation("by must be fetched_at or sample_time")),
+        }
+    }
+
+    fn column(self) -> &'static str {
+        match self {
+            Self::FetchedAt => "fetched_at",
+            Self::SampleTime => "sample_time",
+        }
+    }
+}
+
+const AT_DEFAULT_TOLERANCE_SECS: i64 = 300;
+const AT_MAX_TOLERANCE_SECS: i64 = 86_400;
+
+async fn iss_at(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let time = parse_time_param(&q, "time")?
+        .ok_or_else(|| ApiError::validation("time is required"))?;
+    let tolerance = parse_i64_param(&q, "tolerance_secs")?.unwrap_or(AT_DEFAULT_TOLERANCE_SECS);
+    if !(0..=AT_MAX_TOLERANCE_SECS).contains(&tolerance) {
+        return Err(ApiError::validation(format!(
+            "tolerance_secs must be between 0 and {}",
+            AT_MAX_TOLERANCE_SECS
+        )));
+    }
+    let clock = SampleClock::from_query(&q)?;
+    let sat = parse_sat_param(&q)?;
+
+    let (before, after) = bracketing_samples(&st.pool, sat, time, clock).await?;
+    let within = |s: &Option<IssSample>| {
+        s.as_ref()
+            .map(|s| s.offset_sec.abs())
+            .filter(|d| *d <= tolerance as f64)
+    };
+    // Some(true) — ближе запись до момента, Some(false) — после
+    let take_before = match (within(&before), within(&after)) {
+        (Some(b), Some(a)) => Some(b <= a),
+        (Some(_), None) => Some(true),
+        (None, Some(_)) => Some(false),
+        (None, None) => None,
+    };
+
+    let mut body = serde_json::json!({
+        "time": time,
+        "tolerance_secs": tolerance,
+        "by": clock.column(),
+        "norad_id": sat
+    });
+    match take_before {
+        Some(take_before) => {
+            body["status"] = "ok".into();
+            body["sample"] = serde_json::json!(if take_before { before } else { after });
+        }
+        None => {
+            body["status"] = "no_sample_within_tolerance".into();
+            body["before"] = serde_json::json!(before);
+            body["after"] = serde_json::json!(after);
+        }
+    }
+    ok(body)
+}
+
+/// Положение на момент `time` между двумя соседними записями
+async fn iss_interpolate(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let time = parse_time_param(&q, "time")?
+        .ok_or_else(|| ApiError::validation("time is required"))?;
+    let clock = SampleClock::from_query(&q)?;
+    let sat = parse_sat_param(&q)?;
+    let max_gap_secs = st.config.interpolate_max_gap_secs as f64;
+
+    let (before, after) = bracketing_samples(&st.pool, sat, time, clock).await?;
+    let (Some(before), Some(after)) = (before, after) else {
+        return Err(ApiError::not_found(
+            "time is outside the recorded history; nothing to interpolate between",
+        ));
+    };
+    let (Some(from), Some(to)) = (&before.position, &after.position) else {
+        return Err(ApiError::not_found("bracketing samples have no parsable position"));
+    };
+
+    let gap_sec = after.offset_sec - before.offset_sec;
+    if gap_sec > max_gap_secs {
+        return Err(ApiError::validation(format!(
+            "bracketing samples are {:.0} s apart, more than the {} s allowed",
+            gap_sec, max_gap_secs
+        )));
+    }
+    // Момент совпал с записью: обе стороны — одна и та же строка
+    let fraction = if gap_sec > 0.0 { -before.offset_sec / gap_sec } else { 0.0 };
+    let estimate = from
+        .interpolate(to, fraction, time)
+        .ok_or_else(|| ApiError::internal("bracketing samples are antipodal"))?;
+
+    ok(serde_json::json!({
+        "time": time,
+        "by": clock.column(),
+        "norad_id": sat,
+        "fraction": fraction,
+        "gap_sec": gap_sec,
+        "max_gap_secs": max_gap_secs,
+        "position": estimate,
+        "before": before,
+        "after": after
+    }))
+}
+
+/// Ближайшие записи не позже и не раньше `time`: два запроса по индексу
+/// вместо сортировки всей таблицы по расстоянию
+async fn bracketing_samples(
+    pool: &PgPool,
+    sat: i64,
+    time: DateTime<Utc>,
+    clock: SampleClock,
+) -> Result<(Option<IssSample>, Option<IssSample>), ApiError> {
+    let (before_sql, after_sql) = match clock {
+        SampleClock::FetchedAt => (
+            "SELECT id, fetched_at, payload,
+                    latitude, longitude, altitude_km, velocity_kmh, sample_time, visibility
+             FROM iss_fetch_log
+             WHERE fetched_at <= $1 AND norad_id = $2
+             ORDER BY fetched_at DESC LIMIT 1",
+            "SELECT id, fetched_at, payload,
+                    latitude, longitude, altitude_km, velocity_kmh, sample_time, visibility
+             FROM iss_fetch_log
+             WHERE fetched_at >= $1 AND norad_id = $2
+             ORDER BY fetched_at LIMIT 1",
+        ),
+        SampleClock::SampleTime => (
+            "SELECT id, fetched_at, payload,
+                    latitude, longitude, altitude_km, velocity_kmh, sample_time, visibility
+             FROM iss_fetch_log
+             WHERE sample_time <= $1 AND norad_id = $2
+             ORDER BY sample_time DESC LIMIT 1",
+            "SELECT id, fetched_at, payload,
+                    latitude, longitude, altitude_km, velocity_kmh, sample_time, visibility
+             FROM iss_fetch_log
+             WHERE sample_time >= $1 AND norad_id = $2
+             ORDER BY sample_time LIMIT 1",
+        ),
+    };
+
+    let mut found = [None, None];
+    for (slot, sql) in found.iter_mut().zip([before_sql, after_sql]) {
+        let row = sqlx::query(sql)
+            .bind(time)
+            .bind(sat)
+            .fetch_optional(pool)
+            .await?;
+        if let Some(row) = row {
+            let fetched_at: DateTime<Utc> = row.try_get("fetched_at")?;
+            let sample_time: Option<DateTime<Utc>> = row.try_get("sample_time")?;
+            let at = match clock {
+                SampleClock::FetchedAt => fetched_at,
+                SampleClock::SampleTime => sample_time.unwrap_or(fetched_at),
+            };
+            *slot = Some(IssSample {
+                id: row.try_get("id")?,
+                fetched_at,
+                sample_time,
+                offset_sec: seconds_between(time, at),
+                position: StoredPosition::from_row(&row),
+            });
+        }
+    }
+    let [before, after] = found;
+    Ok((before, after))
+}
+
+async fn trigger_iss(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let force = parse_bool_param(&q, "force")?.unwrap_or(false);
+    let sat = parse_tracked_sat_param(&st, &q)?;
+    let (outcome, coalesced) = fetch_and_store_iss_shared(&st, sat, force).await;
+    let outcome = outcome?;
+
+    let mut last = load_last_iss(&st, sat).await?;
+    if let Some(obj) = last.as_object_mut() {
+        if coalesced {
+            obj.insert("coalesced".into(), Value::Bool(true));
+        }
+        obj.insert(
+            "skipped_duplicate".into(),
+            Value::Bool(outcome == IssStoreOutcome::SkippedDuplicate),
+        );
+        obj.insert(
+            "duplicates_skipped_total".into(),
+            st.iss_duplicates_skipped.load(Ordering::Relaxed).into(),
+        );
+    }
+    ok(last)
+}
+
+const BURST_MAX_COUNT: i64 = 30;
+const BURST_MIN_INTERVAL_SECS: i64 = 2;
+const BURST_MAX_INTERVAL_SECS: i64 = 60;
+
+/// Снимает флаг пакетной съёмки при выходе, в том числе при ошибке
+/// или обрыве клиентского соединения
+struct BurstGuard(Arc<AtomicBool>);
+
+impl Drop for BurstGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+/// Серия из `count` последовательных запросов с шагом `interval_secs`;
+/// фоновая задача ISS продолжает работать по своему расписанию
+async fn trigger_iss_burst(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let count = parse_i64_param(&q, "count")?.unwrap_or(10);
+    if !(1..=BURST_MAX_COUNT).contains(&count) {
+        return Err(ApiError::validation(format!(
+            "count must be between 1 and {}",
+            BURST_MAX_COUNT
+        )));
+    }
+    let interval_secs = parse_i64_param(&q, "interval_secs")?.unwrap_or(BURST_MIN_INTERVAL_SECS);
+    if !(BURST_MIN_INTERVAL_SECS..=BURST_MAX_INTERVAL_SECS).contains(&interval_secs) {
+        return Err(ApiError::validation(format!(
+            "interval_secs must be between {} and {}",
+            BURST_MIN_INTERVAL_SECS, BURST_MAX_INTERVAL_SECS
+        )));
+    }
+    let sat = parse_tracked_sat_param(&st, &q)?;
+
+    if st
+        .iss_burst_running
+        .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+    {
+        return Err(ApiError::conflict("another burst is already running"));
+    }
+    let _guard = BurstGuard(st.iss_burst_running.clone());
+
+    let started = tokio::time::Instant::now();
+    let started_at = Utc::now();
+    let interval = Duration::from_secs(interval_secs as u64);
+    let (mut written, mut deduplicated) = (0u32, 0u32);
+    let mut errors = Vec::new();
+
+    for i in 0..count {
+        if i > 0 {
+            tokio::time::sleep_until(started + interval * i as u32).await;
+        }
+        match fetch_and_store_iss(&st, sat, false).await {
+            Ok(IssStoreOutcome::Inserted) => written += 1,
+            Ok(IssStoreOutcome::SkippedDuplicate) => deduplicated += 1,
+            Err(e) => errors.push(e.error.message),
+        }
+    }
+
+    ok(serde_json::json!({
+        "norad_id": sat,
+        "requested": count,
+        "interval_secs": interval_secs,
+        "written": written,
+        "deduplicated": deduplicated,
+        "failed": errors.len(),
+        "errors": errors,
+        "started_at": started_at,
+        "wall_time_sec": started.elapsed().as_secs_f64()
+    }))
+}
+
+const TREND_DEFAULT_SAMPLES: i64 = 2;
+const TREND_MAX_SAMPLES: i64 = 50;
+
+async fn iss_trend(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Trend> {
+    let samples = parse_i64_param(&q, "samples")?.unwrap_or(TREND_DEFAULT_SAMPLES);
+    if !(2..=TREND_MAX_SAMPLES).contains(&samples) {
+        return Err(ApiError::validation(format!(
+            "samples must be between 2 and {}",
+            TREND_MAX_SAMPLES
+        )));
+    }
+    let sat = parse_sat_param(&q)?;
+    let exclude_anomalies = parse_bool_param(&q, "exclude_anomalies")?.unwrap_or(false);
+
+    let rows = sqlx::query_as::<_, IssLogRow>(
+        "SELECT id, fetched_at, source_url, payload, norad_id,
+                latitude, longitude, altitude_km, velocity_kmh, sample_time, visibility
+         FROM iss_fetch_log
+         WHERE norad_id = $2
+           AND NOT ($3 AND EXISTS (
+               SELECT 1 FROM iss_anomalies a WHERE a.log_id = iss_fetch_log.id))
+         ORDER BY fetched_at DESC, id DESC LIMIT $1"
+    )
+    .bind(samples)
+    .bind(sat)
+    .bind(exclude_anomalies)
+    .fetch_all(&st.pool)
+    .await?;
+
+    let velocity_kmh = rows
+        .first()
+        .and_then(IssLogRow::position)
+        .and_then(|p| p.velocity_kmh);
+
+    // Строки приходят от новых к старым, тренд считаем в хронологическом порядке
+    let points: Vec<TrendSample> = rows.iter().rev().filter_map(TrendSample::from_row).collect();
+
+    ok(compute_trend(&points, rows.len() - points.len(), velocity_kmh))
+}
+
+/* ---------- OSDR Handlers ---------- */
+#[derive(Serialize)]
+struct OsdrSyncResponse {
+    /// completed или already_running
+    status: &'static str,
+    started_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    finished_at: Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    stats: Option<SyncStats>,
+}
+
+/// Снимает отметку о синхронизации OSDR при выходе, в том числе при ошибке
+/// или обрыве клиентского соединения
+struct OsdrSyncGuard {
+    slot: Arc<std::sync::Mutex<Option<DateTime<Utc>>>>,
+    started_at: DateTime<Utc>,
+}
+
+impl Drop for OsdrSyncGuard {
+    fn drop(&mut self) {
+        *self.slot.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+/// Занимает синхронизацию OSDR; если она уже идёт — возвращает момент её старта
+fn try_start_osdr_sync(st: &AppState) -> Result<OsdrSyncGuard, DateTime<Utc>> {
+    let mut started = st.osdr_sync_started.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(at) = *started {
+        return Err(at);
+    }
+    let started_at = Utc::now();
+    *started = Some(started_at);
+    Ok(OsdrSyncGuard {
+        slot: st.osdr_sync_started.clone(),
+        started_at,
+    })
+}
+
+/// Одна синхронизация за раз: второй вызов сразу получает already_running
+async fn osdr_sync(State(st): State<AppState>) -> ApiResult<OsdrSyncResponse> {
+    let guard = match try_start_osdr_sync(&st) {
+        Ok(guard) => guard,
+        Err(started_at) => {
+            return ok(OsdrSyncResponse {
+                status: "already_running",
+                started_at,
+                finished_at: None,
+                stats: None,
+            })
+        }
+    };
+    let stats = fetch_and_store_osdr(&st).await?;
+    ok(OsdrSyncResponse {
+        status: "completed",
+        started_at: guard.started_at,
+        finished_at: Some(Utc::now()),
+        stats: Some(stats),
+    })
+}
+
+/// GET /osdr/sync — устаревший вариант POST, будет удалён в следующем релизе
+async fn osdr_sync_deprecated(State(st): State<AppState>) -> impl IntoResponse {
+    warn!("GET /osdr/sync is deprecated, use POST");
+    ([("Deprecation", "true")], osdr_sync(State(st)).await)
+}
+
+const OSDR_SYNC_HISTORY_DEFAULT_LIMIT: i64 = 50;
+const OSDR_SYNC_HISTORY_MAX_LIMIT: i64 = 500;
+
+async fn osdr_sync_history(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let limit = parse_i64_param(&q, "limit")?.unwrap_or(OSDR_SYNC_HISTORY_DEFAULT_LIMIT);
+    if !(1..=OSDR_SYNC_HISTORY_MAX_LIMIT).contains(&limit) {
+        return Err(ApiError::validation(format!(
+            "limit must be between 1 and {}",
+            OSDR_SYNC_HISTORY_MAX_LIMIT
+        )));
+    }
+
+    let runs = sqlx::query_as::<_, OsdrSyncLogRow>(
+        "SELECT id, started_at, finished_at, pages, inserted, updated, unchanged,
+                attempts, outcome, error
+         FROM osdr_sync_log
+         ORDER BY id DESC
+         LIMIT $1"
+    )
+    .bind(limit)
+    .fetch_all(&st.pool)
+    .await?;
+
+    ok(serde_json::json!({ "count": runs.len(), "runs": runs }))
+}
+
+/// Сводка по osdr_items и последним запускам синхронизации
+async fn osdr_stats(State(st): State<AppState>) -> ApiResult<Value> {
+    let (total, missing): (i64, i64) = sqlx::query_as(
+        "SELECT count(*), count(missing_since) FROM osdr_items"
+    )
+    .fetch_one(&st.pool)
+    .await?;
+    let by_status: Vec<(Option<String>, i64)> = sqlx::query_as(
+        "SELECT status, count(*) FROM osdr_items GROUP BY status ORDER BY count(*) DESC"
+    )
+    .fetch_all(&st.pool)
+    .await?;
+
+    let last_run = sqlx::query_as::<_, OsdrSyncLogRow>(
+        "SELECT id, started_at, finished_at, pages, inserted, updated, unchanged,
+                attempts, outcome, error
+         FROM osdr_sync_log ORDER BY id DESC LIMIT 1"
+    )
+    .fetch_optional(&st.pool)
+    .await?;
+    let last_success = sqlx::query_as::<_, OsdrSyncLogRow>(
+        "SELECT id, started_at, finished_at, pages, inserted, updated, unchanged,
+                attempts, outcome, error
+         FROM osdr_sync_log WHERE error IS NULL ORDER BY id DESC LIMIT 1"
+    )
+    .fetch_optional(&st.pool)
+    .await?;
+
+    let by_status: serde_json::Map<String, Value> = by_status
+        .into_iter()
+        .map(|(status, n)| (status.unwrap_or_else(|| "unknown".into()), n.into()))
+        .collect();
+
+    ok(serde_json::json!({
+        "total": total,
+        "missing": missing,
+        "by_status": by_status,
+        "last_sync_at": last_run.as_ref().map(|r| r.finished_at),
+        "last_successful_sync_at": last_success.as_ref().map(|r| r.finished_at),
+        "last_sync": last_run,
+        "last_successful_sync": last_success
+    }))
+}
+
+#[derive(Serialize)]
+struct OsdrList {
+    items: Vec<OsdrItemRow>,
+    count: usize,
+    limit: i64,
+    /// Только при include_total=true: count(*) по всей таблице недёшев
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total: Option<i64>,
+    sort: String,
+    /// Передаётся в ?cursor= за следующей страницей
+    next_cursor: Option<String>,
+}
+
+const OSDR_LIST_MAX_LIMIT: i64 = 200;
+
+/// Фильтры /osdr/list, общие со списком, счётчиком total и экспортом
+struct OsdrFilter {
+    statuses: Option<Vec<String>>,
+    /// Организмы и миссии в нижнем регистре
+    organisms: Option<Vec<String>>,
+    missions: Option<Vec<String>>,
+    updated_after: Option<DateTime<Utc>>,
+    updated_before: Option<DateTime<Utc>>,
+    include_missing: bool,
+}
+
+impl OsdrFilter {
+    fn from_query(q: &HashMap<String, String>) -> Result<Self, ApiError> {
+        let filter = Self {
+            statuses: parse_list_param(q, "status"),
+            organisms: parse_list_param(q, "organism").map(lowercase_all),
+            missions: parse_list_param(q, "mission").map(lowercase_all),
+            updated_after: parse_time_param(q, "updated_after")?,
+            updated_before: parse_time_param(q, "updated_before")?,
+            include_missing: parse_bool_param(q, "include_missing")?.unwrap_or(false),
+        };
+        if let (Some(a), Some(b)) = (filter.updated_after, filter.updated_before) {
+            if a > b {
+                return Err(ApiError::validation(
+                    "updated_after must not be later than updated_before",
+                ));
+            }
+        }
+        Ok(filter)
+    }
+}
+
+fn lowercase_all(values: Vec<String>) -> Vec<String> {
+    values.into_iter().map(|v| v.to_lowercase()).collect()
+}
+
+/// Постраничный список в порядке ?sort= (по умолчанию новые первыми);
+/// курсор next_cursor хранит значение колонки сортировки и id последней строки
+async fn osdr_list(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<OsdrList> {
+    let default_limit = (st.config.osdr_list_limit as i64).clamp(1, OSDR_LIST_MAX_LIMIT);
+    let limit = parse_i64_param(&q, "limit")?.unwrap_or(default_limit);
+    if !(1..=OSDR_LIST_MAX_LIMIT).contains(&limit) {
+        return Err(ApiError::validation(format!(
+            "limit must be between 1 and {}",
+            OSDR_LIST_MAX_LIMIT
+        )));
+    }
+    let sort = match q.get("sort").map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        Some(raw) => OsdrSort::parse(raw).map_err(ApiError::validation)?,
+        None => OsdrSort::default(),
+    };
+    let cursor = match q.get("cursor").map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        Some(raw) => Some(ListCursor::decode(raw).map_err(ApiError::validation)?),
+        None => None,
+    };
+    if let Some(c) = &cursor {
+        if c.sort != sort {
+            return Err(ApiError::validation(format!(
+                "cursor was issued for sort={}, not sort={}",
+                c.sort.as_param(),
+                sort.as_param()
+            )));
+        }
+    }
+    let include_total = parse_bool_param(&q, "include_total")?.unwrap_or(false);
+    let filter = OsdrFilter::from_query(&q)?;
+
+    let sql = format!(
+        "SELECT id, dataset_id, title, status, updated_at, inserted_at, missing_since,
+                organism, assay_type, mission, raw
+         FROM osdr_items
+         WHERE ($2::text[] IS NULL OR status = ANY($2))
+           AND ($3::timestamptz IS NULL OR updated_at >= $3)
+           AND ($4::timestamptz IS NULL OR updated_at <= $4)
+           AND ($5 OR missing_since IS NULL)
+           AND ($6::bigint IS NULL OR {after})
+           AND ($8::text[] IS NULL OR lower(organism) = ANY($8))
+           AND ($9::text[] IS NULL OR lower(mission) = ANY($9))
+         ORDER BY {order_by}
+         LIMIT $1",
+        after = sort.after_predicate(7, 6),
+        order_by = sort.order_by(),
+    );
+    let items = sqlx::query_as::<_, OsdrItemRow>(&sql)
+        .bind(limit)
+        .bind(&filter.statuses)
+        .bind(filter.updated_after)
+        .bind(filter.updated_before)
+        .bind(filter.include_missing)
+        .bind(cursor.as_ref().map(|c| c.id))
+        .bind(cursor.and_then(|c| c.value))
+        .bind(&filter.organisms)
+        .bind(&filter.missions)
+        .fetch_all(&st.pool)
+        .await?;
+
+    // total учитывает фильтры, но не курсор
+    let total = if include_total {
+        Some(
+            sqlx::query_scalar(
+                "SELECT count(*) FROM osdr_items
+                 WHERE ($1::text[] IS NULL OR status = ANY($1))
+                   AND ($2::timestamptz IS NULL OR updated_at >= $2)
+                   AND ($3::timestamptz IS NULL OR updated_at <= $3)
+                   AND ($4 OR missing_since IS NULL)
+                   AND ($5::text[] IS NULL OR lower(organism) = ANY($5))
+                   AND ($6::text[] IS NULL OR lower(mission) = ANY($6))"
+            )
+            .bind(&filter.statuses)
+            .bind(filter.updated_after)
+            .bind(filter.updated_before)
+            .bind(filter.include_missing)
+            .bind(&filter.organisms)
+            .bind(&filter.missions)
+            .fetch_one(&st.pool)
+            .await?,
+        )
+    } else {
+        None
+    };
+    let next_cursor = if items.len() as i64 == limit {
+        items.last().map(|row| {
+            ListCursor {
+                sort,
+                value: sort.value_of(row),
+                id: row.id,
+            }
+            .encode()
+        })
+    } else {
+        None
+    };
+
+    ok(OsdrList {
+        count: items.len(),
+        limit,
+        total,
+        sort: sort.as_param(),
+        next_cursor,
+        items,
+    })
+}
+
+#[derive(Serialize)]
+struct OsdrItem {
+    #[serde(flatten)]
+    row: OsdrItemRow,
+    /// Сколько секунд прошло с первой записи строки
+    inserted_age_sec: f64,
+}
+
+impl From<OsdrItemRow> for OsdrItem {
+    fn from(row: OsdrItemRow) -> Self {
+        Self {
+            inserted_age_sec: seconds_between(row.inserted_at, Utc::now()),
+            row,
+        }
+    }
+}
+
+async fn osdr_item(
+    Path(dataset_id): Path<String>,
+    State(st): State<AppState>,
+) -> ApiResult<OsdrItem> {
+    let dataset_id = parse_dataset_id(&dataset_id)?;
+    let row = sqlx::query_as::<_, OsdrItemRow>(
+        "SELECT id, dataset_id, title, status, updated_at, inserted_at, missing_since,
+                organism, assay_type, mission, raw
+         FROM osdr_items WHERE dataset_id = $1"
+    )
+    .bind(&dataset_id)
+    .fetch_optional(&st.pool)
+    .await?
+    .ok_or_else(|| ApiError::not_found(format!("OSDR dataset {} not found", dataset_id)))?;
+    ok(row.into())
+}
+
+/// dataset_id из пути в том же виде, в каком он хранится
+fn parse_dataset_id(raw: &str) -> Result<String, ApiError> {
+    osdr::normalize_dataset_id(raw).ok_or_else(|| ApiError::validation("dataset_id must not be empty"))
+}
+
+/// По первичному ключу — для строк без dataset_id
+async fn osdr_item_by_id(
+    Path(id): Path<String>,
+    State(st): State<AppState>,
+) -> ApiResult<OsdrItem> {
+    let id: i64 = id
+        .parse()
+        .map_err(|_| ApiError::validation(format!("invalid id: {}", id)))?;
+    let row = sqlx::query_as::<_, OsdrItemRow>(
+        "SELECT id, dataset_id, title, status, updated_at, inserted_at, missing_since,
+                organism, assay_type, mission, raw
+         FROM osdr_items WHERE id = $1"
+    )
+    .bind(id)
+    .fetch_optional(&st.pool)
+    .await?
+    .ok_or_else(|| ApiError::not_found(format!("OSDR item {} not found", id)))?;
+    ok(row.into())
+}
+
+/// Колонки osdr_items, которые можно запросить в ?fields=, и что читать
+/// вместо незапрошенной: так тяжёлый raw не тянется из БД ради строк без него.
+/// NOT NULL колонки дешёвые и читаются всегда
+const OSDR_EXPORT_FIELDS: [(&str, &str); 11] = [
+    ("id", "id"),
+    ("dataset_id", "NULL::text"),
+    ("title", "NULL::text"),
+    ("status", "NULL::text"),
+    ("updated_at", "NULL::timestamptz"),
+    ("inserted_at", "inserted_at"),
+    ("missing_since", "NULL::timestamptz"),
+    ("organism", "NULL::text"),
+    ("assay_type", "NULL::text"),
+    ("mission", "NULL::text"),
+    ("raw", "'null'::jsonb"),
+];
+
+/// Список SELECT для выгрузки: все поля или только запрошенные
+fn osdr_export_columns(fields: Option<&[String]>) -> String {
+    OSDR_EXPORT_FIELDS
+        .iter()
+        .map(|(name, placeholder)| match fields {
+            Some(fields) if !fields.iter().any(|f| f == name) => format!("{} AS {}", placeholder, name),
+            _ => name.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Все строки osdr_items построчно в NDJSON прямо из курсора; каждая строка
+/// уходит отдельным чанком, так что `curl | jq` видит данные сразу
+async fn osdr_export_ndjson(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> Result<Response, ApiError> {
+    let filter = OsdrFilter::from_query(&q)?;
+    let fields = match parse_list_param(&q, "fields") {
+        Some(fields) => {
+            if let Some(bad) = fields.iter().find(|f| !OSDR_EXPORT_FIELDS.iter().any(|(name, _)| name == f)) {
+                let names: Vec<&str> = OSDR_EXPORT_FIELDS.iter().map(|(name, _)| *name).collect();
+                return Err(ApiError::validation(format!(
+                    "unknown field {:?}, expected any of {}",
+                    bad,
+                    names.join(",")
+                )));
+            }
+            Some(fields)
+        }
+        None => None,
+    };
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<String, ApiError>>(64);
+    let pool = st.pool.clone();
+    let sql = format!(
+        "SELECT {}
+         FROM osdr_items
+         WHERE ($1::text[] IS NULL OR status = ANY($1))
+           AND ($2::timestamptz IS NULL OR updated_at >= $2)
+           AND ($3::timestamptz IS NULL OR updated_at <= $3)
+           AND ($4 OR missing_since IS NULL)
+           AND ($5::text[] IS NULL OR lower(organism) = ANY($5))
+           AND ($6::text[] IS NULL OR lower(mission) = ANY($6))
+         ORDER BY id",
+        osdr_export_columns(fields.as_deref())
+    );
+
+    tokio::spawn(async move {
+        let mut rows = sqlx::query_as::<_, OsdrItemRow>(&sql)
+            .bind(&filter.statuses)
+            .bind(filter.updated_after)
+            .bind(filter.updated_before)
+            .bind(filter.include_missing)
+            .bind(&filter.organisms)
+            .bind(&filter.missions)
+            .fetch(&pool);
+
+        loop {
+            let line = match rows.try_next().await {
+                Ok(Some(row)) => Ok(osdr_ndjson_line(&row, fields.as_deref())),
+                Ok(None) => break,
+                Err(e) => Err(e.into()),
+            };
+            let failed = line.is_err();
+            // Клиент отключился — прекращаем чтение
+            if tx.send(line).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    let stream = futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|line| (line, rx))
+    });
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(stream),
+    )
+        .into_response())
+}
+
+fn osdr_ndjson_line(row: &OsdrItemRow, fields: Option<&[String]>) -> String {
+    let mut value = serde_json::json!(row);
+    if let (Some(fields), Some(obj)) = (fields, value.as_object_mut()) {
+        obj.retain(|k, _| fields.iter().any(|f| f == k));
+    }
+    format!("{}\n", value)
+}
+
+const OSDR_CHANGES_DEFAULT_LIMIT: i64 = 100;
+const OSDR_CHANGES_MAX_LIMIT: i64 = 1000;
+
+fn parse_changes_limit(q: &HashMap<String, String>) -> Result<i64, ApiError> {
+    let limit = parse_i64_param(q, "limit")?.unwrap_or(OSDR_CHANGES_DEFAULT_LIMIT);
+    if !(1..=OSDR_CHANGES_MAX_LIMIT).contains(&limit) {
+        return Err(ApiError::validation(format!(
+            "limit must be between 1 and {}",
+            OSDR_CHANGES_MAX_LIMIT
+        )));
+    }
+    Ok(limit)
+}
+
+/// История изменений одного датасета, новые первыми
+async fn osdr_item_changes(
+    Path(dataset_id): Path<String>,
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let dataset_id = parse_dataset_id(&dataset_id)?;
+    let limit = parse_changes_limit(&q)?;
+    let changes = sqlx::query_as::<_, OsdrChangeRow>(
+        "SELECT id, dataset_id, field, old_value, new_value, changed_at
+         FROM osdr_changes
+         WHERE dataset_id = $1
+         ORDER BY changed_at DESC, id DESC
+         LIMIT $2"
+    )
+    .bind(&dataset_id)
+    .bind(limit)
+    .fetch_all(&st.pool)
+    .await?;
+
+    ok(serde_json::json!({
+        "dataset_id": dataset_id,
+        "count": changes.len(),
+        "changes": changes
+    }))
+}
+
+/// Общая лента изменений начиная с since, в хронологическом порядке
+async fn osdr_changes(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let since = parse_time_param(&q, "since")?;
+    let limit = parse_changes_limit(&q)?;
+    let changes = sqlx::query_as::<_, OsdrChangeRow>(
+        "SELECT id, dataset_id, field, old_value, new_value, changed_at
+         FROM osdr_changes
+         WHERE ($1::timestamptz IS NULL OR changed_at >= $1)
+         ORDER BY changed_at, id
+         LIMIT $2"
+    )
+    .bind(since)
+    .bind(limit)
+    .fetch_all(&st.pool)
+    .await?;
+
+    ok(serde_json::json!({
+        "since": since,
+        "count": changes.len(),
+        "changes": changes
+    }))
+}
+
+/* ---------- NEO Handlers ---------- */
+
+const NEO_LIST_DEFAULT_LIMIT: i64 = 100;
+const NEO_LIST_MAX_LIMIT: i64 = 1000;
+
+/// Сближения из neo_objects по времени сближения; hazardous фильтрует
+/// по признаку потенциальной опасности
+async fn neo_list(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let from = parse_time_param(&q, "from")?;
+    let to = parse_time_param(&q, "to")?;
+    let hazardous = parse_bool_param(&q, "hazardous")?;
+    let limit = parse_i64_param(&q, "limit")?.unwrap_or(NEO_LIST_DEFAULT_LIMIT);
+
+    if !(1..=NEO_LIST_MAX_LIMIT).contains(&limit) {
+        return Err(ApiError::validation(format!(
+            "limit must be between 1 and {}",
+            NEO_LIST_MAX_LIMIT
+        )));
+    }
+    if let (Some(f), Some(t)) = (from, to) {
+        if f > t {
+            return Err(ApiError::validation("from must not be later than to"));
+        }
+    }
+
+    let items = sqlx::query_as::<_, NeoObjectRow>(
+        "SELECT neo_reference_id, name, est_diameter_min_m, est_diameter_max_m,
+                is_potentially_hazardous, close_approach_time, miss_distance_km,
+                relative_velocity_kms
+         FROM neo_objects
+         WHERE ($1::timestamptz IS NULL OR close_approach_time >= $1)
+           AND ($2::timestamptz IS NULL OR close_approach_time <= $2)
+           AND ($3::boolean IS NULL OR is_potentially_hazardous = $3)
+         ORDER BY close_approach_time, neo_reference_id
+         LIMIT $4"
+    )
+    .bind(from)
+    .bind(to)
+    .bind(hazardous)
+    .bind(limit)
+    .fetch_all(&st.pool)
+    .await?;
+
+    ok(serde_json::json!({ "count": items.len(), "items": items }))
+}
+
+/// По умолчанию — столько, сколько вперёд покрывает лента NeoWs
+const NEO_HAZARDOUS_DEFAULT_DAYS: i64 = neo::FEED_DAYS_AHEAD as i64;
+const NEO_HAZARDOUS_MAX_DAYS: i64 = 30;
+
+/// Предстоящие за `days` дней сближения потенциально опасных астероидов,
+/// ближайшие первыми; closest — самое близкое из них. Дальше окна ленты
+/// сближения известны только из обхода каталога, о чём говорит note
+async fn neo_hazardous(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let days = parse_i64_param(&q, "days")?.unwrap_or(NEO_HAZARDOUS_DEFAULT_DAYS);
+    if !(1..=NEO_HAZARDOUS_MAX_DAYS).contains(&days) {
+        return Err(ApiError::validation(format!(
+            "days must be between 1 and {}",
+            NEO_HAZARDOUS_MAX_DAYS
+        )));
+    }
+
+    let rows = sqlx::query_as::<_, NeoObjectRow>(
+        "SELECT neo_reference_id, name, est_diameter_min_m, est_diameter_max_m,
+                is_potentially_hazardous, close_approach_time, miss_distance_km,
+                relative_velocity_kms
+         FROM neo_objects
+         WHERE is_potentially_hazardous
+           AND close_approach_time >= now()
+           AND close_approach_time < now() + make_interval(days => $1::int)
+         ORDER BY miss_distance_km NULLS LAST, close_approach_time"
+    )
+    .bind(days)
+    .fetch_all(&st.pool)
+    .await?;
+
+    let items: Vec<Value> = rows
+        .iter()
+        .map(|r| {
+            serde_json::json!({
+                "neo_reference_id": r.neo_reference_id,
+                "name": r.name,
+                "diameter_m": { "min": r.est_diameter_min_m, "max": r.est_diameter_max_m },
+                "close_approach_time": r.close_approach_time,
+                "miss_distance_km": r.miss_distance_km,
+                "miss_distance_ld": r.miss_distance_km.map(neo::lunar_distances),
+                "relative_velocity_kms": r.relative_velocity_kms,
+            })
+        })
+        .collect();
+
+    let mut body = serde_json::json!({
+        "days": days,
+        "count": items.len(),
+        "closest": items.first(),
+        "items": items
+    });
+    if days > NEO_HAZARDOUS_DEFAULT_DAYS {
+        body["note"] = Value::String(format!(
+            "the NeoWs feed covers only {} days ahead; later approaches come from the NEO catalog browse, if enabled",
+            neo::FEED_DAYS_AHEAD
+        ));
+    }
+    ok(body)
+}
+
+const NEO_CLOSEST_DEFAULT_DAYS: i64 = 30;
+const NEO_CLOSEST_MAX_DAYS: i64 = 3650;
+const NEO_CLOSEST_TOP: i64 = 10;
+
+/// Самые близкие сближения в пределах within_days дней до и после текущего
+/// момента; closest — первое из top
+async fn neo_closest(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let days = parse_i64_param(&q, "within_days")?.unwrap_or(NEO_CLOSEST_DEFAULT_DAYS);
+    if !(1..=NEO_CLOSEST_MAX_DAYS).contains(&days) {
+        return Err(ApiError::validation(format!(
+            "within_days must be between 1 and {}",
+            NEO_CLOSEST_MAX_DAYS
+        )));
+    }
+
+    let top = sqlx::query_as::<_, NeoObjectRow>(
+        "SELECT neo_reference_id, name, est_diameter_min_m, est_diameter_max_m,
+                is_potentially_hazardous, close_approach_time, miss_distance_km,
+                relative_velocity_kms
+         FROM neo_objects
+         WHERE miss_distance_km IS NOT NULL
+           AND close_approach_time BETWEEN now() - make_interval(days => $1::int)
+                                       AND now() + make_interval(days => $1::int)
+         ORDER BY miss_distance_km, close_approach_time
+         LIMIT $2"
+    )
+    .bind(days)
+    .bind(NEO_CLOSEST_TOP)
+    .fetch_all(&st.pool)
+    .await?;
+
+    ok(serde_json::json!({
+        "within_days": days,
+        "closest": top.first(),
+        "top": top
+    }))
+}
+
+/// Все известные сближения объекта. Если локально их нет, объект один раз
+/// запрашивается у NASA (не чаще NEO_LOOKUPS_PER_HOUR в час), ответ
+/// сохраняется в space_cache как neo_lookup, а сближения — в neo_objects
+async fn neo_object(
+    Path(neo_reference_id): Path<String>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    // Идентификаторы NeoWs числовые; остальное к NASA не отправляем
+    if neo_reference_id.is_empty() || !neo_reference_id.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ApiError::validation("neo_reference_id must be numeric"));
+    }
+
+    let mut approaches = neo_approaches(&st.pool, &neo_reference_id).await?;
+    let mut looked_up = false;
+    if approaches.is_empty() {
+        let retry_after = st
+            .neo_lookups
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .try_acquire(std::time::Instant::now())
+            .err();
+        if let Some(wait) = retry_after {
+            return Err(ApiError::rate_limited(format!(
+                "NASA lookup limit reached, retry in {} s",
+                wait.as_secs().max(1)
+            )));
+        }
+        let object = fetch_neo_lookup(&st, &neo_reference_id).await?.ok_or_else(|| {
+            ApiError::not_found(format!("near-earth object {} not found", neo_reference_id))
+        })?;
+        let parsed = neo::approaches(&object).map_err(ApiError::invalid_payload)?;
+        upsert_neo_approaches(&st.pool, &parsed).await?;
+        approaches = neo_approaches(&st.pool, &neo_reference_id).await?;
+        looked_up = true;
+    }
+
+    ok(serde_json::json!({
+        "neo_reference_id": neo_reference_id,
+        "name": approaches.first().map(|a| a.name.clone()),
+        "looked_up": looked_up,
+        "count": approaches.len(),
+        "approaches": approaches
+    }))
+}
+
+async fn neo_approaches(pool: &PgPool, neo_reference_id: &str) -> Result<Vec<NeoObjectRow>, ApiError> {
+    let rows = sqlx::query_as::<_, NeoObjectRow>(
+        "SELECT neo_reference_id, name, est_diameter_min_m, est_diameter_max_m,
+                is_potentially_hazardous, close_approach_time, miss_distance_km,
+                relative_velocity_kms
+         FROM neo_objects
+         WHERE neo_reference_id = $1
+         ORDER BY close_approach_time"
+    )
+    .bind(neo_reference_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/* ---------- Webhook Handlers ---------- */
+const WEBHOOK_DELIVERIES_DEFAULT_LIMIT: i64 = 50;
+const WEBHOOK_DELIVERIES_MAX_LIMIT: i64 = 500;
+
+#[derive(Deserialize)]
+struct WebhookRequest {
+    url: String,
+    event: String,
+    title_filter: Option<String>,
+}
+
+async fn create_webhook(
+    State(st): State<AppState>,
+    body: Result<Json<WebhookRequest>, JsonRejection>,
+) -> ApiResult<WebhookRow> {
+    let Json(req) = body.map_err(|e| ApiError::validation(e.body_text()))?;
+    let url = reqwest::Url::parse(req.url.trim())
+        .map_err(|e| ApiError::validation(format!("invalid url: {}", e)))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(ApiError::validation("url must use http or https"));
+    }
+    webhooks::resolve_target(&url, webhooks::is_internal).await.map_err(ApiError::validation)?;
+    if !WEBHOOK_EVENTS.contains(&req.event.as_str()) {
+        return Err(ApiError::validation(format!(
+            "event must be one of: {}",
+            WEBHOOK_EVENTS.join(", ")
+        )));
+    }
+    let title_filter = req
+        .title_filter
+        .map(|f| f.trim().to_string())
+        .filter(|f| !f.is_empty());
+
+    let hook = sqlx::query_as::<_, WebhookRow>(
+        "INSERT INTO webhooks(url, event, title_filter)
+         VALUES ($1, $2, $3)
+         RETURNING id, url, event, title_filter, created_at"
+    )
+    .bind(url.as_str())
+    .bind(&req.event)
+    .bind(title_filter)
+    .fetch_one(&st.pool)
+    .await?;
+    ok(hook)
+}
+
+async fn list_webhooks(State(st): State<AppState>) -> ApiResult<Value> {
+    let hooks = sqlx::query_as::<_, WebhookRow>(
+        "SELECT id, url, event, title_filter, created_at FROM webhooks ORDER BY id"
+    )
+    .fetch_all(&st.pool)
+    .await?;
+    ok(serde_json::json!({ "count": hooks.len(), "webhooks": hooks }))
+}
+
+fn parse_webhook_id(id: &str) -> Result<i64, ApiError> {
+    id.parse()
+        .map_err(|_| ApiError::validation(format!("invalid webhook id: {}", id)))
+}
+
+/// Удаляет подписку вместе с журналом её доставок
+async fn delete_webhook(
+    Path(id): Path<String>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let id = parse_webhook_id(&id)?;
+    let deleted = sqlx::query("DELETE FROM webhooks WHERE id = $1")
+        .bind(id)
+        .execute(&st.pool)
+        .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(ApiError::not_found(format!("webhook {} not found", id)));
+    }
+    ok(serde_json::json!({ "deleted": id }))
+}
+
+/// Последние доставки подписки, новые первыми
+async fn webhook_deliveries(
+    Path(id): Path<String>,
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let id = parse_webhook_id(&id)?;
+    let limit = parse_i64_param(&q, "limit")?.unwrap_or(WEBHOOK_DELIVERIES_DEFAULT_LIMIT);
+    if !(1..=WEBHOOK_DELIVERIES_MAX_LIMIT).contains(&limit) {
+        return Err(ApiError::validation(format!(
+            "limit must be between 1 and {}",
+            WEBHOOK_DELIVERIES_MAX_LIMIT
+        )));
+    }
+
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM webhooks WHERE id = $1)")
+        .bind(id)
+        .fetch_one(&st.pool)
+        .await?;
+    if !exists {
+        return Err(ApiError::not_found(format!("webhook {} not found", id)));
+    }
+
+    let deliveries = sqlx::query_as::<_, WebhookDeliveryRow>(
+        "SELECT id, webhook_id, event, item_count, attempts, http_status, success, error, delivered_at
+         FROM webhook_deliveries
+         WHERE webhook_id = $1
+         ORDER BY id DESC
+         LIMIT $2"
+    )
+    .bind(id)
+    .bind(limit)
+    .fetch_all(&st.pool)
+    .await?;
+
+    ok(serde_json::json!({
+        "webhook_id": id,
+        "count": deliveries.len(),
+        "deliveries": deliveries
+    }))
+}
+
+/* ---------- Space Cache Handlers ---------- */
+use std::collections::HashMap;
+
+/// Последний снимок источника с распакованным payload
+async fn latest_snapshot(st: &AppState, source: &str) -> Result<Option<SpaceCacheRow>, ApiError> {
+    sqlx::query_as::<_, SpaceCacheRow>(
+        "SELECT fetched_at, COALESCE(last_checked_at, fetched_at) AS last_checked_at,
+                payload, payload_compressed
+         FROM space_cache
+         WHERE source = $1 ORDER BY id DESC LIMIT 1"
+    )
+    .bind(source)
+    .fetch_optional(&st.pool)
+    .await?
+    .map(read_payload)
+    .transpose()
+}
+
+/// Ответ эндпоинта, у источника которого ещё нет снимков
+fn no_data(source: &str) -> ApiResult<Value> {
+    ok(serde_json::json!({ "source": source, "message": "no data" }))
+}
+
+async fn space_latest(
+    Path(src): Path<String>,
+    State(st): State<AppState>,
+    headers: HeaderMap,
+) -> CachedResult<Value> {
+    let src = st.sources.get(&src)?.clone();
+    let max_age = st.config.cache_max_age_secs(src.interval(&st.config));
+    let row = sqlx::query_as::<_, SpaceCacheEntry>(
+        "SELECT id, fetched_at, COALESCE(last_checked_at, fetched_at) AS last_checked_at,
+                payload, payload_compressed
+         FROM space_cache
+         WHERE source = $1 ORDER BY id DESC LIMIT 1"
+    )
+    .bind(src.name())
+    .fetch_optional(&st.pool)
+    .await?
+    .map(read_payload)
+    .transpose()?;
+
+    // Снимок с тем же id меняется только вместе с last_checked_at
+    if let Some(r) = row {
+        let etag = format!("{}-{}-{}", src.name(), r.id, r.last_checked_at.timestamp_millis());
+        let data = serde_json::json!({
+            "source": src.name(),
+            "fetched_at": r.fetched_at,
+            "last_checked_at": r.last_checked_at,
+            "payload": r.payload
+        });
+        return ok_with_etag(data, &etag, max_age, &headers);
+    }
+
+    let data = serde_json::json!({
+        "source": src.name(),
+        "message": "no data"
+    });
+    ok_with_etag(data, &format!("{}-none", src.name()), max_age, &headers)
+}
+
+const SPACE_HISTORY_DEFAULT_LIMIT: i64 = 20;
+const SPACE_HISTORY_MAX_LIMIT: i64 = 100;
+
+/// Снимки источника, новые первыми; курсор before_id — id последней
+/// полученной записи, from/to ограничивают fetched_at
+async fn space_history(
+    Path(src): Path<String>,
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let src = st.sources.get(&src)?.clone();
+    let limit = parse_i64_param(&q, "limit")?.unwrap_or(SPACE_HISTORY_DEFAULT_LIMIT);
+    if !(1..=SPACE_HISTORY_MAX_LIMIT).contains(&limit) {
+        return Err(ApiError::validation(format!(
+            "limit must be between 1 and {}",
+            SPACE_HISTORY_MAX_LIMIT
+        )));
+    }
+    let before_id = parse_i64_param(&q, "before_id")?;
+    let from = parse_time_param(&q, "from")?;
+    let to = parse_time_param(&q, "to")?;
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return Err(ApiError::validation("from must not be later than to"));
+        }
+    }
+
+    let entries = sqlx::query_as::<_, SpaceCacheEntry>(
+        "SELECT id, fetched_at, COALESCE(last_checked_at, fetched_at) AS last_checked_at,
+                payload, payload_compressed
+         FROM space_cache
+         WHERE source = $1
+           AND ($2::bigint IS NULL OR id < $2)
+           AND ($3::timestamptz IS NULL OR fetched_at >= $3)
+           AND ($4::timestamptz IS NULL OR fetched_at <= $4)
+         ORDER BY id DESC
+         LIMIT $5"
+    )
+    .bind(src.name())
+    .bind(before_id)
+    .bind(from)
+    .bind(to)
+    .bind(limit)
+    .fetch_all(&st.pool)
+    .await?
+    .into_iter()
+    .map(read_payload)
+    .collect::<Result<Vec<_>, _>>()?;
+
+    let next_before_id = if entries.len() as i64 == limit {
+        entries.last().map(|e| e.id)
+    } else {
+        None
+    };
+    ok(serde_json::json!({
+        "source": src.name(),
+        "count": entries.len(),
+        "limit": limit,
+        "next_before_id": next_before_id,
+        "items": entries
+    }))
+}
+
+/// Отличия последнего снимка от предыдущего с другим содержимым;
+/// ?key= переопределяет ключ идентичности элементов массивов
+async fn space_diff(
+    Path(src): Path<String>,
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let src = st.sources.get(&src)?.clone();
+    let key = q
+        .get("key")
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .or_else(|| src.identity_key().map(str::to_string));
+
+    let latest = sqlx::query_as::<_, SpaceCacheEntry>(
+        "SELECT id, fetched_at, COALESCE(last_checked_at, fetched_at) AS last_checked_at,
+                payload, payload_compressed
+         FROM space_cache
+         WHERE source = $1 ORDER BY id DESC LIMIT 1"
+    )
+    .bind(src.name())
+    .fetch_optional(&st.pool)
+    .await?
+    .map(read_payload)
+    .transpose()?;
+
+    let previous = match &latest {
+        Some(latest) => sqlx::query_as::<_, SpaceCacheEntry>(
+            "SELECT id, fetched_at, COALESCE(last_checked_at, fetched_at) AS last_checked_at,
+                    payload, payload_compressed
+             FROM space_cache
+             WHERE source = $1 AND id < $2
+               AND payload_hash IS DISTINCT FROM (SELECT payload_hash FROM space_cache WHERE id = $2)
+             ORDER BY id DESC LIMIT 1"
+        )
+        .bind(src.name())
+        .bind(latest.id)
+        .fetch_optional(&st.pool)
+        .await?
+        .map(read_payload)
+        .transpose()?,
+        None => None,
+    };
+
+    let changes = match (&previous, &latest) {
+        (Some(old), Some(new)) => json_diff(&old.payload, &new.payload, key.as_deref()),
+        _ => Vec::new(),
+    };
+    let count = |kind: ChangeKind| changes.iter().filter(|c| c.kind == kind).count();
+    let snapshot = |e: &SpaceCacheEntry| serde_json::json!({ "id": e.id, "fetched_at": e.fetched_at });
+
+    ok(serde_json::json!({
+        "source": src.name(),
+        "key": key,
+        "from": previous.as_ref().map(snapshot),
+        "to": latest.as_ref().map(snapshot),
+        "added": count(ChangeKind::Added),
+        "removed": count(ChangeKind::Removed),
+        "changed": count(ChangeKind::Changed),
+        "changes": changes
+    }))
+}
+
+/// Снимок источника, действовавший в момент ?time= (последний с fetched_at <= time),
+/// и следующий за ним; оба запроса идут по индексу (source, fetched_at DESC)
+async fn space_at(
+    Path(src): Path<String>,
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let src = st.sources.get(&src)?.clone();
+    let time = parse_time_param(&q, "time")?
+        .ok_or_else(|| ApiError::validation("time is required"))?;
+
+    let at = sqlx::query_as::<_, SpaceCacheEntry>(
+        "SELECT id, fetched_at, COALESCE(last_checked_at, fetched_at) AS last_checked_at,
+                payload, payload_compressed
+         FROM space_cache
+         WHERE source = $1 AND fetched_at <= $2
+         ORDER BY fetched_at DESC LIMIT 1"
+    )
+    .bind(src.name())
+    .bind(time)
+    .fetch_optional(&st.pool)
+    .await?
+    .map(read_payload)
+    .transpose()?
+    .ok_or_else(|| {
+        ApiError::not_found(format!("no {} snapshot at or before {}", src.name(), time.to_rfc3339()))
+    })?;
+
+    let next = sqlx::query_as::<_, SpaceCacheEntry>(
+        "SELECT id, fetched_at, COALESCE(last_checked_at, fetched_at) AS last_checked_at,
+                payload, payload_compressed
+         FROM space_cache
+         WHERE source = $1 AND fetched_at > $2
+         ORDER BY fetched_at ASC LIMIT 1"
+    )
+    .bind(src.name())
+    .bind(time)
+    .fetch_optional(&st.pool)
+    .await?
+    .map(read_payload)
+    .transpose()?;
+
+    ok(serde_json::json!({
+        "source": src.name(),
+        "time": time,
+        "at": at,
+        "next": next
+    }))
+}
+
+const FETCH_METRICS_DEFAULT_HOURS: i64 = 24;
+const FETCH_METRICS_MAX_HOURS: i64 = 720;
+
+/// Перцентили задержки, доля ошибок по кодам, средний размер ответа и число
+/// повторов запросов источника за ?hours=
+async fn space_fetch_metrics(
+    Path(src): Path<String>,
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let src = st.sources.get(&src)?.clone();
+    let hours = parse_i64_param(&q, "hours")?.unwrap_or(FETCH_METRICS_DEFAULT_HOURS);
+    if !(1..=FETCH_METRICS_MAX_HOURS).contains(&hours) {
+        return Err(ApiError::validation(format!(
+            "hours must be between 1 and {}",
+            FETCH_METRICS_MAX_HOURS
+        )));
+    }
+
+    let metrics = sqlx::query_as::<_, FetchMetricsRow>(
+        "SELECT count(*) FILTER (WHERE ok) AS successes,
+                count(*) FILTER (WHERE NOT ok) AS errors,
+                percentile_cont(0.5) WITHIN GROUP (ORDER BY latency_ms) AS latency_p50_ms,
+                percentile_cont(0.9) WITHIN GROUP (ORDER BY latency_ms) AS latency_p90_ms,
+                percentile_cont(0.99) WITHIN GROUP (ORDER BY latency_ms) AS latency_p99_ms,
+                avg(body_bytes)::float8 AS avg_body_bytes,
+                avg(attempts)::float8 AS avg_attempts,
+                count(*) FILTER (WHERE attempts > 1) AS retried
+         FROM fetch_attempts
+         WHERE source = $1 AND occurred_at >= now() - make_interval(hours => $2)"
+    )
+    .bind(src.name())
+    .bind(hours as i32)
+    .fetch_one(&st.pool)
+    .await?;
+
+    let errors_by_code: std::collections::BTreeMap<String, i64> = sqlx::query_as::<_, (String, i64)>(
+        "SELECT COALESCE(code, 'UNKNOWN'), count(*)
+         FROM fetch_attempts
+         WHERE source = $1 AND NOT ok AND occurred_at >= now() - make_interval(hours => $2)
+         GROUP BY 1"
+    )
+    .bind(src.name())
+    .bind(hours as i32)
+    .fetch_all(&st.pool)
+    .await?
+    .into_iter()
+    .collect();
+
+    let total = metrics.successes + metrics.errors;
+    let error_rate = (total > 0).then(|| metrics.errors as f64 / total as f64);
+    ok(serde_json::json!({
+        "source": src.name(),
+        "hours": hours,
+        "error_rate": error_rate,
+        "errors_by_code": errors_by_code,
+        "metrics": metrics
+    }))
+}
+
+#[derive(Serialize)]
+struct SpaceSourceInfo {
+    source: String,
+    /// Зарегистрирован ли источник, то есть загружается ли он в фоне
+    fetchable: bool,
+    interval_sec: Option<u64>,
+    rows: i64,
+    latest_fetched_at: Option<DateTime<Utc>>,
+    last_checked_at: Option<DateTime<Utc>>,
+    /// Размер payload последнего снимка в хранилище, байт (после сжатия TOAST)
+    latest_payload_bytes: Option<i32>,
+    /// Сколько секунд назад менялось содержимое
+    age_sec: Option<f64>,
+    /// Сколько секунд назад источник опрашивали
+    checked_age_sec: Option<f64>,
+}
+
+impl SpaceSourceInfo {
+    fn new(source: String, row: Option<SpaceSourceRow>, interval: Option<Duration>) -> Self {
+        let now = Utc::now();
+        Self {
+            source,
+            fetchable: interval.is_some(),
+            interval_sec: interval.map(|i| i.as_secs()),
+            rows: row.as_ref().map_or(0, |r| r.rows),
+            latest_fetched_at: row.as_ref().map(|r| r.latest_fetched_at),
+            last_checked_at: row.as_ref().map(|r| r.last_checked_at),
+            latest_payload_bytes: row.as_ref().map(|r| r.latest_payload_bytes),
+            age_sec: row.as_ref().map(|r| seconds_between(r.latest_fetched_at, now)),
+            checked_age_sec: row.as_ref().map(|r| seconds_between(r.last_checked_at, now)),
+        }
+    }
+}
+
+/// Снимки space_cache в NDJSON по возрастанию id для архивации; строки
+/// читаются потоком, так что память не зависит от объёма выгрузки
+async fn space_export_ndjson(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> Result<Response, ApiError> {
+    // Имена не сверяются с реестром: в архив попадают и источники прежних версий
+    let sources: Option<Vec<String>> =
+        parse_list_param(&q, "src").map(|v| v.iter().map(|s| s.to_lowercase()).collect());
+    let from = parse_time_param(&q, "from")?;
+    let to = parse_time_param(&q, "to")?;
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return Err(ApiError::validation("from must not be later than to"));
+        }
+    }
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<String, ApiError>>(64);
+    let pool = st.pool.clone();
+
+    tokio::spawn(async move {
+        let mut rows = sqlx::query_as::<_, SpaceCacheDump>(
+            "SELECT id, source, fetched_at, payload, payload_compressed
+             FROM space_cache
+             WHERE ($1::text[] IS NULL OR source = ANY($1))
+               AND ($2::timestamptz IS NULL OR fetched_at >= $2)
+               AND ($3::timestamptz IS NULL OR fetched_at <= $3)
+             ORDER BY id"
+        )
+        .bind(&sources)
+        .bind(from)
+        .bind(to)
+        .fetch(&pool);
+
+        loop {
+            let line = match rows.try_next().await {
+                Ok(Some(row)) => read_payload(row).map(|row| format!("{}\n", serde_json::json!(row))),
+                Ok(None) => break,
+                Err(e) => Err(e.into()),
+            };
+            let failed = line.is_err();
+            // Клиент отключился — прекращаем чтение
+            if tx.send(line).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    // Сжатие опрашивает тело и после его конца
+    let stream = futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|line| (line, rx))
+    })
+    .fuse();
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(stream),
+    )
+        .into_response())
+}
+
+/// Строк на один INSERT при импорте space_cache
+const SPACE_IMPORT_BATCH_SIZE: usize = 500;
+
+/// Загрузка выгрузки /space/export.ndjson. Строки с уже существующим id
+/// пропускаются; ошибка в любой строке откатывает весь импорт
+async fn space_import_ndjson(State(st): State<AppState>, body: Body) -> ApiResult<Value> {
+    let mut chunks = body.into_data_stream();
+    let mut buf: Vec<u8> = Vec::new();
+    let mut batch: Vec<SpaceCacheDump> = Vec::with_capacity(SPACE_IMPORT_BATCH_SIZE);
+    let (mut line_no, mut rows, mut inserted) = (0usize, 0u64, 0u64);
+    let mut tx = st.pool.begin().await?;
+
+    loop {
+        let chunk = chunks
+            .try_next()
+            .await
+            .map_err(|e| ApiError::validation(format!("failed to read body: {}", e)))?;
+        let done = chunk.is_none();
+        if let Some(chunk) = chunk {
+            buf.extend_from_slice(&chunk);
+        }
+        // Последняя строка может быть без перевода строки в конце
+        let mut lines: Vec<Vec<u8>> = Vec::new();
+        while let Some(pos) = buf.iter().position(|&b| b == b'\n') {
+            lines.push(buf.drain(..=pos).collect());
+        }
+        if done && !buf.is_empty() {
+            lines.push(std::mem::take(&mut buf));
+        }
+
+        for line in lines {
+            line_no += 1;
+            if line.trim_ascii().is_empty() {
+                continue;
+            }
+            let mut row: SpaceCacheDump = serde_json::from_slice(&line)
+                .map_err(|e| ApiError::validation(format!("line {}: {}", line_no, e)))?;
+            row.source = row.source.trim().to_lowercase();
+            if row.source.is_empty() {
+                return Err(ApiError::validation(format!("line {}: source must not be empty", line_no)));
+            }
+            batch.push(row);
+            rows += 1;
+            if batch.len() >= SPACE_IMPORT_BATCH_SIZE {
+                inserted += insert_space_dump(&mut tx, &st.config, &mut batch).await?;
+            }
+        }
+        if done {
+            break;
+        }
+    }
+    inserted += insert_space_dump(&mut tx, &st.config, &mut batch).await?;
+
+    // Явные id не двигают последовательность; следующий снимок не должен с ними столкнуться
+    sqlx::query(
+        "SELECT setval(pg_get_serial_sequence('space_cache', 'id'),
+                       GREATEST((SELECT max(id) FROM space_cache), 1))"
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    info!("space_cache import: {} rows read, {} inserted", rows, inserted);
+    ok(serde_json::json!({
+        "rows": rows,
+        "inserted": inserted,
+        "skipped": rows - inserted
+    }))
+}
+
+async fn insert_space_dump(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    cfg: &Config,
+    batch: &mut Vec<SpaceCacheDump>,
+) -> Result<u64, ApiError> {
+    if batch.is_empty() {
+        return Ok(0);
+    }
+    let mut ids = Vec::with_capacity(batch.len());
+    let mut sources = Vec::with_capacity(batch.len());
+    let mut fetched = Vec::with_capacity(batch.len());
+    let mut hashes = Vec::with_capacity(batch.len());
+    let mut payloads = Vec::with_capacity(batch.len());
+    let mut compressed = Vec::with_capacity(batch.len());
+    for row in batch.drain(..) {
+        ids.push(row.id);
+        sources.push(row.source);
+        fetched.push(row.fetched_at);
+        hashes.push(content_hash(&row.payload));
+        let packed = pack(row.payload, cfg.cache_compression, cfg.cache_compress_min_bytes);
+        payloads.push(packed.payload);
+        compressed.push(packed.compressed);
+    }
+    let done = sqlx::query(
+        "INSERT INTO space_cache(id, source, fetched_at, last_checked_at,
+                                 payload, payload_compressed, payload_hash)
+         SELECT id, source, fetched_at, fetched_at, payload, payload_compressed, payload_hash
+         FROM UNNEST($1::bigint[], $2::text[], $3::timestamptz[], $4::jsonb[], $5::bytea[], $6::text[])
+              AS u(id, source, fetched_at, payload, payload_compressed, payload_hash)
+         ON CONFLICT (id) DO NOTHING"
+    )
+    .bind(&ids)
+    .bind(&sources)
+    .bind(&fetched)
+    .bind(&payloads)
+    .bind(&compressed)
+    .bind(&hashes)
+    .execute(&mut **tx)
+    .await?;
+    Ok(done.rows_affected())
+}
+
+/// Зарегистрированные источники в порядке реестра, затем оставшиеся в
+/// space_cache от прежних версий; статистика — одним сгруппированным запросом
+async fn space_sources(State(st): State<AppState>) -> ApiResult<Value> {
+    let rows = sqlx::query_as::<_, SpaceSourceRow>(
+        "SELECT source,
+                count(*) AS rows,
+                max(fetched_at) AS latest_fetched_at,
+                max(COALESCE(last_checked_at, fetched_at)) AS last_checked_at,
+                (array_agg(COALESCE(octet_length(payload_compressed), pg_column_size(payload))
+                           ORDER BY id DESC))[1] AS latest_payload_bytes
+         FROM space_cache
+         GROUP BY source
+         ORDER BY source"
+    )
+    .fetch_all(&st.pool)
+    .await?;
+
+    let mut rows: HashMap<String, SpaceSourceRow> =
+        rows.into_iter().map(|r| (r.source.clone(), r)).collect();
+    let mut sources: Vec<SpaceSourceInfo> = st
+        .sources
+        .iter()
+        .map(|src| {
+            let row = rows.remove(src.name());
+            SpaceSourceInfo::new(src.name().to_string(), row, Some(src.interval(&st.config)))
+        })
+        .collect();
+    let mut legacy: Vec<SpaceSourceRow> = rows.into_values().collect();
+    legacy.sort_by(|a, b| a.source.cmp(&b.source));
+    sources.extend(legacy.into_iter().map(|r| SpaceSourceInfo::new(r.source.clone(), Some(r), None)));
+
+    ok(serde_json::json!({ "count": sources.len(), "sources": sources }))
+}
+
+const APOD_GALLERY_DEFAULT_LIMIT: i64 = 30;
+const APOD_GALLERY_MAX_LIMIT: i64 = 200;
+
+/// Сохранённые дни APOD, новые первыми; q ищет подстроку в title и
+/// explanation. random=true вместо страницы отдаёт один случайный день
+async fn apod_gallery(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let today = Utc::now().date_naive();
+    let date_param = |key: &str| -> Result<Option<chrono::NaiveDate>, ApiError> {
+        match q.get(key).map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            Some(s) => apod::parse_date(s, today)
+                .map(Some)
+                .map_err(|e| ApiError::validation(format!("{}: {}", key, e))),
+            None => Ok(None),
+        }
+    };
+    let from = date_param("from")?;
+    let to = date_param("to")?;
+    if let (Some(f), Some(t)) = (from, to) {
+        if f > t {
+            return Err(ApiError::validation("from must not be later than to"));
+        }
+    }
+    let media_type = q.get("media_type").map(|s| s.trim()).filter(|s| !s.is_empty());
+    let search = q
+        .get("q")
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(apod::like_pattern);
+    let random = parse_bool_param(&q, "random")?.unwrap_or(false);
+    let limit = parse_i64_param(&q, "limit")?.unwrap_or(APOD_GALLERY_DEFAULT_LIMIT);
+    let offset = parse_i64_param(&q, "offset")?.unwrap_or(0);
+    if !(1..=APOD_GALLERY_MAX_LIMIT).contains(&limit) {
+        return Err(ApiError::validation(format!(
+            "limit must be between 1 and {}",
+            APOD_GALLERY_MAX_LIMIT
+        )));
+    }
+    if offset < 0 {
+        return Err(ApiError::validation("offset must not be negative"));
+    }
+
+    let filters = "WHERE ($1::date IS NULL OR e.date >= $1)
+           AND ($2::date IS NULL OR e.date <= $2)
+           AND ($3::text IS NULL OR e.media_type = $3)
+           AND ($4::text IS NULL OR e.title ILIKE $4 OR e.explanation ILIKE $4)";
+    let order = if random { "ORDER BY random() LIMIT 1" } else { "ORDER BY e.date DESC LIMIT $5 OFFSET $6" };
+    let sql = format!(
+        "SELECT e.date, e.title, e.media_type, e.url, e.thumbnail_url,
+                EXISTS (SELECT 1 FROM apod_media m WHERE m.date = e.date) AS image_cached
+         FROM apod_entries e
+         {}
+         {}",
+        filters, order
+    );
+    let mut query = sqlx::query_as::<_, ApodGalleryRow>(&sql)
+        .bind(from)
+        .bind(to)
+        .bind(media_type)
+        .bind(&search);
+    if !random {
+        query = query.bind(limit).bind(offset);
+    }
+    let rows = query.fetch_all(&st.pool).await?;
+    let items: Vec<Value> = rows
+        .into_iter()
+        .map(|row| {
+            let proxy_url = row
+                .image_cached
+                .then(|| format!("/space/apod/image?date={}", row.date));
+            let mut item = serde_json::json!(row);
+            item["proxy_url"] = serde_json::json!(proxy_url);
+            item
+        })
+        .collect();
+
+    if random {
+        return ok(serde_json::json!({ "item": items.into_iter().next() }));
+    }
+    let total: i64 = sqlx::query_scalar(&format!("SELECT count(*) FROM apod_entries e {}", filters))
+        .bind(from)
+        .bind(to)
+        .bind(media_type)
+        .bind(&search)
+        .fetch_one(&st.pool)
+        .await?;
+    ok(serde_json::json!({
+        "total": total,
+        "count": items.len(),
+        "limit": limit,
+        "offset": offset,
+        "items": items
+    }))
+}
+
+/// Наибольший диапазон одного /space/apod/backfill, дней
+const APOD_BACKFILL_MAX_DAYS: i64 = 366;
+
+#[derive(Deserialize)]
+struct ApodBackfillRequest {
+    start_date: String,
+    end_date: String,
+}
+
+/// Загружает дни APOD за диапазон в apod_entries запросами не длиннее
+/// APOD_CHUNK_DAYS дней; неудачный отрезок не мешает остальным
+async fn apod_backfill(
+    State(st): State<AppState>,
+    body: Result<Json<ApodBackfillRequest>, JsonRejection>,
+) -> ApiResult<Value> {
+    let Json(req) = body.map_err(|e| ApiError::validation(e.body_text()))?;
+    let today = Utc::now().date_naive();
+    let start = apod::parse_date(&req.start_date, today).map_err(|e| ApiError::validation(format!("start_date: {}", e)))?;
+    let end = apod::parse_date(&req.end_date, today).map_err(|e| ApiError::validation(format!("end_date: {}", e)))?;
+    if start > end {
+        return Err(ApiError::validation("start_date must not be later than end_date"));
+    }
+    let days = (end - start).num_days() + 1;
+    if days > APOD_BACKFILL_MAX_DAYS {
+        return Err(ApiError::validation(format!(
+            "at most {} days per backfill, got {}",
+            APOD_BACKFILL_MAX_DAYS, days
+        )));
+    }
+
+    let chunks = apod::chunks(start, end, APOD_CHUNK_DAYS);
+    let mut stored = 0;
+    let mut errors = Vec::new();
+    for (from, to) in &chunks {
+        let query = vec![
+            ("start_date", from.to_string()),
+            ("end_date", to.to_string()),
+            ("thumbs", "true".to_string()),
+        ];
+        match fetch_apod_entries(&st, query).await {
+            Ok(entries) => stored += entries.len(),
+            Err(e) => errors.push(format!("{}..{}: {}", from, to, e.error.message)),
+        }
+    }
+
+    ok(serde_json::json!({
+        "start_date": start,
+        "end_date": end,
+        "days": days,
+        "chunks": chunks.len(),
+        "stored": stored,
+        "errors": errors
+    }))
+}
+
+/// Один день архива APOD, дата в формате YYYY-MM-DD
+async fn apod_day(Path(date): Path<String>, State(st): State<AppState>) -> ApiResult<ApodEntryRow> {
+    let date = apod::parse_date(&date, Utc::now().date_naive()).map_err(ApiError::validation)?;
+    ok(load_apod_entry(&st, date).await?)
+}
+
+/// День APOD из apod_entries; если его там нет, он загружается у NASA
+async fn load_apod_entry(st: &AppState, date: chrono::NaiveDate) -> Result<ApodEntryRow, ApiError> {
+    if let Some(row) = apod_entry(&st.pool, date).await? {
+        return Ok(row);
+    }
+    let query = vec![("date", date.to_string()), ("thumbs", "true".to_string())];
+    fetch_apod_entries(st, query).await?;
+    apod_entry(&st.pool, date)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("APOD has no entry for {}", date)))
+}
+
+/// Картинка дня APOD. При первом запросе скачивается и сохраняется в
+/// apod_media, дальше отдаётся оттуда: содержимое за дату не меняется
+async fn apod_image(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> Result<Response, ApiError> {
+    let date = q
+        .get("date")
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| ApiError::validation("date is required"))?;
+    let date = apod::parse_date(date, Utc::now().date_naive()).map_err(ApiError::validation)?;
+    let size = MediaSize::parse(q.get("size").map(String::as_str).unwrap_or_default())
+        .map_err(ApiError::validation)?;
+
+    let stored = sqlx::query_as::<_, ApodMediaRow>(
+        "SELECT content_type, bytes FROM apod_media WHERE date = $1 AND size = $2"
+    )
+    .bind(date)
+    .bind(size.as_str())
+    .fetch_optional(&st.pool)
+    .await?;
+    let media = match stored {
+        Some(media) => media,
+        None => {
+            let entry = load_apod_entry(&st, date).await?;
+            let started = std::time::Instant::now();
+            let attempts = http::Attempts::default();
+            let fetched = async {
+                // NASA ответила, но картинки у этого дня нет — отдавать нечего
+                let url = apod::media_url(&entry, size).map_err(|reason| ApiError::upstream(404, reason))?;
+                Ok::<_, ApiError>((url, download_apod_media(&st, url, &attempts).await?))
+            }
+            .await;
+            let (url, media) = match fetched {
+                Ok(fetched) => fetched,
+                Err(e) => {
+                    let latency = elapsed_ms(started);
+                    record_fetch_error(&st.pool, "apod_media", e.upstream_status(), &e, latency, attempts.get(), attempts.request_id())
+                        .await;
+                    return Err(e);
+                }
+            };
+            sqlx::query(
+                "INSERT INTO apod_media(date, size, source_url, content_type, byte_size, bytes)
+                 VALUES ($1, $2, $3, $4, $5, $6)
+                 ON CONFLICT (date, size) DO NOTHING"
+            )
+            .bind(date)
+            .bind(size.as_str())
+            .bind(url)
+            .bind(&media.content_type)
+            .bind(media.bytes.len() as i64)
+            .bind(&media.bytes)
+            .execute(&st.pool)
+            .await?;
+            media
+        }
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, media.content_type),
+            (header::CACHE_CONTROL, "public, max-age=31536000, immutable".to_string()),
+        ],
+        media.bytes,
+    )
+        .into_response())
+}
+
+async fn apod_entry(pool: &PgPool, date: chrono::NaiveDate) -> Result<Option<ApodEntryRow>, ApiError> {
+    let row = sqlx::query_as::<_, ApodEntryRow>(
+        "SELECT date, title, explanation, media_type, url, hdurl, thumbnail_url, copyright, fetched_at
+         FROM apod_entries WHERE date = $1"
+    )
+    .bind(date)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
+const SPACEX_UPCOMING_DEFAULT_LIMIT: i64 = 5;
+const SPACEX_UPCOMING_MAX_LIMIT: i64 = 50;
+
+/// Ближайшие запуски SpaceX из последнего снимка spacex_upcoming
+async fn spacex_upcoming(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let limit = parse_i64_param(&q, "limit")?.unwrap_or(SPACEX_UPCOMING_DEFAULT_LIMIT);
+    if !(1..=SPACEX_UPCOMING_MAX_LIMIT).contains(&limit) {
+        return Err(ApiError::validation(format!(
+            "limit must be between 1 and {}",
+            SPACEX_UPCOMING_MAX_LIMIT
+        )));
+    }
+
+    let Some((fetched_at, mut launches)) = load_upcoming_launches(&st).await? else {
+        return no_data("spacex_upcoming");
+    };
+    let total = launches.len();
+    launches.truncate(limit as usize);
+    ok(serde_json::json!({
+        "source": "spacex_upcoming",
+        "fetched_at": fetched_at,
+        "total": total,
+        "count": launches.len(),
+        "launches": launches
+    }))
+}
+
+/// Ближайшие запуски SpaceX в формате iCalendar для подписки из календаря.
+/// Без снимка spacex_upcoming календарь пустой
+async fn spacex_launches_ics(State(st): State<AppState>) -> Result<Response, ApiError> {
+    let launches = load_upcoming_launches(&st).await?.map(|(_, l)| l).unwrap_or_default();
+    Ok((
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        ics::launches_calendar(&launches, Utc::now()),
+    )
+        .into_response())
+}
+
+/// Ещё не прошедшие запуски из последнего снимка spacex_upcoming
+/// и время этого снимка
+async fn load_upcoming_launches(
+    st: &AppState,
+) -> Result<Option<(DateTime<Utc>, Vec<spacex::UpcomingLaunch>)>, ApiError> {
+    let Some(row) = latest_snapshot(st, "spacex_upcoming").await? else {
+        return Ok(None);
+    };
+    let launches = spacex::upcoming(&row.payload, Utc::now()).map_err(ApiError::invalid_payload)?;
+    Ok(Some((row.fetched_at, launches)))
+}
+
+const LAUNCHES_DEFAULT_LIMIT: i64 = 20;
+const LAUNCHES_MAX_LIMIT: i64 = 100;
+
+/// Предстоящие запуски всех провайдеров: снимок Launch Library 2,
+/// дополненный запусками SpaceX, которых в нём нет
+async fn launches_upcoming(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let limit = parse_i64_param(&q, "limit")?.unwrap_or(LAUNCHES_DEFAULT_LIMIT);
+    if !(1..=LAUNCHES_MAX_LIMIT).contains(&limit) {
+        return Err(ApiError::validation(format!(
+            "limit must be between 1 and {}",
+            LAUNCHES_MAX_LIMIT
+        )));
+    }
+    let provider = q.get("provider").map(|p| p.trim()).filter(|p| !p.is_empty());
+
+    let ll2_row = latest_snapshot(&st, "launches_ll2").await?;
+    let now = Utc::now();
+    let ll2 = match &ll2_row {
+        Some(row) => launches::ll2_launches(&row.payload).map_err(ApiError::invalid_payload)?,
+        None => Vec::new(),
+    };
+    let spacex = load_upcoming_launches(&st).await?;
+
+    let mut items: Vec<launches::Launch> =
+        launches::merge(ll2, spacex.as_ref().map(|(_, l)| l.as_slice()).unwrap_or_default())
+            .into_iter()
+            .filter(|l| l.net >= now - chrono::Duration::hours(1))
+            .filter(|l| provider.is_none_or(|p| launches::matches_provider(l, p)))
+            .collect();
+    let total = items.len();
+    items.truncate(limit as usize);
+    ok(serde_json::json!({
+        "fetched_at": {
+            "launches_ll2": ll2_row.map(|r| r.fetched_at),
+            "spacex_upcoming": spacex.map(|(at, _)| at)
+        },
+        "total": total,
+        "count": items.len(),
+        "launches": items
+    }))
+}
+
+/// Насколько дата запуска может отстать от текущего момента, прежде чем
+/// /spacex/countdown обновит снимок сам
+const SPACEX_COUNTDOWN_REFRESH_AFTER_SECS: i64 = 3600;
+
+/// Обратный отсчёт до ближайшего запуска из снимка spacex, а если его нет —
+/// из spacex_upcoming. Запуск, прошедший больше часа назад, означает устаревший
+/// снимок: источник обновляется сразу (под его блокировкой), а если и после
+/// этого запуск в прошлом, берётся ближайший из spacex_upcoming
+async fn spacex_countdown(State(st): State<AppState>) -> ApiResult<Value> {
+    let overdue = |next: &Option<(&str, spacex::UpcomingLaunch, SpaceCacheRow)>| {
+        next.as_ref().is_some_and(|(_, launch, _)| {
+            (Utc::now() - launch.date_utc).num_seconds() > SPACEX_COUNTDOWN_REFRESH_AFTER_SECS
+        })
+    };
+    let mut next = cached_next_launch(&st, &["spacex", "spacex_upcoming"]).await?;
+    let mut refreshed = false;
+    if let (true, Some((source, _, _))) = (overdue(&next), &next) {
+        let src = st.sources.get(source)?.clone();
+        match fetch_source_shared(&st, &src).await.0 {
+            Ok(FetchRun::Done) => {
+                refreshed = true;
+                next = cached_next_launch(&st, &[src.name(), "spacex_upcoming"]).await?;
+            }
+            Ok(FetchRun::InProgress) => {}
+            Err(e) => warn!("{} refresh for countdown failed: {}", source, e.error.message),
+        }
+    }
+    if overdue(&next) {
+        if let Some(upcoming) = cached_next_launch(&st, &["spacex_upcoming"]).await? {
+            next = Some(upcoming);
+        }
+    }
+
+    let Some((source, launch, row)) = next else {
+        return no_data("spacex");
+    };
+    let now = Utc::now();
+    let seconds_remaining = (launch.date_utc - now).num_seconds();
+    let src = st.sources.get(source)?;
+    let age = seconds_between(row.last_checked_at, now);
+    ok(serde_json::json!({
+        "source": source,
+        "name": launch.name,
+        "date_utc": launch.date_utc,
+        "date_precision": launch.date_precision,
+        "approximate": launch.approximate,
+        "seconds_remaining": seconds_remaining,
+        "countdown": spacex::countdown(seconds_remaining),
+        "fetched_at": row.fetched_at,
+        "stale": st.config.is_stale(src.interval(&st.config), Some(age)),
+        "refreshed": refreshed
+    }))
+}
+
+/// Ближайший запуск из первого по порядку источника, где он есть, вместе с
+/// именем источника и его снимком
+async fn cached_next_launch(
+    st: &AppState,
+    sources: &[&'static str],
+) -> Result<Option<(&'static str, spacex::UpcomingLaunch, SpaceCacheRow)>, ApiError> {
+    for &source in sources {
+        let Some(row) = latest_snapshot(st, source).await? else { continue };
+        let launch = match source {
+            "spacex" => spacex::launch(&row.payload),
+            _ => spacex::upcoming(&row.payload, Utc::now()).ok().and_then(|l| l.into_iter().next()),
+        };
+        if let Some(launch) = launch {
+            return Ok(Some((source, launch, row)));
+        }
+    }
+    Ok(None)
+}
+
+const SPACEX_LAUNCHES_DEFAULT_LIMIT: i64 = 50;
+const SPACEX_LAUNCHES_MAX_LIMIT: i64 = 500;
+/// Год первого запуска SpaceX
+const SPACEX_FIRST_YEAR: i64 = 2006;
+
+/// Прошедшие запуски из spacex_launches, новые первыми
+async fn spacex_launches(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let year = parse_i64_param(&q, "year")?;
+    let success = parse_bool_param(&q, "success")?;
+    let limit = parse_i64_param(&q, "limit")?.unwrap_or(SPACEX_LAUNCHES_DEFAULT_LIMIT);
+    if !(1..=SPACEX_LAUNCHES_MAX_LIMIT).contains(&limit) {
+        return Err(ApiError::validation(format!(
+            "limit must be between 1 and {}",
+            SPACEX_LAUNCHES_MAX_LIMIT
+        )));
+    }
+    let this_year = i64::from(Utc::now().year());
+    if year.is_some_and(|y| !(SPACEX_FIRST_YEAR..=this_year).contains(&y)) {
+        return Err(ApiError::validation(format!(
+            "year must be between {} and {}",
+            SPACEX_FIRST_YEAR, this_year
+        )));
+    }
+
+    let items = sqlx::query_as::<_, SpacexLaunchRow>(
+        "SELECT id, name, date_utc, success, rocket, launchpad, details
+         FROM spacex_launches
+         WHERE ($1::int IS NULL OR date_utc >= make_timestamptz($1::int, 1, 1, 0, 0, 0, 'UTC')
+                               AND date_utc < make_timestamptz($1::int + 1, 1, 1, 0, 0, 0, 'UTC'))
+           AND ($2::boolean IS NULL OR success = $2)
+         ORDER BY date_utc DESC
+         LIMIT $3"
+    )
+    .bind(year.map(|y| y as i32))
+    .bind(success)
+    .bind(limit)
+    .fetch_all(&st.pool)
+    .await?;
+
+    ok(serde_json::json!({ "count": items.len(), "items": items }))
+}
+
+/// Запуски и доля успешных по годам и за всё время
+async fn spacex_stats(State(st): State<AppState>) -> ApiResult<Value> {
+    let years = sqlx::query_as::<_, SpacexYearStatsRow>(
+        "SELECT extract(year FROM date_utc AT TIME ZONE 'UTC')::int AS year,
+                count(*) AS launches,
+                count(*) FILTER (WHERE success) AS successes,
+                count(*) FILTER (WHERE NOT success) AS failures,
+                round(100.0 * count(*) FILTER (WHERE success)
+                      / NULLIF(count(success), 0), 1)::float8 AS success_rate
+         FROM spacex_launches
+         GROUP BY 1
+         ORDER BY 1"
+    )
+    .fetch_all(&st.pool)
+    .await?;
+
+    let total = sqlx::query_as::<_, (i64, i64, Option<f64>)>(
+        "SELECT count(*),
+                count(*) FILTER (WHERE success),
+                round(100.0 * count(*) FILTER (WHERE success) / NULLIF(count(success), 0), 1)::float8
+         FROM spacex_launches"
+    )
+    .fetch_one(&st.pool)
+    .await?;
+
+    ok(serde_json::json!({
+        "launches": total.0,
+        "successes": total.1,
+        "success_rate": total.2,
+        "years": years
+    }))
+}
+
+/// Численность экипажей по кораблям из последнего снимка astros
+async fn astros_summary(State(st): State<AppState>) -> ApiResult<Value> {
+    let Some(row) = latest_snapshot(&st, "astros").await? else {
+        return no_data("astros");
+    };
+    let summary = AstrosSummary::from_payload(&row.payload).map_err(ApiError::invalid_payload)?;
+    ok(serde_json::json!({
+        "source": "astros",
+        "fetched_at": row.fetched_at,
+        "total": summary.total,
+        "by_craft": summary.by_craft,
+        "iss_crew": summary.iss_crew
+    }))
+}
+
+/// Самый поздний кадр из последнего снимка EPIC со ссылками на файлы архива
+async fn epic_latest_image(State(st): State<AppState>) -> ApiResult<Value> {
+    let Some(row) = latest_snapshot(&st, "epic").await? else {
+        return no_data("epic");
+    };
+    let image = EpicImage::latest(&row.payload).map_err(ApiError::invalid_payload)?;
+    ok(serde_json::json!({
+        "source": "epic",
+        "fetched_at": row.fetched_at,
+        "identifier": image.identifier,
+        "date": image.date,
+        "caption": image.caption,
+        "url": image.archive_url("png"),
+        "jpg_url": image.archive_url("jpg"),
+        "thumb_url": image.archive_url("thumbs")
+    }))
+}
+
+const MARS_PHOTOS_DEFAULT_LIMIT: i64 = 25;
+const MARS_PHOTOS_MAX_LIMIT: i64 = 200;
+
+/// Снимки марсохода из последнего кэша mars_photos с фильтром по ?camera=.
+/// Кэшируется только MARS_ROVER, поэтому ?rover= лишь сверяется с ним
+async fn mars_photos(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let rover = match q.get("rover").map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        Some(raw) => mars::parse_rover(raw).map_err(ApiError::validation)?,
+        None => st.config.mars_rover,
+    };
+    if rover != st.config.mars_rover {
+        return Err(ApiError::not_found(format!(
+            "photos of {} are not cached, MARS_ROVER is {}",
+            rover, st.config.mars_rover
+        )));
+    }
+    let camera = match q.get("camera").map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        Some(raw) => Some(mars::parse_camera(rover, raw).map_err(ApiError::validation)?),
+        None => None,
+    };
+    let limit = parse_i64_param(&q, "limit")?.unwrap_or(MARS_PHOTOS_DEFAULT_LIMIT);
+    if !(1..=MARS_PHOTOS_MAX_LIMIT).contains(&limit) {
+        return Err(ApiError::validation(format!(
+            "limit must be between 1 and {}",
+            MARS_PHOTOS_MAX_LIMIT
+        )));
+    }
+
+    let Some(row) = latest_snapshot(&st, "mars_photos").await? else {
+        return no_data("mars_photos");
+    };
+    let mut photos = mars::photos(&row.payload, camera).map_err(ApiError::invalid_payload)?;
+    let total = photos.len();
+    photos.truncate(limit as usize);
+    ok(serde_json::json!({
+        "source": "mars_photos",
+        "fetched_at": row.fetched_at,
+        "rover": rover,
+        "camera": camera,
+        "total": total,
+        "count": photos.len(),
+        "photos": photos
+    }))
+}
+
+/// Последняя геомагнитная буря из кэша gst с наибольшим Kp; active = false,
+/// если за окно DONKI бурь не было
+async fn gst_current(State(st): State<AppState>) -> ApiResult<Value> {
+    let Some(row) = latest_snapshot(&st, "gst").await? else {
+        return no_data("gst");
+    };
+    match gst::latest_storm(&row.payload).map_err(ApiError::invalid_payload)? {
+        Some(storm) => ok(serde_json::json!({
+            "source": "gst",
+            "fetched_at": row.fetched_at,
+            "active": true,
+            "storm": storm
+        })),
+        None => ok(serde_json::json!({
+            "source": "gst",
+            "fetched_at": row.fetched_at,
+            "active": false,
+            "message": "no active storm"
+        })),
+    }
+}
+
+const FLR_SUMMARY_DEFAULT_DAYS: i64 = 5;
+const FLR_SUMMARY_MAX_DAYS: i64 = 90;
+/// Сколько снимков flr просматривается, чтобы покрыть окно
+const FLR_SUMMARY_MAX_SNAPSHOTS: i64 = 200;
+
+/// Сводка по вспышкам за ?days= дней: число по классам, самая сильная
+/// и ещё идущие. Окно длиннее окна запроса DONKI покрывается более старыми
+/// снимками flr; у вспышки из нескольких снимков берётся самая новая версия
+async fn flr_summary(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let days = parse_i64_param(&q, "days")?.unwrap_or(FLR_SUMMARY_DEFAULT_DAYS);
+    if !(1..=FLR_SUMMARY_MAX_DAYS).contains(&days) {
+        return Err(ApiError::validation(format!(
+            "days must be between 1 and {}",
+            FLR_SUMMARY_MAX_DAYS
+        )));
+    }
+    let since = Utc::now() - chrono::Duration::days(days);
+
+    // Последний снимок берётся всегда, остальные — если обновлялись в окне
+    let rows = sqlx::query_as::<_, SpaceCacheEntry>(
+        "SELECT id, fetched_at, COALESCE(last_checked_at, fetched_at) AS last_checked_at,
+                payload, payload_compressed
+         FROM space_cache
+         WHERE source = 'flr'
+           AND (COALESCE(last_checked_at, fetched_at) >= $1
+                OR id = (SELECT max(id) FROM space_cache WHERE source = 'flr'))
+         ORDER BY id DESC
+         LIMIT $2"
+    )
+    .bind(since)
+    .bind(FLR_SUMMARY_MAX_SNAPSHOTS)
+    .fetch_all(&st.pool)
+    .await?
+    .into_iter()
+    .map(read_payload)
+    .collect::<Result<Vec<_>, _>>()?;
+
+    let Some(latest) = rows.first() else {
+        return no_data("flr");
+    };
+    let flares = flr::flares(rows.iter().map(|r| &r.payload));
+    ok(serde_json::json!({
+        "source": "flr",
+        "fetched_at": latest.fetched_at,
+        "days": days,
+        "snapshots": rows.len(),
+        "summary": flr::summary(&flares, since)
+    }))
+}
+
+const CME_SUMMARY_DEFAULT_DAYS: i64 = 5;
+const CME_SUMMARY_MAX_DAYS: i64 = 90;
+
+/// CME из последнего снимка cme за ?days= дней со скоростью, углом
+/// и прогнозом удара по Земле по моделям ENLIL
+async fn cme_summary(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let days = parse_i64_param(&q, "days")?.unwrap_or(CME_SUMMARY_DEFAULT_DAYS);
+    if !(1..=CME_SUMMARY_MAX_DAYS).contains(&days) {
+        return Err(ApiError::validation(format!(
+            "days must be between 1 and {}",
+            CME_SUMMARY_MAX_DAYS
+        )));
+    }
+
+    let Some(row) = latest_snapshot(&st, "cme").await? else {
+        return no_data("cme");
+    };
+    let since = Utc::now() - chrono::Duration::days(days);
+    let events = cme::events(&row.payload, since).map_err(ApiError::invalid_payload)?;
+    let max_speed = events.iter().filter_map(|e| e.speed).max_by(f64::total_cmp);
+    ok(serde_json::json!({
+        "source": "cme",
+        "fetched_at": row.fetched_at,
+        "days": days,
+        "count": events.len(),
+        "earth_directed_count": events.iter().filter(|e| e.earth_directed).count(),
+        "max_speed": max_speed,
+        "events": events
+    }))
+}
+
+/// За сколько часов учитываются вспышки и направленные к Земле CME
+const WEATHER_WINDOW_HOURS: i64 = 48;
+
+/// Общая оценка космической погоды 0–100 по Kp, вспышкам и CME.
+/// Источник без данных, с устаревшим снимком или неразборчивым payload
+/// в оценку не входит и снижает confidence
+async fn space_weather_score(State(st): State<AppState>) -> ApiResult<Value> {
+    let now = Utc::now();
+    let since = now - chrono::Duration::hours(WEATHER_WINDOW_HOURS);
+    let mut inputs = weather::Inputs::default();
+    let mut sources = serde_json::Map::new();
+
+    let kp_row = weather_input(&st, "kp").await?;
+    let kp_latest = kp_row
+        .as_ref()
+        .and_then(|r| kp::samples(&r.payload).ok())
+        .and_then(|s| s.last().copied());
+    inputs.kp = kp_latest.map(|s| s.kp);
+    sources.insert("kp".into(), weather_source_json(kp_row.as_ref(), kp_latest.map(|s| s.time)));
+
+    let flr_row = weather_input(&st, "flr").await?;
+    if let Some(row) = &flr_row {
+        let summary = flr::summary(&flr::flares([&row.payload]), since);
+        inputs.largest_flare =
+            Some(summary.largest.and_then(|f| f.class_type.as_deref().and_then(flr::FlareClass::parse)));
+    }
+    sources.insert("flr".into(), weather_source_json(flr_row.as_ref(), None));
+
+    let cme_row = weather_input(&st, "cme").await?;
+    inputs.earth_directed_cmes = cme_row
+        .as_ref()
+        .and_then(|r| cme::events(&r.payload, since).ok())
+        .map(|events| events.iter().filter(|e| e.earth_directed).map(|e| e.speed).collect());
+    sources.insert("cme".into(), weather_source_json(cme_row.as_ref(), None));
+
+    let score = weather::score(&inputs);
+    ok(serde_json::json!({
+        "at": now,
+        "window_hours": WEATHER_WINDOW_HOURS,
+        "score": score.score,
+        "confidence": score.confidence,
+        "components": score.components,
+        "inputs": sources
+    }))
+}
+
+/// Последний снимок источника для оценки погоды; None, если источник
+/// не настроен, данных нет или снимок устарел
+async fn weather_input(st: &AppState, source: &str) -> Result<Option<SpaceCacheRow>, ApiError> {
+    let Ok(src) = st.sources.get(source) else {
+        return Ok(None);
+    };
+    let row = latest_snapshot(st, src.name()).await?;
+    Ok(row.filter(|r| {
+        let age = seconds_between(r.last_checked_at, Utc::now());
+        !st.config.is_stale(src.interval(&st.config), Some(age))
+    }))
+}
+
+fn weather_source_json(row: Option<&SpaceCacheRow>, sample_time: Option<DateTime<Utc>>) -> Value {
+    match row {
+        Some(r) => serde_json::json!({
+            "fetched_at": r.fetched_at,
+            "last_checked_at": r.last_checked_at,
+            "sample_time": sample_time
+        }),
+        None => serde_json::json!({ "missing": true }),
+    }
+}
+
+/// Последнее значение планетарного Kp из кэша kp, его уровень
+/// и наибольшее значение за сутки
+async fn kp_current(State(st): State<AppState>) -> ApiResult<Value> {
+    let Some(row) = latest_snapshot(&st, "kp").await? else {
+        return no_data("kp");
+    };
+    let samples = kp::samples(&row.payload).map_err(ApiError::invalid_payload)?;
+    let Some(latest) = samples.last() else {
+        return ok(serde_json::json!({ "source": "kp", "fetched_at": row.fetched_at, "message": "no data" }));
+    };
+    let max_24h = kp::max_since(&samples, Utc::now() - chrono::Duration::hours(24));
+    ok(serde_json::json!({
+        "source": "kp",
+        "fetched_at": row.fetched_at,
+        "time": latest.time,
+        "kp": latest.kp,
+        "level": kp::level(latest.kp),
+        "max_24h": max_24h.map(|m| serde_json::json!({ "time": m.time, "kp": m.kp, "level": kp::level(m.kp) }))
+    }))
+}
+
+/// Окно статистики /space/solar-wind/latest
+const SOLAR_WIND_STATS_HOURS: i64 = 6;
+
+/// Последнее полное измерение солнечного ветра из кэша solar_wind
+/// и минимум/максимум/среднее каждого параметра за 6 часов
+async fn solar_wind_latest(State(st): State<AppState>) -> ApiResult<Value> {
+    let Some(row) = latest_snapshot(&st, "solar_wind").await? else {
+        return no_data("solar_wind");
+    };
+    let samples = solarwind::samples(&row.payload).map_err(ApiError::invalid_payload)?;
+    let since = Utc::now() - chrono::Duration::hours(SOLAR_WIND_STATS_HOURS);
+    ok(serde_json::json!({
+        "source": "solar_wind",
+        "fetched_at": row.fetched_at,
+        "latest": solarwind::latest(&samples),
+        "window_hours": SOLAR_WIND_STATS_HOURS,
+        "density": solarwind::stats(&samples, since, |s| s.density),
+        "speed": solarwind::stats(&samples, since, |s| s.speed),
+        "temperature": solarwind::stats(&samples, since, |s| s.temperature)
+    }))
+}
+
+const NOTIFICATIONS_DEFAULT_LIMIT: i64 = 10;
+const NOTIFICATIONS_MAX_LIMIT: i64 = 100;
+
+/// Уведомления DONKI из кэша, новые первыми, с фильтром ?type= по messageType.
+/// Без ?full=true от текста остаётся первый абзац
+async fn space_notifications(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let message_type = q.get("type").map(|s| s.trim()).filter(|s| !s.is_empty());
+    let full = parse_bool_param(&q, "full")?.unwrap_or(false);
+    let limit = parse_i64_param(&q, "limit")?.unwrap_or(NOTIFICATIONS_DEFAULT_LIMIT);
+    if !(1..=NOTIFICATIONS_MAX_LIMIT).contains(&limit) {
+        return Err(ApiError::validation(format!(
+            "limit must be between 1 and {}",
+            NOTIFICATIONS_MAX_LIMIT
+        )));
+    }
+
+    let Some(row) = latest_snapshot(&st, "donki_notifications").await? else {
+        return no_data("donki_notifications");
+    };
+    let mut items = notifications::notifications(&row.payload, message_type, full)
+        .map_err(ApiError::invalid_payload)?;
+    let total = items.len();
+    items.truncate(limit as usize);
+    ok(serde_json::json!({
+        "source": "donki_notifications",
+        "fetched_at": row.fetched_at,
+        "type": message_type,
+        "total": total,
+        "count": items.len(),
+        "notifications": items
+    }))
+}
+
+const EONET_NEAR_DEFAULT_RADIUS_KM: f64 = 500.0;
+/// Половина длины экватора: дальше любой точки Земли
+const EONET_NEAR_MAX_RADIUS_KM: f64 = 20_038.0;
+
+/// Открытые события EONET из последнего кэша в пределах ?radius_km= от точки,
+/// по возрастанию расстояния до последнего наблюдения
+async fn eonet_near(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> ApiResult<Value> {
+    let lat = parse_f64_param(&q, "lat")?
+        .ok_or_else(|| ApiError::validation("lat is required"))?;
+    let lon = parse_f64_param(&q, "lon")?
+        .ok_or_else(|| ApiError::validation("lon is required"))?;
+    if !(-90.0..=90.0).contains(&lat) {
+        return Err(ApiError::validation("lat must be between -90 and 90"));
+    }
+    if !(-180.0..=180.0).contains(&lon) {
+        return Err(ApiError::validation("lon must be between -180 and 180"));
+    }
+    let radius_km = parse_f64_param(&q, "radius_km")?.unwrap_or(EONET_NEAR_DEFAULT_RADIUS_KM);
+    if !(radius_km > 0.0 && radius_km <= EONET_NEAR_MAX_RADIUS_KM) {
+        return Err(ApiError::validation(format!(
+            "radius_km must be greater than 0 and at most {}",
+            EONET_NEAR_MAX_RADIUS_KM
+        )));
+    }
+
+    let Some(row) = latest_snapshot(&st, "eonet").await? else {
+        return no_data("eonet");
+    };
+    let mut near: Vec<(f64, eonet::EonetEvent)> = eonet::events(&row.payload)
+        .map_err(ApiError::invalid_payload)?
+        .into_iter()
+        .map(|e| (haversine_km(lat, lon, e.lat, e.lon), e))
+        .filter(|(distance, _)| *distance <= radius_km)
+        .collect();
+    near.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let events: Vec<Value> = near
+        .into_iter()
+        .map(|(distance, event)| {
+            let mut value = serde_json::json!(event);
+            value["distance_km"] = distance.into();
+            value
+        })
+        .collect();
+
+    ok(serde_json::json!({
+        "source": "eonet",
+        "fetched_at": row.fetched_at,
+        "point": { "lat": lat, "lon": lon },
+        "radius_km": radius_km,
+        "count": events.len(),
+        "events": events
+    }))
+}
+
+/// Итог обновления одного источника
+#[derive(Serialize)]
+struct SourceRefresh {
+    ok: bool,
+    /// refreshed — данные запрошены заново, fresh — кэш моложе min_age_secs,
+    /// skipped_in_progress — источник уже загружается, rate_limited — upstream
+    /// просил подождать до available_at, failed — ошибка
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    available_at: Option<DateTime<Utc>>,
+    /// Возраст кэша для fresh
+    #[serde(skip_serializing_if = "Option::is_none")]
+    age_sec: Option<f64>,
+    /// Результат взят у одновременного запроса того же источника
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    coalesced: bool,
+    ms: u64,
+}
+
+impl SourceRefresh {
+    fn new(status: &'static str, ms: u64) -> Self {
+        Self { ok: true, status, error: None, available_at: None, age_sec: None, coalesced: false, ms }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FetchRun {
+    Done,
+    /// Источник уже загружается другой задачей
+    InProgress,
+}
+
+/// Загрузка источника в space_cache под его блокировкой; второй одновременный
+/// вызов пропускается. Пока действует Retry-After источника — RATE_LIMITED
+async fn fetch_source(st: &AppState, src: &Arc<dyn SpaceSource>) -> Result<FetchRun, ApiError> {
+    if let Some(until) = held_off_until(st, src.name()) {
+        return Err(ApiError::rate_limited(format!(
+            "{} is rate limited until {}",
+            src.name(),
+            until.to_rfc3339()
+        )));
+    }
+    let Some(_guard) = st.sources.try_lock(src.name()) else {
+        info!("{} fetch already in progress, skipped", src.name());
+        return Ok(FetchRun::InProgress);
+    };
+    if let Some(fetched) = src.fetch(st).await? {
+        store_fetched(st, src.name(), fetched).await?;
+    }
+    Ok(FetchRun::Done)
+}
+
+/// `fetch_source`, общий для одновременных запросов клиентов: upstream
+/// запрашивается один раз, все получают его итог. true — итог чужого вызова
+async fn fetch_source_shared(st: &AppState, src: &Arc<dyn SpaceSource>) -> (Result<FetchRun, ApiError>, bool) {
+    let (owned, src) = (st.clone(), src.clone());
+    st.space_flights
+        .run(src.name(), || async move { fetch_source(&owned, &src).await })
+        .await
+}
+
+/// Кэш без принудительного обновления считается свежим столько секунд
+const REFRESH_DEFAULT_MIN_AGE_SECS: u64 = 60;
+
+#[derive(Deserialize)]
+struct RefreshRequest {
+    /// Пусто — все источники
+    #[serde(default)]
+    sources: Vec<String>,
+    #[serde(default)]
+    force: bool,
+    min_age_secs: Option<u64>,
+}
+
+/// Обновляет выбранные источники параллельно. Без force источник, проверенный
+/// меньше min_age_secs назад, upstream не запрашивается; неизвестные имена — в skipped
+async fn space_refresh(
+    State(st): State<AppState>,
+    body: Result<Json<RefreshRequest>, JsonRejection>,
+) -> ApiResult<Value> {
+    let Json(req) = body.map_err(|e| ApiError::validation(e.body_text()))?;
+    let min_age = (!req.force).then(|| req.min_age_secs.unwrap_or(REFRESH_DEFAULT_MIN_AGE_SECS));
+    refresh_sources(st, req.sources, min_age).await
+}
+
+/// GET /space/refresh?src= — устаревший вариант POST, всегда обновляет принудительно
+async fn space_refresh_deprecated(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+) -> impl IntoResponse {
+    warn!("GET /space/refresh is deprecated, use POST");
+    let sources = parse_list_param(&q, "src").unwrap_or_default();
+    ([("Deprecation", "true")], refresh_sources(st, sources, None).await)
+}
+
+enum RefreshOutcome {
+    Fetched(FetchRun),
+    /// Кэш моложе min_age_secs, возраст в секундах
+    Fresh(f64),
+    /// Upstream просил не обращаться до этого момента
+    RateLimited(DateTime<Utc>),
+}
+
+/// Итог и признак того, что загрузку выполнил одновременный запрос
+async fn refresh_source(
+    st: &AppState,
+    src: &Arc<dyn SpaceSource>,
+    min_age_secs: Option<u64>,
+) -> (Result<RefreshOutcome, ApiError>, bool) {
+    match check_refresh(st, src, min_age_secs).await {
+        Ok(Some(outcome)) => return (Ok(outcome), false),
+        Ok(None) => {}
+        Err(e) => return (Err(e), false),
+    }
+    let (result, coalesced) = fetch_source_shared(st, src).await;
+    let outcome = match result {
+        Ok(run) => Ok(RefreshOutcome::Fetched(run)),
+        // Upstream ответил 429 только что
+        Err(e) => held_off_until(st, src.name()).map(RefreshOutcome::RateLimited).ok_or(e),
+    };
+    (outcome, coalesced)
+}
+
+/// Итог без обращения к upstream: Retry-After ещё действует или кэш свежий
+async fn check_refresh(
+    st: &AppState,
+    src: &Arc<dyn SpaceSource>,
+    min_age_secs: Option<u64>,
+) -> Result<Option<RefreshOutcome>, ApiError> {
+    if let Some(until) = held_off_until(st, src.name()) {
+        return Ok(Some(RefreshOutcome::RateLimited(until)));
+    }
+    if let Some(min_age) = min_age_secs {
+        let checked_at: Option<DateTime<Utc>> = sqlx::query_scalar(
+            "SELECT COALESCE(last_checked_at, fetched_at) FROM space_cache
+             WHERE source = $1 ORDER BY id DESC LIMIT 1"
+        )
+        .bind(src.name())
+        .fetch_optional(&st.pool)
+        .await?;
+        if let Some(at) = checked_at {
+            let age = seconds_between(at, Utc::now());
+            if age < min_age as f64 {
+                return Ok(Some(RefreshOutcome::Fresh(age)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+async fn refresh_sources(
+    st: AppState,
+    requested: Vec<String>,
+    min_age_secs: Option<u64>,
+) -> ApiResult<Value> {
+    let mut selected: Vec<Arc<dyn SpaceSource>> = Vec::new();
+    let mut skipped: Vec<String> = Vec::new();
+    if requested.is_empty() {
+        selected.extend(st.sources.iter().cloned());
+    }
+    for name in requested {
+        match st.sources.get(&name) {
+            Ok(src) if !selected.iter().any(|s| s.name() == src.name()) => selected.push(src.clone()),
+            Ok(_) => {}
+            Err(_) if !skipped.contains(&name) => skipped.push(name),
+            Err(_) => {}
+        }
+    }
+
+    let runs = selected.into_iter().map(|src| {
+        let st = st.clone();
+        async move {
+            let started = std::time::Instant::now();
+            let (outcome, coalesced) = refresh_source(&st, &src, min_age_secs).await;
+            let outcome = match outcome {
+                Ok(RefreshOutcome::Fresh(age)) => SourceRefresh {
+                    age_sec: Some(age),
+                    ..SourceRefresh::new("fresh", 0)
+                },
+                Ok(RefreshOutcome::RateLimited(until)) => SourceRefresh {
+                    ok: false,
+                    error: Some("RATE_LIMITED".to_string()),
+                    available_at: Some(until),
+                    ..SourceRefresh::new("rate_limited", 0)
+                },
+                Ok(RefreshOutcome::Fetched(FetchRun::Done)) => SourceRefresh::new("refreshed", 0),
+                Ok(RefreshOutcome::Fetched(FetchRun::InProgress)) => {
+                    SourceRefresh::new("skipped_in_progress", 0)
+                }
+                Err(e) => {
+                    warn!("space refresh {} failed: {}", src.name(), e.error.message);
+                    SourceRefresh {
+                        ok: false,
+                        error: Some(e.error.code),
+                        ..SourceRefresh::new("failed", 0)
+                    }
+                }
+            };
+            let ms = started.elapsed().as_millis() as u64;
+            (src.name(), SourceRefresh { coalesced, ms, ..outcome })
+        }
+    });
+    let results: std::collections::BTreeMap<&str, SourceRefresh> =
+        futures::future::join_all(runs).await.into_iter().collect();
+
+    ok(serde_json::json!({ "results": results, "skipped": skipped }))
+}
+
+/// Сколько данных снимка отдавать в сводке
+#[derive(Clone, Copy, PartialEq)]
+enum PayloadMode {
+    None,
+    Meta,
+    Full,
+}
+
+impl PayloadMode {
+    fn parse(q: &HashMap<String, String>) -> Result<Self, ApiError> {
+        match q.get("payload").map(|s| s.trim().to_ascii_lowercase()).as_deref() {
+            None | Some("") | Some("full") => Ok(Self::Full),
+            Some("meta") => Ok(Self::Meta),
+            Some("none") => Ok(Self::None),
+            Some(_) => Err(ApiError::validation("payload must be one of: none, meta, full")),
+        }
+    }
+
+    /// Дописать к записи сводки снимок целиком или его ключевые поля
+    fn attach(self, entry: &mut Value, payload: Value, headline: impl FnOnce(&Value) -> Value) {
+        let (key, value) = match self {
+            Self::None => return,
+            Self::Meta => ("headline", headline(&payload)),
+            Self::Full => ("payload", payload),
+        };
+        if let Some(obj) = entry.as_object_mut() {
+            obj.insert(key.to_string(), value);
+        }
+    }
+}
+
+/// Последний снимок источника с возрастом последней проверки и флагом stale
+async fn latest_from_cache(
+    st: &AppState,
+    src: &Arc<dyn SpaceSource>,
+    mode: PayloadMode,
+) -> (Value, bool) {
+    let row = sqlx::query_as::<_, SpaceCacheRow>(
+        "SELECT fetched_at, COALESCE(last_checked_at, fetched_at) AS last_checked_at,
+                payload, payload_compressed
+         FROM space_cache
+         WHERE source = $1 ORDER BY id DESC LIMIT 1"
+    )
+    .bind(src.name())
+    .fetch_optional(&st.pool)
+    .await
+    .ok()
+    .flatten()
+    .and_then(|r| read_payload(r).ok());
+
+    let age = row.as_ref().map(|r| seconds_between(r.last_checked_at, Utc::now()));
+    let stale = st.config.is_stale(src.interval(&st.config), age);
+    let value = match row {
+        Some(r) => {
+            let mut value = serde_json::json!({
+                "at": r.fetched_at,
+                "last_checked_at": r.last_checked_at,
+                "age_seconds": age,
+                "stale": stale
+            });
+            mode.attach(&mut value, r.payload, |p| src.headline(p));
+            value
+        }
+        None => serde_json::json!({ "age_seconds": null, "stale": stale }),
+    };
+    (value, stale)
+}
+
+/// Последние снимки источников; degraded — хотя бы один из включённых устарел
+/// (старше своего интервала обновления × STALE_FACTOR).
+/// `?sources=apod,iss,osdr` ограничивает набор, `?payload=none|meta|full`
+/// убирает снимки или заменяет их ключевыми полями
+async fn space_summary(
+    State(st): State<AppState>,
+    Query(q): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> CachedResult<Value> {
+    let mode = PayloadMode::parse(&q)?;
+    let selected = match parse_list_param(&q, "sources") {
+        Some(raw) => {
+            let mut names = Vec::with_capacity(raw.len());
+            for name in raw {
+                let name = name.to_lowercase();
+                if name != "iss" && name != "osdr" {
+                    st.sources.get(&name)?;
+                }
+                names.push(name);
+            }
+            Some(names)
+        }
+        None => None,
+    };
+    let included = |name: &str| selected.as_ref().is_none_or(|s| s.iter().any(|n| n == name));
+
+    let mut summary = serde_json::Map::new();
+    let mut degraded = false;
+    for src in st.sources.iter().filter(|s| included(s.name())) {
+        let (value, stale) = latest_from_cache(&st, src, mode).await;
+        degraded |= stale;
+        summary.insert(src.name().to_string(), value);
+    }
+
+    if included("iss") {
+        let iss_row = latest_iss_row(&st.pool, ISS_NORAD_ID).await.ok().flatten();
+        let iss_age = iss_row.as_ref().map(|r| seconds_between(r.fetched_at, Utc::now()));
+        let iss_stale = st.config.is_stale(st.config.iss_interval(), iss_age);
+        degraded |= iss_stale;
+        let iss_last = match iss_row {
+            Some(r) => {
+                let mut value = serde_json::json!({
+                    "at": r.fetched_at,
+                    "age_seconds": iss_age,
+                    "stale": iss_stale
+                });
+                let headline = serde_json::json!({
+                    "latitude": r.latitude,
+                    "longitude": r.longitude,
+                    "altitude_km": r.altitude_km,
+                    "velocity_kmh": r.velocity_kmh
+                });
+                mode.attach(&mut value, r.payload, |_| headline);
+                value
+            }
+            None => serde_json::json!({ "age_seconds": null, "stale": iss_stale }),
+        };
+        summary.insert("iss".to_string(), iss_last);
+    }
+
+    if included("osdr") {
+        let osdr_count: i64 = sqlx::query("SELECT count(*) AS c FROM osdr_items")
+            .fetch_one(&st.pool)
+            .await
+            .map(|r| r.get::<i64, _>("c"))
+            .unwrap_or(0);
+        summary.insert("osdr_count".to_string(), osdr_count.into());
+    }
+    summary.insert("degraded".to_string(), degraded.into());
+
+    // age_seconds растёт с каждой секундой; версию сводки задают сами снимки
+    let mut version = summary.clone();
+    for entry in version.values_mut() {
+        if let Some(obj) = entry.as_object_mut() {
+            obj.remove("age_seconds");
+        }
+    }
+    let etag = content_hash(&Value::Object(version));
+    // МКС обновляется чаще остальных источников сводки
+    let max_age = st.config.cache_max_age_secs(st.config.iss_interval());
+    ok_with_etag(Value::Object(summary), &etag, max_age, &headers)
+}
+
+/* ---------- Admin Handlers ---------- */
+async fn admin_cleanup(State(st): State<AppState>) -> ApiResult<CleanupReport> {
+    ok(run_cleanup(&st, "manual").await)
+}
+
+async fn admin_cleanup_status(State(st): State<AppState>) -> ApiResult<Value> {
+    let last = st.last_cleanup.lock().await.clone();
+    ok(serde_json::json!({
+        "iss_retention_days": st.config.iss_retention_days,
+        "space_cache_retention_days": st.config.space_cache_retention_days,
+        "cleanup_every_seconds": st.config.cleanup_every_seconds,
+        "last": last
+    }))
+}
+
+#[derive(Deserialize)]
+struct BackfillRequest {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    step_secs: i64,
+    timeout_secs: Option<u64>,
+}
+
+/// Шаг не меньше удвоенного допуска, иначе соседние моменты считались бы дублями
+const BACKFILL_MIN_STEP_SECS: i64 = 10;
+const BACKFILL_MAX_EPOCHS: usize = 5000;
+const BACKFILL_DEFAULT_TIMEOUT_SECS: u64 = 600;
+const BACKFILL_MAX_TIMEOUT_SECS: u64 = 3600;
+/// Выборка ближе этого к запрошенному моменту считается уже загруженной
+const BACKFILL_MATCH_TOLERANCE_SECS: i64 = 5;
+
+/// Дозагрузка пропущенной истории через bulk-эндпоинт /positions.
+/// По таймауту возвращает частичный итог с timed_out: true
+async fn admin_iss_backfill(
+    Query(q): Query<HashMap<String, String>>,
+    State(st): State<AppState>,
+    body: Result<Json<BackfillRequest>, JsonRejection>,
+) -> ApiResult<Value> {
+    let Json(req) = body.map_err(|e| ApiError::validation(e.body_text()))?;
+    let sat = parse_tracked_sat_param(&st, &q)?;
+    if req.step_secs < BACKFILL_MIN_STEP_SECS {
+        return Err(ApiError::validation(format!(
+            "step_secs must be at least {}",
+            BACKFILL_MIN_STEP_SECS
+        )));
+    }
+    if req.from > req.to {
+        return Err(ApiError::validation("from must not be after to"));
+    }
+    if req.to > Utc::now() {
+        return Err(ApiError::validation("to must not be in the future"));
+    }
+    let timeout_secs = req.timeout_secs.unwrap_or(BACKFILL_DEFAULT_TIMEOUT_SECS);
+    if !(1..=BACKFILL_MAX_TIMEOUT_SECS).contains(&timeout_secs) {
+        return Err(ApiError::validation(format!(
+            "timeout_secs must be between 1 and {}",
+            BACKFILL_MAX_TIMEOUT_SECS
+        )));
+    }
+    let epochs = backfill::epochs(req.from.timestamp(), req.to.timestamp(), req.step_secs);
+    if epochs.len() > BACKFILL_MAX_EPOCHS {
+        return Err(ApiError::validation(format!(
+            "range yields {} timestamps, at most {} allowed",
+            epochs.len(),
+            BACKFILL_MAX_EPOCHS
+        )));
+    }
+
+    let started = tokio::time::Instant::now();
+    let mut progress = BackfillProgress::default();
+    let outcome = tokio::time::timeout(
+        Duration::from_secs(timeout_secs),
+        run_backfill(&st, sat, &epochs, &mut progress),
+    )
+    .await;
+    let timed_out = outcome.is_err();
+    if let Ok(Err(e)) = outcome {
+        progress.errors.push(e.error.message);
+    }
+    info!(
+        "sat {} backfill: {} inserted, {} skipped, {} failed{}",
+        sat,
+        progress.inserted,
+        progress.skipped,
+        progress.failed,
+        if timed_out { " (timed out)" } else { "" }
+    );
+
+    ok(serde_json::json!({
+        "norad_id": sat,
+        "from": req.from,
+        "to": req.to,
+        "step_secs": req.step_secs,
+        "requested": epochs.len(),
+        "inserted": progress.inserted,
+        "skipped": progress.skipped,
+        "failed": progress.failed,
+        "errors": progress.errors,
+        "timed_out": timed_out,
+        "wall_time_sec": started.elapsed().as_secs_f64()
+    }))
+}
+
+async fn run_backfill(
+    st: &AppState,
+    norad_id: i64,
+    epochs: &[i64],
+    progress: &mut BackfillProgress,
+) -> Result<(), ApiError> {
+    let client = &st.upstream.client;
+    let base = st.config.satellite_url(norad_id).ok_or_else(|| {
+        ApiError::not_found("backfill needs a WHERE_ISS_URL addressed by NORAD id, not a fixed file")
+    })?;
+    let delay = Duration::from_millis(st.config.backfill_delay_ms);
+    let mut first_request = true;
+
+    for batch in epochs.chunks(POSITIONS_BATCH) {
+        let existing = existing_sample_epochs(&st.pool, norad_id, batch).await?;
+        let missing = backfill::missing_epochs(batch, &existing, BACKFILL_MATCH_TOLERANCE_SECS);
+        progress.skipped += (batch.len() - missing.len()) as u64;
+        if missing.is_empty() {
+            continue;
+        }
+
+        if !first_request {
+            tokio::time::sleep(delay).await;
+        }
+        first_request = false;
+
+        let url = backfill::positions_url(&base, &missing);
+        match store_backfill_batch(st, client, &url, norad_id, &missing).await {
+            Ok((inserted, rejected)) => {
+                progress.inserted += inserted;
+                progress.failed += rejected.len() as u64;
+                progress.errors.extend(rejected);
+            }
+            Err(e) => {
+                warn!("sat {} backfill batch failed: {}", norad_id, e);
+                progress.failed += missing.len() as u64;
+                progress.errors.push(e.error.message);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Моменты (эпоха, сек) уже записанных выборок рядом с пачкой, по возрастанию
+async fn existing_sample_epochs(
+    pool: &PgPool,
+    norad_id: i64,
+    batch: &[i64],
+) -> Result<Vec<i64>, ApiError> {
+    let (Some(first), Some(last)) = (batch.first(), batch.last()) else {
+        return Ok(Vec::new());
+    };
+    let from = Utc.timestamp_opt(first - BACKFILL_MATCH_TOLERANCE_SECS, 0).single();
+    let to = Utc.timestamp_opt(last + BACKFILL_MATCH_TOLERANCE_SECS, 0).single();
+    let rows: Vec<i64> = sqlx::query_scalar(
+        "SELECT EXTRACT(EPOCH FROM sample_time)::bigint FROM iss_fetch_log
+         WHERE norad_id = $1 AND sample_time BETWEEN $2 AND $3
+         ORDER BY sample_time"
+    )
+    .bind(norad_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Один bulk-запрос; возвращает число записанных выборок и причины отказов.
+/// fetched_at берётся равным моменту измерения, чтобы старые точки
+/// не выглядели свежими
+async fn store_backfill_batch(
+    st: &AppState,
+    client: &reqwest::Client,
+    url: &str,
+    norad_id: i64,
+    epochs: &[i64],
+) -> Result<(u64, Vec<String>), ApiError> {
+    let attempts = http::Attempts::default();
+    let timeout = st.config.http_timeout("iss_backfill");
+    let retry = st.config.retry_policy("iss_backfill");
+    let resp = http::send(&st.upstream, &retry, &attempts, || client.get(url).timeout(timeout)).await?;
+    let status = resp.status();
+    let body = resp.text().await?;
+    if !status.is_success() {
+        let excerpt: String = body.chars().take(200).collect();
+        return Err(ApiError::upstream(status.as_u16(), excerpt));
+    }
+    let items = match serde_json::from_str::<Value>(&body) {
+        Ok(Value::Array(items)) => items,
+        Ok(_) => return Err(ApiError::invalid_payload("positions response is not an array")),
+        Err(e) => return Err(ApiError::invalid_payload(format!("non-JSON body: {}", e))),
+    };
+
+    let mut inserted = 0;
+    let mut rejected = Vec::new();
+    for (i, item) in items.into_iter().enumerate() {
+        let pos = match IssPosition::from_payload(&item) {
+            Ok(pos) => pos,
+            Err(reason) => {
+                rejected.push(reason);
+                continue;
+            }
+        };
+        let requested = epochs.get(i).and_then(|&t| Utc.timestamp_opt(t, 0).single());
+        let Some(sample_time) = pos.sample_time().or(requested) else {
+            rejected.push("position without timestamp".to_string());
+            continue;
+        };
+        sqlx::query(
+            "INSERT INTO iss_fetch_log
+                (fetched_at, source_url, payload, latitude, longitude, altitude_km,
+                 velocity_kmh, sample_time, visibility, norad_id)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"
+        )
+        .bind(sample_time)
+        .bind(url)
+        .bind(&item)
+        .bind(pos.latitude)
+        .bind(pos.longitude)
+        .bind(pos.altitude)
+        .bind(pos.velocity)
+        .bind(sample_time)
+        .bind(&pos.visibility)
+        .bind(norad_id)
+        .execute(&st.pool)
+        .await?;
+        inserted += 1;
+    }
+    Ok((inserted, rejected))
+}
+
+/* ---------- Retention ---------- */
+#[derive(Serialize, Clone)]
+struct CleanupReport {
+    trigger: &'static str,
+    started_at: DateTime<Utc>,
+    finished_at: DateTime<Utc>,
+    /// Удалённые строки по таблицам; таблицы с retention = 0 не попадают
+    deleted: std::collections::BTreeMap<&'static str, u64>,
+    error: Option<String>,
+}
+
+/// Сколько строк удалять одним DELETE, чтобы не держать долгие блокировки
+const CLEANUP_BATCH_SIZE: i64 = 10_000;
+
+/// Удаляет записи старше срока хранения и запоминает итог в AppState
+async fn run_cleanup(st: &AppState, trigger: &'static str) -> CleanupReport {
+    let _running = st.cleanup_running.lock().await;
+    let mut report = CleanupReport {
+        trigger,
+        started_at: Utc::now(),
+        finished_at: Utc::now(),
+        deleted: Default::default(),
+        error: None,
+    };
+
+    if let Err(e) = prune_tables(st, &mut report).await {
+        report.error = Some(e.to_string());
+    }
+    report.finished_at = Utc::now();
+    for (table, n) in &report.deleted {
+        info!("cleanup ({}): removed {} rows from {}", trigger, n, table);
+    }
+
+    *st.last_cleanup.lock().await = Some(report.clone());
+    report
+}
+
+async fn prune_tables(st: &AppState, report: &mut CleanupReport) -> Result<(), ApiError> {
+    if st.config.iss_retention_days > 0 {
+        let cutoff = Utc::now() - chrono::Duration::days(st.config.iss_retention_days as i64);
+        let n = delete_in_batches(
+            &st.pool,
+            "DELETE FROM iss_fetch_log WHERE id IN (
+                SELECT id FROM iss_fetch_log WHERE fetched_at < $1 LIMIT $2
+             )",
+            cutoff,
+        )
+        .await?;
+        report.deleted.insert("iss_fetch_log", n);
+
+        let n = delete_in_batches(
+            &st.pool,
+            "DELETE FROM iss_fetch_rejects WHERE id IN (
+                SELECT id FROM iss_fetch_rejects WHERE fetched_at < $1 LIMIT $2
+             )",
+            cutoff,
+        )
+        .await?;
+        report.deleted.insert("iss_fetch_rejects", n);
+    }
+
+    if st.config.space_cache_retention_days > 0 {
+        let cutoff =
+            Utc::now() - chrono::Duration::days(st.config.space_cache_retention_days as i64);
+        // Последняя запись каждого источника остаётся, иначе /space/:src/latest
+        // опустеет для источника, который давно не обновлялся
+        let n = delete_in_batches(
+            &st.pool,
+            "DELETE FROM space_cache WHERE id IN (
+                SELECT c.id FROM space_cache c
+                WHERE c.fetched_at < $1
+                  AND EXISTS (
+                    SELECT 1 FROM space_cache n
+                    WHERE n.source = c.source AND n.fetched_at > c.fetched_at
+                  )
+                LIMIT $2
+             )",
+            cutoff,
+        )
+        .await?;
+        report.deleted.insert("space_cache", n);
+    }
+
+    Ok(())
+}
+
+/// Повторяет DELETE с параметрами ($1 = cutoff, $2 = размер пачки),
+/// пока он удаляет полные пачки
+async fn delete_in_batches(
+    pool: &PgPool,
+    sql: &'static str,
+    cutoff: DateTime<Utc>,
+) -> Result<u64, ApiError> {
+    let mut total = 0u64;
+    loop {
+        let n = sqlx::query(sql)
+            .bind(cutoff)
+            .bind(CLEANUP_BATCH_SIZE)
+            .execute(pool)
+            .await?
+            .rows_affected();
+        total += n;
+        if n < CLEANUP_BATCH_SIZE as u64 {
+            return Ok(total);
+        }
+    }
+}
+
+/* ---------- Backfill ---------- */
+const BACKFILL_BATCH_SIZE: i64 = 1000;
+
+/// Заполняет latitude/longitude/altitude_km/velocity_kmh/sample_time/visibility
+/// у строк, записанных до появления колонок. Строки идут по id пачками;
+/// неразборчивые payload пропускаются и остаются с NULL.
+/// Возвращает (обновлено, пропущено)
+async fn backfill_iss_columns(pool: &PgPool) -> Result<(u64, u64), ApiError> {
+    let mut after_id = 0i64;
+    let (mut updated, mut unparsed) = (0u64, 0u64);
+    loop {
+        let rows = sqlx::query(
+            "SELECT id, payload FROM iss_fetch_log
+             WHERE latitude IS NULL AND id > $1
+             ORDER BY id LIMIT $2"
+        )
+        .bind(after_id)
+        .bind(BACKFILL_BATCH_SIZE)
+        .fetch_all(pool)
+        .await?;
+        let Some(last) = rows.last() else {
+            return Ok((updated, unparsed));
+        };
+        after_id = last.try_get("id")?;
+
+        let mut ids = Vec::with_capacity(rows.len());
+        let mut positions = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let payload: Value = row.try_get("payload")?;
+            match IssPosition::from_payload(&payload) {
+                Ok(pos) => {
+                    ids.push(row.try_get::<i64, _>("id")?);
+                    positions.push(pos);
+                }
+                Err(_) => unparsed += 1,
+            }
+        }
+        if ids.is_empty() {
+            continue;
+        }
+
+        updated += sqlx::query(
+            "UPDATE iss_fetch_log t
+             SET latitude = u.latitude, longitude = u.longitude,
+                 altitude_km = u.altitude_km, velocity_kmh = u.velocity_kmh,
+                 sample_time = u.sample_time, visibility = u.visibility
+             FROM UNNEST($1::bigint[], $2::float8[], $3::float8[], $4::float8[],
+                         $5::float8[], $6::timestamptz[], $7::text[])
+                  AS u(id, latitude, longitude, altitude_km, velocity_kmh, sample_time, visibility)
+             WHERE t.id = u.id"
+        )
+        .bind(&ids)
+        .bind(positions.iter().map(|p| p.latitude).collect::<Vec<_>>())
+        .bind(positions.iter().map(|p| p.longitude).collect::<Vec<_>>())
+        .bind(positions.iter().map(|p| p.altitude).collect::<Vec<_>>())
+        .bind(positions.iter().map(|p| p.velocity).collect::<Vec<_>>())
+        .bind(positions.iter().map(|p| p.sample_time()).collect::<Vec<_>>())
+        .bind(positions.iter().map(|p| p.visibility.clone()).collect::<Vec<_>>())
+        .execute(pool)
+        .await?
+        .rows_affected();
+    }
+}
+
+/// Снимки space_cache бывают по сотне КБ, поэтому пачки меньше обычных
+const SPACE_CACHE_BACKFILL_BATCH_SIZE: i64 = 100;
+
+/// Считает payload_hash у снимков, записанных до появления колонки.
+/// Возвращает число обновлённых строк
+async fn backfill_space_cache_hashes(pool: &PgPool) -> Result<u64, ApiError> {
+    let mut after_id = 0i64;
+    let mut updated = 0u64;
+    loop {
+        let rows = sqlx::query(
+            "SELECT id, payload FROM space_cache
+             WHERE payload_hash IS NULL AND id > $1
+             ORDER BY id LIMIT $2"
+        )
+        .bind(after_id)
+        .bind(SPACE_CACHE_BACKFILL_BATCH_SIZE)
+        .fetch_all(pool)
+        .await?;
+        let Some(last) = rows.last() else {
+            return Ok(updated);
+        };
+        after_id = last.try_get("id")?;
+
+        let mut ids = Vec::with_capacity(rows.len());
+        let mut hashes = Vec::with_capacity(rows.len());
+        for row in &rows {
+            ids.push(row.try_get::<i64, _>("id")?);
+            hashes.push(content_hash(&row.try_get::<Value, _>("payload")?));
+        }
+        updated += sqlx::query(
+            "UPDATE space_cache t SET payload_hash = u.payload_hash
+             FROM UNNEST($1::bigint[], $2::text[]) AS u(id, payload_hash)
+             WHERE t.id = u.id AND t.payload_hash IS NULL"
+        )
+        .bind(&ids)
+        .bind(&hashes)
+        .execute(pool)
+        .await?
+        .rows_affected();
+    }
+}
+
+/// Сжимает несжатые снимки space_cache не короче CACHE_COMPRESS_MIN_BYTES.
+/// Размер сверяется по тексту JSON, как при записи. Возвращает число сжатых строк
+async fn compress_space_cache(pool: &PgPool, cfg: &Config) -> Result<u64, ApiError> {
+    let mut after_id = 0i64;
+    let mut compressed = 0u64;
+    loop {
+        let rows = sqlx::query(
+            "SELECT id, payload FROM space_cache
+             WHERE payload_compressed IS NULL AND id > $1
+               AND octet_length(payload::text) >= $2
+             ORDER BY id LIMIT $3"
+        )
+        .bind(after_id)
+        .bind(cfg.cache_compress_min_bytes as i64)
+        .bind(SPACE_CACHE_BACKFILL_BATCH_SIZE)
+        .fetch_all(pool)
+        .await?;
+        let Some(last) = rows.last() else {
+            return Ok(compressed);
+        };
+        after_id = last.try_get("id")?;
+
+        let mut ids = Vec::with_capacity(rows.len());
+        let mut hashes = Vec::with_capacity(rows.len());
+        let mut stubs = Vec::with_capacity(rows.len());
+        let mut blobs = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let payload: Value = row.try_get("payload")?;
+            let hash = content_hash(&payload);
+            let packed = pack(payload, cfg.cache_compression, cfg.cache_compress_min_bytes);
+            // jsonb::text длиннее компактного JSON, так что пограничный снимок может остаться как есть
+            let Some(blob) = packed.compressed else {
+                continue;
+            };
+            ids.push(row.try_get::<i64, _>("id")?);
+            hashes.push(hash);
+            stubs.push(packed.payload);
+            blobs.push(blob);
+        }
+        compressed += sqlx::query(
+            "UPDATE space_cache t
+             SET payload = u.payload, payload_compressed = u.payload_compressed,
+                 payload_hash = COALESCE(t.payload_hash, u.payload_hash)
+             FROM UNNEST($1::bigint[], $2::jsonb[], $3::bytea[], $4::text[])
+                  AS u(id, payload, payload_compressed, payload_hash)
+             WHERE t.id = u.id AND t.payload_compressed IS NULL"
+        )
+        .bind(&ids)
+        .bind(&stubs)
+        .bind(&blobs)
+        .bind(&hashes)
+        .execute(pool)
+        .await?
+        .rows_affected();
+    }
+}
+
+/// Заполняет organism/assay_type/mission из raw у строк, где все три пусты.
+/// Строки, в raw которых полей нет, остаются с NULL и просматриваются
+/// снова при следующем запуске. Возвращает число обновлённых строк
+async fn backfill_osdr_meta(pool: &PgPool, keys: &config::OsdrKeys) -> Result<u64, ApiError> {
+    let mut after_id = 0i64;
+    let mut updated = 0u64;
+    loop {
+        let rows = sqlx::query(
+            "SELECT id, raw FROM osdr_items
+             WHERE organism IS NULL AND assay_type IS NULL AND mission IS NULL AND id > $1
+             ORDER BY id LIMIT $2"
+        )
+        .bind(after_id)
+        .bind(BACKFILL_BATCH_SIZE)
+        .fetch_all(pool)
+        .await?;
+        let Some(last) = rows.last() else {
+            return Ok(updated);
+        };
+        after_id = last.try_get("id")?;
+
+        let mut ids = Vec::with_capacity(rows.len());
+        let mut metas = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let meta = OsdrMeta::extract(&row.try_get::<Value, _>("raw")?, keys);
+            if meta != OsdrMeta::default() {
+                ids.push(row.try_get::<i64, _>("id")?);
+                metas.push(meta);
+            }
+        }
+        if ids.is_empty() {
+            continue;
+        }
+
+        updated += sqlx::query(
+            "UPDATE osdr_items t
+             SET organism = u.organism, assay_type = u.assay_type, mission = u.mission
+             FROM UNNEST($1::bigint[], $2::text[], $3::text[], $4::text[])
+                  AS u(id, organism, assay_type, mission)
+             WHERE t.id = u.id"
+        )
+        .bind(&ids)
+        .bind(metas.iter().map(|m| m.organism.clone()).collect::<Vec<_>>())
+        .bind(metas.iter().map(|m| m.assay_type.clone()).collect::<Vec<_>>())
+        .bind(metas.iter().map(|m| m.mission.clone()).collect::<Vec<_>>())
+        .execute(pool)
+        .await?
+        .rows_affected();
+    }
+}
+
+/* ---------- Fetch Functions ---------- */
+/// Параметры ответа upstream, которые хранятся вместе со снимком
+#[derive(Debug, Clone)]
+struct FetchMeta {
+    http_status: i16,
+    latency_ms: i32,
+    /// None у 304: тела нет, и в средний размер ответа он не входит
+    body_bytes: Option<i32>,
+    attempts: i32,
+    request_id: String,
+}
+
+impl FetchMeta {
+    fn new(status: u16, started: std::time::Instant, body_bytes: usize, attempts: &http::Attempts) -> Self {
+        Self {
+            body_bytes: Some(i32::try_from(body_bytes).unwrap_or(i32::MAX)),
+            ..Self::not_modified(status, started, attempts)
+        }
+    }
+
+    fn not_modified(status: u16, started: std::time::Instant, attempts: &http::Attempts) -> Self {
+        Self {
+            http_status: status as i16,
+            latency_ms: elapsed_ms(started),
+            body_bytes: None,
+            attempts: attempts.get() as i32,
+            request_id: attempts.request_id().to_string(),
+        }
+    }
+}
+
+fn elapsed_ms(started: std::time::Instant) -> i32 {
+    i32::try_from(started.elapsed().as_millis()).unwrap_or(i32::MAX)
+}
+
+/// Неудачный запрос к источнику; ошибка записи только логируется, чтобы не
+/// подменять исходную
+async fn record_fetch_error(
+    pool: &PgPool,
+    source: &str,
+    http_status: Option<u16>,
+    error: &ApiError,
+    latency_ms: i32,
+    attempts: u32,
+    request_id: &str,
+) {
+    let result = sqlx::query(
+        "INSERT INTO fetch_errors(source, http_status, latency_ms, error, attempts, code, request_id)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)"
+    )
+    .bind(source)
+    .bind(http_status.map(|s| s as i16))
+    .bind(latency_ms)
+    .bind(&error.error.message)
+    .bind(attempts as i32)
+    .bind(&error.error.code)
+    .bind(request_id)
+    .execute(pool)
+    .await;
+    if let Err(e) = result {
+        warn!("failed to record {} fetch error: {}", source, e);
+    }
+    let result = sqlx::query(
+        "INSERT INTO fetch_attempts(source, ok, http_status, latency_ms, attempts, code)
+         VALUES ($1, false, $2, $3, $4, $5)"
+    )
+    .bind(source)
+    .bind(http_status.map(|s| s as i16))
+    .bind(latency_ms)
+    .bind(attempts as i32)
+    .bind(&error.error.code)
+    .execute(pool)
+    .await;
+    if let Err(e) = result {
+        warn!("failed to record {} fetch attempt: {}", source, e);
+    }
+}
+
+/// Удачный запрос к источнику в fetch_attempts — и тогда, когда снимок не
+/// изменился. Ошибка записи только логируется
+async fn record_fetch_success(pool: &PgPool, source: &str, meta: &FetchMeta) {
+    let result = sqlx::query(
+        "INSERT INTO fetch_attempts(source, ok, http_status, latency_ms, body_bytes, attempts)
+         VALUES ($1, true, $2, $3, $4, $5)"
+    )
+    .bind(source)
+    .bind(meta.http_status)
+    .bind(meta.latency_ms)
+    .bind(meta.body_bytes)
+    .bind(meta.attempts)
+    .execute(pool)
+    .await;
+    if let Err(e) = result {
+        warn!("failed to record {} fetch attempt: {}", source, e);
+    }
+}
+
+/// Новая строка пишется, только если payload отличается от последнего снимка
+/// источника; иначе у того снимка обновляются last_checked_at и параметры ответа
+async fn write_cache(st: &AppState, source: &str, payload: Value, meta: FetchMeta) -> Result<(), ApiError> {
+    let pool = &st.pool;
+    record_fetch_success(pool, source, &meta).await;
+    let hash = content_hash(&payload);
+    // payload нужен только строкам, записанным до появления payload_hash
+    let latest = sqlx::query(
+        "SELECT id, payload_hash, CASE WHEN payload_hash IS NULL THEN payload END AS payload
+         FROM space_cache WHERE source = $1 ORDER BY id DESC LIMIT 1"
+    )
+    .bind(source)
+    .fetch_optional(pool)
+    .await?;
+
+    if let Some(row) = latest {
+        let stored_hash = match row.try_get::<Option<String>, _>("payload_hash")? {
+            Some(h) => h,
+            None => content_hash(&row.try_get::<Value, _>("payload")?),
+        };
+        if stored_hash == hash {
+            sqlx::query(
+                "UPDATE space_cache
+                 SET last_checked_at = now(), payload_hash = $2,
+                     http_status = $3, latency_ms = $4, body_bytes = $5, attempts = $6
+                 WHERE id = $1"
+            )
+            .bind(row.try_get::<i64, _>("id")?)
+            .bind(hash)
+            .bind(meta.http_status)
+            .bind(meta.latency_ms)
+            .bind(meta.body_bytes)
+            .bind(meta.attempts)
+            .execute(pool)
+            .await?;
+            return Ok(());
+        }
+    }
+
+    let packed = pack(payload, st.config.cache_compression, st.config.cache_compress_min_bytes);
+    sqlx::query(
+        "INSERT INTO space_cache(source, payload, payload_compressed, payload_hash, last_checked_at,
+                                 http_status, latency_ms, body_bytes, attempts)
+         VALUES ($1, $2, $3, $4, now(), $5, $6, $7, $8)"
+    )
+    .bind(source)
+    .bind(packed.payload)
+    .bind(packed.compressed)
+    .bind(hash)
+    .bind(meta.http_status)
+    .bind(meta.latency_ms)
+    .bind(meta.body_bytes)
+    .bind(meta.attempts)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Валидаторы последнего ответа upstream
+#[derive(Debug, Default, sqlx::FromRow)]
+struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl Validators {
+    fn from_headers(headers: &reqwest::header::HeaderMap) -> Self {
+        let get = |name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        Self {
+            etag: get(reqwest::header::ETAG),
+            last_modified: get(reqwest::header::LAST_MODIFIED),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// Ответ источника, готовый к записи в space_cache
+struct Fetched {
+    json: Value,
+    validators: Validators,
+    meta: FetchMeta,
+}
+
+/// Запрос JSON у источника с If-None-Match / If-Modified-Since.
+/// None — upstream ответил 304, и last_checked_at последнего снимка обновлён.
+/// Неудачи пишутся в fetch_errors
+async fn fetch_json(
+    st: &AppState,
+    source: &str,
+    url: &str,
+    query: &[(&str, String)],
+) -> Result<Option<Fetched>, ApiError> {
+    fetch_json_with_headers(st, source, url, query, &[]).await
+}
+
+/// `fetch_json` с дополнительными заголовками запроса, например ключом API
+async fn fetch_json_with_headers(
+    st: &AppState,
+    source: &str,
+    url: &str,
+    query: &[(&str, String)],
+    headers: &[(&str, String)],
+) -> Result<Option<Fetched>, ApiError> {
+    let started = std::time::Instant::now();
+    let attempts = http::Attempts::default();
+    let result = request_json(st, source, url, query, headers, started, &attempts).await;
+    if let Err(e) = &result {
+        let latency = elapsed_ms(started);
+        record_fetch_error(&st.pool, source, e.upstream_status(), e, latency, attempts.get(), attempts.request_id()).await;
+    }
+    result
+}
+
+async fn request_json(
+    st: &AppState,
+    source: &str,
+    url: &str,
+    query: &[(&str, String)],
+    headers: &[(&str, String)],
+    started: std::time::Instant,
+    attempts: &http::Attempts,
+) -> Result<Option<Fetched>, ApiError> {
+    let retry = st.config.retry_policy(source);
+    let timeout = st.config.http_timeout(source);
+    let client = &st.upstream.client;
+    let get = || {
+        headers
+            .iter()
+            .fold(client.get(url).query(query).timeout(timeout), |req, (name, value)| req.header(*name, value))
+    };
+
+    let stored = sqlx::query_as::<_, Validators>(
+        "SELECT etag, last_modified FROM source_state WHERE source = $1"
+    )
+    .bind(source)
+    .fetch_optional(&st.pool)
+    .await?
+    .unwrap_or_default();
+
+    let resp = http::send(&st.upstream, &retry, attempts, || {
+        let mut req = get();
+        if let Some(etag) = &stored.etag {
+            req = req.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &stored.last_modified {
+            req = req.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
+        req
+    })
+    .await?;
+
+    if resp.status() == reqwest::StatusCode::NOT_MODIFIED {
+        let meta = FetchMeta::not_modified(resp.status().as_u16(), started, attempts);
+        if touch_cache(&st.pool, source, meta).await? {
+            return Ok(None);
+        }
+        // Снимка, к которому относились валидаторы, уже нет — берём всё заново
+        save_validators(&st.pool, source, &Validators::default()).await?;
+        let resp = http::send(&st.upstream, &retry, attempts, get).await?;
+        return read_json(st, source, resp, started, attempts).await.map(Some);
+    }
+    read_json(st, source, resp, started, attempts).await.map(Some)
+}
+
+async fn read_json(
+    st: &AppState,
+    source: &str,
+    resp: reqwest::Response,
+    started: std::time::Instant,
+    attempts: &http::Attempts,
+) -> Result<Fetched, ApiError> {
+    if !resp.status().is_success() {
+        let mut message = format!("{} request failed: {}", source, resp.status());
+        if let Some(until) = hold_off_on_429(st, source, &resp) {
+            message = format!("{}, retry after {}", message, until.to_rfc3339());
+        }
+        return Err(ApiError::upstream(resp.status().as_u16(), http::with_attempts(message, attempts)));
+    }
+    let status = resp.status().as_u16();
+    let validators = Validators::from_headers(resp.headers());
+    // Вместо JSON иногда приходит HTML-заглушка
+    let body = resp.text().await?;
+    let meta = FetchMeta::new(status, started, body.len(), attempts);
+    let json = serde_json::from_str(&body)
+        .map_err(|e| ApiError::invalid_payload(format!("{} returned non-JSON body: {}", source, e)))?;
+    Ok(Fetched { json, validators, meta })
+}
+
+/// 429 с Retry-After: источник не запрашивается до указанного момента
+fn hold_off_on_429(st: &AppState, source: &str, resp: &reqwest::Response) -> Option<DateTime<Utc>> {
+    if resp.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    let until = http::retry_after(resp.headers(), Utc::now())?;
+    warn!("{} is rate limited upstream, next request after {}", source, until.to_rfc3339());
+    st.not_before
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(source.to_string(), until);
+    Some(until)
+}
+
+/// До какого момента источник нельзя запрашивать; None — можно сейчас
+fn held_off_until(st: &AppState, source: &str) -> Option<DateTime<Utc>> {
+    let not_before = st.not_before.read().unwrap_or_else(|e| e.into_inner());
+    not_before.get(source).copied().filter(|until| *until > Utc::now())
+}
+
+/// Раскладывает ленту NeoWs по neo_objects. Объекты, которые не удалось
+/// разобрать, пишутся в лог и пропускаются; возвращает число записанных сближений
+async fn store_neo_objects(pool: &PgPool, payload: &Value) -> Result<u64, ApiError> {
+    let (approaches, failures) = neo::parse_feed(payload).map_err(ApiError::invalid_payload)?;
+    for failure in &failures {
+        warn!("neo object skipped: {}", failure);
+    }
+    upsert_neo_approaches(pool, &approaches).await
+}
+
+async fn upsert_neo_approaches(pool: &PgPool, approaches: &[NeoApproach]) -> Result<u64, ApiError> {
+    if approaches.is_empty() {
+        return Ok(0);
+    }
+    let done = sqlx::query(
+        "INSERT INTO neo_objects(neo_reference_id, name, est_diameter_min_m, est_diameter_max_m,
+                                 is_potentially_hazardous, close_approach_time,
+                                 miss_distance_km, relative_velocity_kms, raw)
+         SELECT * FROM UNNEST($1::text[], $2::text[], $3::float8[], $4::float8[], $5::bool[],
+                              $6::timestamptz[], $7::float8[], $8::float8[], $9::jsonb[])
+         ON CONFLICT (neo_reference_id, close_approach_time) DO UPDATE
+         SET name = EXCLUDED.name,
+             est_diameter_min_m = EXCLUDED.est_diameter_min_m,
+             est_diameter_max_m = EXCLUDED.est_diameter_max_m,
+             is_potentially_hazardous = EXCLUDED.is_potentially_hazardous,
+             miss_distance_km = EXCLUDED.miss_distance_km,
+             relative_velocity_kms = EXCLUDED.relative_velocity_kms,
+             raw = EXCLUDED.raw,
+             updated_at = now()"
+    )
+    .bind(approaches.iter().map(|a| a.neo_reference_id.clone()).collect::<Vec<_>>())
+    .bind(approaches.iter().map(|a| a.name.clone()).collect::<Vec<_>>())
+    .bind(approaches.iter().map(|a| a.est_diameter_min_m).collect::<Vec<_>>())
+    .bind(approaches.iter().map(|a| a.est_diameter_max_m).collect::<Vec<_>>())
+    .bind(approaches.iter().map(|a| a.is_potentially_hazardous).collect::<Vec<_>>())
+    .bind(approaches.iter().map(|a| a.close_approach_time).collect::<Vec<_>>())
+    .bind(approaches.iter().map(|a| a.miss_distance_km).collect::<Vec<_>>())
+    .bind(approaches.iter().map(|a| a.relative_velocity_kms).collect::<Vec<_>>())
+    .bind(approaches.iter().map(|a| a.raw.clone()).collect::<Vec<_>>())
+    .execute(pool)
+    .await?;
+    Ok(done.rows_affected())
+}
+
+/// Следующие NEO_BROWSE_PAGES_PER_RUN страниц каталога NEO в neo_objects.
+/// Позиция сохраняется после каждой страницы; на 429 прогон заканчивается
+/// досрочно и продолжится с той же страницы
+async fn browse_neo_catalog(st: &AppState) -> Result<(), ApiError> {
+    let mut progress = load_browse_progress(&st.pool).await?;
+    write_tasks(st).progress("neo_browse", serde_json::json!(progress));
+    for i in 0..st.config.neo_browse_pages_per_run {
+        if i > 0 {
+            tokio::time::sleep(Duration::from_millis(st.config.neo_browse_delay_ms)).await;
+        }
+        let page = match fetch_neo_browse_page(st, progress.pages_done).await {
+            Ok(page) => page,
+            Err(e) if e.upstream_status() == Some(429) => {
+                warn!("neo browse rate limited at page {}, stopping until next run", progress.pages_done);
+                break;
+            }
+            Err(e) => return Err(e),
+        };
+        let (objects, total_pages) = neo::browse_page(&page).map_err(ApiError::invalid_payload)?;
+        let mut approaches = Vec::new();
+        let mut stored_objects = 0;
+        for object in objects {
+            match neo::approaches(object) {
+                Ok(mut parsed) => {
+                    stored_objects += u64::from(!parsed.is_empty());
+                    approaches.append(&mut parsed);
+                }
+                Err(reason) => warn!("neo browse object skipped: {}", reason),
+            }
+        }
+        neo::dedupe(&mut approaches);
+        upsert_neo_approaches(&st.pool, &approaches).await?;
+        progress.page_done(total_pages, stored_objects);
+        save_browse_progress(&st.pool, &progress).await?;
+        write_tasks(st).progress("neo_browse", serde_json::json!(progress));
+    }
+    Ok(())
+}
+
+async fn load_browse_progress(pool: &PgPool) -> Result<BrowseProgress, ApiError> {
+    let stored: Option<Value> =
+        sqlx::query_scalar("SELECT progress FROM source_state WHERE source = 'neo_browse'")
+            .fetch_optional(pool)
+            .await?
+            .flatten();
+    // Непонятная запись — начинаем обход сначала
+    Ok(stored.and_then(|v| serde_json::from_value(v).ok()).unwrap_or_default())
+}
+
+async fn save_browse_progress(pool: &PgPool, progress: &BrowseProgress) -> Result<(), ApiError> {
+    sqlx::query(
+        "INSERT INTO source_state(source, progress, updated_at)
+         VALUES ('neo_browse', $1, now())
+         ON CONFLICT (source) DO UPDATE SET progress = EXCLUDED.progress, updated_at = now()"
+    )
+    .bind(serde_json::json!(progress))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Картинка по ссылке из APOD, читаемая по частям; больше APOD_MEDIA_MAX_BYTES
+/// или не картинка — ошибка, без сохранения
+async fn download_apod_media(
+    st: &AppState,
+    url: &str,
+    attempts: &http::Attempts,
+) -> Result<ApodMediaRow, ApiError> {
+    let max_bytes = st.config.apod_media_max_bytes;
+    let too_large = || ApiError::invalid_payload(format!("APOD image is larger than {} bytes", max_bytes));
+    let client = &st.upstream.client;
+    let timeout = st.config.http_timeout("apod_media");
+    let retry = st.config.retry_policy("apod_media");
+    let mut resp = http::send(&st.upstream, &retry, attempts, || client.get(url).timeout(timeout)).await?;
+    if !resp.status().is_success() {
+        return Err(ApiError::upstream(
+            resp.status().as_u16(),
+            format!("APOD image request failed: {}", resp.status()),
+        ));
+    }
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    // Вместо картинки иногда приходит HTML-страница с ошибкой
+    if !content_type.starts_with("image/") {
+        return Err(ApiError::invalid_payload(format!(
+            "APOD media is {:?}, not an image",
+            content_type
+        )));
+    }
+    if resp.content_length().is_some_and(|len| len > max_bytes) {
+        return Err(too_large());
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = resp.chunk().await? {
+        if (bytes.len() + chunk.len()) as u64 > max_bytes {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(ApodMediaRow { content_type, bytes })
+}
+
+/// Предел страниц за одну синхронизацию истории SpaceX на случай, если
+/// upstream будет бесконечно сообщать hasNextPage
+const SPACEX_PAST_MAX_PAGES: i64 = 50;
+
+/// Дозагружает в spacex_launches прошедшие запуски начиная с последней
+/// сохранённой даты; возвращает число записанных запусков
+async fn sync_spacex_past(st: &AppState) -> Result<u64, ApiError> {
+    let since: Option<DateTime<Utc>> = sqlx::query_scalar("SELECT max(date_utc) FROM spacex_launches")
+        .fetch_one(&st.pool)
+        .await?;
+    let mut page = 1;
+    let mut stored = 0;
+    for _ in 0..SPACEX_PAST_MAX_PAGES {
+        let payload = fetch_spacex_past_page(st, &spacex::past_query(since, page)).await?;
+        let (launches, next) = spacex::past_page(&payload).map_err(ApiError::invalid_payload)?;
+        stored += upsert_spacex_launches(&st.pool, &launches).await?;
+        match next {
+            Some(next) => page = next,
+            None => break,
+        }
+    }
+    if stored > 0 {
+        info!("spacex_launches: {} launches stored", stored);
+    }
+    Ok(stored)
+}
+
+/// Страница POST /v4/launches/query. Неудачи пишутся в fetch_errors
+async fn fetch_spacex_past_page(st: &AppState, body: &Value) -> Result<Value, ApiError> {
+    let started = std::time::Instant::now();
+    let attempts = http::Attempts::default();
+    let result = async {
+        let client = &st.upstream.client;
+        let timeout = st.config.http_timeout("spacex_past");
+        let resp = http::send(&st.upstream, &st.config.retry_policy("spacex_past"), &attempts, || {
+            client.post("https://api.spacexdata.com/v4/launches/query").json(body).timeout(timeout)
+        })
+        .await?;
+        read_json(st, "spacex_past", resp, started, &attempts).await.map(|fetched| fetched.json)
+    }
+    .await;
+    if let Err(e) = &result {
+        let latency = elapsed_ms(started);
+        record_fetch_error(&st.pool, "spacex_past", e.upstream_status(), e, latency, attempts.get(), attempts.request_id()).await;
+    }
+    result
+}
+
+async fn upsert_spacex_launches(pool: &PgPool, launches: &[spacex::PastLaunch]) -> Result<u64, ApiError> {
+    if launches.is_empty() {
+        return Ok(0);
+    }
+    let done = sqlx::query(
+        "INSERT INTO spacex_launches(id, name, date_utc, success, rocket, launchpad, details, raw)
+         SELECT * FROM UNNEST($1::text[], $2::text[], $3::timestamptz[], $4::bool[],
+                              $5::text[], $6::text[], $7::text[], $8::jsonb[])
+         ON CONFLICT (id) DO UPDATE
+         SET name = EXCLUDED.name,
+             date_utc = EXCLUDED.date_utc,
+             success = EXCLUDED.success,
+             rocket = EXCLUDED.rocket,
+             launchpad = EXCLUDED.launchpad,
+             details = EXCLUDED.details,
+             raw = EXCLUDED.raw,
+             updated_at = now()"
+    )
+    .bind(launches.iter().map(|l| l.id.clone()).collect::<Vec<_>>())
+    .bind(launches.iter().map(|l| l.name.clone()).collect::<Vec<_>>())
+    .bind(launches.iter().map(|l| l.date_utc).collect::<Vec<_>>())
+    .bind(launches.iter().map(|l| l.success).collect::<Vec<_>>())
+    .bind(launches.iter().map(|l| l.rocket.clone()).collect::<Vec<_>>())
+    .bind(launches.iter().map(|l| l.launchpad.clone()).collect::<Vec<_>>())
+    .bind(launches.iter().map(|l| l.details.clone()).collect::<Vec<_>>())
+    .bind(launches.iter().map(|l| l.raw.clone()).collect::<Vec<_>>())
+    .execute(pool)
+    .await?;
+    Ok(done.rows_affected())
+}
+
+/// Дни APOD по запросу `query` с записью в apod_entries. Неудачи пишутся
+/// в fetch_errors как apod_archive, чтобы не смешиваться с источником apod
+async fn fetch_apod_entries(st: &AppState, query: Vec<(&'static str, String)>) -> Result<Vec<ApodEntry>, ApiError> {
+    let started = std::time::Instant::now();
+    let attempts = http::Attempts::default();
+    let result = async {
+        let client = &st.upstream.client;
+        let timeout = st.config.http_timeout("apod_archive");
+        let query = feeds::nasa_query(st, query);
+        let resp = http::send(&st.upstream, &st.config.retry_policy("apod_archive"), &attempts, || {
+            client.get("https://api.nasa.gov/planetary/apod").query(&query).timeout(timeout)
+        })
+        .await?;
+        let fetched = read_json(st, "apod_archive", resp, started, &attempts).await?;
+        apod::entries(&fetched.json).map_err(ApiError::invalid_payload)
+    }
+    .await;
+    let entries = match result {
+        Ok(entries) => entries,
+        Err(e) => {
+            let latency = elapsed_ms(started);
+            record_fetch_error(&st.pool, "apod_archive", e.upstream_status(), &e, latency, attempts.get(), attempts.request_id())
+                .await;
+            return Err(e);
+        }
+    };
+    upsert_apod_entries(&st.pool, &entries).await?;
+    Ok(entries)
+}
+
+async fn upsert_apod_entries(pool: &PgPool, entries: &[ApodEntry]) -> Result<(), ApiError> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    sqlx::query(
+        "INSERT INTO apod_entries(date, title, explanation, media_type, url, hdurl,
+                                  thumbnail_url, copyright, raw)
+         SELECT * FROM UNNEST($1::date[], $2::text[], $3::text[], $4::text[], $5::text[],
+                              $6::text[], $7::text[], $8::text[], $9::jsonb[])
+         ON CONFLICT (date) DO UPDATE
+         SET title = EXCLUDED.title,
+             explanation = EXCLUDED.explanation,
+             media_type = EXCLUDED.media_type,
+             url = EXCLUDED.url,
+             hdurl = EXCLUDED.hdurl,
+             thumbnail_url = EXCLUDED.thumbnail_url,
+             copyright = EXCLUDED.copyright,
+             raw = EXCLUDED.raw,
+             fetched_at = now()"
+    )
+    .bind(entries.iter().map(|e| e.date).collect::<Vec<_>>())
+    .bind(entries.iter().map(|e| e.title.clone()).collect::<Vec<_>>())
+    .bind(entries.iter().map(|e| e.explanation.clone()).collect::<Vec<_>>())
+    .bind(entries.iter().map(|e| e.media_type.clone()).collect::<Vec<_>>())
+    .bind(entries.iter().map(|e| e.url.clone()).collect::<Vec<_>>())
+    .bind(entries.iter().map(|e| e.hdurl.clone()).collect::<Vec<_>>())
+    .bind(entries.iter().map(|e| e.thumbnail_url.clone()).collect::<Vec<_>>())
+    .bind(entries.iter().map(|e| e.copyright.clone()).collect::<Vec<_>>())
+    .bind(entries.iter().map(|e| e.raw.clone()).collect::<Vec<_>>())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Страница каталога NEO. Неудачи пишутся в fetch_errors
+async fn fetch_neo_browse_page(st: &AppState, page: i64) -> Result<Value, ApiError> {
+    let started = std::time::Instant::now();
+    let attempts = http::Attempts::default();
+    let result = async {
+        let client = &st.upstream.client;
+        let timeout = st.config.http_timeout("neo_browse");
+        let query = feeds::nasa_query(st, vec![("page", page.to_string())]);
+        let resp = http::send(&st.upstream, &st.config.retry_policy("neo_browse"), &attempts, || {
+            client.get("https://api.nasa.gov/neo/rest/v1/neo/browse").query(&query).timeout(timeout)
+        })
+        .await?;
+        read_json(st, "neo_browse", resp, started, &attempts).await.map(|fetched| fetched.json)
+    }
+    .await;
+    if let Err(e) = &result {
+        let latency = elapsed_ms(started);
+        record_fetch_error(&st.pool, "neo_browse", e.upstream_status(), e, latency, attempts.get(), attempts.request_id()).await;
+    }
+    result
+}
+
+/// Объект NeoWs по id; None — NASA о нём не знает. Неудачи пишутся в fetch_errors
+async fn fetch_neo_lookup(st: &AppState, neo_reference_id: &str) -> Result<Option<Value>, ApiError> {
+    let started = std::time::Instant::now();
+    let attempts = http::Attempts::default();
+    let result = request_neo_lookup(st, neo_reference_id, started, &attempts).await;
+    if let Err(e) = &result {
+        let latency = elapsed_ms(started);
+        record_fetch_error(&st.pool, "neo_lookup", e.upstream_status(), e, latency, attempts.get(), attempts.request_id()).await;
+    }
+    result
+}
+
+/// Условные заголовки не используются: у neo_lookup один набор валидаторов на все объекты
+async fn request_neo_lookup(
+    st: &AppState,
+    neo_reference_id: &str,
+    started: std::time::Instant,
+    attempts: &http::Attempts,
+) -> Result<Option<Value>, ApiError> {
+    let client = &st.upstream.client;
+    let timeout = st.config.http_timeout("neo_lookup");
+    let url = format!("https://api.nasa.gov/neo/rest/v1/neo/{}", neo_reference_id);
+    let query = feeds::nasa_query(st, Vec::new());
+    let resp = http::send(&st.upstream, &st.config.retry_policy("neo_lookup"), attempts, || {
+        client.get(&url).query(&query).timeout(timeout)
+    })
+    .await?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let fetched = read_json(st, "neo_lookup", resp, started, attempts).await?;
+    write_cache(st, "neo_lookup", fetched.json.clone(), fetched.meta).await?;
+    Ok(Some(fetched.json))
+}
+
+/// Снимок и валидаторы сохраняются вместе: валидатор без снимка дал бы
+/// вечный 304 на данные, которых у нас нет
+async fn store_fetched(st: &AppState, source: &str, fetched: Fetched) -> Result<(), ApiError> {
+    write_cache(st, source, fetched.json, fetched.meta).await?;
+    save_validators(&st.pool, source, &fetched.validators).await
+}
+
+/// Upstream без валидаторов просто очищает запись — следующий запрос будет обычным
+async fn save_validators(pool: &PgPool, source: &str, validators: &Validators) -> Result<(), ApiError> {
+    if validators.is_empty() {
+        sqlx::query("DELETE FROM source_state WHERE source = $1")
+            .bind(source)
+            .execute(pool)
+            .await?;
+        return Ok(());
+    }
+    sqlx::query(
+        "INSERT INTO source_state(source, etag, last_modified, updated_at)
+         VALUES ($1, $2, $3, now())
+         ON CONFLICT (source) DO UPDATE
+         SET etag = EXCLUDED.etag, last_modified = EXCLUDED.last_modified, updated_at = now()"
+    )
+    .bind(source)
+    .bind(&validators.etag)
+    .bind(&validators.last_modified)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Отмечает проверку без изменений; false — снимков источника нет
+async fn touch_cache(pool: &PgPool, source: &str, meta: FetchMeta) -> Result<bool, ApiError> {
+    let updated = sqlx::query(
+        "UPDATE space_cache
+         SET last_checked_at = now(), http_status = $2, latency_ms = $3, body_bytes = $4, attempts = $5
+         WHERE id = (SELECT max(id) FROM space_cache WHERE source = $1)"
+    )
+    .bind(source)
+    .bind(meta.http_status)
+    .bind(meta.latency_ms)
+    .bind(meta.body_bytes)
+    .bind(meta.attempts)
+    .execute(pool)
+    .await?
+    .rows_affected();
+    if updated > 0 {
+        record_fetch_success(pool, source, &meta).await;
+    }
+    Ok(updated > 0)
+}
+
+/// TLE МКС и готовый к записи снимок с ним
+async fn fetch_tle(st: &AppState) -> Result<(Tle, Fetched), ApiError> {
+    let started = std::time::Instant::now();
+    let attempts = http::Attempts::default();
+    let result = request_tle(st, started, &attempts).await;
+    if let Err(e) = &result {
+        let latency = elapsed_ms(started);
+        record_fetch_error(&st.pool, "tle", e.upstream_status(), e, latency, attempts.get(), attempts.request_id()).await;
+    }
+    result
+}
+
+async fn request_tle(
+    st: &AppState,
+    started: std::time::Instant,
+    attempts: &http::Attempts,
+) -> Result<(Tle, Fetched), ApiError> {
+    let url = "https://celestrak.org/NORAD/elements/gp.php";
+    let client = &st.upstream.client;
+    let timeout = st.config.http_timeout("tle");
+
+    let resp = http::send(&st.upstream, &st.config.retry_policy("tle"), attempts, || {
+        client.get(url).query(&[("CATNR", "25544"), ("FORMAT", "TLE")]).timeout(timeout)
+    })
+    .await?;
+    if !resp.status().is_success() {
+        let mut message = format!("Celestrak request failed: {}", resp.status());
+        if let Some(until) = hold_off_on_429(st, "tle", &resp) {
+            message = format!("{}, retry after {}", message, until.to_rfc3339());
+        }
+        return Err(ApiError::upstream(resp.status().as_u16(), http::with_attempts(message, attempts)));
+    }
 
-async fn osdr_list(State(st): State<AppState>) -> ApiResult<Value> {
-    let limit = std::env::var("OSDR_LIST_LIMIT")
-        .ok()
-        .and_then(|s| s.parse::<i64>().ok())
-        .unwrap_or(20);
+    let status = resp.status().as_u16();
+    let text = resp.text().await?;
+    let meta = FetchMeta::new(status, started, text.len(), attempts);
+    let tle = Tle::parse(&text).map_err(|e| ApiError::invalid_payload(format!("bad TLE: {}", e)))?;
 
-    let rows = sqlx::query(
-        "SELECT id, dataset_id, title, status, updated_at, inserted_at, raw
-         FROM osdr_items
-         ORDER BY inserted_at DESC
-         LIMIT $1"
-    )
-    .bind(limit)
-    .fetch_all(&st.pool)
-    .await?;
+    let mut payload = serde_json::to_value(&tle).map_err(|e| ApiError::internal(e.to_string()))?;
+    payload["raw"] = Value::String(text);
+    let fetched = Fetched { json: payload, validators: Validators::default(), meta };
+    Ok((tle, fetched))
+}
 
-    let items: Vec<Value> = rows
-        .into_iter()
-        .map(|r| {
-            serde_json::json!({
-                "id": r.get::<i64, _>("id"),
-                "dataset_id": r.get::<Option<String>, _>("dataset_id"),
-                "title": r.get::<Option<String>, _>("title"),
-                "status": r.get::<Option<String>, _>("status"),
-                "updated_at": r.get::<Option<DateTime<Utc>>, _>("updated_at"),
-                "inserted_at": r.get::<DateTime<Utc>, _>("inserted_at"),
-                "raw": r.get::<Value, _>("raw"),
-            })
-        })
+/* ---------- Helper Functions ---------- */
+fn parse_time_param(
+    q: &HashMap<String, String>,
+    key: &str,
+) -> Result<Option<DateTime<Utc>>, ApiError> {
+    match q.get(key).map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        Some(s) => DateTime::parse_from_rfc3339(s)
+            .map(|dt| Some(dt.with_timezone(&Utc)))
+            .map_err(|_| ApiError::validation(format!("{} must be an RFC3339 timestamp", key))),
+        None => Ok(None),
+    }
+}
+
+/// Список через запятую; пустые элементы отбрасываются, пустой список — None
+fn parse_list_param(q: &HashMap<String, String>, key: &str) -> Option<Vec<String>> {
+    let items: Vec<String> = q
+        .get(key)?
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
         .collect();
+    (!items.is_empty()).then_some(items)
+}
 
-    ok(serde_json::json!({ "items": items }))
+fn parse_f64_param(q: &HashMap<String, String>, key: &str) -> Result<Option<f64>, ApiError> {
+    match q.get(key).map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        Some(s) => s
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .map(Some)
+            .ok_or_else(|| ApiError::validation(format!("{} must be a number", key))),
+        None => Ok(None),
+    }
 }
 
-/* ---------- Space Cache Handlers ---------- */
-use std::collections::HashMap;
+fn parse_bool_param(q: &HashMap<String, String>, key: &str) -> Result<Option<bool>, ApiError> {
+    match q.get(key).map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty()) {
+        Some(s) => match s.as_str() {
+            "true" | "1" | "yes" => Ok(Some(true)),
+            "false" | "0" | "no" => Ok(Some(false)),
+            _ => Err(ApiError::validation(format!("{} must be true or false", key))),
+        },
+        None => Ok(None),
+    }
+}
 
-async fn space_latest(
-    Path(src): Path<String>,
-    State(st): State<AppState>,
-) -> ApiResult<Value> {
-    let row = sqlx::query(
-        "SELECT fetched_at, payload FROM space_cache
-         WHERE source = $1 ORDER BY id DESC LIMIT 1"
-    )
-    .bind(&src)
-    .fetch_optional(&st.pool)
-    .await?;
+fn parse_i64_param(q: &HashMap<String, String>, key: &str) -> Result<Option<i64>, ApiError> {
+    match q.get(key).map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        Some(s) => s
+            .parse::<i64>()
+            .map(Some)
+            .map_err(|_| ApiError::validation(format!("{} must be an integer", key))),
+        None => Ok(None),
+    }
+}
 
-    if let Some(r) = row {
-        let fetched_at: DateTime<Utc> = r.try_get("fetched_at")
-            .map_err(|e| ApiError::database(e.to_string()))?;
-        let payload: Value = r.try_get("payload")
-            .unwrap_or_else(|_| serde_json::json!({}));
-        
-        return ok(serde_json::json!({
-            "source": src,
-            "fetched_at": fetched_at,
-            "payload": payload
-        }));
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IssStoreOutcome {
+    Inserted,
+    SkippedDuplicate,
+}
+
+/// `fetch_and_store_iss`, общий для одновременных вызовов с тем же спутником
+/// и force; true — результат получен от чужого вызова
+async fn fetch_and_store_iss_shared(
+    st: &AppState,
+    norad_id: i64,
+    force: bool,
+) -> (Result<IssStoreOutcome, ApiError>, bool) {
+    let key = format!("{} force={}", norad_id, force);
+    let owned = st.clone();
+    st.iss_flights
+        .run(&key, || async move { fetch_and_store_iss(&owned, norad_id, force).await })
+        .await
+}
+
+/// Положение с одного зеркала; неудача пишется в fetch_errors, а
+/// непригодный ответ ещё и в журнал отказов
+async fn fetch_iss_mirror(st: &AppState, url: &str) -> Result<(Value, IssPosition, FetchMeta), ApiError> {
+    let pool = &st.pool;
+    let client = &st.upstream.client;
+    let timeout = st.config.http_timeout("iss");
+
+    let started = std::time::Instant::now();
+    let attempts = http::Attempts::default();
+    let response = async {
+        let resp = http::send(&st.upstream, &st.config.retry_policy("iss"), &attempts, || client.get(url).timeout(timeout))
+            .await?;
+        let status = resp.status().as_u16();
+        Ok::<_, ApiError>((status, resp.text().await?))
     }
+    .await;
+    let (status, body) = match response {
+        Ok(r) => r,
+        Err(e) => {
+            let latency = elapsed_ms(started);
+            record_fetch_error(pool, "iss", e.upstream_status(), &e, latency, attempts.get(), attempts.request_id()).await;
+            return Err(e);
+        }
+    };
+    let meta = FetchMeta::new(status, started, body.len(), &attempts);
 
-    ok(serde_json::json!({
-        "source": src,
-        "message": "no data"
-    }))
+    let parsed = serde_json::from_str::<Value>(&body)
+        .map_err(|e| format!("non-JSON body: {}", e))
+        .and_then(|json| IssPosition::from_payload(&json).map(|pos| (json, pos)));
+
+    match parsed {
+        Ok((json, pos)) => {
+            record_fetch_success(pool, "iss", &meta).await;
+            Ok((json, pos, meta))
+        }
+        Err(reason) => {
+            warn!("rejected ISS payload from {} (HTTP {}): {}", url, status, reason);
+            record_iss_reject(pool, url, status, &reason, &body).await?;
+            let err = ApiError::invalid_payload(reason);
+            record_fetch_error(pool, "iss", Some(status), &err, meta.latency_ms, attempts.get(), attempts.request_id()).await;
+            Err(err)
+        }
+    }
 }
 
-async fn space_refresh(
-    Query(q): Query<HashMap<String, String>>,
-    State(st): State<AppState>,
-) -> ApiResult<Value> {
-    let list = q
-        .get("src")
-        .cloned()
-        .unwrap_or_else(|| "apod,neo,flr,cme,spacex".to_string());
-    
-    let mut done = Vec::new();
-    
-    for s in list.split(',').map(|x| x.trim().to_lowercase()) {
-        match s.as_str() {
-            "apod" => {
-                let _ = fetch_apod(&st).await;
-                done.push("apod");
-            }
-            "neo" => {
-                let _ = fetch_neo_feed(&st).await;
-                done.push("neo");
-            }
-            "flr" => {
-                let _ = fetch_donki_flr(&st).await;
-                done.push("flr");
-            }
-            "cme" => {
-                let _ = fetch_donki_cme(&st).await;
-                done.push("cme");
+async fn fetch_and_store_iss(
+    st: &AppState,
+    norad_id: i64,
+    force: bool,
+) -> Result<IssStoreOutcome, ApiError> {
+    let pool = &st.pool;
+    let mirrors = st.iss_mirrors.order(st.config.satellite_mirror_urls(norad_id), Utc::now());
+
+    // Зеркала по очереди до первого пригодного ответа; в source_url попадает
+    // то, которое ответило
+    let mut last_error = ApiError::not_found(format!("no WHERE_ISS_URL mirror serves satellite {}", norad_id));
+    let mut fetched = None;
+    for url in mirrors {
+        match fetch_iss_mirror(st, &url).await {
+            Ok(ok) => {
+                st.iss_mirrors.succeeded(&url);
+                fetched = Some((url, ok));
+                break;
             }
-            "spacex" => {
-                let _ = fetch_spacex_next(&st).await;
-                done.push("spacex");
+            Err(e) => {
+                warn!("ISS mirror {} failed: {}", url, e.error.message);
+                st.iss_mirrors.failed(&url, Utc::now());
+                last_error = e;
             }
-            _ => {}
         }
     }
-    
-    ok(serde_json::json!({ "refreshed": done }))
-}
+    let Some((url, (json, pos, meta))) = fetched else {
+        return Err(last_error);
+    };
 
-async fn latest_from_cache(pool: &PgPool, src: &str) -> Value {
-    sqlx::query(
-        "SELECT fetched_at, payload FROM space_cache 
-         WHERE source=$1 ORDER BY id DESC LIMIT 1"
-    )
-    .bind(src)
-    .fetch_optional(pool)
-    .await
-    .ok()
-    .flatten()
-    .map(|r| {
-        serde_json::json!({
-            "at": r.get::<DateTime<Utc>, _>("fetched_at"),
-            "payload": r.get::<Value, _>("payload")
-        })
-    })
-    .unwrap_or_else(|| serde_json::json!({}))
-}
+    if !force && is_duplicate_of_last(pool, norad_id, &json, &pos).await? {
+        let total = st.iss_duplicates_skipped.fetch_add(1, Ordering::Relaxed) + 1;
+        info!(
+            "sat {} payload unchanged since last sample, skipped (total skipped: {})",
+            norad_id, total
+        );
+        return Ok(IssStoreOutcome::SkippedDuplicate);
+    }
 
-async fn space_summary(State(st): State<AppState>) -> ApiResult<Value> {
-    let apod = latest_from_cache(&st.pool, "apod").await;
-    let neo = latest_from_cache(&st.pool, "neo").await;
-    let flr = latest_from_cache(&st.pool, "flr").await;
-    let cme = latest_from_cache(&st.pool, "cme").await;
-    let spacex = latest_from_cache(&st.pool, "spacex").await;
+    let anomalies = detect_iss_anomalies(st, norad_id, &pos).await?;
 
-    let iss_last = sqlx::query(
-        "SELECT fetched_at, payload FROM iss_fetch_log 
-         ORDER BY id DESC LIMIT 1"
+    // Выборка и её аномалии пишутся вместе: флаг не теряется и не повисает без записи
+    let mut tx = pool.begin().await?;
+    let log_id: i64 = sqlx::query_scalar(
+        "INSERT INTO iss_fetch_log
+            (source_url, payload, latitude, longitude, altitude_km, velocity_kmh,
+             sample_time, visibility, norad_id, http_status, latency_ms, body_bytes, attempts)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+         RETURNING id"
     )
-    .fetch_optional(&st.pool)
-    .await
-    .ok()
-    .flatten()
-    .map(|r| {
-        serde_json::json!({
-            "at": r.get::<DateTime<Utc>, _>("fetched_at"),
-            "payload": r.get::<Value, _>("payload")
-        })
-    })
-    .unwrap_or_else(|| serde_json::json!({}));
+    .bind(url)
+    .bind(json)
+    .bind(pos.latitude)
+    .bind(pos.longitude)
+    .bind(pos.altitude)
+    .bind(pos.velocity)
+    .bind(pos.sample_time())
+    .bind(pos.visibility)
+    .bind(norad_id)
+    .bind(meta.http_status)
+    .bind(meta.latency_ms)
+    .bind(meta.body_bytes)
+    .bind(meta.attempts)
+    .fetch_one(&mut *tx)
+    .await?;
 
-    let osdr_count: i64 = sqlx::query("SELECT count(*) AS c FROM osdr_items")
-        .fetch_one(&st.pool)
-        .await
-        .map(|r| r.get::<i64, _>("c"))
-        .unwrap_or(0);
+    for a in anomalies {
+        warn!("sat {} sample {} flagged: {}", norad_id, log_id, a.reason);
+        sqlx::query(
+            "INSERT INTO iss_anomalies(log_id, norad_id, field, value, median, reason)
+             VALUES ($1, $2, $3, $4, $5, $6)"
+        )
+        .bind(log_id)
+        .bind(norad_id)
+        .bind(a.field)
+        .bind(a.value)
+        .bind(a.median)
+        .bind(a.reason)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
 
-    ok(serde_json::json!({
-        "apod": apod,
-        "neo": neo,
-        "flr": flr,
-        "cme": cme,
-        "spacex": spacex,
-        "iss": iss_last,
-        "osdr_count": osdr_count
-    }))
-}
+    // Ошибка отправки означает лишь отсутствие подписчиков
+    if st.iss_events.receiver_count() > 0 {
+        let _ = st.iss_events.send(load_last_iss(st, norad_id).await?);
+    }
 
-/* ---------- Fetch Functions ---------- */
-async fn write_cache(pool: &PgPool, source: &str, payload: Value) -> Result<(), ApiError> {
-    sqlx::query("INSERT INTO space_cache(source, payload) VALUES ($1, $2)")
-        .bind(source)
-        .bind(payload)
-        .execute(pool)
-        .await?;
-    Ok(())
+    Ok(IssStoreOutcome::Inserted)
 }
 
-async fn fetch_apod(st: &AppState) -> Result<(), ApiError> {
-    let url = "https://api.nasa.gov/planetary/apod";
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(30))
-        .build()?;
-    
-    let mut req = client.get(url).query(&[("thumbs", "true")]);
-    
-    if !st.config.nasa_api_key.is_empty() {
-        req = req.query(&[("api_key", &st.config.nasa_api_key)]);
+/// Сравнение новой выборки со скользящей медианой последних записей спутника
+async fn detect_iss_anomalies(
+    st: &AppState,
+    norad_id: i64,
+    pos: &IssPosition,
+) -> Result<Vec<anomaly::Anomaly>, ApiError> {
+    let rows = sqlx::query(
+        "SELECT velocity_kmh, altitude_km FROM iss_fetch_log
+         WHERE norad_id = $1
+         ORDER BY fetched_at DESC, id DESC LIMIT $2"
+    )
+    .bind(norad_id)
+    .bind(ANOMALY_WINDOW)
+    .fetch_all(&st.pool)
+    .await?;
+
+    let mut velocities = Vec::with_capacity(rows.len());
+    let mut altitudes = Vec::with_capacity(rows.len());
+    for row in &rows {
+        velocities.extend(row.try_get::<Option<f64>, _>("velocity_kmh")?);
+        altitudes.extend(row.try_get::<Option<f64>, _>("altitude_km")?);
     }
-    
-    let json: Value = req.send().await?.json().await?;
-    write_cache(&st.pool, "apod", json).await
+    let thresholds = AnomalyThresholds {
+        velocity_kmh: st.config.anomaly_velocity_kmh as f64,
+        altitude_km: st.config.anomaly_altitude_km as f64,
+    };
+    Ok(anomaly::detect(pos.velocity, pos.altitude, &velocities, &altitudes, thresholds))
 }
 
-async fn fetch_neo_feed(st: &AppState) -> Result<(), ApiError> {
-    let today = Utc::now().date_naive();
-    let start = today - chrono::Days::new(2);
-    let url = "https://api.nasa.gov/neo/rest/v1/feed";
-    
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(30))
-        .build()?;
-    
-    let mut req = client.get(url).query(&[
-        ("start_date", start.to_string()),
-        ("end_date", today.to_string()),
-    ]);
-    
-    if !st.config.nasa_api_key.is_empty() {
-        req = req.query(&[("api_key", &st.config.nasa_api_key)]);
+/// Ответ считается повтором, если он побайтно совпадает с последней записью
+/// или upstream-время измерения не продвинулось
+async fn is_duplicate_of_last(
+    pool: &PgPool,
+    norad_id: i64,
+    json: &Value,
+    pos: &IssPosition,
+) -> Result<bool, ApiError> {
+    let last = sqlx::query(
+        "SELECT payload, sample_time FROM iss_fetch_log
+         WHERE norad_id = $1
+         ORDER BY fetched_at DESC, id DESC LIMIT 1"
+    )
+    .bind(norad_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(row) = last else {
+        return Ok(false);
+    };
+
+    let last_payload: Value = row.try_get("payload").unwrap_or(Value::Null);
+    if &last_payload == json {
+        return Ok(true);
     }
-    
-    let json: Value = req.send().await?.json().await?;
-    write_cache(&st.pool, "neo", json).await
+
+    let last_time: Option<DateTime<Utc>> = row.try_get("sample_time")?;
+    Ok(matches!(
+        (last_time, pos.sample_time()),
+        (Some(prev), Some(next)) if next <= prev
+    ))
 }
 
-async fn fetch_donki(st: &AppState) -> Result<(), ApiError> {
-    let _ = fetch_donki_flr(st).await;
-    let _ = fetch_donki_cme(st).await;
+/// Сохраняет отвергнутый ответ (тело обрезается до 4 КБ) для последующего разбора
+async fn record_iss_reject(
+    pool: &PgPool,
+    url: &str,
+    status: u16,
+    reason: &str,
+    body: &str,
+) -> Result<(), ApiError> {
+    let body: String = body.chars().take(4096).collect();
+    sqlx::query(
+        "INSERT INTO iss_fetch_rejects(source_url, http_status, reason, body)
+         VALUES ($1, $2, $3, $4)"
+    )
+    .bind(url)
+    .bind(i32::from(status))
+    .bind(reason)
+    .bind(body)
+    .execute(pool)
+    .await?;
     Ok(())
 }
 
-async fn fetch_donki_flr(st: &AppState) -> Result<(), ApiError> {
-    let (from, to) = last_days(5);
-    let url = "https://api.nasa.gov/DONKI/FLR";
-    
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(30))
-        .build()?;
-    
-    let mut req = client.get(url).query(&[("startDate", from), ("endDate", to)]);
-    
-    if !st.config.nasa_api_key.is_empty() {
-        req = req.query(&[("api_key", &st.config.nasa_api_key)]);
+/// Синхронизация OSDR с записью итога в osdr_sync_log, в том числе при ошибке
+async fn fetch_and_store_osdr(st: &AppState) -> Result<SyncStats, ApiError> {
+    let started_at = Utc::now();
+    let timer = std::time::Instant::now();
+    let mut stats = SyncStats::default();
+    let result = sync_osdr_pages(st, &mut stats).await;
+    stats.elapsed_ms = timer.elapsed().as_millis() as u64;
+
+    let logged = sqlx::query(
+        "INSERT INTO osdr_sync_log
+            (started_at, finished_at, pages, inserted, updated, unchanged, attempts, outcome, error)
+         VALUES ($1, now(), $2, $3, $4, $5, $6, $7, $8)"
+    )
+    .bind(started_at)
+    .bind(stats.pages as i32)
+    .bind(stats.inserted as i32)
+    .bind(stats.updated as i32)
+    .bind(stats.unchanged as i32)
+    .bind(stats.attempts as i32)
+    .bind(if result.is_ok() { "success" } else { "failed" })
+    .bind(result.as_ref().err().map(|e| e.error.message.clone()))
+    .execute(&st.pool)
+    .await;
+    if let Err(e) = logged {
+        error!("failed to record OSDR sync run: {}", e);
     }
-    
-    let json: Value = req.send().await?.json().await?;
-    write_cache(&st.pool, "flr", json).await
-}
 
-async fn fetch_donki_cme(st: &AppState) -> Result<(), ApiError> {
-    let (from, to) = last_days(5);
-    let url = "https://api.nasa.gov/DONKI/CME";
-    
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(30))
-        .build()?;
-    
-    let mut req = client.get(url).query(&[("startDate", from), ("endDate", to)]);
-    
-    if !st.config.nasa_api_key.is_empty() {
-        req = req.query(&[("api_key", &st.config.nasa_api_key)]);
+    // Доставка идёт отдельной задачей и на итог синхронизации не влияет
+    if !stats.events.is_empty() {
+        let st = st.clone();
+        let events = std::mem::take(&mut stats.events);
+        tokio::spawn(async move { deliver_webhooks(&st, &events).await });
     }
-    
-    let json: Value = req.send().await?.json().await?;
-    write_cache(&st.pool, "cme", json).await
-}
 
-async fn fetch_spacex_next(st: &AppState) -> Result<(), ApiError> {
-    let url = "https://api.spacexdata.com/v4/launches/next";
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(30))
-        .build()?;
-    
-    let json: Value = client.get(url).send().await?.json().await?;
-    write_cache(&st.pool, "spacex", json).await
+    result.map(|_| stats)
 }
 
-fn last_days(n: i64) -> (String, String) {
-    let to = Utc::now().date_naive();
-    let from = to - chrono::Days::new(n as u64);
-    (from.to_string(), to.to_string())
-}
+/// Все страницы OSDR. Страницы копятся и пишутся пачками от
+/// UPSERT_BATCH_SIZE элементов, каждая пачка — одна транзакция из целых
+/// страниц. `stats` накапливается по ходу, чтобы при ошибке остался частичный итог
+async fn sync_osdr_pages(st: &AppState, stats: &mut SyncStats) -> Result<(), ApiError> {
+    let mut url = reqwest::Url::parse(&st.config.nasa_api_url)
+        .map_err(|e| ApiError::internal(format!("invalid NASA_API_URL: {}", e)))?;
+    let mut seen = std::collections::HashSet::new();
+    let mut seen_ids = Vec::new();
+    let mut pending = Vec::new();
 
-/* ---------- Helper Functions ---------- */
-fn s_pick(v: &Value, keys: &[&str]) -> Option<String> {
-    for k in keys {
-        if let Some(x) = v.get(*k) {
-            if let Some(s) = x.as_str() {
-                if !s.is_empty() {
-                    return Some(s.to_string());
+    loop {
+        seen.insert(url.clone());
+        let json = match fetch_osdr_page(st, &url, stats).await {
+            Ok(json) => json,
+            Err(e) => {
+                // Уже полученные страницы не теряем
+                if let Err(flush_err) = store_osdr_items(st, &mut pending, stats, &mut seen_ids).await {
+                    error!("failed to store fetched OSDR pages: {}", flush_err.error.message);
                 }
-            } else if x.is_number() {
-                return Some(x.to_string());
+                return Err(e);
             }
+        };
+        pending.extend(osdr::page_items(&json));
+        stats.pages += 1;
+        if pending.len() >= osdr::UPSERT_BATCH_SIZE {
+            store_osdr_items(st, &mut pending, stats, &mut seen_ids).await?;
+        }
+
+        let Some(next) = osdr::next_page_url(&url, &json) else {
+            break;
+        };
+        if seen.contains(&next) {
+            warn!("OSDR pagination returned already visited {}, stopping", next);
+            stats.truncated = true;
+            break;
+        }
+        if stats.pages as u64 >= st.config.osdr_max_pages {
+            warn!("OSDR sync stopped after {} pages (OSDR_MAX_PAGES)", stats.pages);
+            stats.truncated = true;
+            break;
         }
+        url = next;
     }
-    None
+    store_osdr_items(st, &mut pending, stats, &mut seen_ids).await?;
+
+    // Пропавшими считаем только после полного обхода: на частичном
+    // и на пустом ответе отметили бы всю таблицу
+    if stats.truncated || seen_ids.is_empty() {
+        warn!("OSDR sync incomplete, missing datasets are not updated");
+        return Ok(());
+    }
+    let marked = sqlx::query(
+        "UPDATE osdr_items SET missing_since = now()
+         WHERE dataset_id IS NOT NULL
+           AND missing_since IS NULL
+           AND NOT (dataset_id = ANY($1))"
+    )
+    .bind(&seen_ids)
+    .execute(&st.pool)
+    .await?;
+    stats.marked_missing = marked.rows_affected() as usize;
+
+    Ok(())
 }
 
-fn t_pick(v: &Value, keys: &[&str]) -> Option<DateTime<Utc>> {
-    for k in keys {
-        if let Some(x) = v.get(*k) {
-            if let Some(s) = x.as_str() {
-                if let Ok(dt) = s.parse::<DateTime<Utc>>() {
-                    return Some(dt);
-                }
-                if let Ok(ndt) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
-                    return Some(Utc.from_utc_datetime(&ndt));
-                }
-            } else if let Some(n) = x.as_i64() {
-                return Utc.timestamp_opt(n, 0).single();
-            }
+/// Страница OSDR с повторами по RetryPolicy; попытки копятся в `stats`
+async fn fetch_osdr_page(st: &AppState, url: &reqwest::Url, stats: &mut SyncStats) -> Result<Value, ApiError> {
+    let attempts = http::Attempts::default();
+    let retry = st.config.retry_policy("osdr");
+    let timeout = st.config.http_timeout("osdr");
+    let result = http::retrying_get_json(&st.upstream, url.as_str(), &[], timeout, &retry, &attempts).await;
+    stats.attempts += attempts.get() as usize;
+    result.map_err(|mut e| {
+        e.error.message = format!("OSDR {}", e.error.message);
+        e
+    })
+}
+
+/// Пишет накопленные элементы в одной транзакции и очищает `pending`.
+/// Элементы с dataset_id идут многострочными запросами, без него — по одному
+async fn store_osdr_items(
+    st: &AppState,
+    pending: &mut Vec<Value>,
+    stats: &mut SyncStats,
+    seen_ids: &mut Vec<String>,
+) -> Result<(), ApiError> {
+    if pending.is_empty() {
+        return Ok(());
+    }
+    let keys = &st.config.osdr_keys;
+    let mut keyed = Vec::new();
+    let mut unkeyed = Vec::new();
+    for item in pending.drain(..) {
+        let fields = TrackedFields {
+            title: s_pick(&item, &keys.title),
+            status: s_pick(&item, &keys.status),
+            updated_at: t_pick(&item, &keys.updated),
+        };
+        let meta = OsdrMeta::extract(&item, keys);
+        match s_pick(&item, &keys.id).and_then(|id| osdr::normalize_dataset_id(&id)) {
+            Some(dataset_id) => keyed.push(PreparedItem {
+                dataset_id,
+                fields,
+                meta,
+                hash: content_hash(&item),
+                raw: item,
+            }),
+            None => unkeyed.push((fields, meta, item)),
         }
     }
-    None
+    let received = keyed.len();
+    let keyed = osdr::dedupe_by_dataset_id(keyed);
+    if keyed.len() < received {
+        let collapsed = received - keyed.len();
+        warn!("OSDR response repeats dataset_id, collapsed {} duplicates", collapsed);
+        stats.duplicates += collapsed;
+    }
+    let ids: Vec<String> = keyed.iter().map(|i| i.dataset_id.clone()).collect();
+
+    let mut events = Vec::new();
+    let mut tx = st.pool.begin().await?;
+    for chunk in keyed.chunks(osdr::UPSERT_BATCH_SIZE) {
+        upsert_osdr_batch(&mut tx, chunk.to_vec(), stats, &mut events).await?;
+    }
+    for (fields, meta, item) in unkeyed {
+        insert_unkeyed_osdr_item(&mut tx, fields, meta, item, stats, &mut events).await?;
+    }
+    tx.commit().await?;
+
+    seen_ids.extend(ids);
+    stats.events.extend(events);
+    Ok(())
 }
 
-async fn fetch_and_store_iss(pool: &PgPool, url: &str) -> Result<(), ApiError> {
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(20))
-        .build()?;
-    
-    let resp = client.get(url).send().await?;
-    let json: Value = resp.json().await?;
-    
-    sqlx::query("INSERT INTO iss_fetch_log (source_url, payload) VALUES ($1, $2)")
-        .bind(url)
-        .bind(json)
-        .execute(pool)
+/// Пачка элементов с dataset_id: один SELECT существующих строк и по одному
+/// запросу на вставку/обновление, отметку неизменных и журнал изменений
+async fn upsert_osdr_batch(
+    conn: &mut sqlx::PgConnection,
+    items: Vec<PreparedItem>,
+    stats: &mut SyncStats,
+    events: &mut Vec<ItemEvent>,
+) -> Result<(), ApiError> {
+    let ids: Vec<&str> = items.iter().map(|i| i.dataset_id.as_str()).collect();
+    // raw нужен только строкам, записанным до появления raw_hash
+    let rows = sqlx::query(
+        "SELECT id, dataset_id, title, status, updated_at,
+                raw_hash, CASE WHEN raw_hash IS NULL THEN raw END AS raw
+         FROM osdr_items WHERE dataset_id = ANY($1)"
+    )
+    .bind(&ids)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut existing = HashMap::with_capacity(rows.len());
+    for row in rows {
+        let hash = match row.try_get::<Option<String>, _>("raw_hash")? {
+            Some(h) => h,
+            None => content_hash(&row.try_get::<Value, _>("raw")?),
+        };
+        let stored = StoredItem {
+            id: row.try_get("id")?,
+            fields: TrackedFields {
+                title: row.try_get("title")?,
+                status: row.try_get("status")?,
+                updated_at: row.try_get("updated_at")?,
+            },
+            hash,
+        };
+        existing.insert(row.try_get::<String, _>("dataset_id")?, stored);
+    }
+    let plan = osdr::plan_batch(items, &existing);
+
+    if !plan.unchanged.is_empty() {
+        let (row_ids, hashes): (Vec<i64>, Vec<String>) = plan.unchanged.iter().cloned().unzip();
+        sqlx::query(
+            "UPDATE osdr_items AS o
+             SET raw_hash = v.raw_hash, last_synced_at = now(), missing_since = NULL
+             FROM UNNEST($1::bigint[], $2::text[]) AS v(id, raw_hash)
+             WHERE o.id = v.id"
+        )
+        .bind(&row_ids)
+        .bind(&hashes)
+        .execute(&mut *conn)
         .await?;
-    
+        stats.unchanged += plan.unchanged.len();
+    }
+
+    // Журнал изменений пишется в той же транзакции, что и сами строки
+    let changes: Vec<(i64, &str, &osdr::FieldChange)> = plan
+        .changed
+        .iter()
+        .flat_map(|(row_id, item, changes)| {
+            changes.iter().map(move |c| (*row_id, item.dataset_id.as_str(), c))
+        })
+        .collect();
+    if !changes.is_empty() {
+        sqlx::query(
+            "INSERT INTO osdr_changes(item_id, dataset_id, field, old_value, new_value)
+             SELECT * FROM UNNEST($1::bigint[], $2::text[], $3::text[], $4::text[], $5::text[])"
+        )
+        .bind(changes.iter().map(|c| c.0).collect::<Vec<_>>())
+        .bind(changes.iter().map(|c| c.1).collect::<Vec<_>>())
+        .bind(changes.iter().map(|c| c.2.field).collect::<Vec<_>>())
+        .bind(changes.iter().map(|c| c.2.old_value.clone()).collect::<Vec<_>>())
+        .bind(changes.iter().map(|c| c.2.new_value.clone()).collect::<Vec<_>>())
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    let written: Vec<&PreparedItem> = plan
+        .new
+        .iter()
+        .chain(plan.changed.iter().map(|(_, item, _)| item))
+        .collect();
+    if written.is_empty() {
+        return Ok(());
+    }
+    let returned: Vec<(i64, String)> = sqlx::query_as(
+        "INSERT INTO osdr_items(dataset_id, title, status, updated_at, raw, raw_hash,
+                                organism, assay_type, mission, last_synced_at)
+         SELECT v.*, now()
+         FROM UNNEST($1::text[], $2::text[], $3::text[], $4::timestamptz[], $5::jsonb[], $6::text[],
+                     $7::text[], $8::text[], $9::text[])
+              AS v(dataset_id, title, status, updated_at, raw, raw_hash, organism, assay_type, mission)
+         ON CONFLICT (dataset_id) WHERE dataset_id IS NOT NULL DO UPDATE
+         SET title = EXCLUDED.title, status = EXCLUDED.status, updated_at = EXCLUDED.updated_at,
+             raw = EXCLUDED.raw, raw_hash = EXCLUDED.raw_hash,
+             organism = EXCLUDED.organism, assay_type = EXCLUDED.assay_type, mission = EXCLUDED.mission,
+             last_synced_at = now(), missing_since = NULL
+         RETURNING id, dataset_id"
+    )
+    .bind(written.iter().map(|i| i.dataset_id.as_str()).collect::<Vec<_>>())
+    .bind(written.iter().map(|i| i.fields.title.clone()).collect::<Vec<_>>())
+    .bind(written.iter().map(|i| i.fields.status.clone()).collect::<Vec<_>>())
+    .bind(written.iter().map(|i| i.fields.updated_at).collect::<Vec<_>>())
+    .bind(written.iter().map(|i| i.raw.clone()).collect::<Vec<_>>())
+    .bind(written.iter().map(|i| i.hash.as_str()).collect::<Vec<_>>())
+    .bind(written.iter().map(|i| i.meta.organism.clone()).collect::<Vec<_>>())
+    .bind(written.iter().map(|i| i.meta.assay_type.clone()).collect::<Vec<_>>())
+    .bind(written.iter().map(|i| i.meta.mission.clone()).collect::<Vec<_>>())
+    .fetch_all(&mut *conn)
+    .await?;
+    let new_ids: HashMap<String, i64> = returned.into_iter().map(|(id, ds)| (ds, id)).collect();
+
+    stats.inserted += plan.new.len();
+    stats.updated += plan.changed.len();
+    for item in plan.new {
+        events.push(ItemEvent {
+            event: EVENT_OSDR_NEW,
+            id: new_ids.get(&item.dataset_id).copied().unwrap_or_default(),
+            dataset_id: Some(item.dataset_id),
+            title: item.fields.title,
+            status: item.fields.status,
+        });
+    }
+    for (row_id, item, _) in plan.changed {
+        events.push(ItemEvent {
+            event: EVENT_OSDR_UPDATED,
+            id: row_id,
+            dataset_id: Some(item.dataset_id),
+            title: item.fields.title,
+            status: item.fields.status,
+        });
+    }
     Ok(())
 }
 
-async fn fetch_and_store_osdr(st: &AppState) -> Result<usize, ApiError> {
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(30))
-        .build()?;
-    
-    let resp = client.get(&st.config.nasa_api_url).send().await?;
-    
-    if !resp.status().is_success() {
-        return Err(ApiError::upstream(
-            resp.status().as_u16(),
-            format!("OSDR request failed: {}", resp.status()),
-        ));
-    }
-    
-    let json: Value = resp.json().await?;
-    let items = if let Some(a) = json.as_array() {
-        a.clone()
-    } else if let Some(v) = json.get("items").and_then(|x| x.as_array()) {
-        v.clone()
-    } else if let Some(v) = json.get("results").and_then(|x| x.as_array()) {
-        v.clone()
-    } else {
-        vec![json.clone()]
+/// Элемент без dataset_id сопоставить не с чем — он всегда вставляется новой строкой
+async fn insert_unkeyed_osdr_item(
+    conn: &mut sqlx::PgConnection,
+    fields: TrackedFields,
+    meta: OsdrMeta,
+    item: Value,
+    stats: &mut SyncStats,
+    events: &mut Vec<ItemEvent>,
+) -> Result<(), ApiError> {
+    let hash = content_hash(&item);
+    let row_id: i64 = sqlx::query_scalar(
+        "INSERT INTO osdr_items(dataset_id, title, status, updated_at, raw, raw_hash,
+                                organism, assay_type, mission, last_synced_at)
+         VALUES(NULL, $1, $2, $3, $4, $5, $6, $7, $8, now())
+         RETURNING id"
+    )
+    .bind(&fields.title)
+    .bind(&fields.status)
+    .bind(fields.updated_at)
+    .bind(item)
+    .bind(hash)
+    .bind(meta.organism)
+    .bind(meta.assay_type)
+    .bind(meta.mission)
+    .fetch_one(&mut *conn)
+    .await?;
+    stats.inserted += 1;
+    events.push(ItemEvent {
+        event: EVENT_OSDR_NEW,
+        id: row_id,
+        dataset_id: None,
+        title: fields.title,
+        status: fields.status,
+    });
+    Ok(())
+}
+
+/// Рассылает события подписчикам; ошибки только логируются и пишутся
+/// в webhook_deliveries
+async fn deliver_webhooks(st: &AppState, events: &[ItemEvent]) {
+    let hooks = match sqlx::query_as::<_, WebhookRow>(
+        "SELECT id, url, event, title_filter, created_at FROM webhooks ORDER BY id"
+    )
+    .fetch_all(&st.pool)
+    .await
+    {
+        Ok(hooks) => hooks,
+        Err(e) => {
+            error!("failed to load webhooks: {}", e);
+            return;
+        }
     };
+    if hooks.is_empty() {
+        return;
+    }
 
-    let mut written = 0usize;
-    
-    for item in items {
-        let id = s_pick(
-            &item,
-            &["dataset_id", "id", "uuid", "studyId", "accession", "osdr_id"],
-        );
-        let title = s_pick(&item, &["title", "name", "label"]);
-        let status = s_pick(&item, &["status", "state", "lifecycle"]);
-        let updated = t_pick(
-            &item,
-            &["updated", "updated_at", "modified", "lastUpdated", "timestamp"],
-        );
+    let user_agent = http::user_agent(st.config.service_contact.as_deref());
 
-        if let Some(ds) = id.clone() {
-            sqlx::query(
-                "INSERT INTO osdr_items(dataset_id, title, status, updated_at, raw)
-                 VALUES($1, $2, $3, $4, $5)
-                 ON CONFLICT (dataset_id) DO UPDATE
-                 SET title=EXCLUDED.title, status=EXCLUDED.status,
-                     updated_at=EXCLUDED.updated_at, raw=EXCLUDED.raw"
-            )
-            .bind(ds)
-            .bind(title)
-            .bind(status)
-            .bind(updated)
-            .bind(item)
-            .execute(&st.pool)
-            .await?;
-        } else {
-            sqlx::query(
-                "INSERT INTO osdr_items(dataset_id, title, status, updated_at, raw)
-                 VALUES($1, $2, $3, $4, $5)"
-            )
-            .bind::<Option<String>>(None)
-            .bind(title)
-            .bind(status)
-            .bind(updated)
-            .bind(item)
-            .execute(&st.pool)
-            .await?;
+    for hook in hooks {
+        let items: Vec<&ItemEvent> = events
+            .iter()
+            .filter(|e| webhooks::matches(&hook.event, hook.title_filter.as_deref(), e))
+            .collect();
+        if items.is_empty() {
+            continue;
+        }
+        let body = serde_json::json!({
+            "webhook_id": hook.id,
+            "event": hook.event,
+            "sent_at": Utc::now(),
+            "count": items.len(),
+            "items": items
+        });
+
+        let (attempts, http_status, error) =
+            webhooks::post_with_retry(&hook.url, &body, &user_agent, webhooks::is_internal).await;
+        if let Some(e) = &error {
+            warn!("webhook {} delivery to {} failed: {}", hook.id, hook.url, e);
+        }
+        let recorded = sqlx::query(
+            "INSERT INTO webhook_deliveries
+                (webhook_id, event, item_count, attempts, http_status, success, error)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)"
+        )
+        .bind(hook.id)
+        .bind(&hook.event)
+        .bind(items.len() as i32)
+        .bind(attempts as i32)
+        .bind(http_status.map(i32::from))
+        .bind(error.is_none())
+        .bind(error)
+        .execute(&st.pool)
+        .await;
+        if let Err(e) = recorded {
+            error!("failed to record webhook {} delivery: {}", hook.id, e);
         }
-        
-        written += 1;
     }
-    
-    Ok(written)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future::BoxFuture;
+    use std::sync::atomic::AtomicUsize;
+
+    /// Источник поверх медленного mock-сервера; ответ в space_cache не пишется
+    struct SlowSource {
+        url: String,
+    }
+
+    impl SpaceSource for SlowSource {
+        fn name(&self) -> &'static str {
+            "slow"
+        }
+
+        fn interval(&self, _cfg: &Config) -> Duration {
+            Duration::from_secs(3600)
+        }
+
+        fn fetch<'a>(&'a self, st: &'a AppState) -> BoxFuture<'a, Result<Option<Fetched>, ApiError>> {
+            Box::pin(async move {
+                let attempts = http::Attempts::default();
+                let policy = st.config.retry_policy("slow");
+                http::send(&st.upstream, &policy, &attempts, || st.upstream.client.get(&self.url)).await?;
+                Ok(None)
+            })
+        }
+    }
+
+    /// Mock-сервер, отвечающий через 300 мс, и состояние с источником slow.
+    /// Пул ленивый: до БД эти тесты не доходят
+    async fn slow_upstream() -> (AppState, Arc<AtomicUsize>) {
+        use axum::routing::get;
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let slow = move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                "{}"
+            }
+        };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/slow", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, Router::new().route("/slow", get(slow))).await.unwrap() });
+
+        let database_url = "postgres://localhost/unused".to_string();
+        let pool = PgPoolOptions::new().connect_lazy(&database_url).unwrap();
+        let config = Config::with_database_url(database_url).unwrap();
+        let sources = SourceRegistry::new(vec![Arc::new(SlowSource { url })]);
+        (AppState::new(pool, config, sources).unwrap(), hits)
+    }
+
+    #[test]
+    fn csv_line_uses_coordinates_from_any_mirror() {
+        let payload = serde_json::json!({
+            "message": "success", "timestamp": 1700000000,
+            "iss_position": {"latitude": "51.2", "longitude": "-47.8"}
+        });
+        let position = StoredPosition::from(IssPosition::from_payload(&payload).unwrap());
+        let fetched_at = DateTime::from_timestamp(1700000000, 0).unwrap();
+        let url = "http://api.open-notify.org/iss-now.json";
+        assert_eq!(
+            format_iss_csv_line(7, fetched_at, Some(&position), url),
+            "7,2023-11-14T22:13:20+00:00,51.2,-47.8,,,http://api.open-notify.org/iss-now.json\n"
+        );
+        assert_eq!(
+            format_iss_csv_line(8, fetched_at, None, url),
+            "8,2023-11-14T22:13:20+00:00,,,,,http://api.open-notify.org/iss-now.json\n"
+        );
+    }
+
+    #[test]
+    fn osdr_export_reads_raw_only_when_requested() {
+        let fields = ["title".to_string(), "id".to_string()];
+        let columns = osdr_export_columns(Some(&fields));
+        assert!(columns.contains("'null'::jsonb AS raw"), "{}", columns);
+        assert!(columns.starts_with("id, NULL::text AS dataset_id, title,"), "{}", columns);
+        assert!(osdr_export_columns(None).ends_with("mission, raw"));
+    }
+
+    #[tokio::test]
+    async fn overlapping_fetch_source_calls_skip_the_busy_source() {
+        let (st, hits) = slow_upstream().await;
+        let src = st.sources.get("slow").unwrap().clone();
+        let (a, b) = futures::future::join(fetch_source(&st, &src), fetch_source(&st, &src)).await;
+
+        let mut runs = [a.unwrap(), b.unwrap()];
+        runs.sort_by_key(|r| *r == FetchRun::InProgress);
+        assert_eq!(runs, [FetchRun::Done, FetchRun::InProgress]);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        // Блокировка отпущена: следующий вызов снова идёт к upstream
+        assert_eq!(fetch_source(&st, &src).await.unwrap(), FetchRun::Done);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn concurrent_space_refreshes_share_one_upstream_request() {
+        let (st, hits) = slow_upstream().await;
+        let refreshes = (0..20).map(|_| refresh_sources(st.clone(), vec!["slow".to_string()], None));
+        let responses = futures::future::join_all(refreshes).await;
+
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        let results: Vec<Value> = responses
+            .into_iter()
+            .map(|r| serde_json::to_value(&r.unwrap().0.data).unwrap()["results"]["slow"].clone())
+            .collect();
+        assert!(results.iter().all(|r| r["status"] == "refreshed"), "{:?}", results);
+        assert_eq!(results.iter().filter(|r| r["coalesced"] == true).count(), 19);
+    }
+}
@@ -0,0 +1,126 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// Марсоходы Mars Rover Photos API и их камеры
+const ROVERS: &[(&str, &[&str])] = &[
+    ("curiosity", &["FHAZ", "RHAZ", "MAST", "CHEMCAM", "MAHLI", "MARDI", "NAVCAM"]),
+    (
+        "perseverance",
+        &[
+            "EDL_RUCAM",
+            "EDL_RDCAM",
+            "EDL_DDCAM",
+            "EDL_PUCAM1",
+            "EDL_PUCAM2",
+            "NAVCAM_LEFT",
+            "NAVCAM_RIGHT",
+            "MCZ_LEFT",
+            "MCZ_RIGHT",
+            "FRONT_HAZCAM_LEFT_A",
+            "FRONT_HAZCAM_RIGHT_A",
+            "REAR_HAZCAM_LEFT",
+            "REAR_HAZCAM_RIGHT",
+            "SKYCAM",
+            "SHERLOC_WATSON",
+            "SUPERCAM_RMI",
+            "LCAM",
+        ],
+    ),
+    ("opportunity", &["FHAZ", "RHAZ", "NAVCAM", "PANCAM", "MINITES"]),
+    ("spirit", &["FHAZ", "RHAZ", "NAVCAM", "PANCAM", "MINITES"]),
+];
+
+/// Имя марсохода в нижнем регистре; неизвестное — ошибка со списком допустимых
+pub fn parse_rover(raw: &str) -> Result<&'static str, String> {
+    let name = raw.trim().to_lowercase();
+    ROVERS
+        .iter()
+        .map(|(rover, _)| *rover)
+        .find(|rover| *rover == name)
+        .ok_or_else(|| {
+            let names: Vec<&str> = ROVERS.iter().map(|(rover, _)| *rover).collect();
+            format!("unknown rover {:?}, expected one of {}", raw, names.join(", "))
+        })
+}
+
+/// Сокращение камеры в верхнем регистре, если она есть у марсохода
+pub fn parse_camera(rover: &str, raw: &str) -> Result<&'static str, String> {
+    let cameras = ROVERS
+        .iter()
+        .find(|(name, _)| *name == rover)
+        .map(|(_, cameras)| *cameras)
+        .unwrap_or_default();
+    let abbrev = raw.trim().to_uppercase();
+    cameras.iter().copied().find(|c| *c == abbrev).ok_or_else(|| {
+        format!("unknown camera {:?} for {}, expected one of {}", raw, rover, cameras.join(", "))
+    })
+}
+
+/// Фотография в сокращённом виде для /space/mars/photos
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MarsPhoto {
+    pub id: i64,
+    pub img_src: String,
+    pub earth_date: Option<String>,
+    pub camera: String,
+    pub sol: Option<i64>,
+}
+
+impl MarsPhoto {
+    fn from_value(photo: &Value) -> Option<Self> {
+        Some(Self {
+            id: photo.get("id")?.as_i64()?,
+            img_src: photo.get("img_src")?.as_str()?.to_string(),
+            earth_date: photo.get("earth_date").and_then(Value::as_str).map(str::to_string),
+            camera: photo.pointer("/camera/name")?.as_str()?.to_string(),
+            sol: photo.get("sol").and_then(Value::as_i64),
+        })
+    }
+}
+
+/// Фотографии из ответа latest_photos (или photos) с камеры `camera`, если она
+/// задана; записи без id, img_src или камеры пропускаются
+pub fn photos(payload: &Value, camera: Option<&str>) -> Result<Vec<MarsPhoto>, String> {
+    let items = payload
+        .get("latest_photos")
+        .or_else(|| payload.get("photos"))
+        .and_then(Value::as_array)
+        .ok_or("missing latest_photos array")?;
+    Ok(items
+        .iter()
+        .filter_map(MarsPhoto::from_value)
+        .filter(|p| camera.is_none_or(|c| p.camera.eq_ignore_ascii_case(c)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn validates_rovers_and_cameras() {
+        assert_eq!(parse_rover(" Curiosity "), Ok("curiosity"));
+        assert!(parse_rover("sojourner").unwrap_err().contains("curiosity, perseverance"));
+        assert_eq!(parse_camera("curiosity", "navcam"), Ok("NAVCAM"));
+        assert!(parse_camera("curiosity", "PANCAM").unwrap_err().contains("FHAZ, RHAZ, MAST"));
+    }
+
+    #[test]
+    fn filters_photos_by_camera() {
+        let payload = json!({"latest_photos": [
+            {"id": 1, "sol": 4300, "img_src": "https://mars/1.jpg", "earth_date": "2026-10-14",
+             "camera": {"name": "NAVCAM", "full_name": "Navigation Camera"}},
+            {"id": 2, "sol": 4300, "img_src": "https://mars/2.jpg", "earth_date": "2026-10-14",
+             "camera": {"name": "MAST"}},
+            {"id": 3, "img_src": "https://mars/3.jpg"}
+        ]});
+        let all = photos(&payload, None).unwrap();
+        assert_eq!(all.len(), 2);
+        let navcam = photos(&payload, Some("NAVCAM")).unwrap();
+        assert_eq!(navcam.len(), 1);
+        assert_eq!(navcam[0].id, 1);
+        assert_eq!(navcam[0].sol, Some(4300));
+        assert!(photos(&json!({}), None).is_err());
+    }
+}
@@ -0,0 +1,264 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+
+use crate::compression::StoredPayload;
+use crate::iss::{IssPosition, StoredPosition};
+
+/// Строка iss_fetch_log. В JSON попадает только исходная запись
+/// (id, fetched_at, source_url, payload), типизированные колонки
+/// доступны через `position()`
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct IssLogRow {
+    pub id: i64,
+    pub fetched_at: DateTime<Utc>,
+    pub source_url: String,
+    pub payload: Value,
+    #[serde(skip)]
+    pub norad_id: i64,
+    #[serde(skip)]
+    pub latitude: Option<f64>,
+    #[serde(skip)]
+    pub longitude: Option<f64>,
+    #[serde(skip)]
+    pub altitude_km: Option<f64>,
+    #[serde(skip)]
+    pub velocity_kmh: Option<f64>,
+    #[serde(skip)]
+    pub sample_time: Option<DateTime<Utc>>,
+    #[serde(skip)]
+    pub visibility: Option<String>,
+}
+
+impl IssLogRow {
+    /// Положение из типизированных колонок, а для старых строк без них —
+    /// из сырого payload
+    pub fn position(&self) -> Option<StoredPosition> {
+        match (self.latitude, self.longitude) {
+            (Some(latitude), Some(longitude)) => Some(StoredPosition {
+                latitude,
+                longitude,
+                altitude_km: self.altitude_km,
+                velocity_kmh: self.velocity_kmh,
+                sample_time: self.sample_time,
+                visibility: self.visibility.clone(),
+            }),
+            _ => IssPosition::from_payload(&self.payload).ok().map(StoredPosition::from),
+        }
+    }
+}
+
+/// Строка osdr_items
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct OsdrItemRow {
+    pub id: i64,
+    pub dataset_id: Option<String>,
+    pub title: Option<String>,
+    pub status: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub inserted_at: DateTime<Utc>,
+    /// С какого момента датасета нет в ответе upstream
+    pub missing_since: Option<DateTime<Utc>>,
+    pub organism: Option<String>,
+    pub assay_type: Option<String>,
+    pub mission: Option<String>,
+    pub raw: Value,
+}
+
+/// Изменение поля датасета OSDR
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct OsdrChangeRow {
+    pub id: i64,
+    pub dataset_id: String,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Запуск синхронизации OSDR; error заполнен у неудачных
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct OsdrSyncLogRow {
+    pub id: i64,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub pages: i32,
+    pub inserted: i32,
+    pub updated: i32,
+    pub unchanged: i32,
+    /// HTTP-запросов к upstream, включая повторы
+    pub attempts: i32,
+    /// success или failed
+    pub outcome: String,
+    pub error: Option<String>,
+}
+
+/// Прошедший запуск SpaceX из spacex_launches
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct SpacexLaunchRow {
+    pub id: String,
+    pub name: String,
+    pub date_utc: DateTime<Utc>,
+    pub success: Option<bool>,
+    pub rocket: Option<String>,
+    pub launchpad: Option<String>,
+    pub details: Option<String>,
+}
+
+/// Запуски SpaceX за год; success_rate — доля успешных среди запусков
+/// с известным итогом, %
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct SpacexYearStatsRow {
+    pub year: i32,
+    pub launches: i64,
+    pub successes: i64,
+    pub failures: i64,
+    pub success_rate: Option<f64>,
+}
+
+/// День APOD из apod_entries
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ApodEntryRow {
+    pub date: NaiveDate,
+    pub title: Option<String>,
+    pub explanation: Option<String>,
+    pub media_type: Option<String>,
+    pub url: Option<String>,
+    pub hdurl: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub copyright: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// День APOD в галерее /space/apod
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ApodGalleryRow {
+    pub date: NaiveDate,
+    pub title: Option<String>,
+    pub media_type: Option<String>,
+    pub url: Option<String>,
+    pub thumbnail_url: Option<String>,
+    /// Картинка уже скачана и отдаётся через /space/apod/image
+    pub image_cached: bool,
+}
+
+/// Скачанная картинка APOD из apod_media
+#[derive(Debug, Clone, FromRow)]
+pub struct ApodMediaRow {
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Строка neo_objects для /neo/list; исходный объект остаётся в raw таблицы
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct NeoObjectRow {
+    pub neo_reference_id: String,
+    pub name: String,
+    pub est_diameter_min_m: Option<f64>,
+    pub est_diameter_max_m: Option<f64>,
+    pub is_potentially_hazardous: bool,
+    pub close_approach_time: DateTime<Utc>,
+    pub miss_distance_km: Option<f64>,
+    pub relative_velocity_kms: Option<f64>,
+}
+
+/// Последний снимок источника из space_cache. fetched_at — когда содержимое
+/// последний раз изменилось, last_checked_at — когда источник опрашивали
+#[derive(Debug, Clone, FromRow)]
+pub struct SpaceCacheRow {
+    pub fetched_at: DateTime<Utc>,
+    pub last_checked_at: DateTime<Utc>,
+    pub payload: Value,
+    pub payload_compressed: Option<Vec<u8>>,
+}
+
+/// Снимок источника из space_cache для истории
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct SpaceCacheEntry {
+    pub id: i64,
+    pub fetched_at: DateTime<Utc>,
+    pub last_checked_at: DateTime<Utc>,
+    pub payload: Value,
+    #[serde(skip)]
+    pub payload_compressed: Option<Vec<u8>>,
+}
+
+/// Строка space_cache в формате /space/export.ndjson и /space/import.ndjson
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct SpaceCacheDump {
+    pub id: i64,
+    pub source: String,
+    pub fetched_at: DateTime<Utc>,
+    pub payload: Value,
+    #[serde(skip)]
+    pub payload_compressed: Option<Vec<u8>>,
+}
+
+impl StoredPayload for SpaceCacheRow {
+    fn payload_slots(&mut self) -> (&mut Value, &mut Option<Vec<u8>>) {
+        (&mut self.payload, &mut self.payload_compressed)
+    }
+}
+
+impl StoredPayload for SpaceCacheEntry {
+    fn payload_slots(&mut self) -> (&mut Value, &mut Option<Vec<u8>>) {
+        (&mut self.payload, &mut self.payload_compressed)
+    }
+}
+
+impl StoredPayload for SpaceCacheDump {
+    fn payload_slots(&mut self) -> (&mut Value, &mut Option<Vec<u8>>) {
+        (&mut self.payload, &mut self.payload_compressed)
+    }
+}
+
+/// Сводка по одному источнику space_cache
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct SpaceSourceRow {
+    pub source: String,
+    pub rows: i64,
+    pub latest_fetched_at: DateTime<Utc>,
+    pub last_checked_at: DateTime<Utc>,
+    /// Размер payload последнего снимка в хранилище, байт (после сжатия TOAST)
+    pub latest_payload_bytes: i32,
+}
+
+/// Показатели запросов к источнику за окно по строкам fetch_attempts
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct FetchMetricsRow {
+    pub successes: i64,
+    pub errors: i64,
+    pub latency_p50_ms: Option<f64>,
+    pub latency_p90_ms: Option<f64>,
+    pub latency_p99_ms: Option<f64>,
+    /// Без 304 и ошибок, у которых тела нет
+    pub avg_body_bytes: Option<f64>,
+    pub avg_attempts: Option<f64>,
+    /// Запросов, которым понадобился хотя бы один повтор
+    pub retried: i64,
+}
+
+/// Подписка на события OSDR
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct WebhookRow {
+    pub id: i64,
+    pub url: String,
+    pub event: String,
+    pub title_filter: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Итог доставки одного события подписчику после всех попыток
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct WebhookDeliveryRow {
+    pub id: i64,
+    pub webhook_id: i64,
+    pub event: String,
+    pub item_count: i32,
+    pub attempts: i32,
+    pub http_status: Option<i32>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub delivered_at: DateTime<Utc>,
+}
@@ -0,0 +1,221 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::geo::extract_number;
+
+/// Среднее расстояние от Земли до Луны, км
+pub const LUNAR_DISTANCE_KM: f64 = 384_400.0;
+
+/// Окно ленты NeoWs вокруг текущей даты: NASA отдаёт не больше 7 дней,
+/// берутся два прошедших и четыре предстоящих
+pub const FEED_DAYS_BEFORE: u64 = 2;
+pub const FEED_DAYS_AHEAD: u64 = 4;
+
+/// Расстояние в лунных дистанциях (LD)
+pub fn lunar_distances(km: f64) -> f64 {
+    km / LUNAR_DISTANCE_KM
+}
+
+/// Одно сближение объекта из ленты NeoWs, готовое к записи в neo_objects
+#[derive(Debug, Clone, PartialEq)]
+pub struct NeoApproach {
+    pub neo_reference_id: String,
+    pub name: String,
+    pub est_diameter_min_m: Option<f64>,
+    pub est_diameter_max_m: Option<f64>,
+    pub is_potentially_hazardous: bool,
+    pub close_approach_time: DateTime<Utc>,
+    pub miss_distance_km: Option<f64>,
+    pub relative_velocity_kms: Option<f64>,
+    pub raw: Value,
+}
+
+/// Все сближения из ответа /neo/rest/v1/feed. Объекты, которые не удалось
+/// разобрать, не мешают остальным: они возвращаются вторым списком с причиной.
+/// Повторы отбрасываются — иначе upsert одной пачкой упал бы на дубликате
+pub fn parse_feed(payload: &Value) -> Result<(Vec<NeoApproach>, Vec<String>), String> {
+    let days = payload
+        .get("near_earth_objects")
+        .and_then(Value::as_object)
+        .ok_or("missing near_earth_objects map")?;
+    let mut parsed = Vec::new();
+    let mut failures = Vec::new();
+    for (date, objects) in days {
+        for (i, object) in objects.as_array().into_iter().flatten().enumerate() {
+            match approaches(object) {
+                Ok(mut object_approaches) => parsed.append(&mut object_approaches),
+                Err(reason) => failures.push(format!("{}[{}]: {}", date, i, reason)),
+            }
+        }
+    }
+    dedupe(&mut parsed);
+    Ok((parsed, failures))
+}
+
+/// Оставляет первое сближение каждой пары (neo_reference_id, close_approach_time)
+pub fn dedupe(approaches: &mut Vec<NeoApproach>) {
+    let mut seen = HashSet::new();
+    approaches.retain(|a| seen.insert((a.neo_reference_id.clone(), a.close_approach_time)));
+}
+
+/// Сближения одного объекта NeoWs с Землёй; так разбирается и элемент
+/// ленты, и ответ /neo/rest/v1/neo/{id}, где есть сближения с другими телами
+pub fn approaches(object: &Value) -> Result<Vec<NeoApproach>, String> {
+    let id = object
+        .get("neo_reference_id")
+        .or_else(|| object.get("id"))
+        .and_then(Value::as_str)
+        .ok_or("missing neo_reference_id")?;
+    let name = object.get("name").and_then(Value::as_str).unwrap_or(id);
+    let diameter = |key: &str| {
+        object
+            .pointer(&format!("/estimated_diameter/meters/{}", key))
+            .and_then(extract_number)
+    };
+    let data = object
+        .get("close_approach_data")
+        .and_then(Value::as_array)
+        .filter(|a| !a.is_empty())
+        .ok_or("missing close_approach_data")?;
+
+    data.iter()
+        .filter(|approach| {
+            approach
+                .get("orbiting_body")
+                .and_then(Value::as_str)
+                .is_none_or(|body| body == "Earth")
+        })
+        .map(|approach| {
+            let epoch_ms = approach
+                .get("epoch_date_close_approach")
+                .and_then(Value::as_i64)
+                .ok_or("missing epoch_date_close_approach")?;
+            let time = Utc
+                .timestamp_millis_opt(epoch_ms)
+                .single()
+                .ok_or_else(|| format!("bad epoch {}", epoch_ms))?;
+            Ok(NeoApproach {
+                neo_reference_id: id.to_string(),
+                name: name.to_string(),
+                est_diameter_min_m: diameter("estimated_diameter_min"),
+                est_diameter_max_m: diameter("estimated_diameter_max"),
+                is_potentially_hazardous: object
+                    .get("is_potentially_hazardous_asteroid")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+                close_approach_time: time,
+                miss_distance_km: approach.pointer("/miss_distance/kilometers").and_then(extract_number),
+                relative_velocity_kms: approach
+                    .pointer("/relative_velocity/kilometers_per_second")
+                    .and_then(extract_number),
+                raw: object.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Докуда дошёл обход каталога /neo/rest/v1/neo/browse; хранится в
+/// source_state.progress, чтобы переживать перезапуски
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BrowseProgress {
+    /// Следующая страница (с нуля); она же число пройденных страниц текущего прохода
+    pub pages_done: i64,
+    pub total_pages: Option<i64>,
+    /// Записано объектов с сближениями за текущий проход
+    pub objects_stored: u64,
+    /// Сколько раз каталог пройден целиком
+    pub passes: u64,
+}
+
+impl BrowseProgress {
+    /// Отмечает обработанную страницу; после последней обход начинается заново
+    pub fn page_done(&mut self, total_pages: Option<i64>, stored: u64) {
+        self.total_pages = total_pages.or(self.total_pages);
+        self.pages_done += 1;
+        self.objects_stored += stored;
+        if self.total_pages.is_some_and(|total| self.pages_done >= total) {
+            self.pages_done = 0;
+            self.objects_stored = 0;
+            self.passes += 1;
+        }
+    }
+}
+
+/// Объекты страницы browse и общее число страниц каталога
+pub fn browse_page(payload: &Value) -> Result<(&[Value], Option<i64>), String> {
+    let objects = payload
+        .get("near_earth_objects")
+        .and_then(Value::as_array)
+        .ok_or("missing near_earth_objects array")?;
+    Ok((objects, payload.pointer("/page/total_pages").and_then(Value::as_i64)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_objects_and_collects_failures() {
+        let payload = json!({"element_count": 3, "near_earth_objects": {"2026-10-16": [
+            {"neo_reference_id": "3542519", "name": "(2010 PK9)",
+             "estimated_diameter": {"meters": {"estimated_diameter_min": 120.5, "estimated_diameter_max": 269.4}},
+             "is_potentially_hazardous_asteroid": true,
+             "close_approach_data": [{"epoch_date_close_approach": 1791979200000_i64,
+                                      "miss_distance": {"kilometers": "7234567.12"},
+                                      "relative_velocity": {"kilometers_per_second": "17.5"}}]},
+            {"neo_reference_id": "3542519", "name": "(2010 PK9)",
+             "close_approach_data": [{"epoch_date_close_approach": 1791979200000_i64}]},
+            {"neo_reference_id": "54016", "name": "no approaches"},
+            {"name": "no id", "close_approach_data": []}
+        ]}});
+        let (approaches, failures) = parse_feed(&payload).unwrap();
+        assert_eq!(approaches.len(), 1);
+        let neo = &approaches[0];
+        assert_eq!(neo.neo_reference_id, "3542519");
+        assert!(neo.is_potentially_hazardous);
+        assert_eq!(neo.miss_distance_km, Some(7234567.12));
+        assert_eq!(neo.relative_velocity_kms, Some(17.5));
+        assert_eq!(neo.close_approach_time.timestamp(), 1791979200);
+        assert_eq!(failures.len(), 2);
+        assert!(failures[0].starts_with("2026-10-16[2]"));
+
+        assert!(parse_feed(&json!({})).is_err());
+    }
+
+    #[test]
+    fn keeps_only_earth_approaches() {
+        let object = json!({"neo_reference_id": "2099942", "name": "99942 Apophis",
+            "close_approach_data": [
+                {"epoch_date_close_approach": 1050000000000_i64, "orbiting_body": "Venus"},
+                {"epoch_date_close_approach": 1871769600000_i64, "orbiting_body": "Earth",
+                 "miss_distance": {"kilometers": "38012.3"}}
+            ]});
+        let parsed = approaches(&object).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].miss_distance_km, Some(38012.3));
+    }
+
+    #[test]
+    fn browse_wraps_after_last_page() {
+        let page = json!({"page": {"size": 20, "total_pages": 2, "number": 1},
+                          "near_earth_objects": [{"neo_reference_id": "2000433"}]});
+        let (objects, total) = browse_page(&page).unwrap();
+        assert_eq!((objects.len(), total), (1, Some(2)));
+
+        let mut progress = BrowseProgress::default();
+        progress.page_done(total, 40);
+        assert_eq!((progress.pages_done, progress.objects_stored), (1, 40));
+        progress.page_done(None, 15);
+        assert_eq!(progress, BrowseProgress { pages_done: 0, total_pages: Some(2), objects_stored: 0, passes: 1 });
+    }
+
+    #[test]
+    fn converts_to_lunar_distances() {
+        assert_eq!(lunar_distances(384_400.0), 1.0);
+        assert!((lunar_distances(7_234_567.12) - 18.82).abs() < 0.01);
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Уведомление DONKI /notifications
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Notification {
+    pub message_id: String,
+    pub message_type: String,
+    pub message_issue_time: String,
+    pub message_url: Option<String>,
+    /// Первый абзац текста или весь текст при full
+    pub message_body: String,
+}
+
+/// Уведомления из снимка: без повторов по messageID, с типом `message_type`
+/// (без учёта регистра), если он задан, новые первыми
+pub fn notifications(
+    payload: &Value,
+    message_type: Option<&str>,
+    full: bool,
+) -> Result<Vec<Notification>, String> {
+    let items = payload.as_array().ok_or("expected an array of notifications")?;
+    let mut seen = HashSet::new();
+    let mut out: Vec<Notification> = items
+        .iter()
+        .filter_map(|item| notification(item, full))
+        .filter(|n| message_type.is_none_or(|t| n.message_type.eq_ignore_ascii_case(t)))
+        .filter(|n| seen.insert(n.message_id.clone()))
+        .collect();
+    out.sort_by(|a, b| b.message_issue_time.cmp(&a.message_issue_time));
+    Ok(out)
+}
+
+fn notification(item: &Value, full: bool) -> Option<Notification> {
+    let text = |key: &str| item.get(key).and_then(Value::as_str).map(str::to_string);
+    let body = text("messageBody").unwrap_or_default();
+    Some(Notification {
+        message_id: text("messageID")?,
+        message_type: text("messageType").unwrap_or_default(),
+        message_issue_time: text("messageIssueTime").unwrap_or_default(),
+        message_url: text("messageURL"),
+        message_body: if full { body } else { first_paragraph(&body) },
+    })
+}
+
+/// Текст до первой пустой строки; в теле бывают и \n, и \r\n
+fn first_paragraph(body: &str) -> String {
+    let normalized = body.replace("\r\n", "\n");
+    let mut paragraphs = normalized.split("\n\n").map(str::trim).filter(|p| !p.is_empty());
+    paragraphs.next().unwrap_or_default().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn dedupes_filters_and_trims() {
+        let payload = json!([
+            {"messageID": "20261014-AL-001", "messageType": "FLR", "messageIssueTime": "2026-10-14T10:00Z",
+             "messageBody": "## Summary:\r\n\r\nM2.1 flare detected.\r\n\r\n## Notes: long text"},
+            {"messageID": "20261015-AL-002", "messageType": "CME", "messageIssueTime": "2026-10-15T08:00Z",
+             "messageBody": "CME observed.\n\nDetails"},
+            {"messageID": "20261014-AL-001", "messageType": "FLR", "messageIssueTime": "2026-10-14T10:00Z",
+             "messageBody": "duplicate"}
+        ]);
+        let all = notifications(&payload, None, false).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].message_id, "20261015-AL-002");
+        assert_eq!(all[0].message_body, "CME observed.");
+
+        let flares = notifications(&payload, Some("flr"), false).unwrap();
+        assert_eq!(flares.len(), 1);
+        assert_eq!(flares[0].message_body, "## Summary:");
+        let full = notifications(&payload, Some("FLR"), true).unwrap();
+        assert!(full[0].message_body.ends_with("long text"));
+    }
+}
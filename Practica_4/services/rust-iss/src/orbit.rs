@@ -0,0 +1,166 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+/// Звёздные сутки в минутах: за это время Земля поворачивается на 360°
+const SIDEREAL_DAY_MIN: f64 = 1436.068;
+
+/// Точка истории для оценки орбиты
+#[derive(Debug, Clone)]
+pub struct OrbitSample {
+    pub t: DateTime<Utc>,
+    pub latitude: f64,
+    pub altitude_km: Option<f64>,
+    pub velocity_kmh: Option<f64>,
+}
+
+/// Оценка орбиты по восходящим узлам
+#[derive(Debug, Clone, Serialize)]
+pub struct OrbitEstimate {
+    pub period_min: f64,
+    pub orbits_observed: u32,
+    pub crossings: usize,
+    pub mean_altitude_km: Option<f64>,
+    pub mean_velocity_kmh: Option<f64>,
+    pub orbits_per_day: f64,
+    /// Смещение трассы к западу за виток
+    pub track_shift_deg: f64,
+}
+
+/// Моменты пересечения экватора с юга на север. Пересечение засчитывается,
+/// только если соседние точки отстоят не дальше чем на `max_gap`;
+/// время уточняется линейной интерполяцией широты
+pub fn ascending_nodes(samples: &[OrbitSample], max_gap: Duration) -> Vec<DateTime<Utc>> {
+    samples
+        .windows(2)
+        .filter_map(|w| {
+            let (a, b) = (&w[0], &w[1]);
+            if !(a.latitude < 0.0 && b.latitude >= 0.0) || b.t - a.t > max_gap || b.t <= a.t {
+                return None;
+            }
+            let frac = -a.latitude / (b.latitude - a.latitude);
+            let span_ms = (b.t - a.t).num_milliseconds() as f64;
+            Some(a.t + Duration::milliseconds((span_ms * frac).round() as i64))
+        })
+        .collect()
+}
+
+/// Период по интервалам между узлами. Узел ближе половины медианного
+/// интервала к предыдущему считается шумом. Пропуски в данных могут съесть
+/// целый виток, поэтому каждый интервал делится на число витков относительно
+/// медианы. None, если узлов меньше двух
+pub fn estimate_orbit(samples: &[OrbitSample], max_gap: Duration) -> Option<OrbitEstimate> {
+    let nodes = ascending_nodes(samples, max_gap);
+    let minutes = |a: DateTime<Utc>, b: DateTime<Utc>| (b - a).num_milliseconds() as f64 / 60_000.0;
+
+    let mut raw: Vec<f64> = nodes.windows(2).map(|w| minutes(w[0], w[1])).collect();
+    if raw.is_empty() {
+        return None;
+    }
+    raw.sort_by(f64::total_cmp);
+    let median = raw[(raw.len() - 1) / 2];
+
+    let mut kept: Vec<DateTime<Utc>> = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        if kept.last().is_none_or(|&prev| minutes(prev, node) >= median / 2.0) {
+            kept.push(node);
+        }
+    }
+    let intervals: Vec<f64> = kept.windows(2).map(|w| minutes(w[0], w[1])).collect();
+    if intervals.is_empty() || median <= 0.0 {
+        return None;
+    }
+
+    let orbits: u32 = intervals
+        .iter()
+        .map(|d| ((d / median).round() as u32).max(1))
+        .sum();
+    let period_min = intervals.iter().sum::<f64>() / orbits as f64;
+
+    Some(OrbitEstimate {
+        period_min,
+        orbits_observed: orbits,
+        crossings: kept.len(),
+        mean_altitude_km: mean(samples.iter().filter_map(|s| s.altitude_km)),
+        mean_velocity_kmh: mean(samples.iter().filter_map(|s| s.velocity_kmh)),
+        orbits_per_day: 1440.0 / period_min,
+        track_shift_deg: 360.0 * period_min / SIDEREAL_DAY_MIN,
+    })
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, n) = values.fold((0.0, 0u32), |(s, n), v| (s + v, n + 1));
+    (n > 0).then(|| sum / n as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const PERIOD_MIN: f64 = 92.9;
+
+    /// Синусоида широты с наклонением МКС, точки каждые `step_min` минут
+    fn track(minutes: i64, step_min: i64) -> Vec<OrbitSample> {
+        let t0 = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        (0..=minutes / step_min)
+            .map(|i| {
+                let m = (i * step_min) as f64 + 7.0;
+                OrbitSample {
+                    t: t0 + Duration::minutes(i * step_min),
+                    latitude: 51.6 * (std::f64::consts::TAU * m / PERIOD_MIN).sin(),
+                    altitude_km: Some(420.0),
+                    velocity_kmh: Some(27_600.0),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn estimates_period_from_regular_samples() {
+        let est = estimate_orbit(&track(6 * 60, 2), Duration::minutes(10)).unwrap();
+        assert!((est.period_min - PERIOD_MIN).abs() < 0.2, "{}", est.period_min);
+        assert_eq!(est.crossings, 3);
+        assert_eq!(est.orbits_observed, 2);
+        assert_eq!(est.mean_altitude_km, Some(420.0));
+        assert!((est.orbits_per_day - 15.5).abs() < 0.1);
+    }
+
+    #[test]
+    fn tolerates_missed_orbit() {
+        // Вырезаем почти два часа: второй узел пропадает вместе с соседями
+        let full = track(8 * 60, 2);
+        let t0 = full[0].t;
+        let samples: Vec<_> = full
+            .into_iter()
+            .filter(|s| !(100..220).contains(&(s.t - t0).num_minutes()))
+            .collect();
+        let est = estimate_orbit(&samples, Duration::minutes(10)).unwrap();
+        assert!((est.period_min - PERIOD_MIN).abs() < 0.2, "{}", est.period_min);
+        assert_eq!(est.orbits_observed as usize, 4);
+        assert_eq!(est.crossings, 4);
+    }
+
+    #[test]
+    fn ignores_spurious_crossing() {
+        // Одиночный выброс широты рождает лишний узел в середине витка
+        let mut samples = track(8 * 60, 2);
+        samples[65].latitude = -5.0;
+        samples[66].latitude = 5.0;
+        let est = estimate_orbit(&samples, Duration::minutes(10)).unwrap();
+        assert!((est.period_min - PERIOD_MIN).abs() < 0.2, "{}", est.period_min);
+    }
+
+    #[test]
+    fn discards_crossings_across_gaps() {
+        let samples = track(6 * 60, 2);
+        let sparse: Vec<_> = samples.iter().step_by(10).cloned().collect();
+        assert!(ascending_nodes(&sparse, Duration::minutes(10)).is_empty());
+        assert!(estimate_orbit(&sparse, Duration::minutes(10)).is_none());
+    }
+
+    #[test]
+    fn single_crossing_is_insufficient() {
+        assert!(estimate_orbit(&track(90, 2), Duration::minutes(10)).is_none());
+        assert!(estimate_orbit(&[], Duration::minutes(10)).is_none());
+    }
+}
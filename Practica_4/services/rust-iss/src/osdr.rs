@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::OsdrKeys;
+use crate::pick::s_pick;
+use crate::webhooks::ItemEvent;
+
+/// Итог синхронизации OSDR по строкам
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct SyncStats {
+    pub inserted: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub pages: usize,
+    /// Сколько датасетов впервые не нашлось в upstream при этом запуске
+    pub marked_missing: usize,
+    /// Остановились на OSDR_MAX_PAGES или на повторе ссылки, не дойдя до конца
+    pub truncated: bool,
+    /// Сколько повторов dataset_id в ответе upstream схлопнуто
+    pub duplicates: usize,
+    /// HTTP-запросов к upstream вместе с повторами
+    pub attempts: usize,
+    /// Длительность синхронизации целиком
+    pub elapsed_ms: u64,
+    /// Новые и изменённые датасеты из зафиксированных страниц — для вебхуков
+    #[serde(skip)]
+    pub events: Vec<ItemEvent>,
+}
+
+/// Отслеживаемые поля osdr_items в текстовом виде
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrackedFields {
+    pub title: Option<String>,
+    pub status: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Описательные поля датасета: не отслеживаются в osdr_changes, но
+/// хранятся отдельными колонками для фильтров
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OsdrMeta {
+    pub organism: Option<String>,
+    pub assay_type: Option<String>,
+    pub mission: Option<String>,
+}
+
+impl OsdrMeta {
+    pub fn extract(item: &Value, keys: &OsdrKeys) -> Self {
+        Self {
+            organism: s_pick(item, &keys.organism),
+            assay_type: s_pick(item, &keys.assay_type),
+            mission: s_pick(item, &keys.mission),
+        }
+    }
+}
+
+/// Изменение одного поля для osdr_changes
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// Поля, значение которых изменилось; время сравнивается как момент, а не как строка
+pub fn field_changes(old: &TrackedFields, new: &TrackedFields) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    let mut push = |field, old_value: Option<String>, new_value: Option<String>| {
+        if old_value != new_value {
+            changes.push(FieldChange { field, old_value, new_value });
+        }
+    };
+    push("title", old.title.clone(), new.title.clone());
+    push("status", old.status.clone(), new.status.clone());
+    push(
+        "updated_at",
+        old.updated_at.map(|t| t.to_rfc3339()),
+        new.updated_at.map(|t| t.to_rfc3339()),
+    );
+    changes
+}
+
+/// Строк в одном многострочном INSERT/UPDATE при синхронизации
+pub const UPSERT_BATCH_SIZE: usize = 500;
+
+/// Элемент upstream с dataset_id, готовый к записи
+#[derive(Debug, Clone)]
+pub struct PreparedItem {
+    pub dataset_id: String,
+    pub fields: TrackedFields,
+    pub meta: OsdrMeta,
+    pub raw: Value,
+    pub hash: String,
+}
+
+/// Уже сохранённая строка osdr_items
+#[derive(Debug, Clone)]
+pub struct StoredItem {
+    pub id: i64,
+    pub fields: TrackedFields,
+    pub hash: String,
+}
+
+/// Что сделать с пачкой элементов
+#[derive(Debug, Default)]
+pub struct BatchPlan {
+    pub new: Vec<PreparedItem>,
+    /// id строки, новые данные и изменившиеся поля
+    pub changed: Vec<(i64, PreparedItem, Vec<FieldChange>)>,
+    /// id строки и хэш, который надо проставить
+    pub unchanged: Vec<(i64, String)>,
+}
+
+/// dataset_id без пробелов по краям и в верхнем регистре: `osd-123 ` и
+/// `OSD-123` — один датасет. Пустой id считается отсутствующим
+pub fn normalize_dataset_id(id: &str) -> Option<String> {
+    let id = id.trim();
+    (!id.is_empty()).then(|| id.to_uppercase())
+}
+
+/// Один элемент на dataset_id в порядке первого появления: ON CONFLICT не
+/// может обновить одну строку дважды за запрос. Остаётся элемент с самым
+/// поздним updated_at, при равенстве — встреченный последним
+pub fn dedupe_by_dataset_id(items: Vec<PreparedItem>) -> Vec<PreparedItem> {
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut out: Vec<PreparedItem> = Vec::with_capacity(items.len());
+    for item in items {
+        match index.get(&item.dataset_id) {
+            Some(&i) => {
+                if item.fields.updated_at >= out[i].fields.updated_at {
+                    out[i] = item;
+                }
+            }
+            None => {
+                index.insert(item.dataset_id.clone(), out.len());
+                out.push(item);
+            }
+        }
+    }
+    out
+}
+
+/// Раскладывает пачку на новые, изменённые (по хэшу) и неизменные
+pub fn plan_batch(items: Vec<PreparedItem>, existing: &HashMap<String, StoredItem>) -> BatchPlan {
+    let mut plan = BatchPlan::default();
+    for item in items {
+        match existing.get(&item.dataset_id) {
+            None => plan.new.push(item),
+            Some(stored) if stored.hash == item.hash => plan.unchanged.push((stored.id, item.hash)),
+            Some(stored) => {
+                let changes = field_changes(&stored.fields, &item.fields);
+                plan.changed.push((stored.id, item, changes));
+            }
+        }
+    }
+    plan
+}
+
+/// Элементы страницы: массив целиком, поле items/results или сам объект
+pub fn page_items(json: &Value) -> Vec<Value> {
+    if let Some(a) = json.as_array() {
+        a.clone()
+    } else if let Some(v) = json.get("items").and_then(|x| x.as_array()) {
+        v.clone()
+    } else if let Some(v) = json.get("results").and_then(|x| x.as_array()) {
+        v.clone()
+    } else {
+        vec![json.clone()]
+    }
+}
+
+/// Адрес следующей страницы: ссылка next (в том числе links.next и
+/// относительная), иначе page + 1 до total_pages или offset + limit до count
+pub fn next_page_url(current: &Url, json: &Value) -> Option<Url> {
+    let link = json
+        .get("next")
+        .or_else(|| json.get("links").and_then(|l| l.get("next")))
+        .and_then(|n| n.as_str().or_else(|| n.get("href").and_then(Value::as_str)))
+        .filter(|s| !s.trim().is_empty());
+    if let Some(link) = link {
+        return current.join(link.trim()).ok();
+    }
+
+    let num = |key: &str| json.get(key).and_then(Value::as_u64);
+    if let (Some(page), Some(total)) = (num("page"), num("total_pages").or_else(|| num("pages"))) {
+        return (page < total).then(|| with_query(current, "page", page + 1));
+    }
+    if let (Some(offset), Some(limit), Some(count)) =
+        (num("offset"), num("limit"), num("count").or_else(|| num("total")))
+    {
+        return (limit > 0 && offset + limit < count)
+            .then(|| with_query(current, "offset", offset + limit));
+    }
+    None
+}
+
+fn with_query(url: &Url, key: &str, value: u64) -> Url {
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(k, _)| k != key)
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    let mut next = url.clone();
+    next.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair(key, &value.to_string());
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canonical::content_hash;
+    use serde_json::json;
+
+    fn url(s: &str) -> Url {
+        Url::parse(s).unwrap()
+    }
+
+    #[test]
+    fn follows_next_links() {
+        let cur = url("https://osdr.example/api/v2/datasets/?format=json");
+        let abs = json!({"next": "https://osdr.example/api/v2/datasets/?format=json&page=2", "results": []});
+        assert_eq!(next_page_url(&cur, &abs).unwrap().as_str(), "https://osdr.example/api/v2/datasets/?format=json&page=2");
+        let rel = json!({"links": {"next": {"href": "/api/v2/datasets/?cursor=abc"}}});
+        assert_eq!(next_page_url(&cur, &rel).unwrap().as_str(), "https://osdr.example/api/v2/datasets/?cursor=abc");
+        assert!(next_page_url(&cur, &json!({"next": null, "results": []})).is_none());
+        assert!(next_page_url(&cur, &json!([{"id": 1}])).is_none());
+    }
+
+    #[test]
+    fn increments_page_and_offset() {
+        let cur = url("https://osdr.example/datasets?format=json&page=2");
+        let next = next_page_url(&cur, &json!({"page": 2, "total_pages": 3})).unwrap();
+        assert_eq!(next.as_str(), "https://osdr.example/datasets?format=json&page=3");
+        assert!(next_page_url(&cur, &json!({"page": 3, "total_pages": 3})).is_none());
+
+        let cur = url("https://osdr.example/datasets?offset=0&limit=50");
+        let next = next_page_url(&cur, &json!({"offset": 0, "limit": 50, "count": 120})).unwrap();
+        assert_eq!(next.as_str(), "https://osdr.example/datasets?limit=50&offset=50");
+        assert!(next_page_url(&cur, &json!({"offset": 100, "limit": 50, "count": 120})).is_none());
+    }
+
+    #[test]
+    fn reports_only_changed_fields() {
+        let t = DateTime::parse_from_rfc3339("2024-05-01T10:00:00Z").unwrap().with_timezone(&Utc);
+        let old = TrackedFields {
+            title: Some("Rodent Research".into()),
+            status: Some("processing".into()),
+            updated_at: Some(t),
+        };
+        assert!(field_changes(&old, &old.clone()).is_empty());
+
+        let new = TrackedFields {
+            status: Some("released".into()),
+            updated_at: None,
+            ..old.clone()
+        };
+        assert_eq!(
+            field_changes(&old, &new),
+            vec![
+                FieldChange {
+                    field: "status",
+                    old_value: Some("processing".into()),
+                    new_value: Some("released".into()),
+                },
+                FieldChange {
+                    field: "updated_at",
+                    old_value: Some("2024-05-01T10:00:00+00:00".into()),
+                    new_value: None,
+                },
+            ]
+        );
+    }
+
+
+    fn prepared(dataset_id: &str, title: &str) -> PreparedItem {
+        let raw = json!({"id": dataset_id, "title": title});
+        PreparedItem {
+            dataset_id: dataset_id.into(),
+            fields: TrackedFields { title: Some(title.into()), ..Default::default() },
+            meta: OsdrMeta::default(),
+            hash: content_hash(&raw),
+            raw,
+        }
+    }
+
+    #[test]
+    fn dedupe_keeps_last_in_first_position() {
+        let items = vec![prepared("OSD-1", "a"), prepared("OSD-2", "b"), prepared("OSD-1", "c")];
+        let out = dedupe_by_dataset_id(items);
+        let got: Vec<(&str, Option<&str>)> = out
+            .iter()
+            .map(|i| (i.dataset_id.as_str(), i.fields.title.as_deref()))
+            .collect();
+        assert_eq!(got, vec![("OSD-1", Some("c")), ("OSD-2", Some("b"))]);
+    }
+
+    #[test]
+    fn dedupe_prefers_latest_updated_at() {
+        let at = |s: &str| Some(DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc));
+        let mut newer = prepared("OSD-1", "newer");
+        newer.fields.updated_at = at("2024-06-01T00:00:00Z");
+        let mut older = prepared("OSD-1", "older");
+        older.fields.updated_at = at("2024-01-01T00:00:00Z");
+        let undated = prepared("OSD-1", "undated");
+
+        let out = dedupe_by_dataset_id(vec![newer, older, undated]);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].fields.title.as_deref(), Some("newer"));
+    }
+
+    #[test]
+    fn normalizes_dataset_ids() {
+        assert_eq!(normalize_dataset_id(" osd-123 ").as_deref(), Some("OSD-123"));
+        assert_eq!(normalize_dataset_id("OSD-123").as_deref(), Some("OSD-123"));
+        assert_eq!(normalize_dataset_id("  "), None);
+    }
+
+    #[test]
+    fn plans_new_changed_and_unchanged() {
+        let same = prepared("OSD-1", "a");
+        let existing: HashMap<String, StoredItem> = [
+            ("OSD-1".to_string(), StoredItem { id: 10, fields: same.fields.clone(), hash: same.hash.clone() }),
+            ("OSD-2".to_string(), StoredItem { id: 20, fields: prepared("OSD-2", "old").fields, hash: "stale".into() }),
+        ]
+        .into_iter()
+        .collect();
+
+        let plan = plan_batch(vec![same.clone(), prepared("OSD-2", "new"), prepared("OSD-3", "x")], &existing);
+        assert_eq!(plan.unchanged, vec![(10, same.hash)]);
+        assert_eq!(plan.new.len(), 1);
+        assert_eq!(plan.new[0].dataset_id, "OSD-3");
+        assert_eq!(plan.changed.len(), 1);
+        let (id, _, changes) = &plan.changed[0];
+        assert_eq!(*id, 20);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].field, "title");
+    }
+
+    #[test]
+    fn extracts_meta_from_recorded_items() {
+        let keys = OsdrKeys::from_env();
+
+        let search: Value = serde_json::from_str(include_str!("../tests/fixtures/osdr_search_item.json")).unwrap();
+        assert_eq!(s_pick(&search, &keys.id).as_deref(), Some("OSD-48"));
+        assert_eq!(
+            OsdrMeta::extract(&search, &keys),
+            OsdrMeta {
+                organism: Some("Mus musculus".into()),
+                assay_type: Some("RNA Sequencing (RNA-Seq)".into()),
+                mission: Some("SpaceX-4".into()),
+            }
+        );
+
+        let dataset: Value = serde_json::from_str(include_str!("../tests/fixtures/osdr_dataset_item.json")).unwrap();
+        assert_eq!(
+            OsdrMeta::extract(&dataset, &keys),
+            OsdrMeta {
+                organism: Some("Arabidopsis thaliana".into()),
+                assay_type: Some("RNA Sequencing (RNA-Seq)".into()),
+                mission: Some("SpaceX-20".into()),
+            }
+        );
+        assert_eq!(OsdrMeta::extract(&json!({"id": "OSD-1"}), &keys), OsdrMeta::default());
+    }
+}
@@ -0,0 +1,210 @@
+use serde_json::{json, Value};
+
+use crate::models::OsdrItemRow;
+
+/// Допустимые значения ?sort= (с необязательным `-` для убывания)
+pub const SORT_KEYS: [&str; 5] = ["inserted_at", "updated_at", "title", "status", "dataset_id"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SortKey {
+    InsertedAt,
+    UpdatedAt,
+    Title,
+    Status,
+    DatasetId,
+}
+
+/// Порядок /osdr/list. Колонка и направление берутся только из enum, в SQL
+/// не попадает ничего из запроса; id — вторичный ключ для стабильного курсора
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OsdrSort {
+    key: SortKey,
+    descending: bool,
+}
+
+impl Default for OsdrSort {
+    /// Новые записи первыми
+    fn default() -> Self {
+        Self { key: SortKey::InsertedAt, descending: true }
+    }
+}
+
+impl OsdrSort {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let raw = raw.trim();
+        let (descending, name) = match raw.strip_prefix('-') {
+            Some(name) => (true, name),
+            None => (false, raw),
+        };
+        let key = match name {
+            "inserted_at" => SortKey::InsertedAt,
+            "updated_at" => SortKey::UpdatedAt,
+            "title" => SortKey::Title,
+            "status" => SortKey::Status,
+            "dataset_id" => SortKey::DatasetId,
+            _ => {
+                return Err(format!(
+                    "invalid sort {:?}, allowed: {} (prefix with - for descending)",
+                    raw,
+                    SORT_KEYS.join(", ")
+                ))
+            }
+        };
+        Ok(Self { key, descending })
+    }
+
+    /// Каноническая запись, как в ?sort=
+    pub fn as_param(&self) -> String {
+        format!("{}{}", if self.descending { "-" } else { "" }, self.column())
+    }
+
+    fn column(&self) -> &'static str {
+        match self.key {
+            SortKey::InsertedAt => "inserted_at",
+            SortKey::UpdatedAt => "updated_at",
+            SortKey::Title => "title",
+            SortKey::Status => "status",
+            SortKey::DatasetId => "dataset_id",
+        }
+    }
+
+    fn sql_type(&self) -> &'static str {
+        match self.key {
+            SortKey::InsertedAt | SortKey::UpdatedAt => "timestamptz",
+            SortKey::Title | SortKey::Status | SortKey::DatasetId => "text",
+        }
+    }
+
+    fn direction(&self) -> &'static str {
+        if self.descending {
+            "DESC"
+        } else {
+            "ASC"
+        }
+    }
+
+    /// NULL всегда в конце, в любом направлении
+    pub fn order_by(&self) -> String {
+        format!(
+            "{col} {dir} NULLS LAST, id {dir}",
+            col = self.column(),
+            dir = self.direction()
+        )
+    }
+
+    /// Условие «строго после курсора»; `$value` — значение колонки текстом
+    /// (NULL для пустых), `$id` — id последней строки
+    pub fn after_predicate(&self, value_param: usize, id_param: usize) -> String {
+        let col = self.column();
+        let op = if self.descending { "<" } else { ">" };
+        let value = format!("${}::{}", value_param, self.sql_type());
+        format!(
+            "(CASE WHEN {value} IS NULL
+                THEN {col} IS NULL AND id {op} ${id}
+                ELSE {col} {op} {value}
+                     OR ({col} = {value} AND id {op} ${id})
+                     OR {col} IS NULL
+             END)",
+            id = id_param,
+        )
+    }
+
+    /// Значение колонки сортировки у строки — для следующего курсора
+    pub fn value_of(&self, row: &OsdrItemRow) -> Option<String> {
+        match self.key {
+            SortKey::InsertedAt => Some(row.inserted_at.to_rfc3339()),
+            SortKey::UpdatedAt => row.updated_at.map(|t| t.to_rfc3339()),
+            SortKey::Title => row.title.clone(),
+            SortKey::Status => row.status.clone(),
+            SortKey::DatasetId => row.dataset_id.clone(),
+        }
+    }
+}
+
+/// Позиция в списке: порядок, значение колонки и id последней строки
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListCursor {
+    pub sort: OsdrSort,
+    pub value: Option<String>,
+    pub id: i64,
+}
+
+impl ListCursor {
+    /// Непрозрачная строка для ?cursor=: hex от JSON `[sort, value, id]`
+    pub fn encode(&self) -> String {
+        json!([self.sort.as_param(), self.value, self.id])
+            .to_string()
+            .bytes()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    pub fn decode(raw: &str) -> Result<Self, String> {
+        let invalid = || "invalid cursor".to_string();
+        if !raw.len().is_multiple_of(2) {
+            return Err(invalid());
+        }
+        let bytes = (0..raw.len())
+            .step_by(2)
+            .map(|i| raw.get(i..i + 2).and_then(|h| u8::from_str_radix(h, 16).ok()))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(invalid)?;
+        let parts: Value = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+        let (Some(sort), Some(value), Some(id)) = (
+            parts.get(0).and_then(Value::as_str),
+            parts.get(1),
+            parts.get(2).and_then(Value::as_i64),
+        ) else {
+            return Err(invalid());
+        };
+        let value = match value {
+            Value::Null => None,
+            Value::String(s) => Some(s.clone()),
+            _ => return Err(invalid()),
+        };
+        Ok(Self {
+            sort: OsdrSort::parse(sort).map_err(|_| invalid())?,
+            value,
+            id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_whitelisted_keys() {
+        assert_eq!(OsdrSort::parse("-updated_at").unwrap().order_by(), "updated_at DESC NULLS LAST, id DESC");
+        assert_eq!(OsdrSort::parse("title").unwrap().order_by(), "title ASC NULLS LAST, id ASC");
+        assert_eq!(OsdrSort::default().as_param(), "-inserted_at");
+
+        let err = OsdrSort::parse("id; DROP TABLE osdr_items").unwrap_err();
+        assert!(err.contains("inserted_at, updated_at, title, status, dataset_id"));
+        assert!(OsdrSort::parse("-").is_err());
+    }
+
+    #[test]
+    fn cursor_round_trip() {
+        let cursor = ListCursor {
+            sort: OsdrSort::parse("-title").unwrap(),
+            value: Some("Rodent \"Research\" 7".into()),
+            id: 42,
+        };
+        let encoded = cursor.encode();
+        assert!(encoded.bytes().all(|b| b.is_ascii_hexdigit()));
+        assert_eq!(ListCursor::decode(&encoded).unwrap(), cursor);
+
+        let null_value = ListCursor { value: None, ..cursor };
+        assert_eq!(ListCursor::decode(&null_value.encode()).unwrap(), null_value);
+    }
+
+    #[test]
+    fn rejects_garbage_cursors() {
+        assert!(ListCursor::decode("zz").is_err());
+        assert!(ListCursor::decode("abc").is_err());
+        let wrong_shape: String = br#"{"id":1}"#.iter().map(|b| format!("{:02x}", b)).collect();
+        assert!(ListCursor::decode(&wrong_shape).is_err());
+    }
+}
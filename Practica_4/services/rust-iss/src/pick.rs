@@ -0,0 +1,121 @@
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use serde_json::Value;
+
+/// Значение по пути вида `metadata.study.title`. Ключ с точкой целиком
+/// проверяется первым; числовой сегмент — индекс массива, а прочие сегменты
+/// на массиве берут первый элемент, в котором путь найден
+pub fn value_at_path<'a>(v: &'a Value, path: &str) -> Option<&'a Value> {
+    if let Some(x) = v.get(path) {
+        return Some(x);
+    }
+    let segments: Vec<&str> = path.split('.').collect();
+    walk(v, &segments)
+}
+
+fn walk<'a>(v: &'a Value, segments: &[&str]) -> Option<&'a Value> {
+    let Some((head, rest)) = segments.split_first() else {
+        return Some(v);
+    };
+    match v {
+        Value::Object(map) => walk(map.get(*head)?, rest),
+        Value::Array(items) => match head.parse::<usize>() {
+            Ok(i) => walk(items.get(i)?, rest),
+            Err(_) => items.iter().find_map(|item| walk(item, segments)),
+        },
+        _ => None,
+    }
+}
+
+/// Первая непустая строка (или число как строка) по списку путей
+pub fn s_pick(v: &Value, keys: &[impl AsRef<str>]) -> Option<String> {
+    for k in keys {
+        if let Some(x) = value_at_path(v, k.as_ref()) {
+            if let Some(s) = x.as_str() {
+                if !s.is_empty() {
+                    return Some(s.to_string());
+                }
+            } else if x.is_number() {
+                return Some(x.to_string());
+            }
+        }
+    }
+    None
+}
+
+/// Первый момент времени по списку путей: RFC3339, "YYYY-MM-DD HH:MM:SS"
+/// или эпоха в секундах (числом или строкой)
+pub fn t_pick(v: &Value, keys: &[impl AsRef<str>]) -> Option<DateTime<Utc>> {
+    for k in keys {
+        if let Some(x) = value_at_path(v, k.as_ref()) {
+            if let Some(s) = x.as_str() {
+                if let Ok(dt) = s.parse::<DateTime<Utc>>() {
+                    return Some(dt);
+                }
+                if let Ok(ndt) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
+                    return Some(Utc.from_utc_datetime(&ndt));
+                }
+                // Эпоха в секундах, пришедшая строкой
+                if let Ok(n) = s.trim().parse::<i64>() {
+                    return Utc.timestamp_opt(n, 0).single();
+                }
+            } else if let Some(n) = x.as_i64() {
+                return Utc.timestamp_opt(n, 0).single();
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn doc() -> Value {
+        json!({
+            "id": "OSD-42",
+            "metadata": {
+                "study": {"title": "Rodent Research 1", "number": 42},
+                "assays": [
+                    {"type": "rna-seq"},
+                    {"type": "proteomics", "lead": {"name": "Dr. Kim"}}
+                ]
+            },
+            "flat.key": "literal"
+        })
+    }
+
+    #[test]
+    fn walks_nested_objects() {
+        let d = doc();
+        assert_eq!(value_at_path(&d, "metadata.study.title"), Some(&json!("Rodent Research 1")));
+        assert_eq!(value_at_path(&d, "id"), Some(&json!("OSD-42")));
+        assert_eq!(value_at_path(&d, "flat.key"), Some(&json!("literal")));
+    }
+
+    #[test]
+    fn walks_arrays() {
+        let d = doc();
+        assert_eq!(value_at_path(&d, "metadata.assays.1.type"), Some(&json!("proteomics")));
+        assert_eq!(value_at_path(&d, "metadata.assays.type"), Some(&json!("rna-seq")));
+        assert_eq!(value_at_path(&d, "metadata.assays.lead.name"), Some(&json!("Dr. Kim")));
+        assert_eq!(value_at_path(&d, "metadata.assays.5.type"), None);
+    }
+
+    #[test]
+    fn missing_intermediate_keys() {
+        let d = doc();
+        assert_eq!(value_at_path(&d, "metadata.mission.title"), None);
+        assert_eq!(value_at_path(&d, "id.value"), None);
+        assert_eq!(s_pick(&d, &["metadata.mission.title", "metadata.study.title"]).as_deref(), Some("Rodent Research 1"));
+    }
+
+    #[test]
+    fn numeric_leaf_values() {
+        let d = doc();
+        assert_eq!(s_pick(&d, &["metadata.study.number"]).as_deref(), Some("42"));
+        let t = json!({"meta": {"updated": 1_700_000_000, "modified": "1700000100"}});
+        assert_eq!(t_pick(&t, &["meta.updated"]).map(|t| t.timestamp()), Some(1_700_000_000));
+        assert_eq!(t_pick(&t, &["meta.modified"]).map(|t| t.timestamp()), Some(1_700_000_100));
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Не больше `limit` событий за скользящее окно `window`
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: usize,
+    window: Duration,
+    hits: VecDeque<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: usize, window: Duration) -> Self {
+        Self { limit, window, hits: VecDeque::new() }
+    }
+
+    /// Засчитывает событие, если лимит не исчерпан; иначе — через сколько
+    /// освободится место
+    pub fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        while self
+            .hits
+            .front()
+            .is_some_and(|hit| now.saturating_duration_since(*hit) >= self.window)
+        {
+            self.hits.pop_front();
+        }
+        if self.hits.len() < self.limit {
+            self.hits.push_back(now);
+            return Ok(());
+        }
+        let oldest = self.hits.front().copied().unwrap_or(now);
+        Err(self.window.saturating_sub(now.saturating_duration_since(oldest)))
+    }
+}
+
+/// Ведро на `capacity` запросов, которое пополняется на `refill_per_hour`
+/// в час равномерно; в отличие от RateLimiter допускает всплеск до capacity
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_sec: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// Ведро создаётся полным
+    pub fn new(capacity: u64, refill_per_hour: u64, now: Instant) -> Self {
+        Self {
+            capacity: capacity as f64,
+            refill_per_sec: refill_per_hour as f64 / 3600.0,
+            tokens: capacity as f64,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// Забирает жетон; иначе — через сколько он появится (None — никогда)
+    pub fn try_take(&mut self, now: Instant) -> Result<(), Option<Duration>> {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+        if self.refill_per_sec <= 0.0 || self.capacity < 1.0 {
+            return Err(None);
+        }
+        Err(Some(Duration::from_secs_f64((1.0 - self.tokens) / self.refill_per_sec)))
+    }
+
+    /// Целых жетонов в ведре
+    pub fn available(&mut self, now: Instant) -> u64 {
+        self.refill(now);
+        self.tokens as u64
+    }
+
+    /// Не больше `remaining` жетонов: upstream знает о запросах с тем же
+    /// ключом из других процессов
+    pub fn limit_to(&mut self, remaining: u64, now: Instant) {
+        self.refill(now);
+        self.tokens = self.tokens.min(remaining as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frees_slots_as_window_slides() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(2, Duration::from_secs(60));
+        assert!(limiter.try_acquire(start).is_ok());
+        assert!(limiter.try_acquire(start + Duration::from_secs(10)).is_ok());
+        assert_eq!(
+            limiter.try_acquire(start + Duration::from_secs(20)),
+            Err(Duration::from_secs(40))
+        );
+        assert!(limiter.try_acquire(start + Duration::from_secs(60)).is_ok());
+        assert!(limiter.try_acquire(start + Duration::from_secs(61)).is_err());
+
+        let mut disabled = RateLimiter::new(0, Duration::from_secs(60));
+        assert!(disabled.try_acquire(start).is_err());
+    }
+
+    #[test]
+    fn bucket_bursts_then_refills_evenly() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2, 3600, start);
+        assert!(bucket.try_take(start).is_ok());
+        assert!(bucket.try_take(start).is_ok());
+        assert_eq!(bucket.try_take(start), Err(Some(Duration::from_secs(1))));
+        assert!(bucket.try_take(start + Duration::from_millis(1500)).is_ok());
+        assert_eq!(bucket.available(start + Duration::from_secs(60)), 2);
+
+        bucket.limit_to(0, start + Duration::from_secs(60));
+        assert!(bucket.try_take(start + Duration::from_secs(60)).is_err());
+
+        let mut empty = TokenBucket::new(5, 0, start);
+        empty.limit_to(0, start);
+        assert_eq!(empty.try_take(start + Duration::from_secs(3600)), Err(None));
+    }
+}
@@ -0,0 +1,305 @@
+use serde::{Deserialize, Serialize};
+
+/// Упрощённые контуры стран и внутренних морей (WGS84, [lon, lat]).
+/// Кольца, пересекающие антимеридиан, могут быть записаны как долготами
+/// за пределами ±180, так и скачком 179 → -179
+const REGIONS_GEOJSON: &str = include_str!("../data/regions.geojson");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Surface {
+    Land,
+    Ocean,
+}
+
+/// Результат обратного геокодирования
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Location {
+    pub over: Surface,
+    pub region: String,
+}
+
+#[derive(Deserialize)]
+struct FeatureCollection {
+    features: Vec<Feature>,
+}
+
+#[derive(Deserialize)]
+struct Feature {
+    properties: Properties,
+    geometry: Geometry,
+}
+
+#[derive(Deserialize)]
+struct Properties {
+    name: String,
+    over: Surface,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", content = "coordinates")]
+enum Geometry {
+    Polygon(Vec<Vec<[f64; 2]>>),
+    MultiPolygon(Vec<Vec<Vec<[f64; 2]>>>),
+}
+
+/// Полигон с дырами; долготы колец развёрнуты так, чтобы соседние вершины
+/// отличались не больше чем на 180°
+struct Polygon {
+    outer: Vec<(f64, f64)>,
+    holes: Vec<Vec<(f64, f64)>>,
+    lon_range: (f64, f64),
+    lat_range: (f64, f64),
+}
+
+struct Region {
+    name: String,
+    over: Surface,
+    polygons: Vec<Polygon>,
+}
+
+/// Грубый офлайн-справочник регионов: сначала суша, затем моря,
+/// а всё остальное относится к океанам по долготным границам
+pub struct Regions {
+    regions: Vec<Region>,
+}
+
+impl Regions {
+    /// Разбор встроенного набора данных
+    pub fn load() -> Result<Self, String> {
+        Self::from_geojson(REGIONS_GEOJSON)
+    }
+
+    pub fn from_geojson(text: &str) -> Result<Self, String> {
+        let fc: FeatureCollection =
+            serde_json::from_str(text).map_err(|e| format!("invalid regions geojson: {}", e))?;
+
+        let mut regions = Vec::with_capacity(fc.features.len());
+        for f in fc.features {
+            let polygons = match f.geometry {
+                Geometry::Polygon(rings) => vec![rings],
+                Geometry::MultiPolygon(polys) => polys,
+            };
+            let polygons = polygons
+                .into_iter()
+                .filter_map(Polygon::from_rings)
+                .collect::<Vec<_>>();
+            if polygons.is_empty() {
+                return Err(format!("region {} has no usable polygons", f.properties.name));
+            }
+            regions.push(Region {
+                name: f.properties.name,
+                over: f.properties.over,
+                polygons,
+            });
+        }
+        // Моря в наборе данных намеренно заходят на сушу, поэтому суша проверяется первой
+        regions.sort_by_key(|r| r.over != Surface::Land);
+        Ok(Self { regions })
+    }
+
+    pub fn locate(&self, lat: f64, lon: f64) -> Location {
+        for region in &self.regions {
+            if region.polygons.iter().any(|p| p.contains(lat, lon)) {
+                return Location {
+                    over: region.over,
+                    region: region.name.clone(),
+                };
+            }
+        }
+        Location {
+            over: Surface::Ocean,
+            region: open_ocean(lat, lon).to_string(),
+        }
+    }
+}
+
+impl Polygon {
+    fn from_rings(rings: Vec<Vec<[f64; 2]>>) -> Option<Self> {
+        let mut rings = rings.into_iter().map(|r| unwrap_ring(&r));
+        let outer = rings.next().filter(|r| r.len() >= 3)?;
+
+        let (mut min_lon, mut max_lon) = (f64::INFINITY, f64::NEG_INFINITY);
+        let (mut min_lat, mut max_lat) = (f64::INFINITY, f64::NEG_INFINITY);
+        for &(lon, lat) in &outer {
+            min_lon = min_lon.min(lon);
+            max_lon = max_lon.max(lon);
+            min_lat = min_lat.min(lat);
+            max_lat = max_lat.max(lat);
+        }
+        Some(Self {
+            outer,
+            holes: rings.filter(|r| r.len() >= 3).collect(),
+            lon_range: (min_lon, max_lon),
+            lat_range: (min_lat, max_lat),
+        })
+    }
+
+    fn contains(&self, lat: f64, lon: f64) -> bool {
+        if lat < self.lat_range.0 || lat > self.lat_range.1 {
+            return false;
+        }
+        // Развёрнутое кольцо может лежать в (-540, 540): пробуем точку со сдвигами
+        [0.0, 360.0, -360.0].iter().any(|shift| {
+            let x = lon + shift;
+            x >= self.lon_range.0
+                && x <= self.lon_range.1
+                && ring_contains(&self.outer, x, lat)
+                && !self.holes.iter().any(|h| ring_contains(h, x, lat))
+        })
+    }
+}
+
+/// Делает долготы кольца непрерывными: скачок через антимеридиан
+/// превращается в выход за ±180
+fn unwrap_ring(ring: &[[f64; 2]]) -> Vec<(f64, f64)> {
+    let mut out: Vec<(f64, f64)> = Vec::with_capacity(ring.len());
+    for &[lon, lat] in ring {
+        let lon = match out.last() {
+            Some(&(prev, _)) => prev + (lon - prev + 180.0).rem_euclid(360.0) - 180.0,
+            None => lon,
+        };
+        out.push((lon, lat));
+    }
+    out
+}
+
+/// Чётно-нечётное правило на плоскости (lon, lat)
+fn ring_contains(ring: &[(f64, f64)], x: f64, y: f64) -> bool {
+    let mut inside = false;
+    let mut j = ring.len() - 1;
+    for i in 0..ring.len() {
+        let (xi, yi) = ring[i];
+        let (xj, yj) = ring[j];
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// Океан для точки вне суши и морей набора данных
+fn open_ocean(lat: f64, lon: f64) -> &'static str {
+    if lat >= 66.5 {
+        return "Arctic Ocean";
+    }
+    if lat <= -60.0 {
+        return "Southern Ocean";
+    }
+    // Индийский океан: от мыса Игольного до Тасмании, к северу — до Малаккского пролива
+    if lat < 30.0 && lon >= 20.0 && (lon <= 100.0 || (lat < -8.0 && lon <= 147.0)) {
+        return "Indian Ocean";
+    }
+    // Западная граница Атлантики проходит по Америкам
+    let atlantic_west = match lat {
+        l if l >= 20.0 => -100.0,
+        l if l >= 13.0 => -88.0,
+        l if l >= 8.0 => -84.0,
+        l if l >= 0.0 => -78.0,
+        _ => -68.0,
+    };
+    let atlantic_east = if lat >= 30.0 { 45.0 } else { 20.0 };
+    let north = lat >= 0.0;
+    match (lon >= atlantic_west && lon < atlantic_east, north) {
+        (true, true) => "North Atlantic Ocean",
+        (true, false) => "South Atlantic Ocean",
+        (false, true) => "North Pacific Ocean",
+        (false, false) => "South Pacific Ocean",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regions() -> Regions {
+        Regions::load().expect("bundled dataset parses")
+    }
+
+    fn assert_located(r: &Regions, lat: f64, lon: f64, over: Surface, region: &str) {
+        let loc = r.locate(lat, lon);
+        assert_eq!(
+            (loc.over, loc.region.as_str()),
+            (over, region),
+            "at ({lat}, {lon})"
+        );
+    }
+
+    #[test]
+    fn capitals_resolve_to_countries() {
+        let r = regions();
+        for (lat, lon, name) in [
+            (51.16, 71.47, "Kazakhstan"),
+            (55.75, 37.6, "Russia"),
+            (48.85, 2.35, "France"),
+            (-15.8, -47.9, "Brazil"),
+            (30.05, 31.23, "Egypt"),
+            (-33.87, 151.2, "Australia"),
+            (35.68, 139.7, "Japan"),
+            (39.74, -104.99, "United States"),
+            (28.6, 77.2, "India"),
+            (-1.29, 36.82, "Kenya"),
+        ] {
+            assert_located(&r, lat, lon, Surface::Land, name);
+        }
+    }
+
+    #[test]
+    fn seas_and_open_ocean() {
+        let r = regions();
+        assert_located(&r, 35.0, 18.0, Surface::Ocean, "Mediterranean Sea");
+        assert_located(&r, 42.0, 51.0, Surface::Ocean, "Caspian Sea");
+        assert_located(&r, -30.0, -15.0, Surface::Ocean, "South Atlantic Ocean");
+        assert_located(&r, 30.0, -40.0, Surface::Ocean, "North Atlantic Ocean");
+        assert_located(&r, -20.0, 80.0, Surface::Ocean, "Indian Ocean");
+        assert_located(&r, 0.0, -150.0, Surface::Ocean, "North Pacific Ocean");
+        assert_located(&r, -40.0, -120.0, Surface::Ocean, "South Pacific Ocean");
+        assert_located(&r, -65.0, 100.0, Surface::Ocean, "Southern Ocean");
+    }
+
+    #[test]
+    fn antimeridian_polygons() {
+        let r = regions();
+        // Чукотка: кольцо с долготами за 180°
+        assert_located(&r, 66.0, -172.0, Surface::Land, "Russia");
+        assert_located(&r, 64.7, 177.5, Surface::Land, "Russia");
+        // Фиджи: кольцо со скачком долготы
+        assert_located(&r, -16.5, -179.95, Surface::Land, "Fiji");
+        assert_located(&r, -16.4, 179.3, Surface::Land, "Fiji");
+        assert_located(&r, 58.0, -178.0, Surface::Ocean, "Bering Sea");
+        assert_located(&r, 58.0, 178.0, Surface::Ocean, "Bering Sea");
+        assert_located(&r, -80.0, 179.9, Surface::Land, "Antarctica");
+        assert_located(&r, -80.0, -179.9, Surface::Land, "Antarctica");
+        assert_located(&r, 0.0, 180.0, Surface::Ocean, "North Pacific Ocean");
+    }
+
+    #[test]
+    fn wrapping_encodings_are_equivalent() {
+        let jump = r#"{"type":"FeatureCollection","features":[{"type":"Feature",
+            "properties":{"name":"Box","over":"land"},
+            "geometry":{"type":"Polygon","coordinates":[[[170,-10],[-170,-10],[-170,10],[170,10],[170,-10]]]}}]}"#;
+        let extended = jump.replace("-170", "190");
+        for text in [jump.to_string(), extended] {
+            let r = Regions::from_geojson(&text).unwrap();
+            for lon in [175.0, 180.0, -180.0, -175.0] {
+                assert_eq!(r.locate(0.0, lon).over, Surface::Land, "lon {lon}");
+            }
+            assert_eq!(r.locate(0.0, 0.0).over, Surface::Ocean);
+            assert_eq!(r.locate(0.0, 165.0).over, Surface::Ocean);
+            assert_eq!(r.locate(0.0, -165.0).over, Surface::Ocean);
+        }
+    }
+
+    #[test]
+    fn holes_are_excluded() {
+        let text = r#"{"type":"FeatureCollection","features":[{"type":"Feature",
+            "properties":{"name":"Ring","over":"land"},
+            "geometry":{"type":"Polygon","coordinates":[
+                [[0,0],[10,0],[10,10],[0,10],[0,0]],
+                [[4,4],[6,4],[6,6],[4,6],[4,4]]]}}]}"#;
+        let r = Regions::from_geojson(text).unwrap();
+        assert_eq!(r.locate(2.0, 2.0).over, Surface::Land);
+        assert_eq!(r.locate(5.0, 5.0).over, Surface::Ocean);
+    }
+}
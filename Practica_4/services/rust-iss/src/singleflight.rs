@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use futures::future::{BoxFuture, FutureExt, Shared};
+
+/// Идущие вызовы по ключу: номер вызова и его общий future
+type CallMap<T> = HashMap<String, (u64, Shared<BoxFuture<'static, T>>)>;
+type Calls<T> = Mutex<CallMap<T>>;
+
+/// Одновременные одинаковые загрузки: первый вызов с ключом запускает
+/// future, остальные до его завершения ждут тот же future и получают
+/// копию его результата
+pub struct SingleFlight<T> {
+    calls: Arc<Calls<T>>,
+    next_id: AtomicU64,
+}
+
+impl<T> Default for SingleFlight<T> {
+    fn default() -> Self {
+        Self { calls: Arc::new(Mutex::new(HashMap::new())), next_id: AtomicU64::new(0) }
+    }
+}
+
+fn lock<T>(calls: &Calls<T>) -> MutexGuard<'_, CallMap<T>> {
+    calls.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Убирает запись вызова, когда его задача завершилась, в том числе паникой
+struct Forget<T> {
+    calls: Arc<Calls<T>>,
+    key: String,
+    id: u64,
+}
+
+impl<T> Drop for Forget<T> {
+    fn drop(&mut self) {
+        let mut calls = lock(&self.calls);
+        if calls.get(&self.key).is_some_and(|(current, _)| *current == self.id) {
+            calls.remove(&self.key);
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> SingleFlight<T> {
+    /// Результат и признак того, что он получен от чужого вызова. Загрузка
+    /// идёт отдельной задачей tokio: если все ждущие клиенты отключатся, она
+    /// всё равно доработает и отпустит то, что держит, например блокировку источника
+    pub async fn run<F>(&self, key: &str, start: impl FnOnce() -> F) -> (T, bool)
+    where
+        F: Future<Output = T> + Send + 'static,
+    {
+        let (call, coalesced) = {
+            let mut calls = lock(&self.calls);
+            match calls.get(key) {
+                Some((_, call)) => (call.clone(), true),
+                None => {
+                    let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+                    let forget = Forget { calls: self.calls.clone(), key: key.to_string(), id };
+                    let work = start();
+                    let task = tokio::spawn(async move {
+                        let _forget = forget;
+                        work.await
+                    });
+                    let call = async move {
+                        match task.await {
+                            Ok(result) => result,
+                            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+                            Err(e) => panic!("single-flight task cancelled: {}", e),
+                        }
+                    }
+                    .boxed()
+                    .shared();
+                    calls.insert(key.to_string(), (id, call.clone()));
+                    (call, false)
+                }
+            }
+        };
+        (call.await, coalesced)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn finishes_after_every_waiter_leaves() {
+        let flights = SingleFlight::<u32>::default();
+        let finished = Arc::new(AtomicBool::new(false));
+        let flag = finished.clone();
+        let slow = flights.run("apod", || async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            flag.store(true, Ordering::SeqCst);
+            1
+        });
+        // Клиент отключился, не дождавшись ответа
+        assert!(tokio::time::timeout(Duration::from_millis(10), slow).await.is_err());
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(finished.load(Ordering::SeqCst));
+        assert_eq!(flights.run("apod", || async { 2 }).await, (2, false));
+    }
+}
@@ -0,0 +1,124 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::geo::extract_number;
+use crate::kp::parse_time;
+
+/// Измерение солнечного ветра DSCOVR/ACE из plasma-7-day.json
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PlasmaSample {
+    pub time: DateTime<Utc>,
+    /// Плотность протонов, 1/см³
+    pub density: Option<f64>,
+    /// Скорость, км/с
+    pub speed: Option<f64>,
+    /// Температура, K
+    pub temperature: Option<f64>,
+}
+
+impl PlasmaSample {
+    fn complete(&self) -> bool {
+        self.density.is_some() && self.speed.is_some() && self.temperature.is_some()
+    }
+}
+
+/// Минимум, максимум и среднее одного параметра
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Stats {
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+}
+
+/// Измерения по времени. В отличие от остальных источников ответ — массив
+/// массивов, где первая строка — заголовок с именами колонок; порядок колонок
+/// берётся из него, а не из документации
+pub fn samples(payload: &Value) -> Result<Vec<PlasmaSample>, String> {
+    let rows = payload.as_array().ok_or("expected an array of rows")?;
+    let header: Vec<&str> = rows
+        .first()
+        .and_then(Value::as_array)
+        .ok_or("missing header row")?
+        .iter()
+        .map(|h| h.as_str().unwrap_or_default())
+        .collect();
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|h| h.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("missing {} column", name))
+    };
+    let (time, density, speed, temperature) =
+        (column("time_tag")?, column("density")?, column("speed")?, column("temperature")?);
+    let mut out: Vec<PlasmaSample> = rows[1..]
+        .iter()
+        .filter_map(Value::as_array)
+        .filter_map(|cells| {
+            let number = |i: usize| cells.get(i).and_then(extract_number);
+            Some(PlasmaSample {
+                time: parse_time(cells.get(time)?.as_str()?)?,
+                density: number(density),
+                speed: number(speed),
+                temperature: number(temperature),
+            })
+        })
+        .collect();
+    out.sort_by_key(|s| s.time);
+    Ok(out)
+}
+
+/// Последнее измерение, где известны все три параметра: в конце ряда
+/// SWPC часто дописывает строки из одних null
+pub fn latest(samples: &[PlasmaSample]) -> Option<PlasmaSample> {
+    samples.iter().rev().find(|s| s.complete()).copied()
+}
+
+/// Статистика параметра `field` по измерениям не раньше `since`; null пропускаются
+pub fn stats(
+    samples: &[PlasmaSample],
+    since: DateTime<Utc>,
+    field: fn(&PlasmaSample) -> Option<f64>,
+) -> Option<Stats> {
+    let values: Vec<f64> = samples.iter().filter(|s| s.time >= since).filter_map(field).collect();
+    if values.is_empty() {
+        return None;
+    }
+    Some(Stats {
+        min: values.iter().copied().fold(f64::INFINITY, f64::min),
+        max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        avg: values.iter().sum::<f64>() / values.len() as f64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_recorded_plasma_feed() {
+        let payload: Value =
+            serde_json::from_str(include_str!("../tests/fixtures/swpc_plasma_7day.json")).unwrap();
+        let parsed = samples(&payload).unwrap();
+        assert_eq!(parsed.len(), 8);
+
+        let last = latest(&parsed).unwrap();
+        assert_eq!(last.time.to_rfc3339(), "2026-10-16T10:59:00+00:00");
+        assert_eq!(last.speed, Some(505.1));
+
+        let since = last.time - chrono::Duration::hours(6);
+        let speed = stats(&parsed, since, |s| s.speed).unwrap();
+        assert_eq!((speed.min, speed.max), (398.4, 512.0));
+        let density = stats(&parsed, since, |s| s.density).unwrap();
+        assert!((density.avg - (5.21 + 5.02 + 3.88 + 4.16 + 4.40) / 5.0).abs() < 1e-9);
+        assert_eq!(stats(&parsed, last.time + chrono::Duration::hours(1), |s| s.speed), None);
+
+        // Колонки ищутся по заголовку, а не по позиции
+        let reordered = serde_json::json!([
+            ["speed", "time_tag", "temperature", "density"],
+            ["420.5", "2026-10-16 11:00:00.000", "90000", "3.1"]
+        ]);
+        assert_eq!(latest(&samples(&reordered).unwrap()).unwrap().density, Some(3.1));
+        assert!(samples(&serde_json::json!([["time_tag", "speed"]])).unwrap_err().contains("density"));
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use serde_json::Value;
+use tokio::sync::{Mutex, MutexGuard};
+
+use crate::config::Config;
+use crate::errors::ApiError;
+use crate::{AppState, Fetched};
+
+/// Источник снимков space_cache. Новая лента — одна реализация трейта и её
+/// регистрация в `feeds::builtin`; фоновая загрузка, /space/refresh, сводка
+/// и список источников берут всё из реестра
+pub trait SpaceSource: Send + Sync {
+    /// Имя в URL и в колонке space_cache.source
+    fn name(&self) -> &'static str;
+
+    /// Интервал фоновой загрузки
+    fn interval(&self, cfg: &Config) -> Duration;
+
+    /// Запрос к upstream; None — upstream подтвердил, что данные не менялись
+    fn fetch<'a>(&'a self, st: &'a AppState) -> BoxFuture<'a, Result<Option<Fetched>, ApiError>>;
+
+    /// Ключ, по которому сопоставляются события в diff снимков
+    fn identity_key(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Несколько ключевых полей снимка для компактных ответов
+    fn headline(&self, _payload: &Value) -> Value {
+        Value::Object(Default::default())
+    }
+}
+
+/// Зарегистрированные источники в порядке регистрации и блокировки их загрузки:
+/// ручное обновление и фоновая задача не должны одновременно ходить к одному upstream
+#[derive(Clone)]
+pub struct SourceRegistry {
+    sources: Arc<Vec<Arc<dyn SpaceSource>>>,
+    locks: Arc<HashMap<&'static str, Mutex<()>>>,
+}
+
+impl SourceRegistry {
+    pub fn new(sources: Vec<Arc<dyn SpaceSource>>) -> Self {
+        let locks: HashMap<_, _> = sources.iter().map(|s| (s.name(), Mutex::new(()))).collect();
+        assert_eq!(locks.len(), sources.len(), "space source names must be unique");
+        Self { sources: Arc::new(sources), locks: Arc::new(locks) }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn SpaceSource>> {
+        self.sources.iter()
+    }
+
+    /// Регистр и пробелы по краям не важны; неизвестное имя — NOT_FOUND
+    pub fn get(&self, raw: &str) -> Result<&Arc<dyn SpaceSource>, ApiError> {
+        let name = raw.trim().to_lowercase();
+        self.sources.iter().find(|s| s.name() == name).ok_or_else(|| {
+            ApiError::not_found(format!(
+                "unknown source {:?}, expected one of {}",
+                raw,
+                self.names().join(", ")
+            ))
+        })
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.sources.iter().map(|s| s.name()).collect()
+    }
+
+    /// None — загрузка источника уже идёт
+    pub fn try_lock(&self, name: &str) -> Option<MutexGuard<'_, ()>> {
+        self.locks.get(name)?.try_lock().ok()
+    }
+
+    /// Дождаться идущей загрузки, когда результат нужен вызывающему
+    pub async fn lock(&self, name: &str) -> Option<MutexGuard<'_, ()>> {
+        Some(self.locks.get(name)?.lock().await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Источник без upstream: реестр не должен зависеть от встроенных лент
+    struct FakeSource;
+
+    impl SpaceSource for FakeSource {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+
+        fn interval(&self, _cfg: &Config) -> Duration {
+            Duration::from_secs(5)
+        }
+
+        fn fetch<'a>(&'a self, _st: &'a AppState) -> BoxFuture<'a, Result<Option<Fetched>, ApiError>> {
+            Box::pin(async { Ok(None) })
+        }
+    }
+
+    fn registry() -> SourceRegistry {
+        let donki = crate::feeds::donki_types(&crate::config::parse_donki_types(""), &HashMap::new()).unwrap();
+        let mut sources = crate::feeds::builtin(&donki);
+        sources.push(Arc::new(FakeSource));
+        SourceRegistry::new(sources)
+    }
+
+    #[test]
+    fn finds_sources_by_normalized_name() {
+        let registry = registry();
+        assert_eq!(registry.get(" SpaceX ").unwrap().name(), "spacex");
+        assert_eq!(registry.get("FAKE").unwrap().name(), "fake");
+        assert_eq!(registry.get("flr").unwrap().identity_key(), Some("flrID"));
+        assert_eq!(registry.get("fake").unwrap().identity_key(), None);
+
+        let err = registry.get("whatever").err().unwrap();
+        assert_eq!(err.error.code, "NOT_FOUND");
+        let (_, expected) = err.error.message.split_once("expected one of ").unwrap();
+        let listed: Vec<&str> = expected.split(", ").collect();
+        assert!(listed.contains(&"apod"));
+        assert!(listed.contains(&"fake"));
+        assert_eq!(listed, registry.names());
+    }
+
+    #[test]
+    #[should_panic(expected = "unique")]
+    fn rejects_duplicate_names() {
+        SourceRegistry::new(vec![Arc::new(FakeSource), Arc::new(FakeSource)]);
+    }
+
+    #[test]
+    fn locks_each_known_source_once() {
+        let registry = registry();
+        let guard = registry.try_lock("neo");
+        assert!(guard.is_some());
+        assert!(registry.try_lock("neo").is_none());
+        drop(guard);
+        assert!(registry.try_lock("neo").is_some());
+        assert!(registry.try_lock("unknown").is_none());
+    }
+}
@@ -0,0 +1,202 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Предстоящий запуск SpaceX в сокращённом виде для /spacex/upcoming
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UpcomingLaunch {
+    pub id: Option<String>,
+    pub name: String,
+    pub date_utc: DateTime<Utc>,
+    /// half, quarter, year, month, day или hour
+    pub date_precision: Option<String>,
+    /// Известен только месяц или грубее: обратный отсчёт показывать нельзя
+    pub approximate: bool,
+    pub rocket: Option<String>,
+    pub launchpad: Option<String>,
+    pub webcast: Option<String>,
+    pub details: Option<String>,
+}
+
+/// Сколько после date_utc запуск ещё может состояться при такой точности даты
+fn precision_slack(precision: Option<&str>) -> Duration {
+    match precision {
+        Some("day") => Duration::days(1),
+        Some("month") => Duration::days(31),
+        Some("quarter") => Duration::days(92),
+        Some("half") => Duration::days(183),
+        Some("year") => Duration::days(366),
+        _ => Duration::zero(),
+    }
+}
+
+/// Запуски из ответа /v4/launches/upcoming, которые ещё не прошли на `now`,
+/// по возрастанию date_utc; записи без имени или даты пропускаются
+pub fn upcoming(payload: &Value, now: DateTime<Utc>) -> Result<Vec<UpcomingLaunch>, String> {
+    let items = payload.as_array().ok_or("expected an array of launches")?;
+    let mut launches: Vec<UpcomingLaunch> = items
+        .iter()
+        .filter_map(launch)
+        .filter(|l| l.date_utc + precision_slack(l.date_precision.as_deref()) >= now)
+        .collect();
+    launches.sort_by_key(|l| l.date_utc);
+    Ok(launches)
+}
+
+/// Запуск из объекта SpaceX API, например ответа /v4/launches/next
+pub fn launch(item: &Value) -> Option<UpcomingLaunch> {
+    let text = |v: Option<&Value>| v.and_then(Value::as_str).map(str::to_string);
+    let date_utc = DateTime::parse_from_rfc3339(item.get("date_utc")?.as_str()?)
+        .ok()?
+        .with_timezone(&Utc);
+    let date_precision = text(item.get("date_precision"));
+    Some(UpcomingLaunch {
+        id: text(item.get("id")),
+        name: text(item.get("name"))?,
+        date_utc,
+        approximate: !matches!(date_precision.as_deref(), Some("day" | "hour") | None),
+        date_precision,
+        rocket: text(item.get("rocket")),
+        launchpad: text(item.get("launchpad")),
+        webcast: text(item.pointer("/links/webcast")),
+        details: text(item.get("details")),
+    })
+}
+
+/// Сколько запусков запрашивать на странице /v4/launches/query
+pub const PAST_PAGE_SIZE: i64 = 100;
+
+/// Прошедший запуск для spacex_launches
+#[derive(Debug, Clone, PartialEq)]
+pub struct PastLaunch {
+    pub id: String,
+    pub name: String,
+    pub date_utc: DateTime<Utc>,
+    /// None — итог неизвестен
+    pub success: Option<bool>,
+    pub rocket: Option<String>,
+    pub launchpad: Option<String>,
+    pub details: Option<String>,
+    pub raw: Value,
+}
+
+/// Тело POST /v4/launches/query: прошедшие запуски не раньше `since`
+/// (повтор последней даты безопасен — запись обновится), старые первыми
+pub fn past_query(since: Option<DateTime<Utc>>, page: i64) -> Value {
+    let mut query = json!({ "upcoming": false });
+    if let Some(since) = since {
+        query["date_utc"] = json!({ "$gte": since.to_rfc3339() });
+    }
+    json!({
+        "query": query,
+        "options": { "page": page, "limit": PAST_PAGE_SIZE, "sort": { "date_utc": "asc" } }
+    })
+}
+
+/// Запуски страницы и номер следующей. Следующая страница берётся, только
+/// если hasNextPage и nextPage больше текущей — иначе синхронизация могла бы
+/// зациклиться на ответе upstream
+pub fn past_page(payload: &Value) -> Result<(Vec<PastLaunch>, Option<i64>), String> {
+    let docs = payload
+        .get("docs")
+        .and_then(Value::as_array)
+        .ok_or("missing docs array")?;
+    let text = |v: &Value, key: &str| v.get(key).and_then(Value::as_str).map(str::to_string);
+    let launches = docs
+        .iter()
+        .filter_map(|doc| {
+            let date = DateTime::parse_from_rfc3339(doc.get("date_utc")?.as_str()?).ok()?;
+            Some(PastLaunch {
+                id: text(doc, "id")?,
+                name: text(doc, "name").unwrap_or_default(),
+                date_utc: date.with_timezone(&Utc),
+                success: doc.get("success").and_then(Value::as_bool),
+                rocket: text(doc, "rocket"),
+                launchpad: text(doc, "launchpad"),
+                details: text(doc, "details"),
+                raw: doc.clone(),
+            })
+        })
+        .collect();
+    let page = payload.get("page").and_then(Value::as_i64).unwrap_or(1);
+    let next = payload
+        .get("nextPage")
+        .and_then(Value::as_i64)
+        .filter(|&next| payload.get("hasNextPage").and_then(Value::as_bool) == Some(true) && next > page);
+    Ok((launches, next))
+}
+
+/// Оставшееся время вида "2d 4h 12m"; прошедшее — со знаком минус
+pub fn countdown(seconds: i64) -> String {
+    let sign = if seconds < 0 { "-" } else { "" };
+    let minutes = seconds.unsigned_abs() / 60;
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    let mut parts = Vec::new();
+    if days > 0 {
+        parts.push(format!("{}d", days));
+    }
+    if days > 0 || hours > 0 {
+        parts.push(format!("{}h", hours));
+    }
+    parts.push(format!("{}m", minutes));
+    format!("{}{}", sign, parts.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_skips_past_and_flags_imprecise_dates() {
+        let now = DateTime::parse_from_rfc3339("2026-10-16T12:00:00Z").unwrap().with_timezone(&Utc);
+        let payload = json!([
+            {"name": "Starlink 12-9", "date_utc": "2026-10-20T03:00:00.000Z", "date_precision": "hour",
+             "rocket": "5e9d0d95eda69973a809d1ec", "launchpad": "5e9e4501f509094ba4566f84",
+             "links": {"webcast": "https://youtu.be/x"}},
+            {"name": "Crew-13", "date_utc": "2026-10-01T00:00:00.000Z", "date_precision": "month"},
+            {"name": "Scrubbed", "date_utc": "2026-10-15T10:00:00.000Z", "date_precision": "hour"},
+            {"name": "Today", "date_utc": "2026-10-16T00:00:00.000Z", "date_precision": "day"},
+            {"name": "No date"}
+        ]);
+        let launches = upcoming(&payload, now).unwrap();
+        let names: Vec<&str> = launches.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, vec!["Crew-13", "Today", "Starlink 12-9"]);
+        assert!(launches[0].approximate);
+        assert!(!launches[2].approximate);
+        assert_eq!(launches[2].webcast.as_deref(), Some("https://youtu.be/x"));
+        assert!(upcoming(&json!({}), now).is_err());
+    }
+
+    #[test]
+    fn pages_past_launches_without_looping() {
+        let since = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let body = past_query(Some(since), 2);
+        assert_eq!(body["query"]["date_utc"]["$gte"], "2026-01-01T00:00:00+00:00");
+        assert_eq!(body["options"]["page"], 2);
+        assert!(past_query(None, 1)["query"].get("date_utc").is_none());
+
+        let page = json!({"docs": [
+            {"id": "5eb87cd9ffd86e000604b32a", "name": "FalconSat", "date_utc": "2006-03-24T22:30:00.000Z",
+             "success": false, "rocket": "5e9d0d95eda69955f709d1eb", "details": "Engine failure"},
+            {"id": "x", "name": "No date"}
+        ], "page": 1, "hasNextPage": true, "nextPage": 2});
+        let (launches, next) = past_page(&page).unwrap();
+        assert_eq!(launches.len(), 1);
+        assert_eq!(launches[0].success, Some(false));
+        assert_eq!(next, Some(2));
+
+        let stuck = json!({"docs": [], "page": 3, "hasNextPage": true, "nextPage": 3});
+        assert_eq!(past_page(&stuck).unwrap().1, None);
+        let last = json!({"docs": [], "page": 3, "hasNextPage": false, "nextPage": null});
+        assert_eq!(past_page(&last).unwrap().1, None);
+    }
+
+    #[test]
+    fn formats_countdown() {
+        assert_eq!(countdown(2 * 86400 + 4 * 3600 + 12 * 60 + 59), "2d 4h 12m");
+        assert_eq!(countdown(86400 + 5 * 60), "1d 0h 5m");
+        assert_eq!(countdown(3 * 3600), "3h 0m");
+        assert_eq!(countdown(59), "0m");
+        assert_eq!(countdown(-3900), "-1h 5m");
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Состояние одной фоновой задачи с момента запуска процесса
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TaskStatus {
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    /// Код ApiError последней ошибки, например UPSTREAM_DNS
+    pub last_error_code: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+    /// Ошибки подряд с последнего успешного прогона
+    pub consecutive_failures: u32,
+    pub running: bool,
+    /// Прогресс долгой задачи, например обхода каталога NEO
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<serde_json::Value>,
+}
+
+/// Состояние фоновых задач по имени; хранится только в памяти
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(transparent)]
+pub struct TaskStatusMap(BTreeMap<String, TaskStatus>);
+
+impl TaskStatusMap {
+    pub fn started(&mut self, task: &str, at: DateTime<Utc>) {
+        let status = self.0.entry(task.to_string()).or_default();
+        status.last_started_at = Some(at);
+        status.running = true;
+    }
+
+    pub fn succeeded(&mut self, task: &str, at: DateTime<Utc>) {
+        let status = self.0.entry(task.to_string()).or_default();
+        status.last_success_at = Some(at);
+        status.consecutive_failures = 0;
+        status.running = false;
+    }
+
+    pub fn failed(&mut self, task: &str, at: DateTime<Utc>, code: &str, error: String) {
+        let status = self.0.entry(task.to_string()).or_default();
+        status.last_error = Some(error);
+        status.last_error_code = Some(code.to_string());
+        status.last_error_at = Some(at);
+        status.consecutive_failures += 1;
+        status.running = false;
+    }
+
+    pub fn progress(&mut self, task: &str, progress: serde_json::Value) {
+        self.0.entry(task.to_string()).or_default().progress = Some(progress);
+    }
+
+    /// Задачи, у которых ошибок подряд больше `threshold`
+    pub fn failing(&self, threshold: u32) -> Vec<String> {
+        self.0
+            .iter()
+            .filter(|(_, s)| s.consecutive_failures > threshold)
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn success_resets_failure_streak() {
+        let now = Utc::now();
+        let mut tasks = TaskStatusMap::default();
+        tasks.started("apod", now);
+        tasks.failed("apod", now, "UPSTREAM_READ_TIMEOUT", "timeout".into());
+        tasks.started("apod", now);
+        tasks.failed("apod", now, "UPSTREAM_503", "HTTP 503".into());
+        tasks.started("iss", now);
+        tasks.succeeded("iss", now);
+
+        assert_eq!(tasks.failing(1), vec!["apod".to_string()]);
+        assert!(tasks.failing(2).is_empty());
+        let apod = &tasks.0["apod"];
+        assert_eq!(apod.last_error.as_deref(), Some("HTTP 503"));
+        assert_eq!(apod.last_error_code.as_deref(), Some("UPSTREAM_503"));
+        assert!(!apod.running);
+
+        tasks.succeeded("apod", now);
+        assert!(tasks.failing(0).is_empty());
+        // Последняя ошибка остаётся видна и после успеха
+        assert_eq!(tasks.0["apod"].last_error.as_deref(), Some("HTTP 503"));
+    }
+}
@@ -0,0 +1,391 @@
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::geo::{look_angles, LookAngles};
+
+/// Двухстрочный набор элементов орбиты
+#[derive(Debug, Clone, Serialize)]
+pub struct Tle {
+    pub name: Option<String>,
+    pub line1: String,
+    pub line2: String,
+    pub norad_id: u32,
+    pub epoch: DateTime<Utc>,
+    pub inclination_deg: f64,
+    pub raan_deg: f64,
+    pub eccentricity: f64,
+    pub arg_perigee_deg: f64,
+    pub mean_anomaly_deg: f64,
+    /// Среднее движение, оборотов в сутки
+    pub mean_motion: f64,
+    /// Первая производная среднего движения / 2, оборотов в сутки²
+    pub mean_motion_dot: f64,
+}
+
+impl Tle {
+    /// Разбор TLE из текста: две строки элементов, опционально с именем перед ними
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let lines: Vec<&str> = text
+            .lines()
+            .map(|l| l.trim_end())
+            .filter(|l| !l.trim().is_empty())
+            .collect();
+
+        let (name, line1, line2) = match lines.as_slice() {
+            [l1, l2] => (None, *l1, *l2),
+            [name, l1, l2] => (Some(name.trim().to_string()), *l1, *l2),
+            _ => return Err(format!("expected 2 or 3 TLE lines, got {}", lines.len())),
+        };
+
+        if !line1.starts_with("1 ") || line1.len() < 69 {
+            return Err("line 1 is malformed".into());
+        }
+        if !line2.starts_with("2 ") || line2.len() < 69 {
+            return Err("line 2 is malformed".into());
+        }
+        verify_checksum(line1)?;
+        verify_checksum(line2)?;
+
+        let norad_id = field(line1, 3, 7)?.parse::<u32>().map_err(|_| "bad catalog number")?;
+        if field(line2, 3, 7)?.parse::<u32>().ok() != Some(norad_id) {
+            return Err("catalog numbers of line 1 and line 2 differ".into());
+        }
+
+        Ok(Self {
+            name,
+            line1: line1.to_string(),
+            line2: line2.to_string(),
+            norad_id,
+            epoch: parse_epoch(field(line1, 19, 32)?)?,
+            mean_motion_dot: num(line1, 34, 43)?,
+            inclination_deg: num(line2, 9, 16)?,
+            raan_deg: num(line2, 18, 25)?,
+            eccentricity: format!("0.{}", field(line2, 27, 33)?)
+                .parse::<f64>()
+                .map_err(|_| "bad eccentricity")?,
+            arg_perigee_deg: num(line2, 35, 42)?,
+            mean_anomaly_deg: num(line2, 44, 51)?,
+            mean_motion: num(line2, 53, 63)?,
+        })
+    }
+
+    /// Возраст элементов относительно `now`, часы
+    pub fn age_hours(&self, now: DateTime<Utc>) -> f64 {
+        (now - self.epoch).num_seconds() as f64 / 3600.0
+    }
+}
+
+/// Контрольная сумма строки: сумма цифр колонок 1–68, где '-' считается за 1, по модулю 10
+fn verify_checksum(line: &str) -> Result<(), String> {
+    let expected = line
+        .as_bytes()
+        .get(68)
+        .filter(|b| b.is_ascii_digit())
+        .map(|b| u32::from(b - b'0'))
+        .ok_or_else(|| format!("line {} has no checksum digit", &line[..1]))?;
+
+    let actual = line.as_bytes()[..68]
+        .iter()
+        .map(|&b| match b {
+            b'0'..=b'9' => u32::from(b - b'0'),
+            b'-' => 1,
+            _ => 0,
+        })
+        .sum::<u32>()
+        % 10;
+
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "line {} checksum mismatch: expected {}, computed {}",
+            &line[..1],
+            expected,
+            actual
+        ))
+    }
+}
+
+/// Поле TLE по 1-базным номерам колонок включительно
+fn field(line: &str, from: usize, to: usize) -> Result<&str, String> {
+    line.get(from - 1..to)
+        .map(str::trim)
+        .ok_or_else(|| format!("columns {}-{} are missing", from, to))
+}
+
+fn num(line: &str, from: usize, to: usize) -> Result<f64, String> {
+    let raw = field(line, from, to)?;
+    raw.parse::<f64>()
+        .map_err(|_| format!("columns {}-{} are not a number: {:?}", from, to, raw))
+}
+
+/// Эпоха в формате YYDDD.DDDDDDDD
+fn parse_epoch(raw: &str) -> Result<DateTime<Utc>, String> {
+    let yy: i32 = raw.get(..2).and_then(|s| s.parse().ok()).ok_or("bad epoch year")?;
+    let day: f64 = raw.get(2..).and_then(|s| s.parse().ok()).ok_or("bad epoch day")?;
+    let year = if yy < 57 { 2000 + yy } else { 1900 + yy };
+    let jan1 = NaiveDate::from_ymd_opt(year, 1, 1)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .ok_or("bad epoch year")?
+        .and_utc();
+    let micros = ((day - 1.0) * 86_400_000_000.0).round() as i64;
+    Ok(jan1 + ChronoDuration::microseconds(micros))
+}
+
+/// Прогноз по SGP4 (крейт sgp4). Положение получается в системе TEME,
+/// в земные координаты переводится поворотом на звёздное время
+pub struct Propagator {
+    elements: sgp4::Elements,
+    constants: sgp4::Constants,
+}
+
+impl Propagator {
+    pub fn new(tle: &Tle) -> Result<Self, String> {
+        let elements = sgp4::Elements::from_tle(tle.name.clone(), tle.line1.as_bytes(), tle.line2.as_bytes())
+            .map_err(|e| format!("TLE rejected by SGP4: {}", e))?;
+        let constants =
+            sgp4::Constants::from_elements(&elements).map_err(|e| format!("TLE rejected by SGP4: {}", e))?;
+        Ok(Self { elements, constants })
+    }
+
+    /// Инерциальные (TEME) координаты спутника, км; None — SGP4 не смог
+    /// рассчитать положение, например орбита уже разрушилась
+    pub fn position_eci(&self, t: DateTime<Utc>) -> Option<[f64; 3]> {
+        let minutes = self.elements.datetime_to_minutes_since_epoch(&t.naive_utc()).ok()?;
+        self.constants.propagate(minutes).ok().map(|p| p.position)
+    }
+
+    /// Земные (ECEF) координаты спутника, км
+    pub fn position_ecef(&self, t: DateTime<Utc>) -> Option<[f64; 3]> {
+        let [x, y, z] = self.position_eci(t)?;
+        let (s, c) = gmst_rad(t).sin_cos();
+        Some([c * x + s * y, -s * x + c * y, z])
+    }
+
+    pub fn look_angles(&self, observer: &Observer, t: DateTime<Utc>) -> Option<LookAngles> {
+        Some(look_angles(observer.lat, observer.lon, observer.alt_km, self.position_ecef(t)?))
+    }
+}
+
+/// Гринвичское среднее звёздное время, радианы
+pub fn gmst_rad(t: DateTime<Utc>) -> f64 {
+    let jd = t.timestamp_millis() as f64 / 86_400_000.0 + 2_440_587.5;
+    let deg = 280.460_618_37 + 360.985_647_366_29 * (jd - 2_451_545.0);
+    deg.rem_euclid(360.0).to_radians()
+}
+
+/// Наблюдатель на поверхности Земли
+#[derive(Debug, Clone, Copy)]
+pub struct Observer {
+    pub lat: f64,
+    pub lon: f64,
+    pub alt_km: f64,
+}
+
+/// Пролёт спутника над наблюдателем
+#[derive(Debug, Clone, Serialize)]
+pub struct Pass {
+    pub rise_time: DateTime<Utc>,
+    pub rise_azimuth_deg: f64,
+    pub max_elevation_time: DateTime<Utc>,
+    pub max_elevation_deg: f64,
+    pub set_time: DateTime<Utc>,
+    pub set_azimuth_deg: f64,
+    pub duration_sec: i64,
+    /// Пролёт начался до начала окна или не закончился к его концу
+    pub truncated: bool,
+}
+
+const PASS_STEP_SECS: i64 = 30;
+
+/// Пролёты с углом места выше `min_elevation_deg` в окне [start, end]
+pub fn predict_passes(
+    prop: &Propagator,
+    observer: &Observer,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    min_elevation_deg: f64,
+) -> Vec<Pass> {
+    // Момент, для которого SGP4 не дал положения, считается «под горизонтом»
+    let elevation = |t: DateTime<Utc>| prop.look_angles(observer, t).map_or(-90.0, |a| a.elevation_deg);
+    let step = ChronoDuration::seconds(PASS_STEP_SECS);
+
+    let mut passes = Vec::new();
+    let mut t = start;
+    let mut prev_el = elevation(t);
+    let mut rise: Option<(DateTime<Utc>, bool)> = (prev_el > 0.0).then_some((start, true));
+    let mut peak = (start, prev_el);
+
+    while t < end {
+        let next = (t + step).min(end);
+        let el = elevation(next);
+
+        if prev_el <= 0.0 && el > 0.0 {
+            let rt = refine_crossing(&elevation, t, next);
+            rise = Some((rt, false));
+            peak = (rt, 0.0);
+        }
+        if rise.is_some() && el > peak.1 {
+            peak = (next, el);
+        }
+        if prev_el > 0.0 && el <= 0.0 {
+            if let Some((rt, truncated)) = rise.take() {
+                let st = refine_crossing(&elevation, t, next);
+                push_pass(&mut passes, prop, observer, (rt, st), peak, truncated);
+            }
+        }
+
+        prev_el = el;
+        t = next;
+    }
+
+    if let Some((rt, _)) = rise {
+        push_pass(&mut passes, prop, observer, (rt, end), peak, true);
+    }
+
+    passes.retain(|p| p.max_elevation_deg >= min_elevation_deg);
+    passes
+}
+
+fn push_pass(
+    passes: &mut Vec<Pass>,
+    prop: &Propagator,
+    observer: &Observer,
+    (rise, set): (DateTime<Utc>, DateTime<Utc>),
+    (peak_time, peak_el): (DateTime<Utc>, f64),
+    truncated: bool,
+) {
+    let azimuth = |t: DateTime<Utc>| prop.look_angles(observer, t).map_or(f64::NAN, |a| a.azimuth_deg);
+    passes.push(Pass {
+        rise_time: rise,
+        rise_azimuth_deg: azimuth(rise),
+        max_elevation_time: peak_time,
+        max_elevation_deg: peak_el,
+        set_time: set,
+        set_azimuth_deg: azimuth(set),
+        duration_sec: (set - rise).num_seconds(),
+        truncated,
+    });
+}
+
+/// Момент пересечения горизонта внутри [a, b] с точностью до секунды
+fn refine_crossing(
+    elevation: &impl Fn(DateTime<Utc>) -> f64,
+    mut a: DateTime<Utc>,
+    mut b: DateTime<Utc>,
+) -> DateTime<Utc> {
+    let rising = elevation(a) <= 0.0;
+    while (b - a).num_milliseconds() > 1000 {
+        let mid = a + (b - a) / 2;
+        if (elevation(mid) > 0.0) == rising {
+            b = mid;
+        } else {
+            a = mid;
+        }
+    }
+    b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::EARTH_RADIUS_KM as MEAN_RADIUS_KM;
+
+    const ISS_TLE: &str = "ISS (ZARYA)
+1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927
+2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537";
+
+    #[test]
+    fn parses_reference_tle() {
+        let tle = Tle::parse(ISS_TLE).unwrap();
+        assert_eq!(tle.name.as_deref(), Some("ISS (ZARYA)"));
+        assert_eq!(tle.norad_id, 25544);
+        assert_eq!(tle.epoch.format("%Y-%m-%dT%H:%M").to_string(), "2008-09-20T12:25");
+        assert_eq!(tle.inclination_deg, 51.6416);
+        assert_eq!(tle.eccentricity, 0.0006703);
+        assert_eq!(tle.mean_motion, 15.72125391);
+    }
+
+    #[test]
+    fn rejects_bad_checksum() {
+        let corrupted = ISS_TLE.replace("15.72125391563537", "15.72125391563538");
+        let err = Tle::parse(&corrupted).unwrap_err();
+        assert!(err.contains("checksum"), "{err}");
+
+        let flipped = ISS_TLE.replace("51.6416", "51.6417");
+        assert!(Tle::parse(&flipped).is_err());
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(Tle::parse("<html>Not Found</html>").is_err());
+        assert!(Tle::parse("1 25544U\n2 25544").is_err());
+    }
+
+    /// Спутник 00005 из проверочного набора SGP4 (Vallado et al., 2006,
+    /// «Revisiting Spacetrack Report #3», файл tcppver.out), положения TEME, км
+    const VANGUARD_TLE: &str = "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753
+2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+    const VANGUARD_REFERENCE: [(i64, [f64; 3]); 3] = [
+        (0, [7022.46529266, -1400.08296755, 0.03995155]),
+        (360, [-7154.03120202, -3783.17682504, -3536.19412294]),
+        (720, [-7134.59340119, 6531.68641902, 3260.27186304]),
+    ];
+
+    #[test]
+    fn matches_sgp4_reference_vectors() {
+        let tle = Tle::parse(VANGUARD_TLE).unwrap();
+        let prop = Propagator::new(&tle).unwrap();
+        for (minutes, expected) in VANGUARD_REFERENCE {
+            let [x, y, z] = prop.position_eci(tle.epoch + ChronoDuration::minutes(minutes)).unwrap();
+            let miss = ((x - expected[0]).powi(2) + (y - expected[1]).powi(2) + (z - expected[2]).powi(2)).sqrt();
+            // Справочник считан с константами WGS72, Constants::from_elements
+            // берёт WGS84: расхождение — десятки метров
+            assert!(miss < 0.1, "{} km off the reference at +{} min", miss, minutes);
+        }
+    }
+
+    #[test]
+    fn propagated_orbit_stays_in_leo() {
+        let tle = Tle::parse(ISS_TLE).unwrap();
+        let prop = Propagator::new(&tle).unwrap();
+        for minutes in (0..=3 * 1440).step_by(17) {
+            let [x, y, z] = prop.position_ecef(tle.epoch + ChronoDuration::minutes(minutes)).unwrap();
+            let alt = (x * x + y * y + z * z).sqrt() - MEAN_RADIUS_KM;
+            assert!((300.0..450.0).contains(&alt), "altitude {alt} km at +{minutes} min");
+            let lat = z.atan2((x * x + y * y).sqrt()).to_degrees();
+            assert!(lat.abs() <= 52.0, "latitude {lat} exceeds inclination");
+        }
+    }
+
+    #[test]
+    fn predicts_several_passes_per_day_at_mid_latitude() {
+        let tle = Tle::parse(ISS_TLE).unwrap();
+        let prop = Propagator::new(&tle).unwrap();
+        let observer = Observer { lat: 45.0, lon: 10.0, alt_km: 0.0 };
+        let passes = predict_passes(
+            &prop,
+            &observer,
+            tle.epoch,
+            tle.epoch + ChronoDuration::hours(24),
+            0.0,
+        );
+
+        assert!((2..=8).contains(&passes.len()), "{} passes", passes.len());
+        for p in &passes {
+            assert!(p.rise_time < p.max_elevation_time || p.truncated);
+            assert!(p.max_elevation_time <= p.set_time);
+            assert!(p.duration_sec <= 15 * 60, "pass lasts {} s", p.duration_sec);
+            assert!((0.0..=90.0).contains(&p.max_elevation_deg));
+        }
+    }
+
+    #[test]
+    fn no_passes_near_pole() {
+        let tle = Tle::parse(ISS_TLE).unwrap();
+        let prop = Propagator::new(&tle).unwrap();
+        let observer = Observer { lat: 89.0, lon: 0.0, alt_km: 0.0 };
+        let end = tle.epoch + ChronoDuration::hours(24);
+        assert!(predict_passes(&prop, &observer, tle.epoch, end, 0.0).is_empty());
+    }
+}
@@ -0,0 +1,244 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::geo::{cardinal_direction, great_circle_midpoint, haversine_km, initial_bearing_deg};
+use crate::models::IssLogRow;
+use crate::pick::t_pick;
+
+#[derive(Serialize, Default)]
+pub struct Trend {
+    pub movement: bool,
+    pub delta_km: f64,
+    pub dt_sec: f64,
+    pub velocity_kmh: Option<f64>,
+    pub from_time: Option<DateTime<Utc>>,
+    pub to_time: Option<DateTime<Utc>>,
+    pub from_lat: Option<f64>,
+    pub from_lon: Option<f64>,
+    pub to_lat: Option<f64>,
+    pub to_lon: Option<f64>,
+    pub samples_used: usize,
+    /// Строки без координат: в расчёт не попали
+    pub samples_skipped: usize,
+    /// Почему тренда нет, если точек с координатами меньше двух
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub avg_velocity_kmh: Option<f64>,
+    pub max_gap_sec: f64,
+    pub bearing_deg: Option<f64>,
+    pub cardinal: Option<&'static str>,
+    pub midpoint_lat: Option<f64>,
+    pub midpoint_lon: Option<f64>,
+    /// Откуда взяты from_time/to_time: "sample_time", "fetched_at" или "mixed"
+    pub time_source: Option<&'static str>,
+    pub from_fetched_at: Option<DateTime<Utc>>,
+    pub to_fetched_at: Option<DateTime<Utc>>,
+    pub from_sample_time: Option<DateTime<Utc>>,
+    pub to_sample_time: Option<DateTime<Utc>>,
+    /// Задержка загрузки последней точки: fetched_at − sample_time
+    pub clock_skew_sec: Option<f64>,
+}
+
+/// Точка трека для расчёта тренда
+pub struct TrendSample {
+    /// Момент измерения по upstream, иначе время загрузки
+    pub time: DateTime<Utc>,
+    pub fetched_at: DateTime<Utc>,
+    pub sample_time: Option<DateTime<Utc>>,
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl TrendSample {
+    /// Время берётся из колонки sample_time, для старых строк — из
+    /// payload.timestamp; fetched_at только если upstream его не прислал
+    pub fn from_row(row: &IssLogRow) -> Option<Self> {
+        let position = row.position()?;
+        let sample_time = row
+            .sample_time
+            .or_else(|| t_pick(&row.payload, &["timestamp"]));
+        Some(Self {
+            time: sample_time.unwrap_or(row.fetched_at),
+            fetched_at: row.fetched_at,
+            sample_time,
+            lat: position.latitude,
+            lon: position.longitude,
+        })
+    }
+}
+
+/// Тренд по точкам с координатами; `skipped` — сколько строк отброшено без
+/// них. Меньше двух точек — пустой тренд с причиной в `reason`
+pub fn compute_trend(points: &[TrendSample], skipped: usize, velocity_kmh: Option<f64>) -> Trend {
+    let (first, last) = match (points.first(), points.last()) {
+        (Some(f), Some(l)) if points.len() >= 2 => (f, l),
+        _ => {
+            return Trend {
+                samples_used: points.len(),
+                samples_skipped: skipped,
+                reason: Some(format!(
+                    "need at least 2 samples with coordinates, got {} ({} skipped without coordinates)",
+                    points.len(),
+                    skipped
+                )),
+                ..Trend::default()
+            }
+        }
+    };
+
+    let mut delta_km = 0.0;
+    let mut max_gap_sec: f64 = 0.0;
+    // (секунды от первой точки, накопленный путь в км) для МНК
+    let mut cumulative = vec![(0.0, 0.0)];
+
+    for pair in points.windows(2) {
+        delta_km += haversine_km(pair[0].lat, pair[0].lon, pair[1].lat, pair[1].lon);
+        let gap = seconds_between(pair[0].time, pair[1].time);
+        max_gap_sec = max_gap_sec.max(gap);
+        cumulative.push((seconds_between(first.time, pair[1].time), delta_km));
+    }
+
+    let bearing_deg = initial_bearing_deg(first.lat, first.lon, last.lat, last.lon);
+    let midpoint = great_circle_midpoint(first.lat, first.lon, last.lat, last.lon);
+    let with_sample_time = points.iter().filter(|p| p.sample_time.is_some()).count();
+    let time_source = match with_sample_time {
+        0 => "fetched_at",
+        n if n == points.len() => "sample_time",
+        _ => "mixed",
+    };
+
+    Trend {
+        movement: delta_km > 0.1,
+        delta_km,
+        dt_sec: seconds_between(first.time, last.time),
+        velocity_kmh,
+        from_time: Some(first.time),
+        to_time: Some(last.time),
+        from_lat: Some(first.lat),
+        from_lon: Some(first.lon),
+        to_lat: Some(last.lat),
+        to_lon: Some(last.lon),
+        samples_used: points.len(),
+        samples_skipped: skipped,
+        reason: None,
+        avg_velocity_kmh: least_squares_slope(&cumulative).map(|km_per_sec| km_per_sec * 3600.0),
+        max_gap_sec,
+        bearing_deg: Some(bearing_deg),
+        cardinal: Some(cardinal_direction(bearing_deg)),
+        midpoint_lat: midpoint.map(|m| m.0),
+        midpoint_lon: midpoint.map(|m| m.1),
+        time_source: Some(time_source),
+        from_fetched_at: Some(first.fetched_at),
+        to_fetched_at: Some(last.fetched_at),
+        from_sample_time: first.sample_time,
+        to_sample_time: last.sample_time,
+        clock_skew_sec: last.sample_time.map(|t| seconds_between(t, last.fetched_at)),
+    }
+}
+
+pub fn seconds_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    (to - from).num_milliseconds() as f64 / 1000.0
+}
+
+/// Наклон прямой y = a + b·x по методу наименьших квадратов
+fn least_squares_slope(points: &[(f64, f64)]) -> Option<f64> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let (num, den) = points.iter().fold((0.0, 0.0), |(num, den), (x, y)| {
+        (num + (x - mean_x) * (y - mean_y), den + (x - mean_x).powi(2))
+    });
+    if den == 0.0 {
+        None
+    } else {
+        Some(num / den)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    /// Строка журнала в старом формате: только payload, без типизированных колонок
+    fn fixture(id: i64, fetched_at: DateTime<Utc>, payload: serde_json::Value) -> IssLogRow {
+        IssLogRow {
+            id,
+            fetched_at,
+            source_url: "fixture".into(),
+            payload,
+            norad_id: 25544,
+            latitude: None,
+            longitude: None,
+            altitude_km: None,
+            velocity_kmh: None,
+            sample_time: None,
+            visibility: None,
+        }
+    }
+
+    #[test]
+    fn prefers_upstream_timestamp_over_fetch_time() {
+        let t0 = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        // Измерения с интервалом 60 с, но второй запрос ушёл с опозданием на 45 с
+        let rows = [
+            fixture(1, t0 + chrono::Duration::seconds(2), json!({
+                "latitude": 0.0, "longitude": 0.0, "timestamp": t0.timestamp()
+            })),
+            fixture(2, t0 + chrono::Duration::seconds(107), json!({
+                "latitude": 0.0, "longitude": 4.0, "timestamp": (t0.timestamp() + 60).to_string()
+            })),
+        ];
+        let points: Vec<_> = rows.iter().filter_map(TrendSample::from_row).collect();
+        let trend = compute_trend(&points, 0, None);
+
+        assert_eq!(trend.dt_sec, 60.0);
+        assert_eq!(trend.time_source, Some("sample_time"));
+        assert_eq!(trend.from_time, Some(t0));
+        assert_eq!(trend.to_fetched_at, Some(t0 + chrono::Duration::seconds(107)));
+        assert_eq!(trend.clock_skew_sec, Some(47.0));
+        // 4° по экватору ≈ 444.8 км за минуту
+        let v = trend.avg_velocity_kmh.unwrap();
+        assert!((v - 26_687.0).abs() < 10.0, "{v}");
+    }
+
+    #[test]
+    fn falls_back_to_fetched_at() {
+        let t0 = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let mut typed = fixture(2, t0 + chrono::Duration::seconds(90), json!({}));
+        typed.latitude = Some(0.0);
+        typed.longitude = Some(1.0);
+        typed.sample_time = Some(t0 + chrono::Duration::seconds(80));
+        let rows = [
+            fixture(1, t0, json!({"latitude": 0.0, "longitude": 0.0})),
+            typed,
+        ];
+        let points: Vec<_> = rows.iter().filter_map(TrendSample::from_row).collect();
+        let trend = compute_trend(&points, 0, None);
+
+        assert_eq!(trend.time_source, Some("mixed"));
+        assert_eq!(trend.from_sample_time, None);
+        assert_eq!(trend.dt_sec, 80.0);
+        assert_eq!(trend.clock_skew_sec, Some(10.0));
+    }
+
+    #[test]
+    fn single_point_has_no_trend() {
+        let t0 = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let rows = [
+            fixture(1, t0, json!({"latitude": 1.0, "longitude": 2.0})),
+            fixture(2, t0 + chrono::Duration::seconds(60), json!({"message": "no position"})),
+        ];
+        let points: Vec<_> = rows.iter().filter_map(TrendSample::from_row).collect();
+        let trend = compute_trend(&points, rows.len() - points.len(), None);
+        assert!(!trend.movement);
+        assert_eq!(trend.samples_used, 1);
+        assert_eq!(trend.samples_skipped, 1);
+        assert_eq!(trend.time_source, None);
+        assert!(trend.reason.unwrap().contains("got 1 (1 skipped"));
+    }
+}
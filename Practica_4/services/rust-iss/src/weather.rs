@@ -0,0 +1,138 @@
+use serde::Serialize;
+
+use crate::flr::FlareClass;
+
+/// Вес каждой составляющей в итоговой оценке; в сумме 100
+const KP_WEIGHT: f64 = 50.0;
+const FLARE_WEIGHT: f64 = 25.0;
+const CME_WEIGHT: f64 = 25.0;
+
+/// Скорость CME, начиная с которой удар считается максимальным, км/с
+const CME_FULL_SPEED: f64 = 1500.0;
+/// Скорость медленного CME, вклад которого — только базовая половина веса
+const CME_SLOW_SPEED: f64 = 500.0;
+
+/// Исходные данные оценки; None — источник недоступен или устарел
+#[derive(Debug, Clone, Default)]
+pub struct Inputs {
+    pub kp: Option<f64>,
+    /// Самая сильная вспышка за окно; Some(None) — вспышек не было
+    pub largest_flare: Option<Option<FlareClass>>,
+    /// Скорости направленных к Земле CME за окно (без скорости — None)
+    pub earth_directed_cmes: Option<Vec<Option<f64>>>,
+}
+
+/// Вклад одной составляющей: points из weight; None — данных нет
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Component {
+    pub name: &'static str,
+    pub weight: f64,
+    pub points: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Score {
+    /// 0–100 по доступным составляющим; None, если данных нет совсем
+    pub score: Option<u8>,
+    /// Доля веса, по которой оценка посчитана, 0–1
+    pub confidence: f64,
+    pub components: Vec<Component>,
+}
+
+/// Kp 9 — весь вес, линейно от нуля
+fn kp_points(kp: f64) -> f64 {
+    (kp / 9.0).clamp(0.0, 1.0) * KP_WEIGHT
+}
+
+/// X — весь вес, M — половина, C — десятая часть
+fn flare_points(largest: Option<FlareClass>) -> f64 {
+    let share = match largest.map(|c| c.letter) {
+        Some('X') => 1.0,
+        Some('M') => 0.5,
+        Some('C') => 0.1,
+        _ => 0.0,
+    };
+    share * FLARE_WEIGHT
+}
+
+/// Любой направленный к Земле CME — половина веса, остальное растёт со
+/// скоростью самого быстрого; CME без скорости считается медленным
+fn cme_points(speeds: &[Option<f64>]) -> f64 {
+    if speeds.is_empty() {
+        return 0.0;
+    }
+    let fastest = speeds.iter().flatten().copied().fold(CME_SLOW_SPEED, f64::max);
+    let speed_share = ((fastest - CME_SLOW_SPEED) / (CME_FULL_SPEED - CME_SLOW_SPEED)).clamp(0.0, 1.0);
+    (0.5 + 0.5 * speed_share) * CME_WEIGHT
+}
+
+/// Оценка 0–100. Отсутствующая составляющая не считается нулевой: оценка
+/// нормируется на вес доступных, а недостающий вес снижает confidence
+pub fn score(inputs: &Inputs) -> Score {
+    let components = vec![
+        Component { name: "kp", weight: KP_WEIGHT, points: inputs.kp.map(kp_points) },
+        Component { name: "flares", weight: FLARE_WEIGHT, points: inputs.largest_flare.map(flare_points) },
+        Component { name: "cme", weight: CME_WEIGHT, points: inputs.earth_directed_cmes.as_deref().map(cme_points) },
+    ];
+    let available: f64 = components.iter().filter(|c| c.points.is_some()).map(|c| c.weight).sum();
+    let earned: f64 = components.iter().filter_map(|c| c.points).sum();
+    let total: f64 = components.iter().map(|c| c.weight).sum();
+    Score {
+        score: (available > 0.0).then(|| (earned / available * 100.0).round().clamp(0.0, 100.0) as u8),
+        confidence: available / total,
+        components,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(raw: &str) -> Option<FlareClass> {
+        FlareClass::parse(raw)
+    }
+
+    #[test]
+    fn weighs_synthetic_conditions() {
+        let quiet = score(&Inputs {
+            kp: Some(1.0),
+            largest_flare: Some(None),
+            earth_directed_cmes: Some(Vec::new()),
+        });
+        assert_eq!((quiet.score, quiet.confidence), (Some(6), 1.0));
+
+        let x_flare_only = score(&Inputs {
+            kp: Some(0.0),
+            largest_flare: Some(class("X2.1")),
+            earth_directed_cmes: Some(Vec::new()),
+        });
+        assert_eq!(x_flare_only.score, Some(25));
+        assert_eq!(x_flare_only.components[1].points, Some(25.0));
+
+        let extreme = score(&Inputs {
+            kp: Some(9.0),
+            largest_flare: Some(class("X9.3")),
+            earth_directed_cmes: Some(vec![Some(2600.0), None]),
+        });
+        assert_eq!(extreme.score, Some(100));
+
+        let slow_cme = score(&Inputs {
+            kp: Some(0.0),
+            largest_flare: Some(None),
+            earth_directed_cmes: Some(vec![None]),
+        });
+        assert_eq!(slow_cme.score, Some(13));
+    }
+
+    #[test]
+    fn missing_inputs_lower_confidence_not_score() {
+        // Только вспышка X: без Kp и CME оценка не должна выглядеть спокойной
+        let flare_only = score(&Inputs { largest_flare: Some(class("X1.0")), ..Inputs::default() });
+        assert_eq!(flare_only.score, Some(100));
+        assert_eq!(flare_only.confidence, 0.25);
+        assert_eq!(flare_only.components[0].points, None);
+
+        let nothing = score(&Inputs::default());
+        assert_eq!((nothing.score, nothing.confidence), (None, 0.0));
+    }
+}
@@ -0,0 +1,244 @@
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use reqwest::redirect::Policy;
+use reqwest::Url;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Поддерживаемые типы событий
+pub const WEBHOOK_EVENTS: [&str; 2] = [EVENT_OSDR_NEW, EVENT_OSDR_UPDATED];
+pub const EVENT_OSDR_NEW: &str = "osdr.new";
+pub const EVENT_OSDR_UPDATED: &str = "osdr.updated";
+
+/// Попыток доставки на один вебхук за одну синхронизацию
+pub const DELIVERY_ATTEMPTS: u32 = 3;
+const BACKOFF_BASE: Duration = Duration::from_secs(1);
+/// Таймаут одной попытки доставки
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Датасет, появившийся или изменившийся при синхронизации
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemEvent {
+    #[serde(skip)]
+    pub event: &'static str,
+    pub id: i64,
+    pub dataset_id: Option<String>,
+    pub title: Option<String>,
+    pub status: Option<String>,
+}
+
+/// Подходит ли событие подписке: тип совпадает, а заголовок содержит
+/// title_filter без учёта регистра (пустой фильтр пропускает всё)
+pub fn matches(hook_event: &str, title_filter: Option<&str>, item: &ItemEvent) -> bool {
+    if hook_event != item.event {
+        return false;
+    }
+    match title_filter.map(str::trim).filter(|f| !f.is_empty()) {
+        None => true,
+        Some(filter) => item
+            .title
+            .as_deref()
+            .is_some_and(|t| t.to_lowercase().contains(&filter.to_lowercase())),
+    }
+}
+
+/// Пауза перед попыткой `attempt` (с 1): 0, 1 с, 2 с, 4 с…
+pub fn backoff(attempt: u32) -> Duration {
+    if attempt <= 1 {
+        Duration::ZERO
+    } else {
+        BACKOFF_BASE * 2u32.pow(attempt - 2)
+    }
+}
+
+/// Адрес, на который сервис не должен слать запросы от имени клиента:
+/// loopback, частные и link-local сети, unspecified и broadcast
+pub fn is_internal(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+        }
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_internal(IpAddr::V4(v4)),
+            None => {
+                v6.is_loopback()
+                    || v6.is_unspecified()
+                    || v6.is_unique_local()
+                    || v6.is_unicast_link_local()
+            }
+        },
+    }
+}
+
+/// Адрес для соединения с хостом вебхука; ни один из адресов хоста
+/// не должен попадать под `blocked`
+pub async fn resolve_target(url: &Url, blocked: fn(IpAddr) -> bool) -> Result<SocketAddr, String> {
+    let host = url.host_str().ok_or("url has no host")?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let port = url.port_or_known_default().unwrap_or(80);
+    let addrs: Vec<_> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| format!("cannot resolve {}: {}", host, e))?
+        .collect();
+    if let Some(a) = addrs.iter().find(|a| blocked(a.ip())) {
+        return Err(format!("{} resolves to internal address {}", host, a.ip()));
+    }
+    addrs.first().copied().ok_or_else(|| format!("cannot resolve {}", host))
+}
+
+/// Клиент одной попытки: без прокси и редиректов, хост закреплён за уже
+/// проверенным адресом, чтобы запрос не ушёл во внутреннюю сеть после
+/// смены DNS или ответа 3xx
+fn pinned_client(url: &Url, addr: SocketAddr, user_agent: &str) -> reqwest::Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .user_agent(user_agent)
+        .no_proxy()
+        .redirect(Policy::none())
+        .timeout(DELIVERY_TIMEOUT);
+    if let Some(domain) = url.domain() {
+        builder = builder.resolve(domain, addr);
+    }
+    builder.build()
+}
+
+/// POST с повторами; перед каждой попыткой хост разрешается и проверяется
+/// заново. Возвращает число попыток, последний HTTP-статус и ошибку
+pub async fn post_with_retry(
+    url: &str,
+    body: &Value,
+    user_agent: &str,
+    blocked: fn(IpAddr) -> bool,
+) -> (u32, Option<u16>, Option<String>) {
+    let url = match Url::parse(url) {
+        Ok(url) => url,
+        Err(e) => return (0, None, Some(format!("invalid url: {}", e))),
+    };
+    let mut status = None;
+    let mut error = None;
+    for attempt in 1..=DELIVERY_ATTEMPTS {
+        tokio::time::sleep(backoff(attempt)).await;
+        status = None;
+        let sent = match resolve_target(&url, blocked).await {
+            Ok(addr) => match pinned_client(&url, addr, user_agent) {
+                Ok(client) => client.post(url.clone()).json(body).send().await.map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            },
+            Err(e) => Err(e),
+        };
+        match sent {
+            Ok(resp) if resp.status().is_success() => {
+                return (attempt, Some(resp.status().as_u16()), None);
+            }
+            Ok(resp) => {
+                status = Some(resp.status().as_u16());
+                error = Some(format!("HTTP {}", resp.status()));
+            }
+            Err(e) => error = Some(e),
+        }
+    }
+    (DELIVERY_ATTEMPTS, status, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::IntoResponse;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn item(event: &'static str, title: Option<&str>) -> ItemEvent {
+        ItemEvent {
+            event,
+            id: 1,
+            dataset_id: Some("OSD-1".into()),
+            title: title.map(str::to_string),
+            status: None,
+        }
+    }
+
+    #[test]
+    fn filters_by_event_and_title() {
+        let rodent = item(EVENT_OSDR_NEW, Some("Rodent Research 7"));
+        assert!(matches(EVENT_OSDR_NEW, None, &rodent));
+        assert!(matches(EVENT_OSDR_NEW, Some("rodent"), &rodent));
+        assert!(matches(EVENT_OSDR_NEW, Some("  "), &rodent));
+        assert!(!matches(EVENT_OSDR_NEW, Some("plant"), &rodent));
+        assert!(!matches(EVENT_OSDR_UPDATED, None, &rodent));
+        assert!(!matches(EVENT_OSDR_NEW, Some("rodent"), &item(EVENT_OSDR_NEW, None)));
+    }
+
+    #[test]
+    fn exponential_backoff() {
+        let delays: Vec<u64> = (1..=4).map(|a| backoff(a).as_secs()).collect();
+        assert_eq!(delays, vec![0, 1, 2, 4]);
+    }
+
+    #[test]
+    fn detects_internal_addresses() {
+        for ip in [
+            "127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "0.0.0.0",
+            "::1", "fe80::1", "fd00::1", "::ffff:127.0.0.1",
+        ] {
+            assert!(is_internal(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in ["93.184.216.34", "172.32.0.1", "2606:4700::1111"] {
+            assert!(!is_internal(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_internal_targets() {
+        for url in ["http://127.0.0.1:8080/hook", "http://[::1]/hook", "http://localhost/hook"] {
+            assert!(resolve_target(&Url::parse(url).unwrap(), is_internal).await.is_err(), "{}", url);
+        }
+    }
+
+    /// Сервер, считающий запросы; /redirect отвечает 302 на `location`
+    async fn counting_server(location: Option<String>) -> (String, Arc<AtomicUsize>) {
+        use axum::http::{header, StatusCode};
+        use axum::routing::post;
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let handler = move || {
+            let counter = counter.clone();
+            let location = location.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                match location {
+                    Some(to) => (StatusCode::FOUND, [(header::LOCATION, to)]).into_response(),
+                    None => StatusCode::OK.into_response(),
+                }
+            }
+        };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let app = axum::Router::new().route("/hook", post(handler));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, hits)
+    }
+
+    #[tokio::test]
+    async fn does_not_follow_redirects_to_internal_hosts() {
+        let (internal, internal_hits) = counting_server(None).await;
+        let (public, public_hits) = counting_server(Some(internal.clone())).await;
+        let body = serde_json::json!({ "count": 0 });
+
+        // Первый хоп считается публичным, цель редиректа — нет
+        let (attempts, status, error) = post_with_retry(&public, &body, "test", |_| false).await;
+        assert_eq!((attempts, status), (DELIVERY_ATTEMPTS, Some(302)));
+        assert!(error.unwrap().contains("302"));
+        assert_eq!(public_hits.load(Ordering::SeqCst), DELIVERY_ATTEMPTS as usize);
+        assert_eq!(internal_hits.load(Ordering::SeqCst), 0);
+
+        let (_, status, error) = post_with_retry(&internal, &body, "test", is_internal).await;
+        assert_eq!(status, None);
+        assert!(error.unwrap().contains("internal address 127.0.0.1"));
+        assert_eq!(internal_hits.load(Ordering::SeqCst), 0);
+    }
+}